@@ -1,26 +1,21 @@
 use core::fmt::Debug;
 use std::io;
 
-use lazy_static::lazy_static;
-
 use zeroize::Zeroizing;
 use rand_core::{RngCore, CryptoRng};
 
-use sha2::{Digest, Sha256};
 use transcript::Transcript;
 
 use secp256k1::schnorr::Signature;
 use k256::{
-  elliptic_curve::{
-    ops::Reduce,
-    sec1::{Tag, ToEncodedPoint},
-  },
-  U256, Scalar, ProjectivePoint,
+  elliptic_curve::sec1::{Tag, ToEncodedPoint},
+  Scalar, ProjectivePoint,
 };
 use frost::{
   curve::{Ciphersuite, Secp256k1},
   Participant, ThresholdKeys, ThresholdView, FrostError,
-  algorithm::{Hram as HramTrait, Algorithm, Schnorr as FrostSchnorr},
+  algorithm::Algorithm,
+  bip340::Bip340,
 };
 
 use bitcoin::key::XOnlyPublicKey;
@@ -48,42 +43,16 @@ pub fn make_even(mut key: ProjectivePoint) -> (ProjectivePoint, u64) {
   (key, c)
 }
 
-/// A BIP-340 compatible HRAm for use with the modular-frost Schnorr Algorithm.
-///
-/// If passed an odd nonce, it will have the generator added until it is even.
-#[derive(Clone, Copy, Debug)]
-pub struct Hram {}
-
-lazy_static! {
-  static ref TAG_HASH: [u8; 32] = Sha256::digest(b"BIP0340/challenge").into();
-}
-
-#[allow(non_snake_case)]
-impl HramTrait<Secp256k1> for Hram {
-  fn hram(R: &ProjectivePoint, A: &ProjectivePoint, m: &[u8]) -> Scalar {
-    // Convert the nonce to be even
-    let (R, _) = make_even(*R);
-
-    let mut data = Sha256::new();
-    data.update(*TAG_HASH);
-    data.update(*TAG_HASH);
-    data.update(x(&R));
-    data.update(x(A));
-    data.update(m);
-
-    Scalar::reduce(U256::from_be_slice(&data.finalize()))
-  }
-}
-
 /// BIP-340 Schnorr signature algorithm.
 ///
-/// This must be used with a ThresholdKeys whose group key is even. If it is odd, this will panic.
+/// This wraps modular-frost's BIP-340 algorithm, which handles the parity of the group key and
+/// nonce, to produce signatures typed for the secp256k1 crate.
 #[derive(Clone)]
-pub struct Schnorr<T: Sync + Clone + Debug + Transcript>(FrostSchnorr<Secp256k1, T, Hram>);
+pub struct Schnorr<T: Sync + Clone + Debug + Transcript>(Bip340<T>);
 impl<T: Sync + Clone + Debug + Transcript> Schnorr<T> {
   /// Construct a Schnorr algorithm continuing the specified transcript.
   pub fn new(transcript: T) -> Schnorr<T> {
-    Schnorr(FrostSchnorr::new(transcript))
+    Schnorr(Bip340::new(transcript))
   }
 }
 
@@ -131,6 +100,15 @@ impl<T: Sync + Clone + Debug + Transcript> Algorithm<Secp256k1> for Schnorr<T> {
     self.0.sign_share(params, nonce_sums, nonces, msg)
   }
 
+  fn prepare_verification(
+    &mut self,
+    params: &ThresholdView<Secp256k1>,
+    nonce_sums: &[Vec<<Secp256k1 as Ciphersuite>::G>],
+    msg: &[u8],
+  ) {
+    self.0.prepare_verification(params, nonce_sums, msg)
+  }
+
   #[must_use]
   fn verify(
     &self,
@@ -138,15 +116,7 @@ impl<T: Sync + Clone + Debug + Transcript> Algorithm<Secp256k1> for Schnorr<T> {
     nonces: &[Vec<ProjectivePoint>],
     sum: Scalar,
   ) -> Option<Self::Signature> {
-    self.0.verify(group_key, nonces, sum).map(|mut sig| {
-      // Make the R of the final signature even
-      let offset;
-      (sig.R, offset) = make_even(sig.R);
-      // s = r + cx. Since we added to the r, add to s
-      sig.s += Scalar::from(offset);
-      // Convert to a secp256k1 signature
-      Signature::from_slice(&sig.serialize()[1 ..]).unwrap()
-    })
+    self.0.verify(group_key, nonces, sum).map(|sig| Signature::from_slice(&sig).unwrap())
   }
 
   fn verify_share(
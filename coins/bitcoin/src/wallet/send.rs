@@ -1,6 +1,6 @@
 use std::{
   io::{self, Read},
-  collections::HashMap,
+  collections::BTreeMap,
 };
 
 use thiserror::Error;
@@ -309,7 +309,7 @@ impl SignMachine<Transaction> for TransactionSignMachine {
 
   fn sign(
     mut self,
-    commitments: HashMap<Participant, Self::Preprocess>,
+    commitments: BTreeMap<Participant, Self::Preprocess>,
     msg: &[u8],
   ) -> Result<(TransactionSignatureMachine, Self::SignatureShare), FrostError> {
     if !msg.is_empty() {
@@ -321,7 +321,7 @@ impl SignMachine<Transaction> for TransactionSignMachine {
         commitments
           .iter()
           .map(|(l, commitments)| (*l, commitments[c].clone()))
-          .collect::<HashMap<_, _>>()
+          .collect::<BTreeMap<_, _>>()
       })
       .collect::<Vec<_>>();
 
@@ -365,11 +365,11 @@ impl SignatureMachine<Transaction> for TransactionSignatureMachine {
 
   fn complete(
     mut self,
-    mut shares: HashMap<Participant, Self::SignatureShare>,
+    mut shares: BTreeMap<Participant, Self::SignatureShare>,
   ) -> Result<Transaction, FrostError> {
     for (input, schnorr) in self.tx.input.iter_mut().zip(self.sigs.drain(..)) {
       let sig = schnorr.complete(
-        shares.iter_mut().map(|(l, shares)| (*l, shares.remove(0))).collect::<HashMap<_, _>>(),
+        shares.iter_mut().map(|(l, shares)| (*l, shares.remove(0))).collect::<BTreeMap<_, _>>(),
       )?;
 
       let mut witness = Witness::new();
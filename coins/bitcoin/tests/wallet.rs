@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use rand_core::{RngCore, OsRng};
 
@@ -74,7 +74,7 @@ async fn send_and_get_output(rpc: &Rpc, scanner: &Scanner, key: ProjectivePoint)
   outputs.swap_remove(0)
 }
 
-fn keys() -> (HashMap<Participant, ThresholdKeys<Secp256k1>>, ProjectivePoint) {
+fn keys() -> (BTreeMap<Participant, ThresholdKeys<Secp256k1>>, ProjectivePoint) {
   let mut keys = key_gen(&mut OsRng);
   for (_, keys) in keys.iter_mut() {
     *keys = tweak_keys(keys);
@@ -84,10 +84,10 @@ fn keys() -> (HashMap<Participant, ThresholdKeys<Secp256k1>>, ProjectivePoint) {
 }
 
 fn sign(
-  keys: &HashMap<Participant, ThresholdKeys<Secp256k1>>,
+  keys: &BTreeMap<Participant, ThresholdKeys<Secp256k1>>,
   tx: SignableTransaction,
 ) -> Transaction {
-  let mut machines = HashMap::new();
+  let mut machines = BTreeMap::new();
   for i in (1 ..= THRESHOLD).map(|i| Participant::new(i).unwrap()) {
     machines.insert(
       i,
@@ -106,7 +106,7 @@ fn test_tweak_keys() {
 
   // Generate keys until we get an even set and an odd set
   while !(even && odd) {
-    let mut keys = key_gen(&mut OsRng).drain().next().unwrap().1;
+    let mut keys = key_gen(&mut OsRng).into_iter().next().unwrap().1;
     if is_even(keys.group_key()) {
       // Tweaking should do nothing
       assert_eq!(tweak_keys(&keys).group_key(), keys.group_key());
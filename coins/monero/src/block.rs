@@ -1,10 +1,40 @@
-use std::io::{self, Read, Write};
+use std_shims::{
+  vec::Vec,
+  io::{self, Read, Write},
+  collections::HashSet,
+};
+
+use rand_core::{RngCore, CryptoRng};
+
+use curve25519_dalek::{
+  traits::IsIdentity,
+  scalar::Scalar,
+  edwards::{EdwardsPoint, CompressedEdwardsY},
+};
+use dalek_ff_group as dfg;
+use multiexp::BatchVerifier;
 
 use crate::{
+  H,
   serialize::*,
+  ringct::RctPrunable,
   transaction::{Input, Transaction},
 };
 
+/// Errors when verifying a block's transactions.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+pub enum BlockError {
+  #[cfg_attr(feature = "std", error("transactions don't match the block's"))]
+  TransactionsMismatch,
+  #[cfg_attr(feature = "std", error("rings don't match the transactions' inputs"))]
+  RingsMismatch,
+  #[cfg_attr(feature = "std", error("unsupported transaction (transaction {0})"))]
+  UnsupportedTransaction(usize),
+  #[cfg_attr(feature = "std", error("invalid transaction (transaction {0})"))]
+  InvalidTransaction(usize),
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct BlockHeader {
   pub major_version: u64,
@@ -79,3 +109,97 @@ impl Block {
     })
   }
 }
+
+impl Block {
+  /// Verify the RingCT signatures of this block's transactions.
+  ///
+  /// txs must be the transactions this block includes, in order. rings must be the ring, as
+  /// `[key, commitment]` pairs, of every input of every transaction, in order, as fetched from the
+  /// chain via the inputs' key offsets.
+  ///
+  /// Every Bulletproof(+), and every transaction's balance of pseudo-outs against its outputs'
+  /// commitments and fee, is accumulated into a single BatchVerifier. CLSAGs are verified
+  /// individually, as each ring member's challenge is the hash of the prior member's points, which
+  /// must be calculated to be hashed. On failure, the index of one invalid transaction is returned.
+  ///
+  /// This only supports CLSAG transactions. It doesn't check the transactions' key images are
+  /// unspent, solely that they aren't reused within this block, nor any consensus rules beyond
+  /// the transactions' proofs.
+  pub fn verify_transactions<R: RngCore + CryptoRng>(
+    &self,
+    rng: &mut R,
+    txs: &[Transaction],
+    rings: &[Vec<Vec<[EdwardsPoint; 2]>>],
+  ) -> Result<(), BlockError> {
+    if (txs.len() != self.txs.len()) ||
+      txs.iter().zip(&self.txs).any(|(tx, hash)| tx.hash() != *hash)
+    {
+      Err(BlockError::TransactionsMismatch)?;
+    }
+    if rings.len() != txs.len() {
+      Err(BlockError::RingsMismatch)?;
+    }
+
+    let mut key_images = HashSet::<CompressedEdwardsY>::new();
+    let mut verifier = BatchVerifier::new(txs.len());
+    for (t, (tx, rings)) in txs.iter().zip(rings).enumerate() {
+      let (bulletproofs, clsags, pseudo_outs) = match &tx.rct_signatures.prunable {
+        RctPrunable::Clsag { bulletproofs, clsags, pseudo_outs } => {
+          (bulletproofs, clsags, pseudo_outs)
+        }
+        RctPrunable::Null => Err(BlockError::UnsupportedTransaction(t))?,
+      };
+      if rings.len() != tx.prefix.inputs.len() {
+        Err(BlockError::RingsMismatch)?;
+      }
+      if (tx.prefix.version != 2) ||
+        (bulletproofs.len() != 1) ||
+        (clsags.len() != tx.prefix.inputs.len()) ||
+        (pseudo_outs.len() != tx.prefix.inputs.len()) ||
+        (tx.rct_signatures.base.commitments.len() != tx.prefix.outputs.len())
+      {
+        Err(BlockError::InvalidTransaction(t))?;
+      }
+
+      // The sum of the inputs' pseudo-outs must equal the sum of the outputs' commitments and fee
+      let commitments = &tx.rct_signatures.base.commitments;
+      verifier.queue(
+        rng,
+        t,
+        pseudo_outs
+          .iter()
+          .map(|pseudo_out| (Scalar::one(), *pseudo_out))
+          .chain(commitments.iter().map(|commitment| (-Scalar::one(), *commitment)))
+          .chain(core::iter::once((-Scalar::from(tx.rct_signatures.base.fee), *H)))
+          .map(|(scalar, point)| (dfg::Scalar(scalar), dfg::EdwardsPoint(point))),
+      );
+
+      let msg = tx.signature_hash();
+      for (i, input) in tx.prefix.inputs.iter().enumerate() {
+        let (key_offsets, key_image) = match input {
+          Input::ToKey { key_offsets, key_image, .. } => (key_offsets, key_image),
+          Input::Gen(_) => Err(BlockError::InvalidTransaction(t))?,
+        };
+        if rings[i].len() != key_offsets.len() {
+          Err(BlockError::RingsMismatch)?;
+        }
+        // Key images must be in the prime order subgroup and not be reused
+        if (!key_image.is_torsion_free()) ||
+          key_image.is_identity() ||
+          (!key_images.insert(key_image.compress()))
+        {
+          Err(BlockError::InvalidTransaction(t))?;
+        }
+        clsags[i]
+          .verify(&rings[i], key_image, &pseudo_outs[i], &msg)
+          .map_err(|_| BlockError::InvalidTransaction(t))?;
+      }
+
+      if !bulletproofs[0].batch_verify(rng, &mut verifier, t, commitments) {
+        Err(BlockError::InvalidTransaction(t))?;
+      }
+    }
+
+    verifier.verify_vartime_with_vartime_blame().map_err(BlockError::InvalidTransaction)
+  }
+}
@@ -1,7 +1,12 @@
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 #![doc = include_str!("../README.md")]
+#![cfg_attr(not(feature = "std"), no_std)]
 
-use std::io;
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
+
+use std_shims::io;
 
 use lazy_static::lazy_static;
 use rand_core::{RngCore, CryptoRng};
@@ -30,6 +35,7 @@ pub mod transaction;
 pub mod block;
 
 /// Monero daemon RPC interface.
+#[cfg(feature = "rpc")]
 pub mod rpc;
 /// Wallet functionality, enabling scanning and sending transactions.
 pub mod wallet;
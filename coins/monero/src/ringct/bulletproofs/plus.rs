@@ -1,3 +1,5 @@
+use std_shims::vec::Vec;
+
 use lazy_static::lazy_static;
 use rand_core::{RngCore, CryptoRng};
 
@@ -246,6 +246,17 @@ impl Algorithm<Ed25519> for ClsagMultisig {
     nonces: Vec<Zeroizing<dfg::Scalar>>,
     msg: &[u8],
   ) -> dfg::Scalar {
+    self.prepare_verification(view, nonce_sums, msg);
+    (-(dfg::Scalar(self.interim.as_ref().unwrap().p) * view.secret_share().deref())) +
+      nonces[0].deref()
+  }
+
+  fn prepare_verification(
+    &mut self,
+    _: &ThresholdView<Ed25519>,
+    nonce_sums: &[Vec<dfg::EdwardsPoint>],
+    msg: &[u8],
+  ) {
     // Use the transcript to get a seeded random number generator
     // The transcript contains private data, preventing passive adversaries from recreating this
     // process even if they have access to commitments (specifically, the ring index being signed
@@ -266,8 +277,6 @@ impl Algorithm<Ed25519> for ClsagMultisig {
       nonce_sums[0][1].0,
     );
     self.interim = Some(Interim { p, c, clsag, pseudo_out });
-
-    (-(dfg::Scalar(p) * view.secret_share().deref())) + nonces[0].deref()
   }
 
   #[must_use]
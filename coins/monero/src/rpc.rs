@@ -1,5 +1,6 @@
-use std::fmt::Debug;
+use std::{sync::Arc, fmt::Debug};
 
+use async_trait::async_trait;
 use thiserror::Error;
 
 use curve25519_dalek::edwards::{EdwardsPoint, CompressedEdwardsY};
@@ -8,7 +9,7 @@ use serde::{Serialize, Deserialize, de::DeserializeOwned};
 use serde_json::{Value, json};
 
 use digest_auth::AuthContext;
-use reqwest::{Client, RequestBuilder};
+use reqwest::Client;
 
 use crate::{
   Protocol,
@@ -73,18 +74,29 @@ fn rpc_point(point: &str) -> Result<EdwardsPoint, RpcError> {
   .ok_or_else(|| RpcError::InvalidPoint(point.to_string()))
 }
 
+/// A connection to a Monero daemon, transporting requests and their responses.
+///
+/// This enables using a transport other than HTTP, such as a mock daemon for testing.
+#[async_trait]
+pub trait RpcConnection: Send + Sync + Debug {
+  /// Perform a POST request to the specified route, relative to the daemon's URL, with the
+  /// provided body. Returns the body of the response.
+  async fn post(&self, route: &str, body: Vec<u8>) -> Result<Vec<u8>, RpcError>;
+}
+
+/// A HTTP connection to a Monero daemon.
 #[derive(Clone, Debug)]
-pub struct Rpc {
+pub struct HttpRpc {
   client: Client,
   userpass: Option<(String, String)>,
   url: String,
 }
 
-impl Rpc {
-  /// Create a new RPC connection.
+impl HttpRpc {
+  /// Create a new HTTP connection.
   /// A daemon requiring authentication can be used via including the username and password in the
   /// URL.
-  pub fn new(mut url: String) -> Result<Rpc, RpcError> {
+  pub fn new(mut url: String) -> Result<HttpRpc, RpcError> {
     // Parse out the username and password
     let userpass = if url.contains('@') {
       let url_clone = url;
@@ -114,7 +126,68 @@ impl Rpc {
       None
     };
 
-    Ok(Rpc { client: Client::new(), userpass, url })
+    Ok(HttpRpc { client: Client::new(), userpass, url })
+  }
+}
+
+#[async_trait]
+impl RpcConnection for HttpRpc {
+  async fn post(&self, route: &str, body: Vec<u8>) -> Result<Vec<u8>, RpcError> {
+    let mut builder = self.client.post(self.url.clone() + "/" + route);
+    if route.ends_with(".bin") {
+      builder = builder.header("Content-Type", "application/octet-stream");
+    } else if !body.is_empty() {
+      builder = builder.header("Content-Type", "application/json");
+    }
+    builder = builder.body(body);
+
+    if let Some((user, pass)) = &self.userpass {
+      let req = self.client.post(&self.url).send().await.map_err(|_| RpcError::InvalidNode)?;
+      // Only provide authentication if this daemon actually expects it
+      if let Some(header) = req.headers().get("www-authenticate") {
+        builder = builder.header(
+          "Authorization",
+          digest_auth::parse(header.to_str().map_err(|_| RpcError::InvalidNode)?)
+            .map_err(|_| RpcError::InvalidNode)?
+            .respond(&AuthContext::new_post::<_, _, _, &[u8]>(
+              user,
+              pass,
+              "/".to_string() + route,
+              None,
+            ))
+            .map_err(|_| RpcError::InvalidNode)?
+            .to_header_string(),
+        );
+      }
+    }
+
+    Ok(
+      builder
+        .send()
+        .await
+        .map_err(|_| RpcError::ConnectionError)?
+        .bytes()
+        .await
+        .map_err(|_| RpcError::ConnectionError)?
+        .to_vec(),
+    )
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct Rpc(Arc<dyn RpcConnection>);
+
+impl Rpc {
+  /// Create a new RPC connection, over HTTP.
+  /// A daemon requiring authentication can be used via including the username and password in the
+  /// URL.
+  pub fn new(url: String) -> Result<Rpc, RpcError> {
+    Ok(Rpc::from_connection(HttpRpc::new(url)?))
+  }
+
+  /// Create a new RPC connection from an arbitrary transport.
+  pub fn from_connection<C: 'static + RpcConnection>(connection: C) -> Rpc {
+    Rpc(Arc::new(connection))
   }
 
   /// Perform a RPC call to the specified method with the provided parameters.
@@ -125,12 +198,14 @@ impl Rpc {
     method: &str,
     params: Option<Params>,
   ) -> Result<Response, RpcError> {
-    let mut builder = self.client.post(self.url.clone() + "/" + method);
-    if let Some(params) = params.as_ref() {
-      builder = builder.json(params);
-    }
+    let body = match params.as_ref() {
+      Some(params) => serde_json::to_vec(params)
+        .map_err(|_| RpcError::InternalError("Failed to serialize JSON request"))?,
+      None => vec![],
+    };
 
-    self.call_tail(method, builder).await
+    serde_json::from_slice(&self.0.post(method, body).await?)
+      .map_err(|_| RpcError::InternalError("Failed to parse JSON response"))
   }
 
   /// Perform a JSON-RPC call to the specified method with the provided parameters
@@ -152,44 +227,8 @@ impl Rpc {
     method: &str,
     params: Vec<u8>,
   ) -> Result<Response, RpcError> {
-    let builder = self.client.post(self.url.clone() + "/" + method).body(params.clone());
-    self.call_tail(method, builder.header("Content-Type", "application/octet-stream")).await
-  }
-
-  async fn call_tail<Response: DeserializeOwned + Debug>(
-    &self,
-    method: &str,
-    mut builder: RequestBuilder,
-  ) -> Result<Response, RpcError> {
-    if let Some((user, pass)) = &self.userpass {
-      let req = self.client.post(&self.url).send().await.map_err(|_| RpcError::InvalidNode)?;
-      // Only provide authentication if this daemon actually expects it
-      if let Some(header) = req.headers().get("www-authenticate") {
-        builder = builder.header(
-          "Authorization",
-          digest_auth::parse(header.to_str().map_err(|_| RpcError::InvalidNode)?)
-            .map_err(|_| RpcError::InvalidNode)?
-            .respond(&AuthContext::new_post::<_, _, _, &[u8]>(
-              user,
-              pass,
-              "/".to_string() + method,
-              None,
-            ))
-            .map_err(|_| RpcError::InvalidNode)?
-            .to_header_string(),
-        );
-      }
-    }
-
-    let res = builder.send().await.map_err(|_| RpcError::ConnectionError)?;
-
-    Ok(if !method.ends_with(".bin") {
-      serde_json::from_str(&res.text().await.map_err(|_| RpcError::ConnectionError)?)
-        .map_err(|_| RpcError::InternalError("Failed to parse JSON response"))?
-    } else {
-      monero_epee_bin_serde::from_bytes(&res.bytes().await.map_err(|_| RpcError::ConnectionError)?)
-        .map_err(|_| RpcError::InternalError("Failed to parse binary response"))?
-    })
+    monero_epee_bin_serde::from_bytes(&self.0.post(method, params).await?)
+      .map_err(|_| RpcError::InternalError("Failed to parse binary response"))
   }
 
   /// Get the active blockchain protocol version.
@@ -469,6 +508,35 @@ impl Rpc {
       .collect()
   }
 
+  /// Check if the specified key images have been spent, either on-chain or in the mempool.
+  pub async fn is_key_image_spent(
+    &self,
+    key_images: &[EdwardsPoint],
+  ) -> Result<Vec<bool>, RpcError> {
+    #[derive(Deserialize, Debug)]
+    struct SpentResponse {
+      spent_status: Vec<u8>,
+    }
+
+    let res: SpentResponse = self
+      .rpc_call(
+        "is_key_image_spent",
+        Some(json!({
+          "key_images": key_images
+            .iter()
+            .map(|key_image| hex::encode(key_image.compress().to_bytes()))
+            .collect::<Vec<_>>()
+        })),
+      )
+      .await?;
+
+    if res.spent_status.len() != key_images.len() {
+      Err(RpcError::InvalidNode)?;
+    }
+    // 0 is unspent, 1 is spent on-chain, and 2 is spent in the mempool
+    Ok(res.spent_status.iter().map(|status| *status != 0).collect())
+  }
+
   /// Get the currently estimated fee from the node. This may be manipulated to unsafe levels and
   /// MUST be sanity checked.
   // TODO: Take a sanity check argument
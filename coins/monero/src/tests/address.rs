@@ -1,12 +1,18 @@
 use hex_literal::hex;
 
+use zeroize::Zeroizing;
 use rand_core::{RngCore, OsRng};
 
 use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, edwards::CompressedEdwardsY};
 
 use crate::{
   random_scalar,
-  wallet::address::{Network, AddressType, AddressMeta, MoneroAddress},
+  wallet::{
+    address::{Network, AddressType, AddressMeta, AddressError, MoneroAddress},
+    uri::{UriRecipient, MoneroUri},
+    openalias::OpenAlias,
+    ViewPair,
+  },
 };
 
 const SPEND: [u8; 32] = hex!("f8631661f6ab4e6fda310c797330d86e23a682f20d5bc8cc27b18051191f16d7");
@@ -174,3 +180,148 @@ fn featured_vectors() {
     );
   }
 }
+
+#[test]
+fn derived_view_pair() {
+  let spend = &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE;
+  let seed = Zeroizing::new([0xaa; 32]);
+  let pair = ViewPair::derived(spend, &seed);
+  assert_eq!(pair.spend(), spend);
+  assert_eq!(pair.view(), ViewPair::derived(spend, &seed).view());
+
+  // Distinct spend keys, or distinct seeds, should produce distinct view keys
+  let other = &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE;
+  assert!(pair.view() != ViewPair::derived(other, &seed).view());
+  assert!(pair.view() != ViewPair::derived(spend, &Zeroizing::new([0xbb; 32])).view());
+}
+
+#[test]
+fn uri() {
+  let standard = MoneroAddress::from_str(Network::Mainnet, STANDARD).unwrap();
+  let subaddress = MoneroAddress::from_str(Network::Mainnet, SUBADDRESS).unwrap();
+
+  let uri = MoneroUri::from_str(
+    Network::Mainnet,
+    &format!(
+      "monero:{STANDARD}?tx_amount=1.5&recipient_name=Serai%20DEX&tx_description=A%26B&other=%3B"
+    ),
+  )
+  .unwrap();
+  assert_eq!(
+    uri.recipients,
+    vec![UriRecipient {
+      address: standard,
+      amount: Some(1_500_000_000_000),
+      name: Some("Serai DEX".to_string())
+    }]
+  );
+  assert_eq!(uri.description, Some("A&B".to_string()));
+  assert_eq!(uri.unknown_parameters, vec![("other".to_string(), ";".to_string())]);
+  assert_eq!(MoneroUri::from_str(Network::Mainnet, &uri.to_string()).unwrap(), uri);
+
+  let uri = MoneroUri::new(standard);
+  assert_eq!(uri.to_string(), format!("monero:{STANDARD}"));
+  assert_eq!(MoneroUri::from_str_raw(&uri.to_string()).unwrap(), uri);
+
+  // Multiple recipients, where only some have amounts
+  let uri = MoneroUri::from_str(
+    Network::Mainnet,
+    &format!("monero:{STANDARD};{SUBADDRESS}?tx_amount=;0.000000000001&recipient_name=a;b%3Bc"),
+  )
+  .unwrap();
+  assert_eq!(uri.recipients.len(), 2);
+  assert_eq!(uri.recipients[0].address, standard);
+  assert_eq!(uri.recipients[0].amount, None);
+  assert_eq!(uri.recipients[0].name, Some("a".to_string()));
+  assert_eq!(uri.recipients[1].address, subaddress);
+  assert_eq!(uri.recipients[1].amount, Some(1));
+  assert_eq!(uri.recipients[1].name, Some("b;c".to_string()));
+  assert_eq!(MoneroUri::from_str(Network::Mainnet, &uri.to_string()).unwrap(), uri);
+
+  // Payment IDs are embedded into the address
+  let uri = MoneroUri::from_str(
+    Network::Mainnet,
+    &format!("monero:{STANDARD}?tx_payment_id={}", hex::encode(PAYMENT_ID)),
+  )
+  .unwrap();
+  assert_eq!(uri.recipients[0].address.to_string(), INTEGRATED);
+  assert_eq!(uri.to_string(), format!("monero:{INTEGRATED}"));
+
+  for (uri, error) in [
+    (STANDARD.to_string(), AddressError::InvalidUri),
+    (format!("monero:{STANDARD}?tx_amount"), AddressError::InvalidUri),
+    (format!("monero:{STANDARD}?tx_amount=1&tx_amount=1"), AddressError::InvalidUri),
+    (format!("monero:{STANDARD}?tx_amount=1;1"), AddressError::InvalidUri),
+    (format!("monero:{STANDARD}?recipient_name=%zz"), AddressError::InvalidUri),
+    (format!("monero:{STANDARD}?tx_amount=-1"), AddressError::InvalidAmount),
+    (format!("monero:{STANDARD}?tx_amount=.1"), AddressError::InvalidAmount),
+    (format!("monero:{STANDARD}?tx_amount=0.0000000000001"), AddressError::InvalidAmount),
+    (format!("monero:{STANDARD}?tx_amount=18446744073709551616"), AddressError::InvalidAmount),
+    (format!("monero:{STANDARD}?tx_payment_id=zz"), AddressError::InvalidPaymentId),
+    (format!("monero:{SUBADDRESS}?tx_payment_id=b8963a57855cf73f"), AddressError::InvalidPaymentId),
+    (format!("monero:{INTEGRATED}?tx_payment_id=b8963a57855cf73f"), AddressError::InvalidPaymentId),
+    (
+      format!("monero:{STANDARD};{STANDARD}?tx_payment_id=b8963a57855cf73f"),
+      AddressError::InvalidPaymentId,
+    ),
+    (format!("monero:{STANDARD}a"), AddressError::InvalidEncoding),
+  ] {
+    assert_eq!(MoneroUri::from_str(Network::Mainnet, &uri), Err(error));
+  }
+  assert_eq!(
+    MoneroUri::from_str(Network::Testnet, &format!("monero:{STANDARD}")),
+    Err(AddressError::DifferentNetwork)
+  );
+}
+
+#[test]
+fn openalias() {
+  assert_eq!(OpenAlias::dns_name("donate@getmonero.org"), "donate.getmonero.org");
+
+  let record = format!(
+    "oa1:xmr recipient_address={STANDARD}; recipient_name=Monero Development; \
+    tx_description=Donation; tx_amount=0.1; checksum=0;"
+  );
+  let alias = OpenAlias::from_txt_record(Network::Mainnet, &record).unwrap();
+  assert_eq!(alias.address, MoneroAddress::from_str(Network::Mainnet, STANDARD).unwrap());
+  assert_eq!(alias.name, Some("Monero Development".to_string()));
+  assert_eq!(alias.description, Some("Donation".to_string()));
+  assert_eq!(alias.amount, Some(100_000_000_000));
+  assert_eq!(alias.unknown_parameters, vec![("checksum".to_string(), "0".to_string())]);
+
+  let alias = OpenAlias::from_txt_record(
+    Network::Mainnet,
+    &format!("oa1:xmr recipient_address={STANDARD};tx_payment_id={}", hex::encode(PAYMENT_ID)),
+  )
+  .unwrap();
+  assert_eq!(alias.address.to_string(), INTEGRATED);
+  assert_eq!(alias.name, None);
+
+  // Records for other currencies, and other TXT records, are ignored
+  let records = [
+    "v=spf1 -all".to_string(),
+    "oa1:btc recipient_address=bc1;".to_string(),
+    "oa1:xmrt recipient_address=x;".to_string(),
+    record.clone(),
+  ];
+  let aliases =
+    OpenAlias::from_txt_records(Network::Mainnet, records.iter().map(String::as_str)).unwrap();
+  assert_eq!(aliases, vec![OpenAlias::from_txt_record(Network::Mainnet, &record).unwrap()]);
+
+  for (record, error) in [
+    ("oa1:btc recipient_address=bc1;".to_string(), AddressError::InvalidOpenAlias),
+    ("oa1:xmr recipient_name=Monero;".to_string(), AddressError::InvalidOpenAlias),
+    (format!("oa1:xmr recipient_address={STANDARD}; tx_amount"), AddressError::InvalidOpenAlias),
+    (
+      format!("oa1:xmr recipient_address={STANDARD}; recipient_address={STANDARD}"),
+      AddressError::InvalidOpenAlias,
+    ),
+    (format!("oa1:xmr recipient_address={STANDARD}; tx_amount=a"), AddressError::InvalidAmount),
+  ] {
+    assert_eq!(OpenAlias::from_txt_record(Network::Mainnet, &record), Err(error));
+  }
+  assert_eq!(
+    OpenAlias::from_txt_record(Network::Stagenet, &record),
+    Err(AddressError::DifferentNetwork)
+  );
+}
@@ -0,0 +1,143 @@
+use core::ops::Deref;
+
+use zeroize::Zeroizing;
+use rand_core::{RngCore, OsRng};
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, edwards::EdwardsPoint};
+
+use crate::{
+  Protocol, Commitment, random_scalar,
+  ringct::{RctBase, RctPrunable, RctSignatures},
+  transaction::{Input, Timelock, TransactionPrefix, Transaction},
+  block::{BlockHeader, Block, BlockError},
+  wallet::{
+    address::{Network, AddressSpec},
+    ViewPair, Decoys, SpendableOutput, Fee, Change, SignableTransaction, UnsignedTransaction,
+  },
+};
+
+const AMOUNT: u64 = 1_000_000_000_000;
+
+// Create and sign a transaction, returning it with the ring for its input
+fn transaction() -> (Transaction, Vec<[EdwardsPoint; 2]>) {
+  let protocol = Protocol::v16;
+  let spend = Zeroizing::new(random_scalar(&mut OsRng));
+
+  let key_offset = random_scalar(&mut OsRng);
+  let commitment = Commitment::new(random_scalar(&mut OsRng), AMOUNT);
+  let input = SpendableOutput::for_test(
+    &(spend.deref() + key_offset) * &ED25519_BASEPOINT_TABLE,
+    key_offset,
+    commitment.clone(),
+    0,
+  );
+
+  let i = u8::try_from(OsRng.next_u64() % u64::try_from(protocol.ring_len()).unwrap()).unwrap();
+  let mut ring = vec![];
+  for o in 0 .. protocol.ring_len() {
+    ring.push(if o == usize::from(i) {
+      [input.key(), commitment.calculate()]
+    } else {
+      [
+        &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE,
+        Commitment::new(random_scalar(&mut OsRng), OsRng.next_u64()).calculate(),
+      ]
+    });
+  }
+  let decoys = Decoys {
+    i,
+    offsets: (1 ..= u64::try_from(protocol.ring_len()).unwrap()).collect(),
+    ring: ring.clone(),
+  };
+
+  let view = ViewPair::new(
+    spend.deref() * &ED25519_BASEPOINT_TABLE,
+    Zeroizing::new(random_scalar(&mut OsRng)),
+  );
+  let recipient = ViewPair::new(
+    &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE,
+    Zeroizing::new(random_scalar(&mut OsRng)),
+  );
+  let mut r_seed = Zeroizing::new([0; 32]);
+  OsRng.fill_bytes(r_seed.as_mut());
+  let signable = SignableTransaction::new(
+    protocol,
+    Some(r_seed),
+    vec![input],
+    vec![(recipient.address(Network::Mainnet, AddressSpec::Standard), AMOUNT / 2)],
+    Some(Change::new(&view, false)),
+    vec![],
+    Fee { per_weight: 1, mask: 1 },
+  )
+  .unwrap();
+  (UnsignedTransaction::new(signable, vec![decoys]).sign(&mut OsRng, &spend).unwrap(), ring)
+}
+
+fn block(txs: &[Transaction]) -> Block {
+  Block {
+    header: BlockHeader {
+      major_version: 16,
+      minor_version: 16,
+      timestamp: 0,
+      previous: [0; 32],
+      nonce: 0,
+    },
+    miner_tx: Transaction {
+      prefix: TransactionPrefix {
+        version: 2,
+        timelock: Timelock::Block(61),
+        inputs: vec![Input::Gen(1)],
+        outputs: vec![],
+        extra: vec![],
+      },
+      signatures: vec![],
+      rct_signatures: RctSignatures {
+        base: RctBase { fee: 0, ecdh_info: vec![], commitments: vec![] },
+        prunable: RctPrunable::Null,
+      },
+    },
+    txs: txs.iter().map(Transaction::hash).collect(),
+  }
+}
+
+#[test]
+fn verify_block() {
+  let (first, first_ring) = transaction();
+  let (second, second_ring) = transaction();
+  let txs = vec![first, second];
+  let rings = vec![vec![first_ring.clone()], vec![second_ring.clone()]];
+
+  let included = block(&txs);
+  included.verify_transactions(&mut OsRng, &txs, &rings).unwrap();
+
+  // The transactions and rings must match the block
+  assert_eq!(
+    included.verify_transactions(&mut OsRng, &txs[.. 1], &rings),
+    Err(BlockError::TransactionsMismatch)
+  );
+  assert_eq!(
+    included.verify_transactions(&mut OsRng, &[txs[1].clone(), txs[0].clone()], &rings),
+    Err(BlockError::TransactionsMismatch)
+  );
+  assert_eq!(
+    included.verify_transactions(&mut OsRng, &txs, &rings[.. 1]),
+    Err(BlockError::RingsMismatch)
+  );
+
+  // The transaction with the incorrect ring should be identified
+  assert_eq!(
+    included.verify_transactions(&mut OsRng, &txs, &[vec![first_ring.clone()], vec![first_ring]]),
+    Err(BlockError::InvalidTransaction(1))
+  );
+
+  // A key image may not be spent twice within a block
+  let txs = vec![txs[1].clone(), txs[1].clone()];
+  assert_eq!(
+    block(&txs).verify_transactions(
+      &mut OsRng,
+      &txs,
+      &[vec![second_ring.clone()], vec![second_ring]]
+    ),
+    Err(BlockError::InvalidTransaction(1))
+  );
+}
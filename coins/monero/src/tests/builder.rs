@@ -0,0 +1,126 @@
+use zeroize::Zeroizing;
+use rand_core::OsRng;
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar};
+
+use crate::{
+  Protocol, Commitment, random_scalar,
+  wallet::{
+    address::{Network, AddressSpec, MoneroAddress},
+    ViewPair, SpendableOutput, Fee, Change, TransactionError, SignableTransaction,
+    SignableTransactionBuilder,
+  },
+};
+
+const FEE: Fee = Fee { per_weight: 1, mask: 1 };
+
+fn address() -> MoneroAddress {
+  ViewPair::new(
+    &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE,
+    Zeroizing::new(random_scalar(&mut OsRng)),
+  )
+  .address(Network::Mainnet, AddressSpec::Standard)
+}
+
+fn change() -> Change {
+  Change::fingerprintable(address())
+}
+
+fn output(amount: u64) -> SpendableOutput {
+  SpendableOutput::for_test(
+    &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE,
+    Scalar::zero(),
+    Commitment::new(random_scalar(&mut OsRng), amount),
+    0,
+  )
+}
+
+#[test]
+fn select_inputs() {
+  let pool = [output(1000), output(5_000_000), output(2_000_000), output(3000)];
+  let tx = SignableTransactionBuilder::new(Protocol::v16, FEE, Some(change()))
+    .add_payment(address(), 6_000_000)
+    .select_inputs(&pool)
+    .unwrap()
+    .build()
+    .unwrap();
+  // The two largest outputs should've been selected
+  let mut amounts = tx.inputs().iter().map(|input| input.commitment().amount).collect::<Vec<_>>();
+  amounts.sort();
+  assert_eq!(amounts, vec![2_000_000, 5_000_000]);
+
+  // Selection errors if the pool can't cover the payments, without adding any inputs
+  let mut builder = SignableTransactionBuilder::new(Protocol::v16, FEE, Some(change()))
+    .add_payment(address(), 7_004_000);
+  assert!(matches!(builder.select_inputs(&pool), Err(TransactionError::NotEnoughFunds(..))));
+  assert!(matches!(builder.build(), Err(TransactionError::NoInputs)));
+}
+
+#[test]
+fn sweep() {
+  const OUTPUTS: usize = 500;
+  const AMOUNT: u64 = 1_000_000_000;
+
+  let dest = address();
+  let payment = address();
+  let change = change();
+  let mut builder = SignableTransactionBuilder::new(Protocol::v16, FEE, Some(change.clone()));
+  for _ in 0 .. OUTPUTS {
+    builder.add_input(output(AMOUNT));
+  }
+  let (txs, uneconomical) = builder.add_payment(payment, AMOUNT).sweep(dest).unwrap();
+  // This would exceed the max transaction size as one transaction
+  assert!(txs.len() > 1);
+  assert!(uneconomical.is_empty());
+
+  let mut inputs = 0;
+  for (i, tx) in txs.iter().enumerate() {
+    inputs += tx.inputs().len();
+    // The first transaction must also fund the payment
+    let in_amount = tx.inputs().iter().map(|input| input.commitment().amount).sum::<u64>();
+    assert!(in_amount > (tx.fee() + if i == 0 { AMOUNT } else { 0 }));
+
+    // The first transaction has two outputs without a change output, so it shouldn't have one
+    // The rest only have the destination, so they need a change output
+    let (payments, change): (&[_], _) =
+      if i == 0 { (&[(payment, AMOUNT), (dest, 0)], None) } else { (&[(dest, 0)], Some(&change)) };
+    assert_eq!(
+      tx.fee(),
+      SignableTransaction::estimate_fee(
+        Protocol::v16,
+        tx.inputs().len(),
+        payments,
+        change,
+        &[],
+        FEE
+      )
+      .unwrap()
+    );
+  }
+  assert_eq!(inputs, OUTPUTS);
+}
+
+#[test]
+fn sweep_uneconomical() {
+  const AMOUNT: u64 = 1_000_000_000;
+
+  // Outputs worth less than the fee to spend them should be returned instead of swept
+  let mut builder = SignableTransactionBuilder::new(Protocol::v16, FEE, Some(change()));
+  for amount in [1, AMOUNT, 2, AMOUNT, AMOUNT] {
+    builder.add_input(output(amount));
+  }
+  let (txs, uneconomical) = builder.sweep(address()).unwrap();
+  assert_eq!(txs.len(), 1);
+  assert_eq!(txs[0].inputs().len(), 3);
+  assert!(txs[0].inputs().iter().all(|input| input.commitment().amount == AMOUNT));
+  assert_eq!(
+    uneconomical.iter().map(|input| input.commitment().amount).collect::<Vec<_>>(),
+    vec![2, 1]
+  );
+
+  // If every output is uneconomical, sweeping should error
+  let mut builder = SignableTransactionBuilder::new(Protocol::v16, FEE, Some(change()));
+  builder.add_input(output(1));
+  builder.add_input(output(2));
+  assert!(matches!(builder.sweep(address()), Err(TransactionError::NotEnoughFunds(3, _))));
+}
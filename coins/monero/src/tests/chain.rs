@@ -0,0 +1,282 @@
+use core::ops::Deref;
+use std::{
+  sync::{Arc, RwLock},
+  collections::HashMap,
+};
+
+use async_trait::async_trait;
+
+use zeroize::Zeroizing;
+use rand_core::OsRng;
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, edwards::EdwardsPoint};
+
+use serde_json::{Value, json};
+
+use crate::{
+  hash, random_scalar,
+  ringct::{RctBase, RctPrunable, RctSignatures},
+  transaction::{Input, Output, Timelock, TransactionPrefix, Transaction},
+  block::{BlockHeader, Block},
+  rpc::{RpcError, RpcConnection, Rpc},
+  wallet::{shared_key, ViewPair, SignedKeyImage, WalletStore, ChainScanner},
+};
+
+fn random_point() -> EdwardsPoint {
+  &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE
+}
+
+fn transaction(inputs: Vec<Input>, outputs: Vec<Output>, extra: Vec<u8>) -> Transaction {
+  Transaction {
+    prefix: TransactionPrefix { version: 2, timelock: Timelock::None, inputs, outputs, extra },
+    signatures: vec![],
+    rct_signatures: RctSignatures {
+      base: RctBase { fee: 0, ecdh_info: vec![], commitments: vec![] },
+      prunable: RctPrunable::Null,
+    },
+  }
+}
+
+// A transaction with a single output, paying the specified amount to the view pair if specified
+// The amount is left in the clear, as is done by miner transactions
+#[allow(non_snake_case)]
+fn paying(inputs: Vec<Input>, pair: Option<&ViewPair>, amount: u64) -> Transaction {
+  let r = random_scalar(&mut OsRng);
+  let R = &r * &ED25519_BASEPOINT_TABLE;
+  let key = match pair {
+    Some(pair) => {
+      (&shared_key(None, r * pair.view(), 0).1 * &ED25519_BASEPOINT_TABLE) + pair.spend()
+    }
+    None => random_point(),
+  };
+  let mut extra = vec![1];
+  extra.extend(R.compress().to_bytes());
+  transaction(inputs, vec![Output { amount, key: key.compress(), view_tag: None }], extra)
+}
+
+fn spending(key_image: EdwardsPoint) -> Transaction {
+  transaction(vec![Input::ToKey { amount: 0, key_offsets: vec![], key_image }], vec![], vec![])
+}
+
+#[derive(Debug, Default)]
+struct Chain {
+  // Every block ever mined, including those since orphaned
+  blocks: HashMap<[u8; 32], Block>,
+  chain: Vec<[u8; 32]>,
+  txs: HashMap<[u8; 32], Transaction>,
+}
+
+/// A daemon whose chain can be reorganized.
+#[derive(Clone, Debug, Default)]
+struct MockDaemon(Arc<RwLock<Chain>>);
+
+impl MockDaemon {
+  /// Mine a block, whose miner transaction pays the view pair if specified, on top of the block
+  /// with the specified number.
+  fn mine(&self, number: usize, to: Option<&ViewPair>, txs: Vec<Transaction>) {
+    let mut chain = self.0.write().unwrap();
+    chain.chain.truncate(number);
+
+    let miner_tx = paying(vec![Input::Gen(u64::try_from(number).unwrap())], to, 1_000_000);
+    let block = Block {
+      header: BlockHeader {
+        major_version: 16,
+        minor_version: 16,
+        timestamp: 0,
+        previous: number.checked_sub(1).map(|previous| chain.chain[previous]).unwrap_or([0; 32]),
+        nonce: 0,
+      },
+      miner_tx,
+      txs: txs.iter().map(Transaction::hash).collect(),
+    };
+    for tx in txs {
+      chain.txs.insert(tx.hash(), tx);
+    }
+
+    let hash = hash(&block.serialize());
+    chain.blocks.insert(hash, block);
+    chain.chain.push(hash);
+  }
+}
+
+#[async_trait]
+impl RpcConnection for MockDaemon {
+  async fn post(&self, route: &str, body: Vec<u8>) -> Result<Vec<u8>, RpcError> {
+    let chain = self.0.read().unwrap();
+    let request: Value = if body.is_empty() || route.ends_with(".bin") {
+      Value::Null
+    } else {
+      serde_json::from_slice(&body).unwrap()
+    };
+
+    let res = match route {
+      "get_height" => json!({ "height": chain.chain.len() }),
+      "json_rpc" => match request["method"].as_str().unwrap() {
+        "get_block_header_by_height" => {
+          let number = usize::try_from(request["params"]["height"].as_u64().unwrap()).unwrap();
+          let hash = chain.chain.get(number).ok_or(RpcError::InternalError("unknown block"))?;
+          json!({ "result": { "block_header": { "hash": hex::encode(hash) } } })
+        }
+        "get_block" => {
+          let hash = hex::decode(request["params"]["hash"].as_str().unwrap()).unwrap();
+          let block = &chain.blocks[&<[u8; 32]>::try_from(hash).unwrap()];
+          json!({ "result": { "blob": hex::encode(block.serialize()) } })
+        }
+        _ => Err(RpcError::InternalError("unknown method"))?,
+      },
+      "get_transactions" => {
+        let mut txs = vec![];
+        for hash in request["txs_hashes"].as_array().unwrap() {
+          let hash = hex::decode(hash.as_str().unwrap()).unwrap();
+          let tx = &chain.txs[&<[u8; 32]>::try_from(hash.clone()).unwrap()];
+          txs.push(json!({
+            "tx_hash": hex::encode(hash),
+            "as_hex": hex::encode(tx.serialize()),
+            "pruned_as_hex": "",
+          }));
+        }
+        json!({ "txs": txs })
+      }
+      // Global indexes aren't relevant to these tests
+      "get_o_indexes.bin" => {
+        #[derive(serde::Serialize)]
+        struct OIndexes {
+          o_indexes: Vec<u64>,
+          status: String,
+          untrusted: bool,
+          credits: usize,
+          top_hash: String,
+        }
+        return Ok(
+          monero_epee_bin_serde::to_bytes(&OIndexes {
+            o_indexes: vec![0],
+            status: "OK".to_string(),
+            untrusted: false,
+            credits: 0,
+            top_hash: String::new(),
+          })
+          .unwrap(),
+        );
+      }
+      _ => Err(RpcError::InternalError("unknown route"))?,
+    };
+    Ok(serde_json::to_vec(&res).unwrap())
+  }
+}
+
+#[tokio::test]
+async fn reorganization() {
+  let spend = Zeroizing::new(random_scalar(&mut OsRng));
+  let pair = ViewPair::new(
+    spend.deref() * &ED25519_BASEPOINT_TABLE,
+    Zeroizing::new(random_scalar(&mut OsRng)),
+  );
+  let daemon = MockDaemon::default();
+  let rpc = Rpc::from_connection(daemon.clone());
+  let mut scanner = ChainScanner::new(WalletStore::new(pair.clone(), 0, None));
+
+  // Receive an output in block 0 and another in block 2
+  let received = paying(
+    vec![Input::ToKey { amount: 0, key_offsets: vec![], key_image: random_point() }],
+    Some(&pair),
+    2_000_000,
+  );
+  daemon.mine(0, Some(&pair), vec![]);
+  daemon.mine(1, None, vec![]);
+  daemon.mine(2, None, vec![received.clone()]);
+  assert!(scanner.sync(&rpc).await.unwrap().is_empty());
+  assert_eq!(scanner.store().height(), 3);
+  assert_eq!(scanner.store().outputs().len(), 2);
+  assert_eq!(scanner.store().outputs()[1].block, 2);
+
+  // Spend the first output in block 3
+  let first = scanner.store().outputs()[0].output.output.clone();
+  let key_image = SignedKeyImage::new(&mut OsRng, &spend, &first).unwrap();
+  let spent = key_image.key_image();
+  scanner.import_key_images(&[key_image]).unwrap();
+  daemon.mine(3, None, vec![spending(spent)]);
+  assert!(scanner.sync(&rpc).await.unwrap().is_empty());
+  assert_eq!(scanner.store().outputs()[0].spent, Some(3));
+  assert_eq!(scanner.store().unspent().count(), 1);
+  assert_eq!(scanner.store().transactions().len(), 3);
+
+  // Reorganize from block 2, with the received output being included again in block 3 and the
+  // spend not being included
+  daemon.mine(2, Some(&pair), vec![]);
+  daemon.mine(3, None, vec![received.clone()]);
+  daemon.mine(4, None, vec![]);
+  assert_eq!(scanner.sync(&rpc).await.unwrap(), vec![2]);
+  let store = scanner.store();
+  assert_eq!(store.height(), 5);
+  assert_eq!(store.outputs().len(), 3);
+  assert_eq!(store.outputs()[0].spent, None);
+  assert_eq!(store.outputs().iter().map(|output| output.block).collect::<Vec<_>>(), vec![0, 2, 3]);
+  assert_eq!(store.outputs()[2].output.output.absolute.tx, received.hash());
+  assert_eq!(
+    store.transactions().iter().map(|tx| (tx.block, tx.received, tx.spent)).collect::<Vec<_>>(),
+    vec![(0, 1_000_000, 0), (2, 1_000_000, 0), (3, 2_000_000, 0)]
+  );
+  for number in 0 .. 5 {
+    assert_eq!(store.block_hash(number), Some(rpc.get_block_hash(number).await.unwrap()));
+  }
+
+  // Replace the tip, without the chain growing
+  daemon.mine(4, None, vec![spending(spent)]);
+  assert_eq!(scanner.sync(&rpc).await.unwrap(), vec![4]);
+  assert_eq!(scanner.store().height(), 5);
+  assert_eq!(scanner.store().outputs()[0].spent, Some(4));
+
+  // Shorten the chain
+  daemon.0.write().unwrap().chain.truncate(4);
+  assert_eq!(scanner.sync(&rpc).await.unwrap(), vec![4]);
+  assert_eq!(scanner.store().height(), 4);
+  assert_eq!(scanner.store().outputs()[0].spent, None);
+}
+
+#[tokio::test]
+async fn fresh_store() {
+  let daemon = MockDaemon::default();
+  let rpc = Rpc::from_connection(daemon.clone());
+  for number in 0 .. 3 {
+    daemon.mine(number, None, vec![]);
+  }
+
+  // A store created at the current height shouldn't require any block hashes
+  let pair = ViewPair::new(random_point(), Zeroizing::new(random_scalar(&mut OsRng)));
+  let mut scanner = ChainScanner::new(WalletStore::new(pair, 3, None));
+  assert!(scanner.sync(&rpc).await.unwrap().is_empty());
+  assert_eq!(scanner.store().height(), 3);
+}
+
+#[tokio::test]
+async fn spend_key() {
+  let spend = Zeroizing::new(random_scalar(&mut OsRng));
+  let pair = ViewPair::new(
+    spend.deref() * &ED25519_BASEPOINT_TABLE,
+    Zeroizing::new(random_scalar(&mut OsRng)),
+  );
+  let daemon = MockDaemon::default();
+  let rpc = Rpc::from_connection(daemon.clone());
+  let mut scanner = ChainScanner::with_spend_key(WalletStore::new(pair, 0, None), spend.clone());
+
+  // Receive an output in block 0 and spend it in block 1, which should be detected without any key
+  // images being imported
+  daemon.mine(0, Some(scanner.store().view_pair()), vec![]);
+  daemon.mine(1, None, vec![]);
+  daemon.mine(2, None, vec![]);
+  assert_eq!(scanner.sync_blocks(&rpc, 1).await.unwrap(), (vec![], false));
+  assert_eq!(scanner.store().height(), 1);
+  let output = scanner.store().outputs()[0].output.output.clone();
+  let spent = SignedKeyImage::new(&mut OsRng, &spend, &output).unwrap().key_image();
+  daemon.mine(1, None, vec![spending(spent)]);
+  assert_eq!(scanner.sync_blocks(&rpc, 5).await.unwrap(), (vec![], true));
+  assert_eq!(scanner.store().height(), 2);
+  assert_eq!(scanner.store().outputs()[0].spent, Some(1));
+
+  // Key images are regenerated for the outputs already in the store
+  let mut scanner = ChainScanner::with_spend_key(scanner.into_store(), spend);
+  daemon.mine(1, None, vec![]);
+  daemon.mine(2, None, vec![spending(spent)]);
+  assert_eq!(scanner.sync(&rpc).await.unwrap(), vec![1]);
+  assert_eq!(scanner.store().outputs()[0].spent, Some(2));
+}
@@ -17,7 +17,7 @@ use crate::{
   wallet::Decoys,
   ringct::{
     generate_key_image,
-    clsag::{ClsagInput, Clsag},
+    clsag::{ClsagError, ClsagInput, Clsag},
   },
 };
 #[cfg(feature = "multisig")]
@@ -77,9 +77,55 @@ fn clsag() {
     )
     .swap_remove(0);
     clsag.verify(&ring, &image, &pseudo_out, &msg).unwrap();
+
+    // Verification should fail for a different message or ring
+    assert_eq!(clsag.verify(&ring, &image, &pseudo_out, &[2; 32]), Err(ClsagError::InvalidC1));
+    let mut other = ring.clone();
+    other[usize::try_from((real + 1) % RING_LEN).unwrap()].swap(0, 1);
+    assert!(clsag.verify(&other, &image, &pseudo_out, &msg).is_err());
+
+    // As should modifying any part of the signature
+    for i in 0 .. clsag.s.len() {
+      let mut modified = clsag.clone();
+      modified.s[i] += Scalar::one();
+      assert_eq!(modified.verify(&ring, &image, &pseudo_out, &msg), Err(ClsagError::InvalidC1));
+    }
+    let mut modified = clsag.clone();
+    modified.c1 += Scalar::one();
+    assert_eq!(modified.verify(&ring, &image, &pseudo_out, &msg), Err(ClsagError::InvalidC1));
+    let mut modified = clsag.clone();
+    modified.D += ED25519_BASEPOINT_TABLE.basepoint();
+    assert_eq!(modified.verify(&ring, &image, &pseudo_out, &msg), Err(ClsagError::InvalidC1));
   }
 }
 
+#[test]
+fn forged_clsag() {
+  // A CLSAG with random values, for a random ring, must not verify
+  let ring = (0 .. RING_LEN)
+    .map(|_| {
+      [
+        &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE,
+        &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE,
+      ]
+    })
+    .collect::<Vec<_>>();
+  let forged = Clsag {
+    D: &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE,
+    s: (0 .. RING_LEN).map(|_| random_scalar(&mut OsRng)).collect(),
+    c1: random_scalar(&mut OsRng),
+  };
+  assert_eq!(
+    forged.verify(
+      &ring,
+      &generate_key_image(&Zeroizing::new(random_scalar(&mut OsRng))),
+      &(&random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE),
+      &[1; 32]
+    ),
+    Err(ClsagError::InvalidC1)
+  );
+}
+
 #[cfg(feature = "multisig")]
 #[test]
 fn clsag_multisig() {
@@ -0,0 +1,126 @@
+use core::ops::Deref;
+
+use zeroize::Zeroizing;
+use rand_core::{RngCore, OsRng};
+
+use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+
+use crate::{
+  Protocol, Commitment, random_scalar,
+  serialize::{write_byte, write_varint, write_vec},
+  ringct::{generate_key_image, RctPrunable},
+  transaction::{Input, Transaction},
+  wallet::{
+    address::{Network, AddressSpec, MoneroAddress},
+    ViewPair, Decoys, SpendableOutput, Fee, Change, TransactionError, SignableTransaction,
+    UnsignedTransaction,
+  },
+};
+
+const AMOUNT: u64 = 1_000_000_000_000;
+
+#[test]
+fn cold_signing() {
+  let protocol = Protocol::v16;
+
+  let spend = Zeroizing::new(random_scalar(&mut OsRng));
+  let view = ViewPair::new(
+    spend.deref() * &ED25519_BASEPOINT_TABLE,
+    Zeroizing::new(random_scalar(&mut OsRng)),
+  );
+
+  // Create an output, as would've been found by a watch-only scanner, and a ring for it
+  let key_offset = random_scalar(&mut OsRng);
+  let commitment = Commitment::new(random_scalar(&mut OsRng), AMOUNT);
+  let input = SpendableOutput::for_test(
+    &(spend.deref() + key_offset) * &ED25519_BASEPOINT_TABLE,
+    key_offset,
+    commitment.clone(),
+    0,
+  );
+
+  let i = u8::try_from(OsRng.next_u64() % u64::try_from(protocol.ring_len()).unwrap()).unwrap();
+  let mut ring = vec![];
+  for o in 0 .. protocol.ring_len() {
+    ring.push(if o == usize::from(i) {
+      [input.key(), commitment.calculate()]
+    } else {
+      [
+        &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE,
+        Commitment::new(random_scalar(&mut OsRng), OsRng.next_u64()).calculate(),
+      ]
+    });
+  }
+  let decoys =
+    Decoys { i, offsets: (1 ..= u64::try_from(protocol.ring_len()).unwrap()).collect(), ring };
+
+  let recipient = ViewPair::new(
+    &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE,
+    Zeroizing::new(random_scalar(&mut OsRng)),
+  );
+  let signable = SignableTransaction::new(
+    protocol,
+    Some(Zeroizing::new([0xaa; 32])),
+    vec![input.clone()],
+    vec![(recipient.address(Network::Mainnet, AddressSpec::Standard), AMOUNT / 2)],
+    Some(Change::new(&view, false)),
+    vec![],
+    Fee { per_weight: 1, mask: 1 },
+  )
+  .unwrap();
+
+  // Export the unsigned transaction, and import it as the cold signer would
+  let unsigned = UnsignedTransaction::new(signable, vec![decoys.clone()]);
+  let serialized = unsigned.serialize();
+  assert_eq!(UnsignedTransaction::read::<&[u8]>(&mut serialized.as_ref()).unwrap(), unsigned);
+
+  // Serialize an unsigned transaction with the specified payments and fee, without validating it
+  let unvalidated = |payments: &[(MoneroAddress, u64)], fee: u64| {
+    let mut serialized = vec![];
+    protocol.write(&mut serialized).unwrap();
+    serialized.push(1);
+    serialized.extend([0xaa; 32]);
+    write_vec(SpendableOutput::write, &[input.clone()], &mut serialized).unwrap();
+    write_varint(&u64::try_from(payments.len()).unwrap(), &mut serialized).unwrap();
+    for (address, amount) in payments {
+      serialized.push(0);
+      write_vec(write_byte, address.to_string().as_bytes(), &mut serialized).unwrap();
+      serialized.extend(amount.to_le_bytes());
+    }
+    // No data
+    serialized.push(0);
+    serialized.extend(fee.to_le_bytes());
+    write_vec(Decoys::write, &[decoys.clone()], &mut serialized).unwrap();
+    UnsignedTransaction::read::<&[u8]>(&mut serialized.as_ref())
+  };
+  let address = recipient.address(Network::Mainnet, AddressSpec::Standard);
+  assert!(unvalidated(&[(address, AMOUNT / 2), (address, AMOUNT / 4)], 1).is_ok());
+  // Transactions which SignableTransaction::new would reject should be rejected when read
+  assert!(unvalidated(&[], 1).is_err());
+  assert!(unvalidated(&[(address, 1); 17], 1).is_err());
+  assert!(unvalidated(&[(address, AMOUNT / 2), (address, AMOUNT / 2)], 1).is_err());
+  assert!(unvalidated(&[(address, u64::MAX), (address, 2)], 0).is_err());
+
+  // Signing with the wrong key should fail
+  assert_eq!(
+    unsigned.clone().sign(&mut OsRng, &Zeroizing::new(random_scalar(&mut OsRng))),
+    Err(TransactionError::WrongPrivateKey)
+  );
+
+  // Sign, and import the signed transaction as the watch-only machine would
+  let tx = unsigned.clone().sign(&mut OsRng, &spend).unwrap();
+  let tx = Transaction::read::<&[u8]>(&mut tx.serialize().as_ref()).unwrap();
+  assert!(unsigned.eventuality().matches(&tx));
+
+  let image = generate_key_image(&Zeroizing::new(spend.deref() + key_offset));
+  assert_eq!(
+    tx.prefix.inputs,
+    vec![Input::ToKey { amount: 0, key_offsets: decoys.offsets.clone(), key_image: image }]
+  );
+  match &tx.rct_signatures.prunable {
+    RctPrunable::Clsag { clsags, pseudo_outs, .. } => {
+      clsags[0].verify(&decoys.ring, &image, &pseudo_outs[0], &tx.signature_hash()).unwrap();
+    }
+    _ => panic!("signed transaction didn't use CLSAG"),
+  }
+}
@@ -0,0 +1,117 @@
+use core::ops::Deref;
+
+use zeroize::Zeroizing;
+use rand_core::OsRng;
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar};
+
+use crate::{
+  Commitment, random_scalar,
+  ringct::{generate_key_image, RctBase, RctPrunable, RctSignatures},
+  transaction::{Input, Timelock, TransactionPrefix, Transaction},
+  wallet::{ViewPair, Scanner, ReceivedOutput, SpendableOutput, KeyImageError, SignedKeyImage},
+};
+
+fn output(spend: &Zeroizing<Scalar>) -> ReceivedOutput {
+  let key_offset = random_scalar(&mut OsRng);
+  SpendableOutput::for_test(
+    &(spend.deref() + key_offset) * &ED25519_BASEPOINT_TABLE,
+    key_offset,
+    Commitment::new(random_scalar(&mut OsRng), 1),
+    0,
+  )
+  .output
+  .clone()
+}
+
+fn spending_tx(key_image: &SignedKeyImage) -> Transaction {
+  Transaction {
+    prefix: TransactionPrefix {
+      version: 2,
+      timelock: Timelock::None,
+      inputs: vec![Input::ToKey {
+        amount: 0,
+        key_offsets: vec![],
+        key_image: key_image.key_image(),
+      }],
+      outputs: vec![],
+      extra: vec![],
+    },
+    signatures: vec![],
+    rct_signatures: RctSignatures {
+      base: RctBase { fee: 0, ecdh_info: vec![], commitments: vec![] },
+      prunable: RctPrunable::Null,
+    },
+  }
+}
+
+#[test]
+fn signed_key_image() {
+  let spend = Zeroizing::new(random_scalar(&mut OsRng));
+  let output = output(&spend);
+
+  let key_image = SignedKeyImage::new(&mut OsRng, &spend, &output).unwrap();
+  assert_eq!(key_image.key(), output.key());
+  assert_eq!(
+    key_image.key_image(),
+    generate_key_image(&Zeroizing::new(spend.deref() + output.key_offset()))
+  );
+  key_image.verify().unwrap();
+
+  let serialized = key_image.serialize();
+  assert_eq!(SignedKeyImage::read::<&[u8]>(&mut serialized.as_ref()).unwrap(), key_image);
+
+  // A key image for another output shouldn't verify
+  let mut other = serialized.clone();
+  other[32 .. 64].copy_from_slice(
+    &SignedKeyImage::new(&mut OsRng, &spend, &self::output(&spend)).unwrap().serialize()[32 .. 64],
+  );
+  assert_eq!(
+    SignedKeyImage::read::<&[u8]>(&mut other.as_ref()).unwrap().verify(),
+    Err(KeyImageError::InvalidSignature)
+  );
+
+  // Nor should a key image be creatable with the wrong spend key
+  assert_eq!(
+    SignedKeyImage::new(&mut OsRng, &Zeroizing::new(random_scalar(&mut OsRng)), &output),
+    Err(KeyImageError::WrongPrivateKey)
+  );
+}
+
+#[test]
+fn import_key_images() {
+  let spend = Zeroizing::new(random_scalar(&mut OsRng));
+  let mut scanner = Scanner::from_view(
+    ViewPair::new(
+      spend.deref() * &ED25519_BASEPOINT_TABLE,
+      Zeroizing::new(random_scalar(&mut OsRng)),
+    ),
+    None,
+  );
+
+  let spent = output(&spend);
+  let unspent = output(&spend);
+  let spent_image = SignedKeyImage::new(&mut OsRng, &spend, &spent).unwrap();
+  let unspent_image = SignedKeyImage::new(&mut OsRng, &spend, &unspent).unwrap();
+
+  // Scanning the spend before the key image is imported won't detect it
+  scanner.scan_transaction(&spending_tx(&spent_image));
+  assert!(!scanner.is_spent(&spent.key()));
+
+  // Invalid key images should be rejected, without importing any
+  // This uses the signature from the spent key image
+  let mut invalid = unspent_image.serialize();
+  invalid[64 ..].copy_from_slice(&spent_image.serialize()[64 ..]);
+  let invalid = SignedKeyImage::read::<&[u8]>(&mut invalid.as_ref()).unwrap();
+  assert_eq!(
+    scanner.import_key_images(&[spent_image.clone(), invalid]),
+    Err(KeyImageError::InvalidSignature)
+  );
+  scanner.scan_transaction(&spending_tx(&spent_image));
+  assert!(!scanner.is_spent(&spent.key()));
+
+  scanner.import_key_images(&[spent_image.clone(), unspent_image]).unwrap();
+  scanner.scan_transaction(&spending_tx(&spent_image));
+  assert!(scanner.is_spent(&spent.key()));
+  assert!(!scanner.is_spent(&unspent.key()));
+}
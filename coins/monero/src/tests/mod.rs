@@ -2,3 +2,12 @@ mod clsag;
 mod bulletproofs;
 mod address;
 mod seed;
+mod cold;
+#[cfg(feature = "std")]
+mod builder;
+mod key_image;
+mod proof;
+mod block;
+mod store;
+#[cfg(feature = "rpc")]
+mod chain;
@@ -0,0 +1,260 @@
+use core::ops::Deref;
+use std::collections::HashSet;
+
+use hex_literal::hex;
+
+use zeroize::Zeroizing;
+use rand_core::{RngCore, OsRng};
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar};
+
+use crate::{
+  Protocol, Commitment, random_scalar,
+  transaction::Transaction,
+  wallet::{
+    address::{Network, AddressSpec, SubaddressIndex, MoneroAddress},
+    ViewPair, Scanner, Decoys, SpendableOutput, Fee, Change, SignableTransaction,
+    UnsignedTransaction, TxKeys, ProofError, TxProof, ReserveProof,
+  },
+};
+
+const AMOUNT: u64 = 1_000_000_000_000;
+
+fn view_pair() -> (Zeroizing<Scalar>, Zeroizing<Scalar>, ViewPair) {
+  let spend = Zeroizing::new(random_scalar(&mut OsRng));
+  let view = Zeroizing::new(random_scalar(&mut OsRng));
+  let pair = ViewPair::new(spend.deref() * &ED25519_BASEPOINT_TABLE, view.clone());
+  (spend, view, pair)
+}
+
+fn address() -> MoneroAddress {
+  view_pair().2.address(Network::Mainnet, AddressSpec::Standard)
+}
+
+// Create and sign a transaction making the specified payments, returning it with its keys
+fn transaction(payments: Vec<(MoneroAddress, u64)>, change: Change) -> (TxKeys, Transaction) {
+  let protocol = Protocol::v16;
+  let spend = Zeroizing::new(random_scalar(&mut OsRng));
+
+  let key_offset = random_scalar(&mut OsRng);
+  let commitment = Commitment::new(random_scalar(&mut OsRng), AMOUNT);
+  let input = SpendableOutput::for_test(
+    &(spend.deref() + key_offset) * &ED25519_BASEPOINT_TABLE,
+    key_offset,
+    commitment.clone(),
+    0,
+  );
+
+  let i = u8::try_from(OsRng.next_u64() % u64::try_from(protocol.ring_len()).unwrap()).unwrap();
+  let mut ring = vec![];
+  for o in 0 .. protocol.ring_len() {
+    ring.push(if o == usize::from(i) {
+      [input.key(), commitment.calculate()]
+    } else {
+      [
+        &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE,
+        Commitment::new(random_scalar(&mut OsRng), OsRng.next_u64()).calculate(),
+      ]
+    });
+  }
+  let decoys =
+    Decoys { i, offsets: (1 ..= u64::try_from(protocol.ring_len()).unwrap()).collect(), ring };
+
+  let mut r_seed = Zeroizing::new([0; 32]);
+  OsRng.fill_bytes(r_seed.as_mut());
+  let signable = SignableTransaction::new(
+    protocol,
+    Some(r_seed),
+    vec![input],
+    payments,
+    Some(change),
+    vec![],
+    Fee { per_weight: 1, mask: 1 },
+  )
+  .unwrap();
+  let keys = signable.tx_keys().unwrap();
+  let tx = UnsignedTransaction::new(signable, vec![decoys]).sign(&mut OsRng, &spend).unwrap();
+  (keys, tx)
+}
+
+fn test_tx_proof(subaddress: bool) {
+  let (_, view, recipient) = view_pair();
+  let mut scanner = Scanner::from_view(recipient.clone(), Some(HashSet::new()));
+  let spec = if subaddress {
+    let index = SubaddressIndex::new(0, 1).unwrap();
+    scanner.register_subaddress(index);
+    AddressSpec::Subaddress(index)
+  } else {
+    AddressSpec::Standard
+  };
+  let address = recipient.address(Network::Mainnet, spec);
+
+  // Use a change output without a view key, forcing additional keys when paying a subaddress
+  let (keys, tx) = transaction(
+    vec![(address, AMOUNT / 4), (view_pair().2.address(Network::Mainnet, spec), AMOUNT / 8)],
+    Change::fingerprintable(self::address()),
+  );
+  assert_eq!(keys.additional.is_empty(), !subaddress);
+  assert_eq!(scanner.scan_transaction(&tx).not_locked()[0].commitment().amount, AMOUNT / 4);
+
+  let outbound = TxProof::outbound(&mut OsRng, tx.hash(), &address, b"message", &keys);
+  let inbound = TxProof::inbound(&mut OsRng, &tx, &address, b"message", &view).unwrap();
+  for proof in [outbound, inbound] {
+    assert_eq!(proof.verify(&tx, &address, b"message"), Ok(AMOUNT / 4));
+    assert_eq!(proof.to_string().parse::<TxProof>().unwrap(), proof);
+
+    assert_eq!(proof.verify(&tx, &address, b"other message"), Err(ProofError::InvalidProof));
+    let other = view_pair().2.address(Network::Mainnet, spec);
+    assert_eq!(proof.verify(&tx, &other, b"message"), Err(ProofError::InvalidProof));
+  }
+
+  // An inbound proof requires the address's view key
+  assert_eq!(
+    TxProof::inbound(&mut OsRng, &tx, &address, b"message", &view_pair().1),
+    Err(ProofError::WrongPrivateKey)
+  );
+}
+
+#[test]
+fn tx_proof() {
+  test_tx_proof(false);
+}
+
+#[test]
+fn subaddress_tx_proof() {
+  test_tx_proof(true);
+}
+
+#[test]
+fn eventuality_derivation() {
+  // Transactions prepared before proofs were supported must still have the same Eventuality
+  let pair = |i: u64| {
+    ViewPair::new(&Scalar::from(i) * &ED25519_BASEPOINT_TABLE, Zeroizing::new(Scalar::from(i + 1)))
+  };
+  let address = |i| pair(i).address(Network::Mainnet, AddressSpec::Standard);
+  let input = |i: u64| {
+    SpendableOutput::for_test(
+      &Scalar::from(i) * &ED25519_BASEPOINT_TABLE,
+      Scalar::zero(),
+      Commitment::new(Scalar::one(), AMOUNT),
+      0,
+    )
+  };
+
+  for (payments, change, extra) in [
+    (
+      vec![(address(10), 1)],
+      Change::fingerprintable(address(20)),
+      hex!(
+        "01386d021a6b9a2ee229e4f2a73b4a3ce710c57ca16cffab29dfaa32e103f13c98020901d9b9d62479e004e5"
+      )
+      .to_vec(),
+    ),
+    (
+      vec![(address(10), 1), (address(30), 2)],
+      Change::new(&pair(20), false),
+      hex!("01386d021a6b9a2ee229e4f2a73b4a3ce710c57ca16cffab29dfaa32e103f13c98").to_vec(),
+    ),
+  ] {
+    let signable = SignableTransaction::new(
+      Protocol::v16,
+      Some(Zeroizing::new([0xaa; 32])),
+      vec![input(1), input(2)],
+      payments,
+      Some(change),
+      vec![],
+      Fee { per_weight: 1, mask: 1 },
+    )
+    .unwrap();
+    assert_eq!(signable.eventuality().unwrap().extra(), extra);
+  }
+}
+
+#[test]
+fn malformed_tx_proof() {
+  assert!("OutProofV2".parse::<TxProof>().is_err());
+  assert!("OutProofV3".parse::<TxProof>().is_err());
+  assert!("ReserveProofV2".parse::<TxProof>().is_err());
+}
+
+#[test]
+fn reserve_proof() {
+  let (spend, _, view) = view_pair();
+  let index = SubaddressIndex::new(0, 1).unwrap();
+  let mut scanner = Scanner::from_view(view.clone(), Some(HashSet::new()));
+  scanner.register_subaddress(index);
+
+  let change = || Change::fingerprintable(address());
+  let (_, first) = transaction(
+    vec![(view.address(Network::Mainnet, AddressSpec::Standard), AMOUNT / 2)],
+    change(),
+  );
+  let (_, second) = transaction(
+    vec![
+      (view.address(Network::Mainnet, AddressSpec::Subaddress(index)), AMOUNT / 4),
+      (address(), AMOUNT / 4),
+    ],
+    change(),
+  );
+  let txs = vec![first, second];
+  let outputs =
+    txs.iter().flat_map(|tx| scanner.scan_transaction(tx).not_locked()).collect::<Vec<_>>();
+  assert_eq!(outputs.len(), 2);
+
+  let proof = ReserveProof::new(&mut OsRng, &spend, &view, b"message", &outputs, &txs).unwrap();
+  let main = view.address(Network::Mainnet, AddressSpec::Standard);
+  assert_eq!(proof.verify(&main, b"message", &txs), Ok(AMOUNT / 2 + AMOUNT / 4));
+  assert_eq!(proof.key_images().len(), 2);
+  assert_eq!(proof.to_string().parse::<ReserveProof>().unwrap(), proof);
+
+  // Check the proof is encoded as wallet2's binary archive of its entries and spend keys
+  assert!(proof.to_string().starts_with("ReserveProofV2"));
+  let serialized = proof.serialize();
+  const ENTRY_LEN: usize = 1 + 32 + 1 + 32 + 32 + 64 + 64;
+  assert_eq!(serialized.len(), 1 + (2 * ENTRY_LEN) + 1 + (2 * (1 + 32 + 64)));
+  assert_eq!(serialized[0], 2);
+  for ((i, output), key_image) in outputs.iter().enumerate().zip(proof.key_images()) {
+    let entry = &serialized[(1 + (i * ENTRY_LEN)) .. (1 + ((i + 1) * ENTRY_LEN))];
+    // Version
+    assert_eq!(entry[0], 0);
+    assert_eq!(entry[1 .. 33], output.absolute.tx);
+    assert_eq!(entry[33], output.absolute.o);
+    assert_eq!(entry[66 .. 98], key_image.compress().to_bytes());
+  }
+  let spend_keys = &serialized[(1 + (2 * ENTRY_LEN)) ..];
+  assert_eq!(spend_keys[.. 2], [2, 2]);
+  assert_eq!(spend_keys[2 .. 34], view.spend().compress().to_bytes());
+
+  // Unknown entry versions, duplicated spend keys, and trailing data should be rejected
+  let encode = |serialized: &[u8]| {
+    format!("ReserveProofV2{}", base58_monero::base58::encode(serialized).unwrap())
+  };
+  assert_eq!(encode(&serialized).parse::<ReserveProof>().unwrap(), proof);
+  let mut versioned = serialized.clone();
+  versioned[1] = 1;
+  assert!(encode(&versioned).parse::<ReserveProof>().is_err());
+  let mut duplicated = serialized.clone();
+  let spend_keys = 1 + (2 * ENTRY_LEN);
+  duplicated.copy_within((spend_keys + 2) .. (spend_keys + 34), spend_keys + 1 + 1 + 32 + 64 + 1);
+  assert!(encode(&duplicated).parse::<ReserveProof>().is_err());
+  let mut trailing = serialized;
+  trailing.push(0);
+  assert!(encode(&trailing).parse::<ReserveProof>().is_err());
+
+  assert_eq!(proof.verify(&main, b"other message", &txs), Err(ProofError::InvalidProof));
+  assert_eq!(proof.verify(&main, b"message", &txs[.. 1]), Err(ProofError::MissingTransaction));
+  assert_eq!(proof.verify(&address(), b"message", &txs), Err(ProofError::InvalidProof));
+
+  // The spend key must be correct
+  assert_eq!(
+    ReserveProof::new(
+      &mut OsRng,
+      &Zeroizing::new(random_scalar(&mut OsRng)),
+      &view,
+      b"message",
+      &outputs,
+      &txs
+    ),
+    Err(ProofError::WrongPrivateKey)
+  );
+}
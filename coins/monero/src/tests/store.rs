@@ -0,0 +1,396 @@
+use hex_literal::hex;
+
+use zeroize::Zeroizing;
+use rand_core::{RngCore, OsRng};
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, edwards::EdwardsPoint};
+
+use crate::{
+  Commitment, random_scalar,
+  transaction::Timelock,
+  wallet::{
+    address::SubaddressIndex, ViewPair, SpendableOutput, Timelocked, StoreError, StoredTransaction,
+    WalletStore,
+  },
+};
+
+fn view_pair() -> ViewPair {
+  ViewPair::new(
+    &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE,
+    Zeroizing::new(random_scalar(&mut OsRng)),
+  )
+}
+
+fn output(global_index: u64) -> SpendableOutput {
+  SpendableOutput::for_test(
+    &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE,
+    random_scalar(&mut OsRng),
+    Commitment::new(random_scalar(&mut OsRng), 1_000_000),
+    global_index,
+  )
+}
+
+fn hash() -> [u8; 32] {
+  let mut hash = [0; 32];
+  OsRng.fill_bytes(&mut hash);
+  hash
+}
+
+fn transaction(block: usize) -> StoredTransaction {
+  StoredTransaction {
+    hash: hash(),
+    block,
+    received: 1_000_000,
+    spent: 0,
+    fee: 0,
+    timelock: Timelock::None,
+  }
+}
+
+fn key() -> Zeroizing<[u8; 32]> {
+  Zeroizing::new(hash())
+}
+
+// The length of an unencrypted commit record
+const COMMIT_LEN: usize = 4 + 1 + 8;
+
+fn serialize(store: &mut WalletStore) -> Vec<u8> {
+  let mut serialized = vec![];
+  store.write(&mut OsRng, &mut serialized).unwrap();
+  serialized
+}
+
+fn assert_eq_stores(a: &WalletStore, b: &WalletStore) {
+  assert_eq!(a.view_pair().spend(), b.view_pair().spend());
+  assert_eq!(a.view_pair().view(), b.view_pair().view());
+  assert_eq!(a.subaddresses(), b.subaddresses());
+  assert_eq!(a.height(), b.height());
+  for block in 0 .. a.height() {
+    assert_eq!(a.block_hash(block), b.block_hash(block));
+  }
+  assert_eq!(a.outputs(), b.outputs());
+  assert_eq!(a.transactions(), b.transactions());
+  assert_eq!(a.metadata(), b.metadata());
+}
+
+// Push a block with an output, returning the output's key
+fn push_output(store: &mut WalletStore, spent: &[EdwardsPoint]) -> EdwardsPoint {
+  let output = output(u64::try_from(store.height()).unwrap());
+  let key = output.key();
+  store.push_block(
+    hash(),
+    &[Timelocked(Timelock::Block(store.height() + 10), vec![output])],
+    spent,
+    vec![transaction(store.height())],
+  );
+  key
+}
+
+#[test]
+fn store() {
+  let mut store = WalletStore::new(view_pair(), 10, None);
+  assert_eq!(store.height(), 10);
+  assert_eq!(store.block_hash(9), None);
+
+  let subaddress = SubaddressIndex::new(0, 1).unwrap();
+  store.register_subaddress(subaddress);
+  store.register_subaddress(subaddress);
+  assert_eq!(store.subaddresses(), &[subaddress]);
+
+  let first = push_output(&mut store, &[]);
+  store.push_block(hash(), &[], &[], vec![]);
+  push_output(&mut store, &[first]);
+  assert_eq!(store.height(), 13);
+  assert!(store.block_hash(12).is_some());
+  assert_eq!(store.block_hash(13), None);
+
+  assert_eq!(store.outputs().len(), 2);
+  assert_eq!(store.outputs()[0].block, 10);
+  assert_eq!(store.outputs()[0].timelock, Timelock::Block(20));
+  assert_eq!(store.outputs()[0].spent, Some(12));
+  assert_eq!(store.unspent().count(), 1);
+  assert_eq!(store.transactions().len(), 2);
+
+  // The Scanner should treat the received outputs as used
+  let scanner = store.scanner();
+  assert!(scanner.burning_bug.as_ref().unwrap().contains(&first.compress()));
+  assert!(scanner.subaddresses.values().any(|index| *index == Some(subaddress)));
+
+  let serialized = serialize(&mut store);
+  assert_eq_stores(&store, &WalletStore::read::<&[u8]>(&mut serialized.as_ref(), None).unwrap());
+
+  // The version is checked
+  let mut future = serialized.clone();
+  future[8] = WalletStore::VERSION + 1;
+  assert!(matches!(
+    WalletStore::read::<&[u8]>(&mut future.as_ref(), None),
+    Err(StoreError::UnsupportedVersion(_))
+  ));
+  assert!(matches!(
+    WalletStore::read::<&[u8]>(&mut serialized[1 ..].as_ref(), None),
+    Err(StoreError::InvalidFormat)
+  ));
+}
+
+#[test]
+fn incremental() {
+  let mut store = WalletStore::new(view_pair(), 0, None);
+  // A store which was never written in full can't have its changes written
+  assert!(store.write_changes(&mut OsRng, &mut vec![]).is_err());
+
+  let mut serialized = serialize(&mut store);
+  let snapshot_len = serialized.len();
+
+  let key = push_output(&mut store, &[]);
+  store.register_subaddress(SubaddressIndex::new(1, 0).unwrap());
+  push_output(&mut store, &[key]);
+  store.write_changes(&mut OsRng, &mut serialized).unwrap();
+  assert_eq_stores(&store, &WalletStore::read::<&[u8]>(&mut serialized.as_ref(), None).unwrap());
+
+  // Changes aren't written twice
+  let len = serialized.len();
+  store.write_changes(&mut OsRng, &mut serialized).unwrap();
+  assert_eq!(serialized.len(), len);
+
+  // Rewriting the store compacts it
+  let compacted = serialize(&mut store);
+  assert!(compacted.len() > snapshot_len);
+  assert!(compacted.len() < serialized.len());
+  assert_eq_stores(&store, &WalletStore::read::<&[u8]>(&mut compacted.as_ref(), None).unwrap());
+  let mut serialized = compacted;
+
+  // A partially written record should be ignored, with the store then needing to be rewritten
+  let before = WalletStore::read::<&[u8]>(&mut serialized.as_ref(), None).unwrap();
+  push_output(&mut store, &[]);
+  store.write_changes(&mut OsRng, &mut serialized).unwrap();
+  serialized.pop();
+  let mut truncated = WalletStore::read::<&[u8]>(&mut serialized.as_ref(), None).unwrap();
+  assert_eq_stores(&before, &truncated);
+  assert!(truncated.write_changes(&mut OsRng, &mut vec![]).is_err());
+
+  // As should completely written records which weren't committed to
+  serialized.truncate(serialized.len() - (COMMIT_LEN - 1));
+  let mut uncommitted = WalletStore::read::<&[u8]>(&mut serialized.as_ref(), None).unwrap();
+  assert_eq_stores(&before, &uncommitted);
+  assert!(uncommitted.write_changes(&mut OsRng, &mut vec![]).is_err());
+}
+
+#[test]
+fn encrypted() {
+  let key = key();
+  let mut store = WalletStore::new(view_pair(), 0, Some(key.clone()));
+  let mut serialized = serialize(&mut store);
+  push_output(&mut store, &[]);
+  store.write_changes(&mut OsRng, &mut serialized).unwrap();
+
+  // The spend key shouldn't be present in the clear
+  let spend = store.view_pair().spend().compress();
+  assert!(!serialized.windows(32).any(|window| window == spend.0));
+
+  assert_eq_stores(
+    &store,
+    &WalletStore::read::<&[u8]>(&mut serialized.as_ref(), Some(key.clone())).unwrap(),
+  );
+  assert!(matches!(
+    WalletStore::read::<&[u8]>(&mut serialized.as_ref(), None),
+    Err(StoreError::EncryptionKeyRequired)
+  ));
+  assert!(matches!(
+    WalletStore::read::<&[u8]>(&mut serialized.as_ref(), Some(self::key())),
+    Err(StoreError::InvalidEncryptionKey)
+  ));
+
+  // Modified records should be rejected
+  let mut modified = serialized.clone();
+  *modified.last_mut().unwrap() ^= 1;
+  assert!(matches!(
+    WalletStore::read::<&[u8]>(&mut modified.as_ref(), Some(key.clone())),
+    Err(StoreError::InvalidFormat)
+  ));
+
+  // Records from another serialization of the store should be rejected, even at the same position
+  let mut store = WalletStore::new(view_pair(), 0, Some(key.clone()));
+  let mut first = serialize(&mut store);
+  let first_len = first.len();
+  push_output(&mut store, &[]);
+  store.write_changes(&mut OsRng, &mut first).unwrap();
+  let mut second = serialize(&mut store);
+  second.extend(&first[first_len ..]);
+  assert!(matches!(
+    WalletStore::read::<&[u8]>(&mut second.as_ref(), Some(key)),
+    Err(StoreError::InvalidFormat)
+  ));
+
+  let mut plaintext = WalletStore::new(view_pair(), 0, None);
+  assert!(matches!(
+    WalletStore::read::<&[u8]>(&mut serialize(&mut plaintext).as_ref(), Some(self::key())),
+    Err(StoreError::NotEncrypted)
+  ));
+}
+
+#[test]
+fn metadata() {
+  let key = key();
+  let mut store = WalletStore::new(view_pair(), 0, Some(key.clone()));
+  store.set_metadata(Zeroizing::new(b"first".to_vec()));
+  let mut serialized = serialize(&mut store);
+  assert_eq!(
+    WalletStore::read::<&[u8]>(&mut serialized.as_ref(), Some(key.clone())).unwrap().metadata(),
+    b"first"
+  );
+
+  // Setting the same metadata doesn't record a change
+  let len = serialized.len();
+  store.set_metadata(Zeroizing::new(b"first".to_vec()));
+  store.write_changes(&mut OsRng, &mut serialized).unwrap();
+  assert_eq!(serialized.len(), len);
+
+  // Replacing the metadata is recorded as a change, which isn't present in the clear
+  push_output(&mut store, &[]);
+  store.set_metadata(Zeroizing::new(b"second".to_vec()));
+  store.write_changes(&mut OsRng, &mut serialized).unwrap();
+  assert!(!serialized.windows(6).any(|window| window == b"second"));
+  let read = WalletStore::read::<&[u8]>(&mut serialized.as_ref(), Some(key)).unwrap();
+  assert_eq_stores(&store, &read);
+  assert_eq!(read.metadata(), b"second");
+
+  // Rolling back doesn't affect the metadata
+  store.rollback(0);
+  assert_eq!(store.metadata(), b"second");
+}
+
+#[test]
+fn rollback() {
+  let mut store = WalletStore::new(view_pair(), 0, None);
+  let mut serialized = serialize(&mut store);
+
+  let first = push_output(&mut store, &[]);
+  let hash = store.block_hash(0).unwrap();
+  let second = push_output(&mut store, &[]);
+  push_output(&mut store, &[first]);
+  assert_eq!(store.outputs()[0].spent, Some(2));
+
+  store.rollback(2);
+  assert_eq!(store.height(), 2);
+  assert_eq!(store.block_hash(2), None);
+  assert_eq!(store.outputs().len(), 2);
+  assert_eq!(store.outputs()[0].spent, None);
+  assert_eq!(store.transactions().len(), 2);
+
+  store.rollback(1);
+  assert_eq!(store.height(), 1);
+  assert_eq!(store.block_hash(0), Some(hash));
+  assert_eq!(store.outputs().len(), 1);
+  assert_eq!(store.transactions().len(), 1);
+  // Removed outputs should no longer be considered used
+  let used = store.scanner().burning_bug.clone().unwrap();
+  assert!(used.contains(&first.compress()));
+  assert!(!used.contains(&second.compress()));
+
+  // Rolling back to after the current height is a no-op
+  store.rollback(5);
+  assert_eq!(store.height(), 1);
+
+  // Rescan
+  push_output(&mut store, &[first]);
+  store.write_changes(&mut OsRng, &mut serialized).unwrap();
+  assert_eq_stores(&store, &WalletStore::read::<&[u8]>(&mut serialized.as_ref(), None).unwrap());
+  assert_eq!(store.outputs()[0].spent, Some(1));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn persistence() {
+  let key = key();
+  let path = std::env::temp_dir().join(format!("monero-serai-store-{}", OsRng.next_u64()));
+
+  let mut store = WalletStore::new(view_pair(), 0, Some(key.clone()));
+  store.save(&mut OsRng, &path).unwrap();
+  push_output(&mut store, &[]);
+  store.save(&mut OsRng, &path).unwrap();
+  let len = std::fs::metadata(&path).unwrap().len();
+
+  // Further saves append
+  let mut loaded = WalletStore::load(&path, Some(key.clone())).unwrap();
+  assert_eq_stores(&store, &loaded);
+  push_output(&mut loaded, &[]);
+  loaded.save(&mut OsRng, &path).unwrap();
+  assert!(std::fs::metadata(&path).unwrap().len() > len);
+  assert_eq_stores(&loaded, &WalletStore::load(&path, Some(key)).unwrap());
+
+  std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn migration() {
+  // Stores written by versions 0 and 1, with a subaddress and two blocks, the latter appended
+  let stores = [
+    (
+      None,
+      hex!(
+        "4d5357414c4c4554000081000000005866666666666666666666666666666666666666666666666666666666"
+        "6666660200000000000000000000000000000000000000000000000000000000000000010000000000000001"
+        "0000000b00000000000000010000000101010101010101010101010101010101010101010101010101010101"
+        "01010100000000000000002d0000000202020202020202020202020202020202020202020202020202020202"
+        "02020202000000000000000000000000"
+      )
+      .as_ref(),
+    ),
+    (
+      Some(Zeroizing::new([7; 32])),
+      hex!(
+        "4d5357414c4c45540001ad000000454e9ddf3d067fca6f66bf798ceabecf39d1abdcf57162bc5b54c544131d"
+        "6720fc326d381bc9d83fde7fb52c601b581670900ba06c7f0be6791aa43a05d41fa5f7aa9d83c15d4515c52d"
+        "f279af5078597e1470df0df91d02a8bb7fb13a95cb97f3dbf14f5f39fc721898e3a0aed5f3121aee0ae7784d"
+        "da2c5f039abc88629b6e7364ec7d8953c6f31bd086af0a449ce1e3733ec4b6ea190f5a564ff8cdddb505d527"
+        "fc452373d868cd6316f8e4590000006754fbe31a8058236fbd47bda51b62c3f605cfaf791029603d5734f285"
+        "4cca653ec8b122627090b201475c40e7f8d837560dc395211951fb4e252269dcfa47ff20155f2f91d63f10b3"
+        "aa5e5e495b6bf867f6caa981a0eafa84"
+      )
+      .as_ref(),
+    ),
+    (
+      None,
+      hex!(
+        "4d5357414c4c455401007035cc2c23776a4ecb79862fa09385a1546baa90dc9daaaa0107ef3c825f007a8100"
+        "0000005866666666666666666666666666666666666666666666666666666666666666020000000000000000"
+        "00000000000000000000000000000000000000000000000100000000000000010000000b0000000000000001"
+        "0000000101010101010101010101010101010101010101010101010101010101010101000000000000000009"
+        "0000000401000000000000002d00000002020202020202020202020202020202020202020202020202020202"
+        "020202020200000000000000000000000009000000040300000000000000"
+      )
+      .as_ref(),
+    ),
+    (
+      Some(Zeroizing::new([7; 32])),
+      hex!(
+        "4d5357414c4c455401015fe0421e85515dfd325957ae1b67c882bd33a4552b7f6c49a6e7ea30c7917bed9d00"
+        "0000736c80e190617eba427ad2295982414e7ad254de3b60b730fc7ee42093276cc4f8dc6cc81e00a2b7bffe"
+        "276a3c4806283f9fbfb15f10eda42af3caf781706aac5cfcd7ac0dcb60d17e1813eb335bbdd492f150a0ae9e"
+        "3e9c44a9b6e0e87c1f334bfa662f1448745d53bb5c26c04534758df49b9e81c8e889376f24ffeb6326398af5"
+        "6ca73e8455055565cc765e0300875b9d51c812b117c9293fe43cbc25000000a8023f91afc923ed12f49e66fe"
+        "65e5bc87388f6734946a857226af53fda7b271d266c872fb4900000039c80e08218e773030adb437f5c84145"
+        "5bbfd1a254f0799c92936e34d374bc2c938de0c269776e37ca464574559775bd027f2465fb3bf5b8de553dca"
+        "c8a0f85586a46403731cb7621c250000001f6a20622126b52d2c7b83c80bb95acc908e0b337cfb0719926694"
+        "a624c648188ce218d53c"
+      )
+      .as_ref(),
+    ),
+  ];
+
+  for (key, serialized) in stores {
+    let mut store = WalletStore::read::<&[u8]>(&mut &*serialized, key.clone()).unwrap();
+    assert_eq!(store.view_pair().spend(), ED25519_BASEPOINT_TABLE.basepoint());
+    assert_eq!(store.subaddresses(), &[SubaddressIndex::new(0, 1).unwrap()]);
+    assert_eq!(store.height(), 12);
+    assert_eq!(store.block_hash(10), Some([1; 32]));
+    assert_eq!(store.block_hash(11), Some([2; 32]));
+    assert!(store.metadata().is_empty());
+
+    // The store must be written in full to migrate it
+    assert!(store.write_changes(&mut OsRng, &mut vec![]).is_err());
+    let migrated = serialize(&mut store);
+    assert_eq!(migrated[8], WalletStore::VERSION);
+    assert_eq_stores(&store, &WalletStore::read::<&[u8]>(&mut migrated.as_ref(), key).unwrap());
+  }
+}
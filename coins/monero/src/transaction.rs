@@ -1,5 +1,8 @@
 use core::cmp::Ordering;
-use std::io::{self, Read, Write};
+use std_shims::{
+  vec::Vec,
+  io::{self, Read, Write},
+};
 
 use zeroize::Zeroize;
 
@@ -121,7 +124,7 @@ pub enum Timelock {
 }
 
 impl Timelock {
-  fn from_raw(raw: u64) -> Timelock {
+  pub(crate) fn from_raw(raw: u64) -> Timelock {
     if raw == 0 {
       Timelock::None
     } else if raw < 500_000_000 {
@@ -131,7 +134,7 @@ impl Timelock {
     }
   }
 
-  fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+  pub(crate) fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
     write_varint(
       &match self {
         Timelock::None => 0,
@@ -0,0 +1,258 @@
+use core::ops::Deref;
+use std::path::Path;
+
+use thiserror::Error;
+
+use zeroize::Zeroizing;
+use rand_core::{RngCore, CryptoRng};
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar};
+
+use crate::{
+  ringct::generate_key_image,
+  transaction::Input,
+  rpc::{RpcError, Rpc},
+  wallet::{
+    address::SubaddressIndex, Scanner, SpendableOutput, Timelocked, KeyImageError, SignedKeyImage,
+    StoreError, StoredTransaction, WalletStore,
+  },
+};
+
+/// Errors when scanning the blockchain.
+#[derive(Clone, PartialEq, Eq, Debug, Error)]
+pub enum ScanError {
+  #[error("rpc error ({0})")]
+  RpcError(RpcError),
+  #[error("reorganization deeper than the retained block hashes")]
+  ReorganizationTooDeep,
+}
+
+/// The result of scanning a block.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ScanEvent {
+  /// The block with the specified number was scanned.
+  Block(usize),
+  /// A reorganization occurred, with every block from the specified number onwards rolled back.
+  Reorganization(usize),
+}
+
+/// A scanner which follows the blockchain, detecting and handling reorganizations.
+///
+/// Each scanned block's previous hash is checked against the hash of the block scanned before it.
+/// On mismatch, the most recent block still on the chain is found and every block after it is
+/// rolled back, removing the outputs received in them and marking the outputs spent in them as
+/// unspent. Scanning then resumes from the fork point.
+///
+/// Reorganizations deeper than the block hashes retained by the WalletStore can't be handled.
+pub struct ChainScanner {
+  store: WalletStore,
+  scanner: Scanner,
+  // The spend key, if held, used to generate the key images of received outputs
+  spend: Option<Zeroizing<Scalar>>,
+}
+
+impl ChainScanner {
+  pub fn new(store: WalletStore) -> ChainScanner {
+    ChainScanner { scanner: store.scanner(), store, spend: None }
+  }
+
+  /// Create a ChainScanner holding the wallet's spend key, which detects when outputs are spent
+  /// without their key images being imported.
+  pub fn with_spend_key(store: WalletStore, spend: Zeroizing<Scalar>) -> ChainScanner {
+    assert_eq!(
+      spend.deref() * &ED25519_BASEPOINT_TABLE,
+      store.view_pair().spend(),
+      "spend key doesn't correspond to the store's view pair"
+    );
+    let mut scanner = ChainScanner { scanner: store.scanner(), store, spend: Some(spend) };
+    let outputs = scanner.store.outputs().iter().map(|output| output.output.clone()).collect();
+    scanner.generate_key_images(outputs);
+    scanner
+  }
+
+  // Generate the key images for the specified outputs, if the spend key is held
+  fn generate_key_images(&mut self, outputs: Vec<SpendableOutput>) {
+    if let Some(spend) = &self.spend {
+      for output in outputs {
+        let key_image = generate_key_image(&Zeroizing::new(spend.deref() + output.key_offset()));
+        self.scanner.key_images.insert(key_image.compress(), output.key());
+      }
+    }
+  }
+
+  pub fn store(&self) -> &WalletStore {
+    &self.store
+  }
+
+  pub fn into_store(self) -> WalletStore {
+    self.store
+  }
+
+  /// Save the underlying store, as with WalletStore::save.
+  pub fn save<R: RngCore + CryptoRng>(
+    &mut self,
+    rng: &mut R,
+    path: &Path,
+  ) -> Result<(), StoreError> {
+    self.store.save(rng, path)
+  }
+
+  /// Set the store's metadata, as with WalletStore::set_metadata.
+  pub fn set_metadata(&mut self, metadata: Zeroizing<Vec<u8>>) {
+    self.store.set_metadata(metadata);
+  }
+
+  /// Register a subaddress with both the store and the scanner.
+  pub fn register_subaddress(&mut self, subaddress: SubaddressIndex) {
+    self.store.register_subaddress(subaddress);
+    self.scanner.register_subaddress(subaddress);
+  }
+
+  /// Import signed key images, as with Scanner::import_key_images.
+  pub fn import_key_images(&mut self, key_images: &[SignedKeyImage]) -> Result<(), KeyImageError> {
+    self.scanner.import_key_images(key_images)
+  }
+
+  // Find the number of the first block which is no longer on the chain
+  async fn fork(&self, rpc: &Rpc, height: usize) -> Result<usize, ScanError> {
+    let mut number = self.store.height();
+    while number != 0 {
+      let hash = match self.store.block_hash(number - 1) {
+        Some(hash) => hash,
+        // If no blocks have been scanned since the store's creation, there's nothing to check
+        None if number == self.store.height() => break,
+        None => Err(ScanError::ReorganizationTooDeep)?,
+      };
+      if ((number - 1) < height) &&
+        (rpc.get_block_hash(number - 1).await.map_err(ScanError::RpcError)? == hash)
+      {
+        break;
+      }
+      number -= 1;
+    }
+    Ok(number)
+  }
+
+  // Roll back every block from the specified number onwards
+  fn rollback(&mut self, fork: usize) {
+    for output in self.store.outputs() {
+      let key = output.output.key().compress();
+      // Outputs from orphaned blocks may be included again, so they're no longer considered used
+      if output.block >= fork {
+        if let Some(burning_bug) = self.scanner.burning_bug.as_mut() {
+          burning_bug.remove(&key);
+        }
+      }
+      if (output.block >= fork) || output.spent.map(|spent| spent >= fork).unwrap_or(false) {
+        self.scanner.spent.remove(&key);
+      }
+    }
+    self.store.rollback(fork);
+  }
+
+  // Scan the next block, where height is the chain's current height
+  async fn scan_block(&mut self, rpc: &Rpc, height: usize) -> Result<ScanEvent, ScanError> {
+    let number = self.store.height();
+    let hash = rpc.get_block_hash(number).await.map_err(ScanError::RpcError)?;
+    let block = rpc.get_block(hash).await.map_err(ScanError::RpcError)?;
+    match block.miner_tx.prefix.inputs.first() {
+      Some(Input::Gen(actual)) if usize::try_from(*actual) == Ok(number) => (),
+      _ => Err(ScanError::RpcError(RpcError::InvalidNode))?,
+    }
+
+    if let Some(previous) =
+      number.checked_sub(1).and_then(|previous| self.store.block_hash(previous))
+    {
+      if block.header.previous != previous {
+        let fork = self.fork(rpc, height).await?;
+        self.rollback(fork);
+        return Ok(ScanEvent::Reorganization(fork));
+      }
+    }
+
+    let mut outputs: Vec<Timelocked<_>> = vec![];
+    let mut spent = vec![];
+    let mut transactions = vec![];
+    for (tx, received) in
+      self.scanner.scan_block_transactions(rpc, &block).await.map_err(ScanError::RpcError)?
+    {
+      // Find the outputs spent by this transaction, via the imported key images
+      let mut spent_amount = 0;
+      for input in &tx.prefix.inputs {
+        if let Input::ToKey { key_image, .. } = input {
+          if let Some(key) = self.scanner.key_images.get(&key_image.compress()) {
+            let amount = self
+              .store
+              .unspent()
+              .map(|output| output.output.clone())
+              .chain(outputs.iter().flat_map(Timelocked::ignore_timelock))
+              .find(|output| output.key() == *key)
+              .map(|output| output.commitment().amount);
+            if let Some(amount) = amount {
+              spent.push(*key);
+              spent_amount += amount;
+            }
+          }
+        }
+      }
+
+      // Outputs may be spent within the block they're received in
+      self.generate_key_images(received.1.clone());
+
+      if received.1.is_empty() && (spent_amount == 0) {
+        continue;
+      }
+      transactions.push(StoredTransaction {
+        hash: tx.hash(),
+        block: number,
+        received: received.1.iter().map(|output| output.commitment().amount).sum(),
+        spent: spent_amount,
+        fee: tx.rct_signatures.base.fee,
+        timelock: tx.prefix.timelock,
+      });
+      if !received.1.is_empty() {
+        outputs.push(received);
+      }
+    }
+
+    self.store.push_block(hash, &outputs, &spent, transactions);
+    Ok(ScanEvent::Block(number))
+  }
+
+  /// Scan until the tip of the chain, returning the fork point of every reorganization handled.
+  pub async fn sync(&mut self, rpc: &Rpc) -> Result<Vec<usize>, ScanError> {
+    self.sync_blocks(rpc, usize::MAX).await.map(|(reorganizations, _)| reorganizations)
+  }
+
+  /// Scan up to the specified amount of blocks, returning the fork point of every reorganization
+  /// handled and if the tip of the chain was reached.
+  pub async fn sync_blocks(
+    &mut self,
+    rpc: &Rpc,
+    max_blocks: usize,
+  ) -> Result<(Vec<usize>, bool), ScanError> {
+    let mut reorganizations = vec![];
+    let mut scanned = 0;
+    loop {
+      let height = rpc.get_height().await.map_err(ScanError::RpcError)?;
+      while self.store.height() < height {
+        if scanned == max_blocks {
+          return Ok((reorganizations, false));
+        }
+        match self.scan_block(rpc, height).await? {
+          ScanEvent::Block(_) => scanned += 1,
+          ScanEvent::Reorganization(fork) => reorganizations.push(fork),
+        }
+      }
+
+      // The latest block scanned is only checked once its successor is, so check it against the
+      // chain's tip, which also detects the chain becoming shorter
+      let fork = self.fork(rpc, height).await?;
+      if fork == self.store.height() {
+        return Ok((reorganizations, true));
+      }
+      self.rollback(fork);
+      reorganizations.push(fork);
+    }
+  }
+}
@@ -1,28 +1,46 @@
-use std::collections::HashSet;
+use std_shims::{vec::Vec, io};
+#[cfg(feature = "rpc")]
+use std_shims::collections::HashSet;
 
+#[cfg(feature = "rpc")]
 use futures::lock::{Mutex, MutexGuard};
 
+#[cfg(feature = "rpc")]
 use lazy_static::lazy_static;
 
+#[cfg(feature = "rpc")]
 use rand_core::{RngCore, CryptoRng};
+#[cfg(feature = "rpc")]
 use rand_distr::{Distribution, Gamma};
 
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use curve25519_dalek::edwards::EdwardsPoint;
 
+use crate::serialize::{
+  read_byte, read_varint, read_point, read_vec, write_varint, write_point, write_vec,
+};
+
+#[cfg(feature = "rpc")]
 use crate::{
   wallet::SpendableOutput,
   rpc::{RpcError, Rpc},
 };
 
+#[cfg(feature = "rpc")]
 const LOCK_WINDOW: usize = 10;
+#[cfg(feature = "rpc")]
 const MATURITY: u64 = 60;
+#[cfg(feature = "rpc")]
 const RECENT_WINDOW: usize = 15;
+#[cfg(feature = "rpc")]
 const BLOCK_TIME: usize = 120;
+#[cfg(feature = "rpc")]
 const BLOCKS_PER_YEAR: usize = 365 * 24 * 60 * 60 / BLOCK_TIME;
+#[cfg(feature = "rpc")]
 const TIP_APPLICATION: f64 = (LOCK_WINDOW * BLOCK_TIME) as f64;
 
+#[cfg(feature = "rpc")]
 lazy_static! {
   static ref GAMMA: Gamma<f64> = Gamma::new(19.28, 1.0 / 1.61).unwrap();
   // TODO: Expose an API to reset this in case a reorg occurs/the RPC fails/returns garbage
@@ -30,6 +48,7 @@ lazy_static! {
   static ref DISTRIBUTION: Mutex<Vec<u64>> = Mutex::new(Vec::with_capacity(3000000));
 }
 
+#[cfg(feature = "rpc")]
 #[allow(clippy::too_many_arguments)]
 async fn select_n<'a, R: RngCore + CryptoRng>(
   rng: &mut R,
@@ -114,6 +133,7 @@ async fn select_n<'a, R: RngCore + CryptoRng>(
   Ok(confirmed)
 }
 
+#[cfg(feature = "rpc")]
 fn offset(ring: &[u64]) -> Vec<u64> {
   let mut res = vec![ring[0]];
   res.resize(ring.len(), 0);
@@ -136,7 +156,33 @@ impl Decoys {
     self.offsets.len()
   }
 
+  pub fn write<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+    w.write_all(&[self.i])?;
+    write_vec(write_varint, &self.offsets, w)?;
+    write_vec(
+      |member: &[EdwardsPoint; 2], w: &mut W| {
+        write_point(&member[0], w)?;
+        write_point(&member[1], w)
+      },
+      &self.ring,
+      w,
+    )
+  }
+
+  pub fn read<R: io::Read>(r: &mut R) -> io::Result<Decoys> {
+    let decoys = Decoys {
+      i: read_byte(r)?,
+      offsets: read_vec(read_varint, r)?,
+      ring: read_vec(|r: &mut R| Ok([read_point(r)?, read_point(r)?]), r)?,
+    };
+    if (usize::from(decoys.i) >= decoys.len()) || (decoys.offsets.len() != decoys.ring.len()) {
+      Err(io::Error::new(io::ErrorKind::Other, "invalid decoys"))?;
+    }
+    Ok(decoys)
+  }
+
   /// Select decoys using the same distribution as Monero.
+  #[cfg(feature = "rpc")]
   pub async fn select<R: RngCore + CryptoRng>(
     rng: &mut R,
     rpc: &Rpc,
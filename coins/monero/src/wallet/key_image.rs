@@ -0,0 +1,161 @@
+use core::ops::Deref;
+use std_shims::{
+  vec::Vec,
+  io::{self, Read, Write},
+};
+
+use rand_core::{RngCore, CryptoRng};
+
+use zeroize::{Zeroize, Zeroizing};
+
+use curve25519_dalek::{
+  constants::ED25519_BASEPOINT_TABLE, traits::IsIdentity, scalar::Scalar, edwards::EdwardsPoint,
+};
+
+use crate::{
+  random_scalar, hash_to_scalar,
+  serialize::{read_scalar, read_point, write_scalar, write_point},
+  ringct::{generate_key_image, hash_to_point},
+  wallet::ReceivedOutput,
+};
+
+/// Errors when creating or importing key images.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+pub enum KeyImageError {
+  #[cfg_attr(feature = "std", error("wrong spend private key"))]
+  WrongPrivateKey,
+  #[cfg_attr(feature = "std", error("invalid key image"))]
+  InvalidKeyImage,
+  #[cfg_attr(feature = "std", error("invalid key image signature"))]
+  InvalidSignature,
+}
+
+// A ring signature with a ring of just the output key, proving the key image is correct
+// This is Monero's generate_ring_signature, as used for key image exports and reserve proofs
+#[allow(non_snake_case)]
+fn challenge(msg: &[u8; 32], L: &EdwardsPoint, R: &EdwardsPoint) -> Scalar {
+  let mut buf = Vec::with_capacity(96);
+  buf.extend(msg);
+  buf.extend(L.compress().to_bytes());
+  buf.extend(R.compress().to_bytes());
+  hash_to_scalar(&buf)
+}
+
+pub(crate) fn sign_key_image<R: RngCore + CryptoRng>(
+  rng: &mut R,
+  msg: &[u8; 32],
+  key: EdwardsPoint,
+  secret: &Zeroizing<Scalar>,
+) -> (Scalar, Scalar) {
+  let nonce = Zeroizing::new(random_scalar(rng));
+  let c = challenge(
+    msg,
+    &(nonce.deref() * &ED25519_BASEPOINT_TABLE),
+    &(nonce.deref() * hash_to_point(key)),
+  );
+  (c, nonce.deref() - (c * secret.deref()))
+}
+
+// This doesn't check the key image is torsion free, which callers must
+#[allow(non_snake_case)]
+pub(crate) fn verify_key_image(
+  msg: &[u8; 32],
+  key: EdwardsPoint,
+  key_image: EdwardsPoint,
+  (c, r): (Scalar, Scalar),
+) -> bool {
+  let L = (&r * &ED25519_BASEPOINT_TABLE) + (c * key);
+  let R = (r * hash_to_point(key)) + (c * key_image);
+  challenge(msg, &L, &R) == c
+}
+
+/// A key image for an output, with a signature proving it was correctly generated.
+///
+/// This enables a watch-only wallet, which can't generate key images, to detect when its outputs
+/// are spent. The holder of the spend key creates these for the outputs the watch-only wallet
+/// scanned, which are then imported into its Scanner.
+///
+/// The signature is a ring signature with a ring of just the output key, making it compatible
+/// with wallet2's key image exports.
+#[derive(Clone, PartialEq, Eq, Debug, Zeroize)]
+pub struct SignedKeyImage {
+  key: EdwardsPoint,
+  key_image: EdwardsPoint,
+  c: Scalar,
+  r: Scalar,
+}
+
+impl SignedKeyImage {
+  /// Generate and sign the key image for an output.
+  pub fn new<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    spend: &Zeroizing<Scalar>,
+    output: &ReceivedOutput,
+  ) -> Result<SignedKeyImage, KeyImageError> {
+    let key = output.key();
+    let offset = Zeroizing::new(spend.deref() + output.key_offset());
+    if (offset.deref() * &ED25519_BASEPOINT_TABLE) != key {
+      Err(KeyImageError::WrongPrivateKey)?;
+    }
+    let key_image = generate_key_image(&offset);
+    // wallet2 uses the key image itself as the message
+    let (c, r) = sign_key_image(rng, &key_image.compress().to_bytes(), key, &offset);
+
+    Ok(SignedKeyImage { key, key_image, c, r })
+  }
+
+  /// The key of the output this key image is for.
+  pub fn key(&self) -> EdwardsPoint {
+    self.key
+  }
+
+  pub fn key_image(&self) -> EdwardsPoint {
+    self.key_image
+  }
+
+  /// Verify the key image was correctly generated for its output key.
+  pub fn verify(&self) -> Result<(), KeyImageError> {
+    // Monero checks the key image is in the prime order subgroup, preventing the same output from
+    // having multiple, distinct key images
+    if (!self.key.is_torsion_free()) ||
+      (!self.key_image.is_torsion_free()) ||
+      self.key_image.is_identity()
+    {
+      Err(KeyImageError::InvalidKeyImage)?;
+    }
+
+    if !verify_key_image(
+      &self.key_image.compress().to_bytes(),
+      self.key,
+      self.key_image,
+      (self.c, self.r),
+    ) {
+      Err(KeyImageError::InvalidSignature)?;
+    }
+    Ok(())
+  }
+
+  pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    write_point(&self.key, w)?;
+    write_point(&self.key_image, w)?;
+    write_scalar(&self.c, w)?;
+    write_scalar(&self.r, w)
+  }
+
+  pub fn serialize(&self) -> Vec<u8> {
+    let mut serialized = Vec::with_capacity(128);
+    self.write(&mut serialized).unwrap();
+    serialized
+  }
+
+  /// Read a SignedKeyImage. This does not verify it.
+  pub fn read<R: Read>(r: &mut R) -> io::Result<SignedKeyImage> {
+    Ok(SignedKeyImage {
+      key: read_point(r)?,
+      key_image: read_point(r)?,
+      c: read_scalar(r)?,
+      r: read_scalar(r)?,
+    })
+  }
+}
@@ -1,5 +1,5 @@
 use core::ops::Deref;
-use std::collections::{HashSet, HashMap};
+use std_shims::collections::{HashSet, HashMap};
 
 use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
@@ -20,23 +20,42 @@ pub mod seed;
 /// Address encoding and decoding functionality.
 pub mod address;
 use address::{Network, AddressType, SubaddressIndex, AddressSpec, AddressMeta, MoneroAddress};
+/// `monero:` URI parsing and generation.
+pub mod uri;
+/// OpenAlias record parsing.
+pub mod openalias;
 
 mod scan;
 pub use scan::{ReceivedOutput, SpendableOutput, Timelocked};
 
+mod key_image;
+pub use key_image::{KeyImageError, SignedKeyImage};
+
+mod proof;
+pub use proof::{TxKeys, ProofError, TxProof, ReserveProof};
+
+mod store;
+pub use store::{StoreError, StoredOutput, StoredTransaction, WalletStore};
+
+#[cfg(feature = "rpc")]
+mod chain;
+#[cfg(feature = "rpc")]
+pub use chain::{ScanError, ScanEvent, ChainScanner};
+
 pub(crate) mod decoys;
 pub(crate) use decoys::Decoys;
 
 mod send;
-pub use send::{
-  Fee, TransactionError, Change, SignableTransaction, SignableTransactionBuilder, Eventuality,
-};
+pub use send::{Fee, TransactionError, Change, SignableTransaction, Eventuality};
+pub use send::UnsignedTransaction;
+#[cfg(feature = "std")]
+pub use send::SignableTransactionBuilder;
 #[cfg(feature = "multisig")]
 pub(crate) use send::InternalPayment;
 #[cfg(feature = "multisig")]
 pub use send::TransactionMachine;
 
-fn key_image_sort(x: &EdwardsPoint, y: &EdwardsPoint) -> std::cmp::Ordering {
+fn key_image_sort(x: &EdwardsPoint, y: &EdwardsPoint) -> core::cmp::Ordering {
   x.compress().to_bytes().cmp(&y.compress().to_bytes()).reverse()
 }
 
@@ -114,6 +133,23 @@ impl ViewPair {
     ViewPair { spend, view }
   }
 
+  /// Create a ViewPair whose private view key is derived from the public spend key and a seed.
+  ///
+  /// This is intended for multisig wallets, where every participant holds the view key. Binding
+  /// the view key to the group's spend key ensures every set of keys has a distinct view key, so
+  /// the compromise of one set's view key doesn't reveal the incoming transactions of any other.
+  /// The seed SHOULD be secret to the participants, as anyone with it can derive the view key.
+  ///
+  /// This does not make scanning require threshold cooperation. Any single participant with the
+  /// seed is able to scan for the group.
+  pub fn derived(spend: EdwardsPoint, seed: &Zeroizing<[u8; 32]>) -> ViewPair {
+    let view = Zeroizing::new(hash_to_scalar(&Zeroizing::new(
+      [b"monero_serai_derived_view_key".as_ref(), &spend.compress().to_bytes(), seed.as_ref()]
+        .concat(),
+    )));
+    ViewPair { spend, view }
+  }
+
   pub fn spend(&self) -> EdwardsPoint {
     self.spend
   }
@@ -181,6 +217,10 @@ pub struct Scanner {
   // Also contains the spend key as None
   pub(crate) subaddresses: HashMap<CompressedEdwardsY, Option<SubaddressIndex>>,
   pub(crate) burning_bug: Option<HashSet<CompressedEdwardsY>>,
+  // Imported key images, mapped to the keys of the outputs they're for
+  key_images: HashMap<CompressedEdwardsY, EdwardsPoint>,
+  // The keys of outputs whose key images were seen spent
+  spent: HashSet<CompressedEdwardsY>,
 }
 
 impl Zeroize for Scanner {
@@ -197,6 +237,13 @@ impl Zeroize for Scanner {
         output.zeroize();
       }
     }
+    for (mut key_image, mut key) in self.key_images.drain() {
+      key_image.zeroize();
+      key.zeroize();
+    }
+    for mut key in self.spent.drain() {
+      key.zeroize();
+    }
   }
 }
 
@@ -218,7 +265,7 @@ impl Scanner {
   pub fn from_view(pair: ViewPair, burning_bug: Option<HashSet<CompressedEdwardsY>>) -> Scanner {
     let mut subaddresses = HashMap::new();
     subaddresses.insert(pair.spend.compress(), None);
-    Scanner { pair, subaddresses, burning_bug }
+    Scanner { pair, subaddresses, burning_bug, key_images: HashMap::new(), spent: HashSet::new() }
   }
 
   /// Register a subaddress.
@@ -230,4 +277,26 @@ impl Scanner {
     let (spend, _) = self.pair.subaddress_keys(subaddress);
     self.subaddresses.insert(spend.compress(), Some(subaddress));
   }
+
+  /// Import signed key images, as exported by the holder of the spend key.
+  ///
+  /// Once imported, outputs will be marked spent when their key image is present in a scanned
+  /// transaction. Transactions scanned before the key images were imported must be rescanned.
+  ///
+  /// All key images are verified before any are imported. This does not check they're for
+  /// outputs received by this Scanner.
+  pub fn import_key_images(&mut self, key_images: &[SignedKeyImage]) -> Result<(), KeyImageError> {
+    for key_image in key_images {
+      key_image.verify()?;
+    }
+    for key_image in key_images {
+      self.key_images.insert(key_image.key_image().compress(), key_image.key());
+    }
+    Ok(())
+  }
+
+  /// Returns if the output with the specified key was spent, as detected via imported key images.
+  pub fn is_spent(&self, key: &EdwardsPoint) -> bool {
+    self.spent.contains(&key.compress())
+  }
 }
@@ -0,0 +1,101 @@
+use std_shims::{
+  vec::Vec,
+  string::{String, ToString},
+};
+
+use crate::wallet::{
+  address::{Network, AddressError, MoneroAddress},
+  uri::{parse_amount, with_payment_id},
+};
+
+const PREFIX: &str = "oa1:xmr";
+
+/// A Monero recipient specified by an [OpenAlias](https://openalias.org) record.
+///
+/// This solely parses the TXT records, which must be fetched by the caller via any resolver. The
+/// caller is responsible for validating the records with DNSSEC, as without it, the recipient's
+/// address can be trivially spoofed. If a resolver returns a record as multiple strings, they
+/// should be concatenated.
+///
+/// As with MoneroUri, a `tx_payment_id` is embedded into the recipient's address.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct OpenAlias {
+  pub address: MoneroAddress,
+  pub name: Option<String>,
+  pub description: Option<String>,
+  /// The amount requested, in atomic units.
+  pub amount: Option<u64>,
+  /// Parameters not recognized, such as `address_signature` and `checksum`, in the order they
+  /// were present.
+  pub unknown_parameters: Vec<(String, String)>,
+}
+
+impl OpenAlias {
+  /// The DNS name whose TXT records specify the recipient for an OpenAlias, converting the
+  /// `user@domain` form to `user.domain`.
+  pub fn dns_name(alias: &str) -> String {
+    alias.replacen('@', ".", 1)
+  }
+
+  /// Returns if the TXT record is an OpenAlias record for Monero.
+  pub fn is_monero_record(record: &str) -> bool {
+    record
+      .strip_prefix(PREFIX)
+      .map(|rest| rest.chars().next().map(char::is_whitespace).unwrap_or(true))
+      .unwrap_or(false)
+  }
+
+  /// Parse an OpenAlias TXT record for Monero.
+  pub fn from_txt_record(network: Network, record: &str) -> Result<OpenAlias, AddressError> {
+    if !Self::is_monero_record(record) {
+      Err(AddressError::InvalidOpenAlias)?;
+    }
+
+    let mut address = None;
+    let mut payment_id = None;
+    let mut name = None;
+    let mut description = None;
+    let mut amount = None;
+    let mut unknown_parameters = vec![];
+    let mut seen = Vec::new();
+    for parameter in record[PREFIX.len() ..].split(';').map(str::trim) {
+      if parameter.is_empty() {
+        continue;
+      }
+      let (key, value) = parameter.split_once('=').ok_or(AddressError::InvalidOpenAlias)?;
+      let (key, value) = (key.trim(), value.trim());
+      if seen.contains(&key) {
+        Err(AddressError::InvalidOpenAlias)?;
+      }
+      seen.push(key);
+
+      match key {
+        "recipient_address" => address = Some(MoneroAddress::from_str(network, value)?),
+        "recipient_name" => name = Some(value.to_string()),
+        "tx_description" => description = Some(value.to_string()),
+        "tx_amount" => amount = Some(parse_amount(value)?),
+        "tx_payment_id" => payment_id = Some(value),
+        _ => unknown_parameters.push((key.to_string(), value.to_string())),
+      }
+    }
+
+    let mut address = address.ok_or(AddressError::InvalidOpenAlias)?;
+    if let Some(payment_id) = payment_id {
+      address = with_payment_id(address, payment_id)?;
+    }
+    Ok(OpenAlias { address, name, description, amount, unknown_parameters })
+  }
+
+  /// Parse every OpenAlias record for Monero present in a set of TXT records, ignoring records
+  /// which aren't OpenAlias records for Monero.
+  pub fn from_txt_records<'a>(
+    network: Network,
+    records: impl IntoIterator<Item = &'a str>,
+  ) -> Result<Vec<OpenAlias>, AddressError> {
+    records
+      .into_iter()
+      .filter(|record| Self::is_monero_record(record))
+      .map(|record| Self::from_txt_record(network, record))
+      .collect()
+  }
+}
@@ -0,0 +1,750 @@
+use core::{ops::Deref, str::FromStr, fmt};
+use std_shims::{
+  vec::Vec,
+  string::{String, ToString},
+  collections::HashSet,
+  io::{self, Read, Write},
+};
+
+use rand_core::{RngCore, CryptoRng};
+
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use curve25519_dalek::{
+  constants::ED25519_BASEPOINT_TABLE, traits::IsIdentity, scalar::Scalar, edwards::EdwardsPoint,
+};
+
+use base58_monero::base58::{encode, decode};
+
+#[cfg(feature = "rpc")]
+use crate::rpc::{Rpc, RpcError};
+use crate::{
+  Commitment, hash, hash_to_scalar, random_scalar,
+  serialize::{
+    read_bytes, read_varint, read_scalar, read_point, read_vec, write_varint, write_scalar,
+    write_point, write_vec,
+  },
+  ringct::generate_key_image,
+  transaction::{Output, Transaction},
+  wallet::{
+    address::MoneroAddress,
+    key_image::{sign_key_image, verify_key_image},
+    Extra, ViewPair, ReceivedOutput, uniqueness, shared_key, amount_decryption, commitment_mask,
+  },
+};
+
+/// The ephemeral keys of a transaction, as needed to prove the payments it made.
+#[derive(Clone, PartialEq, Eq, Zeroize, ZeroizeOnDrop)]
+pub struct TxKeys {
+  /// The transaction key.
+  pub key: Zeroizing<Scalar>,
+  /// The additional keys, one per output, if the transaction has them.
+  pub additional: Vec<Zeroizing<Scalar>>,
+}
+
+impl fmt::Debug for TxKeys {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("TxKeys").finish_non_exhaustive()
+  }
+}
+
+/// Errors when creating or verifying payment proofs.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+pub enum ProofError {
+  #[cfg_attr(feature = "std", error("wrong private key"))]
+  WrongPrivateKey,
+  #[cfg_attr(feature = "std", error("invalid transaction"))]
+  InvalidTransaction,
+  #[cfg_attr(feature = "std", error("missing transaction"))]
+  MissingTransaction,
+  #[cfg_attr(feature = "std", error("invalid proof encoding"))]
+  InvalidEncoding,
+  #[cfg_attr(feature = "std", error("invalid proof"))]
+  InvalidProof,
+  #[cfg(feature = "rpc")]
+  #[cfg_attr(feature = "std", error("rpc error ({0})"))]
+  RpcError(RpcError),
+}
+
+// The transaction keys, with the main key first, followed by the additional keys
+fn tx_public_keys(tx: &Transaction) -> Result<Vec<EdwardsPoint>, ProofError> {
+  let extra = Extra::read::<&[u8]>(&mut tx.prefix.extra.as_ref())
+    .map_err(|_| ProofError::InvalidTransaction)?;
+  let (key, additional) = extra.keys().ok_or(ProofError::InvalidTransaction)?;
+  let mut keys = vec![key];
+  keys.extend(additional.unwrap_or(vec![]));
+  Ok(keys)
+}
+
+// Monero's generate_tx_proof, proving D = rA where R = rG, or R = rB if B is specified
+// Version 1 only binds the message, D, and the nonces, which is still used by reserve proofs
+#[allow(non_snake_case, clippy::too_many_arguments)]
+fn tx_proof_challenge(
+  version: u8,
+  msg: &[u8; 32],
+  R: EdwardsPoint,
+  A: EdwardsPoint,
+  B: Option<EdwardsPoint>,
+  D: EdwardsPoint,
+  X: EdwardsPoint,
+  Y: EdwardsPoint,
+) -> Scalar {
+  let mut buf = Vec::with_capacity(32 * 8);
+  buf.extend(msg);
+  buf.extend(D.compress().to_bytes());
+  buf.extend(X.compress().to_bytes());
+  buf.extend(Y.compress().to_bytes());
+  if version != 1 {
+    buf.extend(hash(b"TXPROOF_V2"));
+    buf.extend(R.compress().to_bytes());
+    buf.extend(A.compress().to_bytes());
+    buf.extend(B.map(|B| B.compress().to_bytes()).unwrap_or([0; 32]));
+  }
+  hash_to_scalar(&buf)
+}
+
+#[allow(non_snake_case, clippy::too_many_arguments)]
+fn generate_tx_proof<R: RngCore + CryptoRng>(
+  rng: &mut R,
+  version: u8,
+  msg: &[u8; 32],
+  R: EdwardsPoint,
+  A: EdwardsPoint,
+  B: Option<EdwardsPoint>,
+  D: EdwardsPoint,
+  r: &Zeroizing<Scalar>,
+) -> (Scalar, Scalar) {
+  let k = Zeroizing::new(random_scalar(rng));
+  let X = match B {
+    Some(B) => k.deref() * B,
+    None => k.deref() * &ED25519_BASEPOINT_TABLE,
+  };
+  let Y = k.deref() * A;
+  let c = tx_proof_challenge(version, msg, R, A, B, D, X, Y);
+  (c, k.deref() - (c * r.deref()))
+}
+
+#[allow(non_snake_case, clippy::too_many_arguments)]
+fn check_tx_proof(
+  version: u8,
+  msg: &[u8; 32],
+  R: EdwardsPoint,
+  A: EdwardsPoint,
+  B: Option<EdwardsPoint>,
+  D: EdwardsPoint,
+  (c, r): (Scalar, Scalar),
+) -> bool {
+  if ![R, A, D].iter().chain(B.as_ref()).all(EdwardsPoint::is_torsion_free) {
+    return false;
+  }
+
+  let X = (c * R) +
+    match B {
+      Some(B) => r * B,
+      None => &r * &ED25519_BASEPOINT_TABLE,
+    };
+  let Y = (c * D) + (r * A);
+  tx_proof_challenge(version, msg, R, A, B, D, X, Y) == c
+}
+
+// Monero's generate_signature, a Schnorr signature
+#[allow(non_snake_case)]
+fn signature_challenge(msg: &[u8; 32], key: EdwardsPoint, R: EdwardsPoint) -> Scalar {
+  hash_to_scalar(&[msg.as_ref(), &key.compress().to_bytes(), &R.compress().to_bytes()].concat())
+}
+
+fn sign<R: RngCore + CryptoRng>(
+  rng: &mut R,
+  msg: &[u8; 32],
+  key: EdwardsPoint,
+  secret: &Zeroizing<Scalar>,
+) -> (Scalar, Scalar) {
+  let k = Zeroizing::new(random_scalar(rng));
+  let c = signature_challenge(msg, key, k.deref() * &ED25519_BASEPOINT_TABLE);
+  (c, k.deref() - (c * secret.deref()))
+}
+
+fn verify(msg: &[u8; 32], key: EdwardsPoint, (c, r): (Scalar, Scalar)) -> bool {
+  signature_challenge(msg, key, (c * key) + (&r * &ED25519_BASEPOINT_TABLE)) == c
+}
+
+// Decode the amount of an output, verifying its commitment
+fn decode_amount(tx: &Transaction, o: usize, shared_key: Scalar) -> Option<u64> {
+  let output = &tx.prefix.outputs[o];
+  if output.amount != 0 {
+    return Some(output.amount);
+  }
+
+  let amount = amount_decryption(*tx.rct_signatures.base.ecdh_info.get(o)?, shared_key);
+  if Some(&Commitment::new(commitment_mask(shared_key), amount).calculate()) !=
+    tx.rct_signatures.base.commitments.get(o)
+  {
+    None?;
+  }
+  Some(amount)
+}
+
+fn output_key(output: &Output) -> Result<EdwardsPoint, ProofError> {
+  output.key.decompress().ok_or(ProofError::InvalidTransaction)
+}
+
+fn encode_point(res: &mut String, point: EdwardsPoint) {
+  res.push_str(&encode(&point.compress().to_bytes()).unwrap());
+}
+
+fn encode_signature(res: &mut String, (c, r): (Scalar, Scalar)) {
+  res.push_str(&encode(&[c.to_bytes(), r.to_bytes()].concat()).unwrap());
+}
+
+fn decode_point(s: &str) -> Result<EdwardsPoint, ProofError> {
+  let bytes = decode(s).map_err(|_| ProofError::InvalidEncoding)?;
+  read_point(&mut bytes.as_slice())
+    .ok()
+    .filter(|_| bytes.len() == 32)
+    .ok_or(ProofError::InvalidEncoding)
+}
+
+fn decode_signature(s: &str) -> Result<(Scalar, Scalar), ProofError> {
+  let bytes = decode(s).map_err(|_| ProofError::InvalidEncoding)?;
+  if bytes.len() != 64 {
+    Err(ProofError::InvalidEncoding)?;
+  }
+  let mut bytes = bytes.as_slice();
+  Ok((
+    read_scalar(&mut bytes).map_err(|_| ProofError::InvalidEncoding)?,
+    read_scalar(&mut bytes).map_err(|_| ProofError::InvalidEncoding)?,
+  ))
+}
+
+// The length of a base58-encoded point, and a base58-encoded signature
+const POINT_LEN: usize = 44;
+const SIGNATURE_LEN: usize = 88;
+
+/// A proof a transaction sent funds to an address, as wallet2's `get_tx_proof` creates.
+///
+/// Outbound proofs are created by the sender, with the transaction's ephemeral keys. Inbound
+/// proofs are created by the recipient, with their private view key.
+#[derive(Clone, PartialEq, Eq, Debug, Zeroize)]
+pub struct TxProof {
+  inbound: bool,
+  version: u8,
+  // The shared secret for each transaction key, with a proof it was correctly derived
+  proofs: Vec<(EdwardsPoint, (Scalar, Scalar))>,
+}
+
+impl TxProof {
+  fn message(tx: [u8; 32], message: &[u8]) -> [u8; 32] {
+    hash(&[tx.as_ref(), message].concat())
+  }
+
+  /// Prove a transaction sent funds to the specified address, using its ephemeral keys.
+  ///
+  /// The message is arbitrary data the proof commits to, such as to prevent replaying it.
+  #[allow(non_snake_case)]
+  pub fn outbound<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    tx: [u8; 32],
+    address: &MoneroAddress,
+    message: &[u8],
+    keys: &TxKeys,
+  ) -> TxProof {
+    let msg = Self::message(tx, message);
+    let B = Some(address.spend).filter(|_| address.is_subaddress());
+    let proofs = [&keys.key]
+      .into_iter()
+      .chain(&keys.additional)
+      .map(|r| {
+        let R = match B {
+          Some(B) => r.deref() * B,
+          None => r.deref() * &ED25519_BASEPOINT_TABLE,
+        };
+        let D = r.deref() * address.view;
+        (D, generate_tx_proof(rng, 2, &msg, R, address.view, B, D, r))
+      })
+      .collect();
+    TxProof { inbound: false, version: 2, proofs }
+  }
+
+  /// Prove a transaction sent funds to the specified address, using its private view key.
+  #[allow(non_snake_case)]
+  pub fn inbound<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    tx: &Transaction,
+    address: &MoneroAddress,
+    message: &[u8],
+    view: &Zeroizing<Scalar>,
+  ) -> Result<TxProof, ProofError> {
+    let B = Some(address.spend).filter(|_| address.is_subaddress());
+    let expected_view = match B {
+      Some(B) => view.deref() * B,
+      None => view.deref() * &ED25519_BASEPOINT_TABLE,
+    };
+    if expected_view != address.view {
+      Err(ProofError::WrongPrivateKey)?;
+    }
+
+    let msg = Self::message(tx.hash(), message);
+    let proofs = tx_public_keys(tx)?
+      .drain(..)
+      .map(|A| {
+        let D = view.deref() * A;
+        (D, generate_tx_proof(rng, 2, &msg, address.view, A, B, D, view))
+      })
+      .collect();
+    Ok(TxProof { inbound: true, version: 2, proofs })
+  }
+
+  /// Verify this proof, returning the amount the transaction sent to the address.
+  #[allow(non_snake_case)]
+  pub fn verify(
+    &self,
+    tx: &Transaction,
+    address: &MoneroAddress,
+    message: &[u8],
+  ) -> Result<u64, ProofError> {
+    let keys = tx_public_keys(tx)?;
+    if keys.len() != self.proofs.len() {
+      Err(ProofError::InvalidProof)?;
+    }
+
+    // As with wallet2, only one proof has to be valid
+    // When a subaddress is paid with additional keys, the proof for the main key won't be, as the
+    // main key wasn't created with the subaddress's spend key
+    let msg = Self::message(tx.hash(), message);
+    let B = Some(address.spend).filter(|_| address.is_subaddress());
+    let proven = keys
+      .iter()
+      .zip(&self.proofs)
+      .map(|(key, (D, sig))| {
+        let (R, A) = if self.inbound { (address.view, *key) } else { (*key, address.view) };
+        Some(*D).filter(|D| check_tx_proof(self.version, &msg, R, A, B, *D, *sig))
+      })
+      .collect::<Vec<_>>();
+    if proven.iter().all(Option::is_none) {
+      Err(ProofError::InvalidProof)?;
+    }
+
+    // Now that the shared secrets are proven, find the outputs to the address
+    let uniqueness = Some(uniqueness(&tx.prefix.inputs)).filter(|_| address.is_guaranteed());
+    let mut received = 0;
+    for (o, output) in tx.prefix.outputs.iter().enumerate() {
+      let key = output_key(output)?;
+      for D in [proven[0], proven.get(o + 1).copied().flatten()].into_iter().flatten() {
+        let (view_tag, shared_key, _) = shared_key(uniqueness, D, o);
+        if output.view_tag.map(|actual| actual != view_tag).unwrap_or(false) {
+          continue;
+        }
+        if key != ((&shared_key * &ED25519_BASEPOINT_TABLE) + address.spend) {
+          continue;
+        }
+        if let Some(amount) = decode_amount(tx, o, shared_key) {
+          received += amount;
+          break;
+        }
+      }
+    }
+    Ok(received)
+  }
+}
+
+/// Parse a proof from its string encoding, as used by monero-wallet-cli.
+impl FromStr for TxProof {
+  type Err = ProofError;
+  fn from_str(s: &str) -> Result<TxProof, ProofError> {
+    let (inbound, s) = if let Some(s) = s.strip_prefix("InProofV") {
+      (true, s)
+    } else if let Some(s) = s.strip_prefix("OutProofV") {
+      (false, s)
+    } else {
+      Err(ProofError::InvalidEncoding)?
+    };
+
+    let version = match s.get(.. 1) {
+      Some("1") => 1,
+      Some("2") => 2,
+      _ => Err(ProofError::InvalidEncoding)?,
+    };
+    let s = &s[1 ..];
+
+    const PROOF_LEN: usize = POINT_LEN + SIGNATURE_LEN;
+    if (!s.is_ascii()) || s.is_empty() || ((s.len() % PROOF_LEN) != 0) {
+      Err(ProofError::InvalidEncoding)?;
+    }
+    let mut proofs = vec![];
+    for i in 0 .. (s.len() / PROOF_LEN) {
+      let proof = &s[(i * PROOF_LEN) .. ((i + 1) * PROOF_LEN)];
+      proofs.push((decode_point(&proof[.. POINT_LEN])?, decode_signature(&proof[POINT_LEN ..])?));
+    }
+    Ok(TxProof { inbound, version, proofs })
+  }
+}
+
+impl fmt::Display for TxProof {
+  #[allow(non_snake_case)]
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let mut res = if self.inbound { "InProofV" } else { "OutProofV" }.to_string();
+    res.push_str(&self.version.to_string());
+    for (D, sig) in &self.proofs {
+      encode_point(&mut res, *D);
+      encode_signature(&mut res, *sig);
+    }
+    f.write_str(&res)
+  }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Zeroize)]
+struct ReserveProofEntry {
+  tx: [u8; 32],
+  o: u64,
+  shared_secret: EdwardsPoint,
+  key_image: EdwardsPoint,
+  shared_secret_sig: (Scalar, Scalar),
+  key_image_sig: (Scalar, Scalar),
+}
+
+// wallet2 serializes entries with a version field, which is currently always 0
+const RESERVE_PROOF_ENTRY_VERSION: u64 = 0;
+
+impl ReserveProofEntry {
+  fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    write_varint(&RESERVE_PROOF_ENTRY_VERSION, w)?;
+    w.write_all(&self.tx)?;
+    write_varint(&self.o, w)?;
+    write_point(&self.shared_secret, w)?;
+    write_point(&self.key_image, w)?;
+    write_scalar(&self.shared_secret_sig.0, w)?;
+    write_scalar(&self.shared_secret_sig.1, w)?;
+    write_scalar(&self.key_image_sig.0, w)?;
+    write_scalar(&self.key_image_sig.1, w)
+  }
+
+  fn read<R: Read>(r: &mut R) -> io::Result<ReserveProofEntry> {
+    if read_varint(r)? != RESERVE_PROOF_ENTRY_VERSION {
+      Err(io::Error::new(io::ErrorKind::Other, "unknown reserve proof entry version"))?;
+    }
+    Ok(ReserveProofEntry {
+      tx: read_bytes(r)?,
+      o: read_varint(r)?,
+      shared_secret: read_point(r)?,
+      key_image: read_point(r)?,
+      shared_secret_sig: (read_scalar(r)?, read_scalar(r)?),
+      key_image_sig: (read_scalar(r)?, read_scalar(r)?),
+    })
+  }
+}
+
+const RESERVE_PROOF_HEADER: &str = "ReserveProofV2";
+
+/// A proof of funds held by a wallet, as wallet2's `get_reserve_proof` creates.
+///
+/// This proves ownership of a set of outputs, with their key images, enabling the verifier to
+/// check they're yet to be spent. This is encoded as wallet2's `ReserveProofV2`. The deprecated
+/// `ReserveProofV1`, encoded via a Boost archive, isn't supported.
+#[derive(Clone, PartialEq, Eq, Debug, Zeroize)]
+pub struct ReserveProof {
+  entries: Vec<ReserveProofEntry>,
+  // The spend keys of the address and any subaddresses the outputs were received to, with
+  // signatures proving their ownership
+  spend_keys: Vec<(EdwardsPoint, (Scalar, Scalar))>,
+}
+
+impl ReserveProof {
+  fn message(
+    message: &[u8],
+    spend: EdwardsPoint,
+    view: EdwardsPoint,
+    key_images: impl Iterator<Item = EdwardsPoint>,
+  ) -> [u8; 32] {
+    let mut buf = message.to_vec();
+    buf.extend(spend.compress().to_bytes());
+    buf.extend(view.compress().to_bytes());
+    for key_image in key_images {
+      buf.extend(key_image.compress().to_bytes());
+    }
+    hash(&buf)
+  }
+
+  /// Prove ownership of the specified outputs.
+  ///
+  /// The transactions containing the outputs must be provided, in any order.
+  #[allow(non_snake_case)]
+  pub fn new<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    spend: &Zeroizing<Scalar>,
+    view: &ViewPair,
+    message: &[u8],
+    outputs: &[ReceivedOutput],
+    txs: &[Transaction],
+  ) -> Result<ReserveProof, ProofError> {
+    if (spend.deref() * &ED25519_BASEPOINT_TABLE) != view.spend() {
+      Err(ProofError::WrongPrivateKey)?;
+    }
+    let txs = txs.iter().map(|tx| (tx.hash(), tx)).collect::<Vec<_>>();
+
+    let mut offsets = Vec::with_capacity(outputs.len());
+    for output in outputs {
+      let offset = Zeroizing::new(spend.deref() + output.key_offset());
+      if (offset.deref() * &ED25519_BASEPOINT_TABLE) != output.key() {
+        Err(ProofError::WrongPrivateKey)?;
+      }
+      offsets.push(offset);
+    }
+    let key_images = offsets.iter().map(generate_key_image).collect::<Vec<_>>();
+    let msg = Self::message(message, view.spend(), view.view(), key_images.iter().copied());
+
+    let mut entries = Vec::with_capacity(outputs.len());
+    let mut subaddresses = vec![None];
+    for ((output, offset), key_image) in outputs.iter().zip(offsets).zip(key_images) {
+      let tx = txs
+        .iter()
+        .find(|(hash, _)| *hash == output.absolute.tx)
+        .ok_or(ProofError::MissingTransaction)?
+        .1;
+      let o = usize::from(output.absolute.o);
+
+      // Find the transaction key used for this output, as identified by its shared key
+      let subaddress = output.metadata.subaddress;
+      if !subaddresses.contains(&subaddress) {
+        subaddresses.push(subaddress);
+      }
+      let expected = output.key_offset() -
+        subaddress.map(|index| view.subaddress_derivation(index)).unwrap_or(Scalar::zero());
+      let A = tx_public_keys(tx)?
+        .drain(..)
+        .enumerate()
+        .filter(|(i, _)| (*i == 0) || (*i == (o + 1)))
+        .map(|(_, A)| A)
+        .find(|A| {
+          let D = view.view.deref() * A;
+          [None, Some(uniqueness(&tx.prefix.inputs))]
+            .into_iter()
+            .any(|uniqueness| shared_key(uniqueness, D, o).1 == expected)
+        })
+        .ok_or(ProofError::InvalidTransaction)?;
+
+      let shared_secret = view.view.deref() * A;
+      entries.push(ReserveProofEntry {
+        tx: output.absolute.tx,
+        o: u64::from(output.absolute.o),
+        shared_secret,
+        key_image,
+        shared_secret_sig: generate_tx_proof(
+          rng,
+          1,
+          &msg,
+          view.view(),
+          A,
+          None,
+          shared_secret,
+          &view.view,
+        ),
+        key_image_sig: sign_key_image(rng, &msg, output.key(), &offset),
+      });
+    }
+
+    let spend_keys = subaddresses
+      .drain(..)
+      .map(|subaddress| {
+        let secret = Zeroizing::new(
+          spend.deref() +
+            subaddress.map(|index| view.subaddress_derivation(index)).unwrap_or(Scalar::zero()),
+        );
+        let key = secret.deref() * &ED25519_BASEPOINT_TABLE;
+        (key, sign(rng, &msg, key, &secret))
+      })
+      .collect();
+
+    Ok(ReserveProof { entries, spend_keys })
+  }
+
+  /// The key images of the outputs proven, which must be checked as unspent.
+  pub fn key_images(&self) -> Vec<EdwardsPoint> {
+    self.entries.iter().map(|entry| entry.key_image).collect()
+  }
+
+  /// The hashes of the transactions containing the outputs proven.
+  pub fn transactions(&self) -> Vec<[u8; 32]> {
+    let mut res = vec![];
+    for entry in &self.entries {
+      if !res.contains(&entry.tx) {
+        res.push(entry.tx);
+      }
+    }
+    res
+  }
+
+  // Verify this proof, returning the amount of each output proven
+  #[allow(non_snake_case)]
+  fn verify_amounts(
+    &self,
+    address: &MoneroAddress,
+    message: &[u8],
+    txs: &[Transaction],
+  ) -> Result<Vec<u64>, ProofError> {
+    // wallet2 only supports proving funds received to an account's primary address
+    if address.is_subaddress() {
+      Err(ProofError::InvalidProof)?;
+    }
+    let txs = txs.iter().map(|tx| (tx.hash(), tx)).collect::<Vec<_>>();
+
+    let msg = Self::message(message, address.spend, address.view, self.key_images().drain(..));
+    if !self.spend_keys.iter().any(|(key, _)| *key == address.spend) {
+      Err(ProofError::InvalidProof)?;
+    }
+    for (key, sig) in &self.spend_keys {
+      if !verify(&msg, *key, *sig) {
+        Err(ProofError::InvalidProof)?;
+      }
+    }
+
+    let mut key_images = HashSet::new();
+    let mut amounts = Vec::with_capacity(self.entries.len());
+    for entry in &self.entries {
+      let tx =
+        txs.iter().find(|(hash, _)| *hash == entry.tx).ok_or(ProofError::MissingTransaction)?.1;
+      let o = usize::try_from(entry.o).map_err(|_| ProofError::InvalidProof)?;
+      let output = tx.prefix.outputs.get(o).ok_or(ProofError::InvalidProof)?;
+      let key = output_key(output)?;
+
+      // Verify the key image, ensuring each output is only counted once
+      if (!entry.key_image.is_torsion_free()) ||
+        entry.key_image.is_identity() ||
+        (!key_images.insert(entry.key_image.compress().to_bytes())) ||
+        (!verify_key_image(&msg, key, entry.key_image, entry.key_image_sig))
+      {
+        Err(ProofError::InvalidProof)?;
+      }
+
+      // Verify the shared secret, for either the main transaction key or this output's key
+      if !tx_public_keys(tx)?
+        .drain(..)
+        .enumerate()
+        .filter(|(i, _)| (*i == 0) || (*i == (o + 1)))
+        .any(|(_, A)| {
+          check_tx_proof(
+            1,
+            &msg,
+            address.view,
+            A,
+            None,
+            entry.shared_secret,
+            entry.shared_secret_sig,
+          )
+        })
+      {
+        Err(ProofError::InvalidProof)?;
+      }
+
+      // Verify the output was received to one of the proven spend keys, and decode its amount
+      let mut amount = None;
+      for uniqueness in [None, Some(uniqueness(&tx.prefix.inputs))] {
+        let shared_key = shared_key(uniqueness, entry.shared_secret, o).1;
+        let spend = key - (&shared_key * &ED25519_BASEPOINT_TABLE);
+        if self.spend_keys.iter().any(|(key, _)| *key == spend) {
+          amount = decode_amount(tx, o, shared_key);
+          break;
+        }
+      }
+      amounts.push(amount.ok_or(ProofError::InvalidProof)?);
+    }
+
+    Ok(amounts)
+  }
+
+  /// Verify this proof, returning the amount proven.
+  ///
+  /// The transactions containing the outputs proven must be provided, in any order. This does not
+  /// check if the outputs were spent, which requires checking the key images against the
+  /// blockchain.
+  pub fn verify(
+    &self,
+    address: &MoneroAddress,
+    message: &[u8],
+    txs: &[Transaction],
+  ) -> Result<u64, ProofError> {
+    Ok(self.verify_amounts(address, message, txs)?.iter().sum())
+  }
+
+  /// Verify this proof, fetching the transactions it references, returning the amount proven and
+  /// the amount of it which has been spent.
+  #[cfg(feature = "rpc")]
+  pub async fn check(
+    &self,
+    rpc: &Rpc,
+    address: &MoneroAddress,
+    message: &[u8],
+  ) -> Result<(u64, u64), ProofError> {
+    let txs = rpc.get_transactions(&self.transactions()).await.map_err(ProofError::RpcError)?;
+    let amounts = self.verify_amounts(address, message, &txs)?;
+
+    let spent = rpc.is_key_image_spent(&self.key_images()).await.map_err(ProofError::RpcError)?;
+    Ok((
+      amounts.iter().sum(),
+      amounts.iter().zip(spent).filter(|(_, spent)| *spent).map(|(amount, _)| amount).sum(),
+    ))
+  }
+
+  /// Write this proof, as wallet2's binary archive does.
+  pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    write_vec(ReserveProofEntry::write, &self.entries, w)?;
+    // wallet2 serializes the spend keys as a map, where each pair is prefixed by its length of 2
+    write_vec(
+      |(key, sig), w| {
+        write_varint(&2, w)?;
+        write_point(key, w)?;
+        write_scalar(&sig.0, w)?;
+        write_scalar(&sig.1, w)
+      },
+      &self.spend_keys,
+      w,
+    )
+  }
+
+  pub fn serialize(&self) -> Vec<u8> {
+    let mut buf = vec![];
+    self.write(&mut buf).unwrap();
+    buf
+  }
+
+  pub fn read<R: Read>(r: &mut R) -> io::Result<ReserveProof> {
+    let entries = read_vec(ReserveProofEntry::read, r)?;
+    let spend_keys = read_vec(
+      |r| {
+        if read_varint(r)? != 2 {
+          Err(io::Error::new(io::ErrorKind::Other, "spend key entry wasn't a pair"))?;
+        }
+        Ok((read_point(r)?, (read_scalar(r)?, read_scalar(r)?)))
+      },
+      r,
+    )?;
+
+    // The spend keys are a map, so each key may only be present once
+    let mut keys = HashSet::new();
+    if !spend_keys.iter().all(|(key, _)| keys.insert(key.compress().to_bytes())) {
+      Err(io::Error::new(io::ErrorKind::Other, "duplicate spend key"))?;
+    }
+    Ok(ReserveProof { entries, spend_keys })
+  }
+}
+
+impl FromStr for ReserveProof {
+  type Err = ProofError;
+  fn from_str(s: &str) -> Result<ReserveProof, ProofError> {
+    let s = s.strip_prefix(RESERVE_PROOF_HEADER).ok_or(ProofError::InvalidEncoding)?;
+    let bytes = decode(s).map_err(|_| ProofError::InvalidEncoding)?;
+    let mut slice = bytes.as_slice();
+    let res = ReserveProof::read(&mut slice).map_err(|_| ProofError::InvalidEncoding)?;
+    if !slice.is_empty() {
+      Err(ProofError::InvalidEncoding)?;
+    }
+    Ok(res)
+  }
+}
+
+impl fmt::Display for ReserveProof {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str(RESERVE_PROOF_HEADER)?;
+    f.write_str(&encode(&self.serialize()).unwrap())
+  }
+}
@@ -1,5 +1,8 @@
 use core::ops::Deref;
-use std::io::{self, Read, Write};
+use std_shims::{
+  vec::Vec,
+  io::{self, Read, Write},
+};
 
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
@@ -9,13 +12,16 @@ use crate::{
   Commitment,
   serialize::{read_byte, read_u32, read_u64, read_bytes, read_scalar, read_point, read_raw_vec},
   transaction::{Input, Timelock, Transaction},
-  block::Block,
-  rpc::{Rpc, RpcError},
   wallet::{
     PaymentId, Extra, address::SubaddressIndex, Scanner, uniqueness, shared_key, amount_decryption,
     commitment_mask,
   },
 };
+#[cfg(feature = "rpc")]
+use crate::{
+  block::Block,
+  rpc::{Rpc, RpcError},
+};
 
 /// An absolute output ID, defined as its transaction hash and output index.
 #[derive(Clone, PartialEq, Eq, Debug, Zeroize, ZeroizeOnDrop)]
@@ -193,14 +199,34 @@ pub struct SpendableOutput {
 }
 
 impl SpendableOutput {
+  /// Create a spendable output with the specified key data, solely for use in tests.
+  #[cfg(test)]
+  pub(crate) fn for_test(
+    key: EdwardsPoint,
+    key_offset: Scalar,
+    commitment: Commitment,
+    global_index: u64,
+  ) -> SpendableOutput {
+    SpendableOutput {
+      output: ReceivedOutput {
+        absolute: AbsoluteId { tx: [0; 32], o: 0 },
+        data: OutputData { key, key_offset, commitment },
+        metadata: Metadata { subaddress: None, payment_id: [0; 8], arbitrary_data: vec![] },
+      },
+      global_index,
+    }
+  }
+
   /// Update the spendable output's global index. This is intended to be called if a
   /// re-organization occurred.
+  #[cfg(feature = "rpc")]
   pub async fn refresh_global_index(&mut self, rpc: &Rpc) -> Result<(), RpcError> {
     self.global_index =
       rpc.get_o_indexes(self.output.absolute.tx).await?[usize::from(self.output.absolute.o)];
     Ok(())
   }
 
+  #[cfg(feature = "rpc")]
   pub async fn from(rpc: &Rpc, output: ReceivedOutput) -> Result<SpendableOutput, RpcError> {
     let mut output = SpendableOutput { output, global_index: 0 };
     output.refresh_global_index(rpc).await?;
@@ -241,7 +267,7 @@ impl SpendableOutput {
 
 /// A collection of timelocked outputs, either received or spendable.
 #[derive(Zeroize)]
-pub struct Timelocked<O: Clone + Zeroize>(Timelock, Vec<O>);
+pub struct Timelocked<O: Clone + Zeroize>(pub(crate) Timelock, pub(crate) Vec<O>);
 impl<O: Clone + Zeroize> Drop for Timelocked<O> {
   fn drop(&mut self) {
     self.zeroize();
@@ -275,7 +301,18 @@ impl<O: Clone + Zeroize> Timelocked<O> {
 
 impl Scanner {
   /// Scan a transaction to discover the received outputs.
+  ///
+  /// This also marks outputs as spent if their key images were imported and are present in this
+  /// transaction's inputs.
   pub fn scan_transaction(&mut self, tx: &Transaction) -> Timelocked<ReceivedOutput> {
+    for input in &tx.prefix.inputs {
+      if let Input::ToKey { key_image, .. } = input {
+        if let Some(key) = self.key_images.get(&key_image.compress()) {
+          self.spent.insert(key.compress());
+        }
+      }
+    }
+
     let extra = Extra::read::<&[u8]>(&mut tx.prefix.extra.as_ref());
     let extra = if let Ok(extra) = extra {
       extra
@@ -403,43 +440,35 @@ impl Scanner {
     Timelocked(tx.prefix.timelock, res)
   }
 
-  /// Scan a block to obtain its spendable outputs. Its the presence in a block giving these
-  /// transactions their global index, and this must be batched as asking for the index of specific
-  /// transactions is a dead giveaway for which transactions you successfully scanned. This
-  /// function obtains the output indexes for the miner transaction, incrementing from there
-  /// instead.
-  pub async fn scan(
+  // Scan a block's transactions, returning every transaction alongside the spendable outputs it
+  // created
+  #[cfg(feature = "rpc")]
+  pub(crate) async fn scan_block_transactions(
     &mut self,
     rpc: &Rpc,
     block: &Block,
-  ) -> Result<Vec<Timelocked<SpendableOutput>>, RpcError> {
+  ) -> Result<Vec<(Transaction, Timelocked<SpendableOutput>)>, RpcError> {
     let mut index = rpc.get_o_indexes(block.miner_tx.hash()).await?[0];
     let mut txs = vec![block.miner_tx.clone()];
     txs.extend(rpc.get_transactions(&block.txs).await?);
 
     let map = |mut timelock: Timelocked<ReceivedOutput>, index| {
-      if timelock.1.is_empty() {
-        None
-      } else {
-        Some(Timelocked(
-          timelock.0,
-          timelock
-            .1
-            .drain(..)
-            .map(|output| SpendableOutput {
-              global_index: index + u64::from(output.absolute.o),
-              output,
-            })
-            .collect(),
-        ))
-      }
+      Timelocked(
+        timelock.0,
+        timelock
+          .1
+          .drain(..)
+          .map(|output| SpendableOutput {
+            global_index: index + u64::from(output.absolute.o),
+            output,
+          })
+          .collect(),
+      )
     };
 
     let mut res = vec![];
     for tx in txs.drain(..) {
-      if let Some(timelock) = map(self.scan_transaction(&tx), index) {
-        res.push(timelock);
-      }
+      let outputs = map(self.scan_transaction(&tx), index);
       index += u64::try_from(
         tx.prefix
           .outputs
@@ -451,8 +480,34 @@ impl Scanner {
           })
           .count(),
       )
-      .unwrap()
+      .unwrap();
+      res.push((tx, outputs));
     }
     Ok(res)
   }
+
+  /// Scan a block to obtain its spendable outputs. Its the presence in a block giving these
+  /// transactions their global index, and this must be batched as asking for the index of specific
+  /// transactions is a dead giveaway for which transactions you successfully scanned. This
+  /// function obtains the output indexes for the miner transaction, incrementing from there
+  /// instead.
+  ///
+  /// This has no notion of the chain the block is on. ChainScanner additionally detects and
+  /// handles reorganizations.
+  #[cfg(feature = "rpc")]
+  pub async fn scan(
+    &mut self,
+    rpc: &Rpc,
+    block: &Block,
+  ) -> Result<Vec<Timelocked<SpendableOutput>>, RpcError> {
+    Ok(
+      self
+        .scan_block_transactions(rpc, block)
+        .await?
+        .into_iter()
+        .map(|(_, outputs)| outputs)
+        .filter(|outputs| !outputs.1.is_empty())
+        .collect(),
+    )
+  }
 }
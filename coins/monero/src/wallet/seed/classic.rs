@@ -1,5 +1,9 @@
 use core::ops::Deref;
-use std::collections::HashMap;
+use std_shims::{
+  vec::Vec,
+  string::{String, ToString},
+  collections::HashMap,
+};
 
 use lazy_static::lazy_static;
 
@@ -1,3 +1,4 @@
+use core::cmp::Reverse;
 use std::sync::{Arc, RwLock};
 
 use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
@@ -58,6 +59,143 @@ impl SignableTransactionBuilderInternal {
   fn add_data(&mut self, data: Vec<u8>) {
     self.data.push(data);
   }
+
+  fn estimate_fee(
+    &self,
+    inputs: usize,
+    payments: &[(MoneroAddress, u64)],
+    data: &[Vec<u8>],
+  ) -> Result<u64, TransactionError> {
+    SignableTransaction::estimate_fee(
+      self.protocol,
+      inputs,
+      payments,
+      self.change_address.as_ref(),
+      data,
+      self.fee,
+    )
+  }
+
+  // Greedily selects the largest outputs from the pool until the payments and fee are covered
+  // This minimizes the amount of inputs, and accordingly the fee
+  fn select_inputs(&mut self, pool: &[SpendableOutput]) -> Result<(), TransactionError> {
+    let mut candidates = pool
+      .iter()
+      .filter(|output| !self.inputs.iter().any(|input| input.key() == output.key()))
+      .collect::<Vec<_>>();
+    candidates.sort_by_key(|output| Reverse(output.commitment().amount));
+    let mut candidates = candidates.into_iter();
+
+    let mut inputs = self.inputs.clone();
+    let mut in_amount = inputs.iter().map(|input| input.commitment().amount).sum::<u64>();
+    let out_amount = self.payments.iter().map(|payment| payment.1).sum::<u64>();
+    loop {
+      // Without any inputs, the fee can't be estimated, yet we know we need at least one input
+      let needed = if inputs.is_empty() {
+        out_amount
+      } else {
+        out_amount + self.estimate_fee(inputs.len(), &self.payments, &self.data)?
+      };
+      if (!inputs.is_empty()) && (in_amount >= needed) {
+        break;
+      }
+
+      let Some(input) = candidates.next() else {
+        Err(TransactionError::NotEnoughFunds(in_amount, needed))?
+      };
+      in_amount += input.commitment().amount;
+      inputs.push(input.clone());
+    }
+
+    self.inputs = inputs;
+    Ok(())
+  }
+
+  #[allow(clippy::type_complexity)]
+  fn sweep(
+    &self,
+    dest: MoneroAddress,
+  ) -> Result<(Vec<SignableTransaction>, Vec<SpendableOutput>), TransactionError> {
+    if self.inputs.is_empty() {
+      Err(TransactionError::NoInputs)?;
+    }
+
+    let mut inputs = self.inputs.clone();
+    inputs.sort_by_key(|input| Reverse(input.commitment().amount));
+
+    let mut txs = vec![];
+    let mut uneconomical = vec![];
+    while !inputs.is_empty() {
+      // The payments and data are only included in the first transaction
+      let (mut payments, data) =
+        if txs.is_empty() { (self.payments.clone(), self.data.clone()) } else { (vec![], vec![]) };
+      // The amount for the destination is set once the fee is known
+      payments.push((dest, 0));
+      // The change output would receive no funds, so it's only included when needed for the
+      // transaction to have the two outputs Monero requires
+      let change = if payments.len() == 1 { self.change_address.as_ref() } else { None };
+      let estimate_fee = |inputs| {
+        SignableTransaction::estimate_fee(self.protocol, inputs, &payments, change, &data, self.fee)
+      };
+
+      // Find the most inputs this transaction can have without exceeding the maximum size
+      let mut count = 0;
+      while count < inputs.len() {
+        match estimate_fee(count + 1) {
+          Ok(_) => count += 1,
+          Err(TransactionError::TooLargeTransaction) => break,
+          Err(e) => Err(e)?,
+        }
+      }
+      if count == 0 {
+        Err(TransactionError::TooLargeTransaction)?;
+      }
+
+      // Drop inputs worth no more than the fee to spend them. Since the inputs are sorted, every
+      // input after an uneconomical input is also uneconomical
+      let mut economical = count;
+      while economical != 0 {
+        let marginal_fee = estimate_fee(economical)? -
+          if economical == 1 { 0 } else { estimate_fee(economical - 1)? };
+        if inputs[economical - 1].commitment().amount > marginal_fee {
+          break;
+        }
+        economical -= 1;
+      }
+      if economical != count {
+        uneconomical = inputs.split_off(economical);
+      }
+      if economical == 0 {
+        if txs.is_empty() {
+          Err(TransactionError::NotEnoughFunds(
+            uneconomical.iter().map(|input| input.commitment().amount).sum(),
+            payments.iter().map(|payment| payment.1).sum::<u64>() + estimate_fee(1)?,
+          ))?;
+        }
+        break;
+      }
+
+      let inputs = inputs.drain(.. economical).collect::<Vec<_>>();
+      let in_amount = inputs.iter().map(|input| input.commitment().amount).sum::<u64>();
+      let out_amount =
+        payments.iter().map(|payment| payment.1).sum::<u64>() + estimate_fee(economical)?;
+      if in_amount < out_amount {
+        Err(TransactionError::NotEnoughFunds(in_amount, out_amount))?;
+      }
+      payments.last_mut().unwrap().1 = in_amount - out_amount;
+
+      txs.push(SignableTransaction::new(
+        self.protocol,
+        self.r_seed.clone(),
+        inputs,
+        payments,
+        change.cloned(),
+        data,
+        self.fee,
+      )?);
+    }
+    Ok((txs, uneconomical))
+  }
 }
 
 /// A Transaction Builder for Monero transactions.
@@ -129,6 +267,35 @@ impl SignableTransactionBuilder {
     Ok(self.shallow_copy())
   }
 
+  /// Select inputs from a pool of spendable outputs to cover the payments and the fee.
+  ///
+  /// Any inputs already added are kept, with outputs from the pool added, largest first, until
+  /// they suffice. Payments must be added before calling this. On error, no inputs are added.
+  pub fn select_inputs(&mut self, pool: &[SpendableOutput]) -> Result<Self, TransactionError> {
+    self.0.write().unwrap().select_inputs(pool)?;
+    Ok(self.shallow_copy())
+  }
+
+  /// Sweep all added inputs to the specified address.
+  ///
+  /// As many transactions as needed to stay under the maximum transaction size are returned, each
+  /// consolidating as many inputs as possible. The first transaction additionally carries any
+  /// payments and data added to this builder.
+  ///
+  /// Inputs worth no more than the fee to spend them aren't swept, and are instead returned
+  /// alongside the transactions.
+  ///
+  /// Transactions only have a change output if they'd otherwise have a single output, as Monero
+  /// requires two outputs. Such change outputs receive no funds, and require a change address
+  /// to have been specified.
+  #[allow(clippy::type_complexity)]
+  pub fn sweep(
+    self,
+    dest: MoneroAddress,
+  ) -> Result<(Vec<SignableTransaction>, Vec<SpendableOutput>), TransactionError> {
+    self.0.read().unwrap().sweep(dest)
+  }
+
   pub fn build(self) -> Result<SignableTransaction, TransactionError> {
     let read = self.0.read().unwrap();
     SignableTransaction::new(
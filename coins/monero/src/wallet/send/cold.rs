@@ -0,0 +1,137 @@
+use core::ops::Deref;
+use std_shims::{vec::Vec, io};
+
+use rand_core::{RngCore, CryptoRng};
+
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar};
+
+use crate::{
+  serialize::{read_vec, write_vec},
+  ringct::{generate_key_image, clsag::Clsag, RctPrunable},
+  transaction::{Input, Transaction},
+  wallet::{
+    Decoys, TxKeys, key_image_sort, uniqueness,
+    send::{SignableTransaction, TransactionError, Eventuality, prepare_inputs},
+  },
+};
+
+/// A transaction with its decoys selected, which may be signed without RPC access.
+///
+/// This enables cold signing, as with wallet2. A watch-only machine with RPC access creates this
+/// via `SignableTransaction::prepare` and serializes it. An air-gapped machine holding the spend
+/// key then reads and signs it, with the signed transaction being serialized back for publication.
+///
+/// This contains the seed for the transaction's ephemeral keys, and the change output's view key
+/// if one was specified. Accordingly, it must be treated securely.
+#[derive(Clone, PartialEq, Eq, Debug, Zeroize, ZeroizeOnDrop)]
+pub struct UnsignedTransaction {
+  signable: SignableTransaction,
+  decoys: Vec<Decoys>,
+}
+
+impl UnsignedTransaction {
+  #[cfg(any(test, feature = "rpc"))]
+  pub(crate) fn new(signable: SignableTransaction, decoys: Vec<Decoys>) -> UnsignedTransaction {
+    debug_assert!(signable.r_seed.is_some());
+    debug_assert_eq!(signable.inputs.len(), decoys.len());
+    UnsignedTransaction { signable, decoys }
+  }
+
+  pub fn fee(&self) -> u64 {
+    self.signable.fee()
+  }
+
+  /// Returns the eventuality of this transaction.
+  ///
+  /// Unlike with a SignableTransaction, this is always present, as the seed for the ephemeral
+  /// keys was fixed when this was prepared.
+  pub fn eventuality(&self) -> Eventuality {
+    self.signable.eventuality().unwrap()
+  }
+
+  /// Returns the ephemeral keys of this transaction, as needed to prove the payments it makes.
+  pub fn tx_keys(&self) -> TxKeys {
+    self.signable.tx_keys().unwrap()
+  }
+
+  pub fn write<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+    self.signable.write(w)?;
+    write_vec(Decoys::write, &self.decoys, w)
+  }
+
+  pub fn serialize(&self) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(2048);
+    self.write(&mut buf).unwrap();
+    buf
+  }
+
+  /// Read an UnsignedTransaction.
+  ///
+  /// This performs the same validation as SignableTransaction::new, and checks each input is the
+  /// real member of its ring. It cannot check the decoys themselves without RPC access. They're
+  /// trusted as selected by the machine which prepared this transaction.
+  pub fn read<R: io::Read>(r: &mut R) -> io::Result<UnsignedTransaction> {
+    let signable = SignableTransaction::read(r)?;
+    let decoys = read_vec(Decoys::read, r)?;
+
+    if signable.r_seed.is_none() {
+      Err(io::Error::new(io::ErrorKind::Other, "unsigned transaction without an r_seed"))?;
+    }
+    if signable.inputs.len() != decoys.len() {
+      Err(io::Error::new(io::ErrorKind::Other, "amount of decoys didn't match amount of inputs"))?;
+    }
+    for (input, decoys) in signable.inputs.iter().zip(&decoys) {
+      if decoys.len() != signable.protocol.ring_len() {
+        Err(io::Error::new(io::ErrorKind::Other, "invalid ring length"))?;
+      }
+      if decoys.ring[usize::from(decoys.i)] != [input.key(), input.commitment().calculate()] {
+        Err(io::Error::new(io::ErrorKind::Other, "input wasn't the real member of its ring"))?;
+      }
+    }
+
+    Ok(UnsignedTransaction { signable, decoys })
+  }
+
+  /// Sign this transaction.
+  pub fn sign<R: RngCore + CryptoRng>(
+    mut self,
+    rng: &mut R,
+    spend: &Zeroizing<Scalar>,
+  ) -> Result<Transaction, TransactionError> {
+    let mut images = Vec::with_capacity(self.signable.inputs.len());
+    for input in &self.signable.inputs {
+      let mut offset = Zeroizing::new(spend.deref() + input.key_offset());
+      if (offset.deref() * &ED25519_BASEPOINT_TABLE) != input.key() {
+        Err(TransactionError::WrongPrivateKey)?;
+      }
+
+      images.push(generate_key_image(&offset));
+      offset.zeroize();
+    }
+    images.sort_by(key_image_sort);
+
+    let (mut tx, mask_sum) = self.signable.prepare_transaction(
+      rng,
+      uniqueness(
+        &images
+          .iter()
+          .map(|image| Input::ToKey { amount: 0, key_offsets: vec![], key_image: *image })
+          .collect::<Vec<_>>(),
+      ),
+    )?;
+
+    let signable = prepare_inputs(&self.signable.inputs, &self.decoys, spend, &mut tx)?;
+
+    let clsag_pairs = Clsag::sign(rng, signable, mask_sum, tx.signature_hash());
+    match tx.rct_signatures.prunable {
+      RctPrunable::Null => panic!("Signing for RctPrunable::Null"),
+      RctPrunable::Clsag { ref mut clsags, ref mut pseudo_outs, .. } => {
+        clsags.append(&mut clsag_pairs.iter().map(|clsag| clsag.0.clone()).collect::<Vec<_>>());
+        pseudo_outs.append(&mut clsag_pairs.iter().map(|clsag| clsag.1).collect::<Vec<_>>());
+      }
+    }
+    Ok(tx)
+  }
+}
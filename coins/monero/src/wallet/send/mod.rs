@@ -1,7 +1,9 @@
 use core::{ops::Deref, fmt};
-use std::io;
-
-use thiserror::Error;
+use std_shims::{
+  vec::Vec,
+  io,
+  string::{String, ToString},
+};
 
 use rand_core::{RngCore, CryptoRng, SeedableRng};
 use rand_chacha::ChaCha20Rng;
@@ -28,23 +30,33 @@ use crate::{
   },
   ringct::{
     generate_key_image,
-    clsag::{ClsagError, ClsagInput, Clsag},
+    clsag::{ClsagError, ClsagInput},
     bulletproofs::{MAX_OUTPUTS, Bulletproofs},
     RctBase, RctPrunable, RctSignatures,
   },
   transaction::{Input, Output, Timelock, TransactionPrefix, Transaction},
-  rpc::{Rpc, RpcError},
   wallet::{
     address::{Network, AddressSpec, MoneroAddress},
-    ViewPair, SpendableOutput, Decoys, PaymentId, ExtraField, Extra, key_image_sort, uniqueness,
+    ViewPair, SpendableOutput, Decoys, PaymentId, ExtraField, Extra, TxKeys, uniqueness,
     shared_key, commitment_mask, amount_encryption,
     extra::{ARBITRARY_DATA_MARKER, MAX_ARBITRARY_DATA_SIZE},
   },
 };
+#[cfg(feature = "rpc")]
+use crate::rpc::{Rpc, RpcError};
+
+// The actual limit is half the block size, and for the minimum block size of 300k, that'd be 150k
+// wallet2 will only create transactions up to 100k bytes however
+pub(crate) const MAX_TX_SIZE: usize = 100_000;
 
+#[cfg(feature = "std")]
 mod builder;
+#[cfg(feature = "std")]
 pub use builder::SignableTransactionBuilder;
 
+mod cold;
+pub use cold::UnsignedTransaction;
+
 #[cfg(feature = "multisig")]
 mod multisig;
 #[cfg(feature = "multisig")]
@@ -116,58 +128,48 @@ impl SendOutput {
   }
 }
 
-#[derive(Clone, PartialEq, Eq, Debug, Error)]
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
 pub enum TransactionError {
-  #[error("multiple addresses with payment IDs")]
+  #[cfg_attr(feature = "std", error("multiple addresses with payment IDs"))]
   MultiplePaymentIds,
-  #[error("no inputs")]
+  #[cfg_attr(feature = "std", error("no inputs"))]
   NoInputs,
-  #[error("no outputs")]
+  #[cfg_attr(feature = "std", error("no outputs"))]
   NoOutputs,
-  #[error("only one output and no change address")]
+  #[cfg_attr(feature = "std", error("only one output and no change address"))]
   NoChange,
-  #[error("too many outputs")]
+  #[cfg_attr(feature = "std", error("too many outputs"))]
   TooManyOutputs,
-  #[error("too much data")]
+  #[cfg_attr(feature = "std", error("too much data"))]
   TooMuchData,
-  #[error("too many inputs/too much arbitrary data")]
+  #[cfg_attr(feature = "std", error("too many inputs/too much arbitrary data"))]
   TooLargeTransaction,
-  #[error("not enough funds (in {0}, out {1})")]
+  #[cfg_attr(feature = "std", error("not enough funds (in {0}, out {1})"))]
   NotEnoughFunds(u64, u64),
-  #[error("wrong spend private key")]
+  #[cfg_attr(feature = "std", error("wrong spend private key"))]
   WrongPrivateKey,
-  #[error("rpc error ({0})")]
+  #[cfg(feature = "rpc")]
+  #[cfg_attr(feature = "std", error("rpc error ({0})"))]
   RpcError(RpcError),
-  #[error("clsag error ({0})")]
+  #[cfg_attr(feature = "std", error("clsag error ({0})"))]
   ClsagError(ClsagError),
-  #[error("invalid transaction ({0})")]
+  #[cfg(feature = "rpc")]
+  #[cfg_attr(feature = "std", error("invalid transaction ({0})"))]
   InvalidTransaction(RpcError),
   #[cfg(feature = "multisig")]
-  #[error("frost error {0}")]
+  #[cfg_attr(feature = "std", error("frost error {0}"))]
   FrostError(FrostError),
 }
 
-async fn prepare_inputs<R: RngCore + CryptoRng>(
-  rng: &mut R,
-  rpc: &Rpc,
-  ring_len: usize,
+fn prepare_inputs(
   inputs: &[SpendableOutput],
+  decoys: &[Decoys],
   spend: &Zeroizing<Scalar>,
   tx: &mut Transaction,
 ) -> Result<Vec<(Zeroizing<Scalar>, EdwardsPoint, ClsagInput)>, TransactionError> {
   let mut signable = Vec::with_capacity(inputs.len());
 
-  // Select decoys
-  let decoys = Decoys::select(
-    rng,
-    rpc,
-    ring_len,
-    rpc.get_height().await.map_err(TransactionError::RpcError)? - 1,
-    inputs,
-  )
-  .await
-  .map_err(TransactionError::RpcError)?;
-
   for (i, input) in inputs.iter().enumerate() {
     let input_spend = Zeroizing::new(input.key_offset() + spend.deref());
     let image = generate_key_image(&input_spend);
@@ -216,6 +218,54 @@ pub(crate) enum InternalPayment {
   Change(Change, u64),
 }
 
+impl InternalPayment {
+  fn write<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+    match self {
+      InternalPayment::Payment(payment) => {
+        w.write_all(&[0])?;
+        write_vec(write_byte, payment.0.to_string().as_bytes(), w)?;
+        w.write_all(&payment.1.to_le_bytes())
+      }
+      InternalPayment::Change(change, amount) => {
+        w.write_all(&[1])?;
+        write_vec(write_byte, change.address.to_string().as_bytes(), w)?;
+        if let Some(view) = change.view.as_ref() {
+          w.write_all(&[1])?;
+          write_scalar(view, w)?;
+        } else {
+          w.write_all(&[0])?;
+        }
+        w.write_all(&amount.to_le_bytes())
+      }
+    }
+  }
+
+  fn read<R: io::Read>(r: &mut R) -> io::Result<InternalPayment> {
+    fn read_address<R: io::Read>(r: &mut R) -> io::Result<MoneroAddress> {
+      String::from_utf8(read_vec(read_byte, r)?)
+        .ok()
+        .and_then(|str| MoneroAddress::from_str_raw(&str).ok())
+        .ok_or(io::Error::new(io::ErrorKind::Other, "invalid address"))
+    }
+
+    Ok(match read_byte(r)? {
+      0 => InternalPayment::Payment((read_address(r)?, read_u64(r)?)),
+      1 => InternalPayment::Change(
+        Change {
+          address: read_address(r)?,
+          view: match read_byte(r)? {
+            0 => None,
+            1 => Some(Zeroizing::new(read_scalar(r)?)),
+            _ => Err(io::Error::new(io::ErrorKind::Other, "invalid change payment"))?,
+          },
+        },
+        read_u64(r)?,
+      ),
+      _ => Err(io::Error::new(io::ErrorKind::Other, "invalid payment"))?,
+    })
+  }
+}
+
 /// The eventual output of a SignableTransaction.
 ///
 /// If the SignableTransaction has a Change with a view key, this will also have the view key.
@@ -277,24 +327,17 @@ impl Change {
 }
 
 impl SignableTransaction {
-  /// Create a signable transaction.
-  ///
-  /// `r_seed` refers to a seed used to derive the transaction's ephemeral keys (colloquially
-  /// called Rs). If None is provided, one will be automatically generated.
-  ///
-  /// Up to 16 outputs may be present, including the change output. If the change address is
-  /// specified, leftover funds will be sent to it.
-  ///
-  /// Each chunk of data must not exceed MAX_ARBITRARY_DATA_SIZE and will be embedded in TX extra.
-  pub fn new(
+  // Validate the shape of a transaction and estimate its weight.
+  //
+  // This doesn't depend on the amounts of the inputs or payments, solely their quantities, which
+  // lets output selection and sweeping estimate fees before deciding on amounts.
+  fn estimate_weight(
     protocol: Protocol,
-    r_seed: Option<Zeroizing<[u8; 32]>>,
-    inputs: Vec<SpendableOutput>,
-    mut payments: Vec<(MoneroAddress, u64)>,
-    change_address: Option<Change>,
-    data: Vec<Vec<u8>>,
-    fee_rate: Fee,
-  ) -> Result<SignableTransaction, TransactionError> {
+    inputs: usize,
+    payments: &[(MoneroAddress, u64)],
+    change_address: Option<&Change>,
+    data: &[Vec<u8>],
+  ) -> Result<usize, TransactionError> {
     // Make sure there's only one payment ID
     let mut has_payment_id = {
       let mut payment_ids = 0;
@@ -303,10 +346,10 @@ impl SignableTransaction {
           payment_ids += 1
         }
       };
-      for payment in &payments {
+      for payment in payments {
         count(payment.0);
       }
-      if let Some(change) = change_address.as_ref() {
+      if let Some(change) = change_address {
         count(change.address);
       }
       if payment_ids > 1 {
@@ -315,14 +358,14 @@ impl SignableTransaction {
       payment_ids == 1
     };
 
-    if inputs.is_empty() {
+    if inputs == 0 {
       Err(TransactionError::NoInputs)?;
     }
     if payments.is_empty() {
       Err(TransactionError::NoOutputs)?;
     }
 
-    for part in &data {
+    for part in data {
       if part.len() > MAX_ARBITRARY_DATA_SIZE {
         Err(TransactionError::TooMuchData)?;
       }
@@ -338,7 +381,7 @@ impl SignableTransaction {
 
     // Calculate the extra length
     // Assume additional keys are needed in order to cause a worst-case estimation
-    let extra = Extra::fee_weight(outputs, true, has_payment_id, data.as_ref());
+    let extra = Extra::fee_weight(outputs, true, has_payment_id, data);
 
     // https://github.com/monero-project/monero/pull/8733
     const MAX_EXTRA_SIZE: usize = 1060;
@@ -349,12 +392,7 @@ impl SignableTransaction {
     // This is a extremely heavy fee weight estimation which can only be trusted for two things
     // 1) Ensuring we have enough for whatever fee we end up using
     // 2) Ensuring we aren't over the max size
-    let estimated_tx_size = Transaction::fee_weight(protocol, inputs.len(), outputs, extra);
-
-    // The actual limit is half the block size, and for the minimum block size of 300k, that'd be
-    // 150k
-    // wallet2 will only create transactions up to 100k bytes however
-    const MAX_TX_SIZE: usize = 100_000;
+    let estimated_tx_size = Transaction::fee_weight(protocol, inputs, outputs, extra);
 
     // This uses the weight (estimated_tx_size) despite the BP clawback
     // The clawback *increases* the weight, so this will over-estimate, yet it's still safe
@@ -362,8 +400,51 @@ impl SignableTransaction {
       Err(TransactionError::TooLargeTransaction)?;
     }
 
-    // Calculate the fee.
-    let fee = fee_rate.calculate(estimated_tx_size);
+    if outputs > MAX_OUTPUTS {
+      Err(TransactionError::TooManyOutputs)?;
+    }
+
+    Ok(estimated_tx_size)
+  }
+
+  // Validate the shape of a transaction and estimate its fee.
+  pub(crate) fn estimate_fee(
+    protocol: Protocol,
+    inputs: usize,
+    payments: &[(MoneroAddress, u64)],
+    change_address: Option<&Change>,
+    data: &[Vec<u8>],
+    fee_rate: Fee,
+  ) -> Result<u64, TransactionError> {
+    Ok(fee_rate.calculate(Self::estimate_weight(protocol, inputs, payments, change_address, data)?))
+  }
+
+  /// Create a signable transaction.
+  ///
+  /// `r_seed` refers to a seed used to derive the transaction's ephemeral keys (colloquially
+  /// called Rs). If None is provided, one will be automatically generated.
+  ///
+  /// Up to 16 outputs may be present, including the change output. If the change address is
+  /// specified, leftover funds will be sent to it.
+  ///
+  /// Each chunk of data must not exceed MAX_ARBITRARY_DATA_SIZE and will be embedded in TX extra.
+  pub fn new(
+    protocol: Protocol,
+    r_seed: Option<Zeroizing<[u8; 32]>>,
+    inputs: Vec<SpendableOutput>,
+    mut payments: Vec<(MoneroAddress, u64)>,
+    change_address: Option<Change>,
+    data: Vec<Vec<u8>>,
+    fee_rate: Fee,
+  ) -> Result<SignableTransaction, TransactionError> {
+    let fee = SignableTransaction::estimate_fee(
+      protocol,
+      inputs.len(),
+      &payments,
+      change_address.as_ref(),
+      &data,
+      fee_rate,
+    )?;
 
     // Make sure we have enough funds
     let in_amount = inputs.iter().map(|input| input.commitment().amount).sum::<u64>();
@@ -372,10 +453,6 @@ impl SignableTransaction {
       Err(TransactionError::NotEnoughFunds(in_amount, out_amount))?;
     }
 
-    if outputs > MAX_OUTPUTS {
-      Err(TransactionError::TooManyOutputs)?;
-    }
-
     let mut payments = payments.drain(..).map(InternalPayment::Payment).collect::<Vec<_>>();
     if let Some(change) = change_address {
       payments.push(InternalPayment::Change(change, in_amount - out_amount));
@@ -388,13 +465,19 @@ impl SignableTransaction {
     self.fee
   }
 
+  /// The outputs this transaction spends.
+  pub fn inputs(&self) -> &[SpendableOutput] {
+    &self.inputs
+  }
+
   #[allow(clippy::type_complexity)]
   fn prepare_payments(
     seed: &Zeroizing<[u8; 32]>,
     inputs: &[EdwardsPoint],
     payments: &mut Vec<InternalPayment>,
     uniqueness: [u8; 32],
-  ) -> (EdwardsPoint, Vec<Zeroizing<Scalar>>, Vec<SendOutput>, Option<[u8; 8]>) {
+  ) -> (Zeroizing<Scalar>, EdwardsPoint, Vec<Zeroizing<Scalar>>, Vec<SendOutput>, Option<[u8; 8]>)
+  {
     let mut rng = {
       // Hash the inputs into the seed so we don't re-use Rs
       // Doesn't re-use uniqueness as that's based on key images, which requires interactivity
@@ -489,12 +572,18 @@ impl SignableTransaction {
             debug_assert_eq!(tx_public_key, output.R);
           }
 
-          if use_dedicated {
-            additional_keys.push(dedicated);
-          } else {
+          if !use_dedicated {
             // If this used tx_key, randomize its R
             // This is so when extra is created, there's a distinct R for it to use
-            output.R = dfg::EdwardsPoint::random(&mut rng).0;
+            // This uses the unused dedicated key so the R's discrete log is known, as wallet2's
+            // are, enabling proving payments for transactions with additional keys
+            output.R = dedicated.deref() * &ED25519_BASEPOINT_TABLE;
+            // This R used to be a randomly sampled point. Continue sampling it so everything
+            // derived from the r_seed afterwards, such as the dummy payment ID, is unchanged
+            let _ = dfg::EdwardsPoint::random(&mut rng);
+          }
+          if additional {
+            additional_keys.push(dedicated);
           }
           (output, payment_id)
         }
@@ -520,7 +609,7 @@ impl SignableTransaction {
       id = id.or(Some(rand));
     }
 
-    (tx_public_key, additional_keys, outputs, id)
+    (tx_key, tx_public_key, additional_keys, outputs, id)
   }
 
   #[allow(non_snake_case)]
@@ -555,13 +644,31 @@ impl SignableTransaction {
     serialized
   }
 
+  fn tx_keys_internal(
+    r_seed: &Zeroizing<[u8; 32]>,
+    inputs: &[EdwardsPoint],
+    payments: &[InternalPayment],
+  ) -> TxKeys {
+    // The ephemeral keys don't depend on the uniqueness
+    let (key, _, additional, _, _) =
+      Self::prepare_payments(r_seed, inputs, &mut payments.to_vec(), [0; 32]);
+    TxKeys { key, additional }
+  }
+
+  /// Returns the ephemeral keys of this transaction, as needed to prove the payments it makes.
+  /// These are only available if a seed for them was specified.
+  pub fn tx_keys(&self) -> Option<TxKeys> {
+    let inputs = self.inputs.iter().map(|input| input.key()).collect::<Vec<_>>();
+    Some(Self::tx_keys_internal(self.r_seed.as_ref()?, &inputs, &self.payments))
+  }
+
   /// Returns the eventuality of this transaction.
   /// The eventuality is defined as the TX extra/outputs this transaction will create, if signed
   /// with the specified seed. This eventuality can be compared to on-chain transactions to see
   /// if the transaction has already been signed and published.
   pub fn eventuality(&self) -> Option<Eventuality> {
     let inputs = self.inputs.iter().map(|input| input.key()).collect::<Vec<_>>();
-    let (tx_key, additional, outputs, id) = Self::prepare_payments(
+    let (_, tx_key, additional, outputs, id) = Self::prepare_payments(
       self.r_seed.as_ref()?,
       &inputs,
       &mut self.payments.clone(),
@@ -590,7 +697,7 @@ impl SignableTransaction {
     &mut self,
     rng: &mut R,
     uniqueness: [u8; 32],
-  ) -> (Transaction, Scalar) {
+  ) -> Result<(Transaction, Scalar), TransactionError> {
     // If no seed for the ephemeral keys was provided, make one
     let r_seed = self.r_seed.clone().unwrap_or_else(|| {
       let mut res = Zeroizing::new([0; 32]);
@@ -598,7 +705,7 @@ impl SignableTransaction {
       res
     });
 
-    let (tx_key, additional, outputs, id) = Self::prepare_payments(
+    let (_, tx_key, additional, outputs, id) = Self::prepare_payments(
       &r_seed,
       &self.inputs.iter().map(|input| input.key()).collect::<Vec<_>>(),
       &mut self.payments,
@@ -610,8 +717,7 @@ impl SignableTransaction {
     let commitments = outputs.iter().map(|output| output.commitment.clone()).collect::<Vec<_>>();
     let sum = commitments.iter().map(|commitment| commitment.mask).sum();
 
-    // Safe due to the constructor checking MAX_OUTPUTS
-    let bp = Bulletproofs::prove(rng, &commitments, self.protocol.bp_plus()).unwrap();
+    let bp = Bulletproofs::prove(rng, &commitments, self.protocol.bp_plus())?;
 
     // Create the TX extra
     let extra = Self::extra(
@@ -635,7 +741,7 @@ impl SignableTransaction {
       ecdh_info.push(output.amount);
     }
 
-    (
+    Ok((
       Transaction {
         prefix: TransactionPrefix {
           version: 2,
@@ -659,50 +765,123 @@ impl SignableTransaction {
         },
       },
       sum,
+    ))
+  }
+
+  /// Select decoys for this transaction, producing an UnsignedTransaction which may be signed
+  /// without RPC access.
+  ///
+  /// If no seed for the ephemeral keys was provided, one will be generated and fixed now, so the
+  /// UnsignedTransaction's eventuality is known prior to signing.
+  #[cfg(feature = "rpc")]
+  pub async fn prepare<R: RngCore + CryptoRng>(
+    mut self,
+    rng: &mut R,
+    rpc: &Rpc,
+  ) -> Result<UnsignedTransaction, TransactionError> {
+    if self.r_seed.is_none() {
+      let mut r_seed = Zeroizing::new([0; 32]);
+      rng.fill_bytes(r_seed.as_mut());
+      self.r_seed = Some(r_seed);
+    }
+
+    let decoys = Decoys::select(
+      rng,
+      rpc,
+      self.protocol.ring_len(),
+      rpc.get_height().await.map_err(TransactionError::RpcError)? - 1,
+      &self.inputs,
     )
+    .await
+    .map_err(TransactionError::RpcError)?;
+
+    Ok(UnsignedTransaction::new(self, decoys))
   }
 
   /// Sign this transaction.
+  #[cfg(feature = "rpc")]
   pub async fn sign<R: RngCore + CryptoRng>(
-    mut self,
+    self,
     rng: &mut R,
     rpc: &Rpc,
     spend: &Zeroizing<Scalar>,
   ) -> Result<Transaction, TransactionError> {
-    let mut images = Vec::with_capacity(self.inputs.len());
-    for input in &self.inputs {
-      let mut offset = Zeroizing::new(spend.deref() + input.key_offset());
-      if (offset.deref() * &ED25519_BASEPOINT_TABLE) != input.key() {
-        Err(TransactionError::WrongPrivateKey)?;
-      }
+    self.prepare(rng, rpc).await?.sign(rng, spend)
+  }
 
-      images.push(generate_key_image(&offset));
-      offset.zeroize();
+  fn write<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+    self.protocol.write(w)?;
+    match self.r_seed.as_ref() {
+      Some(r_seed) => {
+        w.write_all(&[1])?;
+        w.write_all(r_seed.as_ref())?;
+      }
+      None => w.write_all(&[0])?,
     }
-    images.sort_by(key_image_sort);
+    write_vec(|input: &SpendableOutput, w: &mut W| input.write(w), &self.inputs, w)?;
+    write_vec(InternalPayment::write, &self.payments, w)?;
+    write_vec(|data: &Vec<u8>, w: &mut W| write_vec(write_byte, data, w), &self.data, w)?;
+    w.write_all(&self.fee.to_le_bytes())
+  }
 
-    let (mut tx, mask_sum) = self.prepare_transaction(
-      rng,
-      uniqueness(
-        &images
-          .iter()
-          .map(|image| Input::ToKey { amount: 0, key_offsets: vec![], key_image: *image })
-          .collect::<Vec<_>>(),
-      ),
-    );
+  fn read<R: io::Read>(r: &mut R) -> io::Result<SignableTransaction> {
+    let signable = SignableTransaction {
+      protocol: Protocol::read(r)?,
+      r_seed: match read_byte(r)? {
+        0 => None,
+        1 => Some(Zeroizing::new(read_bytes::<_, 32>(r)?)),
+        _ => Err(io::Error::new(io::ErrorKind::Other, "invalid r_seed flag"))?,
+      },
+      inputs: read_vec(SpendableOutput::read, r)?,
+      payments: read_vec(InternalPayment::read, r)?,
+      data: read_vec(|r: &mut R| read_vec(read_byte, r), r)?,
+      fee: read_u64(r)?,
+    };
 
-    let signable =
-      prepare_inputs(rng, rpc, self.protocol.ring_len(), &self.inputs, spend, &mut tx).await?;
+    // Perform the validation SignableTransaction::new does, as this may have been serialized by
+    // anyone
+    let mut payments = vec![];
+    let mut change = None;
+    let mut out_amount = Some(signable.fee);
+    for payment in &signable.payments {
+      let amount = match payment {
+        InternalPayment::Payment(payment) => {
+          payments.push(*payment);
+          payment.1
+        }
+        InternalPayment::Change(address, amount) => {
+          // Change outputs with a view key can't be to a subaddress
+          if change.is_some() || (address.view.is_some() && address.address.is_subaddress()) {
+            Err(io::Error::new(io::ErrorKind::Other, "invalid change"))?;
+          }
+          change = Some(address);
+          *amount
+        }
+      };
+      out_amount = out_amount.and_then(|out_amount| out_amount.checked_add(amount));
+    }
+    SignableTransaction::estimate_weight(
+      signable.protocol,
+      signable.inputs.len(),
+      &payments,
+      change,
+      &signable.data,
+    )
+    .map_err(|_| io::Error::new(io::ErrorKind::Other, "invalid transaction"))?;
 
-    let clsag_pairs = Clsag::sign(rng, signable, mask_sum, tx.signature_hash());
-    match tx.rct_signatures.prunable {
-      RctPrunable::Null => panic!("Signing for RctPrunable::Null"),
-      RctPrunable::Clsag { ref mut clsags, ref mut pseudo_outs, .. } => {
-        clsags.append(&mut clsag_pairs.iter().map(|clsag| clsag.0.clone()).collect::<Vec<_>>());
-        pseudo_outs.append(&mut clsag_pairs.iter().map(|clsag| clsag.1).collect::<Vec<_>>());
-      }
+    let in_amount = signable
+      .inputs
+      .iter()
+      .try_fold(0u64, |in_amount, input| in_amount.checked_add(input.commitment().amount));
+    if in_amount
+      .zip(out_amount)
+      .map(|(in_amount, out_amount)| in_amount < out_amount)
+      .unwrap_or(true)
+    {
+      Err(io::Error::new(io::ErrorKind::Other, "inputs didn't cover the outputs and fee"))?;
     }
-    Ok(tx)
+
+    Ok(signable)
   }
 }
 
@@ -716,6 +895,11 @@ impl Eventuality {
     &self.extra
   }
 
+  /// Returns the ephemeral keys of the transaction, as needed to prove the payments it makes.
+  pub fn tx_keys(&self) -> TxKeys {
+    SignableTransaction::tx_keys_internal(&self.r_seed, &self.inputs, &self.payments)
+  }
+
   pub fn matches(&self, tx: &Transaction) -> bool {
     if self.payments.len() != tx.prefix.outputs.len() {
       return false;
@@ -735,7 +919,7 @@ impl Eventuality {
     }
 
     // Generate the outputs. This is TX-specific due to uniqueness.
-    let (_, _, outputs, _) = SignableTransaction::prepare_payments(
+    let (_, _, _, outputs, _) = SignableTransaction::prepare_payments(
       &self.r_seed,
       &self.inputs,
       &mut self.payments.clone(),
@@ -763,29 +947,7 @@ impl Eventuality {
     self.protocol.write(w)?;
     write_raw_vec(write_byte, self.r_seed.as_ref(), w)?;
     write_vec(write_point, &self.inputs, w)?;
-
-    fn write_payment<W: io::Write>(payment: &InternalPayment, w: &mut W) -> io::Result<()> {
-      match payment {
-        InternalPayment::Payment(payment) => {
-          w.write_all(&[0])?;
-          write_vec(write_byte, payment.0.to_string().as_bytes(), w)?;
-          w.write_all(&payment.1.to_le_bytes())
-        }
-        InternalPayment::Change(change, amount) => {
-          w.write_all(&[1])?;
-          write_vec(write_byte, change.address.to_string().as_bytes(), w)?;
-          if let Some(view) = change.view.as_ref() {
-            w.write_all(&[1])?;
-            write_scalar(view, w)?;
-          } else {
-            w.write_all(&[0])?;
-          }
-          w.write_all(&amount.to_le_bytes())
-        }
-      }
-    }
-    write_vec(write_payment, &self.payments, w)?;
-
+    write_vec(InternalPayment::write, &self.payments, w)?;
     write_vec(write_byte, &self.extra, w)
   }
 
@@ -796,36 +958,11 @@ impl Eventuality {
   }
 
   pub fn read<R: io::Read>(r: &mut R) -> io::Result<Eventuality> {
-    fn read_address<R: io::Read>(r: &mut R) -> io::Result<MoneroAddress> {
-      String::from_utf8(read_vec(read_byte, r)?)
-        .ok()
-        .and_then(|str| MoneroAddress::from_str_raw(&str).ok())
-        .ok_or(io::Error::new(io::ErrorKind::Other, "invalid address"))
-    }
-
-    fn read_payment<R: io::Read>(r: &mut R) -> io::Result<InternalPayment> {
-      Ok(match read_byte(r)? {
-        0 => InternalPayment::Payment((read_address(r)?, read_u64(r)?)),
-        1 => InternalPayment::Change(
-          Change {
-            address: read_address(r)?,
-            view: match read_byte(r)? {
-              0 => None,
-              1 => Some(Zeroizing::new(read_scalar(r)?)),
-              _ => Err(io::Error::new(io::ErrorKind::Other, "invalid change payment"))?,
-            },
-          },
-          read_u64(r)?,
-        ),
-        _ => Err(io::Error::new(io::ErrorKind::Other, "invalid payment"))?,
-      })
-    }
-
     Ok(Eventuality {
       protocol: Protocol::read(r)?,
       r_seed: Zeroizing::new(read_bytes::<_, 32>(r)?),
       inputs: read_vec(read_point, r)?,
-      payments: read_vec(read_payment, r)?,
+      payments: read_vec(InternalPayment::read, r)?,
       extra: read_vec(read_byte, r)?,
     })
   }
@@ -1,7 +1,7 @@
 use std::{
   io::{self, Read},
   sync::{Arc, RwLock},
-  collections::HashMap,
+  collections::BTreeMap,
 };
 
 use zeroize::Zeroizing;
@@ -263,7 +263,7 @@ impl SignMachine<Transaction> for TransactionSignMachine {
 
   fn sign(
     mut self,
-    mut commitments: HashMap<Participant, Self::Preprocess>,
+    mut commitments: BTreeMap<Participant, Self::Preprocess>,
     msg: &[u8],
   ) -> Result<(TransactionSignatureMachine, Self::SignatureShare), FrostError> {
     if !msg.is_empty() {
@@ -318,7 +318,7 @@ impl SignMachine<Transaction> for TransactionSignMachine {
 
             Ok((*l, preprocess))
           })
-          .collect::<Result<HashMap<_, _>, _>>()
+          .collect::<Result<BTreeMap<_, _>, _>>()
       })
       .collect::<Result<Vec<_>, _>>()?;
 
@@ -333,16 +333,20 @@ impl SignMachine<Transaction> for TransactionSignMachine {
       let mut sorted_images = images.clone();
       sorted_images.sort_by(key_image_sort);
 
-      self.signable.prepare_transaction(
-        // Technically, r_seed is used for the transaction keys if it's provided
-        &mut ChaCha20Rng::from_seed(self.transcript.rng_seed(b"transaction_keys_bulletproofs")),
-        uniqueness(
-          &sorted_images
-            .iter()
-            .map(|image| Input::ToKey { amount: 0, key_offsets: vec![], key_image: *image })
-            .collect::<Vec<_>>(),
-        ),
-      )
+      self
+        .signable
+        .prepare_transaction(
+          // Technically, r_seed is used for the transaction keys if it's provided
+          &mut ChaCha20Rng::from_seed(self.transcript.rng_seed(b"transaction_keys_bulletproofs")),
+          uniqueness(
+            &sorted_images
+              .iter()
+              .map(|image| Input::ToKey { amount: 0, key_offsets: vec![], key_image: *image })
+              .collect::<Vec<_>>(),
+          ),
+        )
+        // The constructor validated the amount of outputs
+        .expect("SignableTransaction had an invalid amount of outputs")
     };
 
     // Sort the inputs, as expected
@@ -415,7 +419,7 @@ impl SignatureMachine<Transaction> for TransactionSignatureMachine {
 
   fn complete(
     mut self,
-    shares: HashMap<Participant, Self::SignatureShare>,
+    shares: BTreeMap<Participant, Self::SignatureShare>,
   ) -> Result<Transaction, FrostError> {
     let mut tx = self.tx;
     match tx.rct_signatures.prunable {
@@ -423,7 +427,7 @@ impl SignatureMachine<Transaction> for TransactionSignatureMachine {
       RctPrunable::Clsag { ref mut clsags, ref mut pseudo_outs, .. } => {
         for (c, clsag) in self.clsags.drain(..).enumerate() {
           let (clsag, pseudo_out) = clsag.complete(
-            shares.iter().map(|(l, shares)| (*l, shares[c].clone())).collect::<HashMap<_, _>>(),
+            shares.iter().map(|(l, shares)| (*l, shares[c].clone())).collect::<BTreeMap<_, _>>(),
           )?;
           clsags.push(clsag);
           pseudo_outs.push(pseudo_out);
@@ -0,0 +1,881 @@
+use std_shims::{
+  vec::Vec,
+  collections::VecDeque,
+  io::{self, Read, Write},
+};
+#[cfg(feature = "std")]
+use std::{fs, path::Path};
+
+use rand_core::{RngCore, CryptoRng};
+
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+use subtle::ConstantTimeEq;
+
+use chacha20::{
+  cipher::{crypto_common::KeyIvInit, StreamCipher},
+  Key as Cc20Key, Nonce as Cc20Iv, ChaCha20,
+};
+use chacha20poly1305::{
+  aead::{Aead, KeyInit, Payload},
+  Key, Nonce, ChaCha20Poly1305,
+};
+
+use curve25519_dalek::edwards::EdwardsPoint;
+
+use crate::{
+  hash,
+  serialize::{
+    read_byte, read_u32, read_u64, read_bytes, read_varint, read_scalar, read_point, write_scalar,
+    write_point,
+  },
+  transaction::Timelock,
+  wallet::{address::SubaddressIndex, ViewPair, Scanner, SpendableOutput, Timelocked},
+};
+
+const MAGIC: &[u8; 8] = b"MSWALLET";
+
+// Record kinds
+const SNAPSHOT: u8 = 0;
+const SUBADDRESS: u8 = 1;
+const BLOCK: u8 = 2;
+const ROLLBACK: u8 = 3;
+// Marks every record before it as completely written, containing the amount of records before it
+const COMMIT: u8 = 4;
+const METADATA: u8 = 5;
+
+// The amount of recent block hashes kept for detecting reorganizations
+const RETAINED_HASHES: usize = 100;
+
+// The amount of records after which saving will rewrite the store, instead of appending to it
+#[cfg(feature = "std")]
+const COMPACT_AFTER: u64 = 1000;
+
+const FILE_ID_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+// The length of Poly1305's tag
+const TAG_LEN: usize = 16;
+// The length of the tag used by version 0
+const LEGACY_TAG_LEN: usize = 32;
+
+/// Errors when reading or saving a wallet store.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+pub enum StoreError {
+  #[cfg_attr(feature = "std", error("io error ({0:?})"))]
+  Io(io::ErrorKind),
+  #[cfg_attr(feature = "std", error("invalid wallet store"))]
+  InvalidFormat,
+  #[cfg_attr(feature = "std", error("unsupported wallet store version ({0})"))]
+  UnsupportedVersion(u8),
+  #[cfg_attr(feature = "std", error("wallet store is encrypted yet no key was provided"))]
+  EncryptionKeyRequired,
+  #[cfg_attr(feature = "std", error("wallet store isn't encrypted yet a key was provided"))]
+  NotEncrypted,
+  #[cfg_attr(feature = "std", error("invalid encryption key"))]
+  InvalidEncryptionKey,
+}
+
+fn io_error(e: io::Error) -> StoreError {
+  StoreError::Io(e.kind())
+}
+
+fn read_usize<R: Read>(r: &mut R) -> io::Result<usize> {
+  usize::try_from(read_u64(r)?)
+    .map_err(|_| io::Error::new(io::ErrorKind::Other, "number exceeded usize"))
+}
+
+fn write_usize<W: Write>(value: usize, w: &mut W) -> io::Result<()> {
+  w.write_all(&u64::try_from(value).unwrap().to_le_bytes())
+}
+
+fn write_len<W: Write>(len: usize, w: &mut W) -> io::Result<()> {
+  w.write_all(&u32::try_from(len).unwrap().to_le_bytes())
+}
+
+fn write_subaddress<W: Write>(subaddress: &SubaddressIndex, w: &mut W) -> io::Result<()> {
+  w.write_all(&subaddress.account().to_le_bytes())?;
+  w.write_all(&subaddress.address().to_le_bytes())
+}
+
+fn read_subaddress<R: Read>(r: &mut R) -> io::Result<SubaddressIndex> {
+  SubaddressIndex::new(read_u32(r)?, read_u32(r)?)
+    .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "invalid subaddress"))
+}
+
+/// An output received by a wallet, as tracked by a WalletStore.
+#[derive(Clone, PartialEq, Eq, Debug, Zeroize, ZeroizeOnDrop)]
+pub struct StoredOutput {
+  pub output: SpendableOutput,
+  /// The number of the block this output was included in.
+  pub block: usize,
+  pub timelock: Timelock,
+  /// The number of the block this output was spent in, if it's been detected as spent.
+  pub spent: Option<usize>,
+}
+
+impl StoredOutput {
+  pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    self.output.write(w)?;
+    write_usize(self.block, w)?;
+    self.timelock.write(w)?;
+    if let Some(spent) = self.spent {
+      w.write_all(&[1])?;
+      write_usize(spent, w)
+    } else {
+      w.write_all(&[0])
+    }
+  }
+
+  pub fn serialize(&self) -> Vec<u8> {
+    let mut serialized = vec![];
+    self.write(&mut serialized).unwrap();
+    serialized
+  }
+
+  pub fn read<R: Read>(r: &mut R) -> io::Result<StoredOutput> {
+    Ok(StoredOutput {
+      output: SpendableOutput::read(r)?,
+      block: read_usize(r)?,
+      timelock: Timelock::from_raw(read_varint(r)?),
+      spent: match read_byte(r)? {
+        0 => None,
+        1 => Some(read_usize(r)?),
+        _ => Err(io::Error::new(io::ErrorKind::Other, "invalid spent flag"))?,
+      },
+    })
+  }
+}
+
+/// A transaction which received to or spent from a wallet.
+#[derive(Clone, PartialEq, Eq, Debug, Zeroize)]
+pub struct StoredTransaction {
+  pub hash: [u8; 32],
+  /// The number of the block this transaction was included in.
+  pub block: usize,
+  /// The amount received by this transaction.
+  pub received: u64,
+  /// The amount spent by this transaction, including its fee.
+  pub spent: u64,
+  pub fee: u64,
+  pub timelock: Timelock,
+}
+
+impl StoredTransaction {
+  pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    w.write_all(&self.hash)?;
+    write_usize(self.block, w)?;
+    w.write_all(&self.received.to_le_bytes())?;
+    w.write_all(&self.spent.to_le_bytes())?;
+    w.write_all(&self.fee.to_le_bytes())?;
+    self.timelock.write(w)
+  }
+
+  pub fn serialize(&self) -> Vec<u8> {
+    let mut serialized = vec![];
+    self.write(&mut serialized).unwrap();
+    serialized
+  }
+
+  pub fn read<R: Read>(r: &mut R) -> io::Result<StoredTransaction> {
+    Ok(StoredTransaction {
+      hash: read_bytes(r)?,
+      block: read_usize(r)?,
+      received: read_u64(r)?,
+      spent: read_u64(r)?,
+      fee: read_u64(r)?,
+      timelock: Timelock::from_raw(read_varint(r)?),
+    })
+  }
+}
+
+// A change to the store, appended as a record after the snapshot
+enum Change {
+  Subaddress(SubaddressIndex),
+  Block {
+    hash: [u8; 32],
+    outputs: Vec<StoredOutput>,
+    // The keys of the outputs spent
+    spent: Vec<EdwardsPoint>,
+    transactions: Vec<StoredTransaction>,
+  },
+  Rollback(usize),
+  Metadata(Zeroizing<Vec<u8>>),
+}
+
+impl Change {
+  fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    match self {
+      Change::Subaddress(subaddress) => {
+        w.write_all(&[SUBADDRESS])?;
+        write_subaddress(subaddress, w)
+      }
+      Change::Block { hash, outputs, spent, transactions } => {
+        w.write_all(&[BLOCK])?;
+        w.write_all(hash)?;
+        write_len(outputs.len(), w)?;
+        for output in outputs {
+          output.write(w)?;
+        }
+        write_len(spent.len(), w)?;
+        for key in spent {
+          write_point(key, w)?;
+        }
+        write_len(transactions.len(), w)?;
+        for tx in transactions {
+          tx.write(w)?;
+        }
+        Ok(())
+      }
+      Change::Rollback(block) => {
+        w.write_all(&[ROLLBACK])?;
+        write_usize(*block, w)
+      }
+      Change::Metadata(metadata) => {
+        w.write_all(&[METADATA])?;
+        write_len(metadata.len(), w)?;
+        w.write_all(metadata)
+      }
+    }
+  }
+
+  fn read<R: Read>(r: &mut R) -> io::Result<Change> {
+    Ok(match read_byte(r)? {
+      SUBADDRESS => Change::Subaddress(read_subaddress(r)?),
+      BLOCK => Change::Block {
+        hash: read_bytes(r)?,
+        outputs: (0 .. read_u32(r)?).map(|_| StoredOutput::read(r)).collect::<Result<_, _>>()?,
+        spent: (0 .. read_u32(r)?).map(|_| read_point(r)).collect::<Result<_, _>>()?,
+        transactions: (0 .. read_u32(r)?)
+          .map(|_| StoredTransaction::read(r))
+          .collect::<Result<_, _>>()?,
+      },
+      ROLLBACK => Change::Rollback(read_usize(r)?),
+      METADATA => Change::Metadata(read_metadata(r)?),
+      _ => Err(io::Error::new(io::ErrorKind::Other, "unknown record kind"))?,
+    })
+  }
+}
+
+fn read_metadata<R: Read>(r: &mut R) -> io::Result<Zeroizing<Vec<u8>>> {
+  let mut metadata = Zeroizing::new(vec![0; usize::try_from(read_u32(r)?).unwrap()]);
+  r.read_exact(&mut metadata)?;
+  Ok(metadata)
+}
+
+// Derive the cipher used to encrypt the records of the file with the specified ID
+fn record_cipher(key: &[u8; 32], file_id: &[u8; FILE_ID_LEN]) -> ChaCha20Poly1305 {
+  let key = Zeroizing::new(hash(&Zeroizing::new(
+    [b"monero_serai_wallet_store".as_ref(), key.as_ref(), file_id.as_ref()].concat(),
+  )));
+  ChaCha20Poly1305::new(Key::from_slice(key.as_ref()))
+}
+
+// Each record is authenticated with the header of its file, which includes the file's ID, and its
+// index, so records can't be moved within or between files
+fn record_aad(header: &[u8], index: u64) -> Vec<u8> {
+  [header, &index.to_le_bytes()].concat()
+}
+
+// Derive the keys used to encrypt and authenticate records by version 0
+fn legacy_record_keys(key: &[u8; 32]) -> (Zeroizing<[u8; 32]>, Zeroizing<[u8; 32]>) {
+  let derive = |dst: &[u8]| Zeroizing::new(hash(&Zeroizing::new([dst, key.as_ref()].concat())));
+  (
+    derive(b"monero_serai_wallet_store_encryption"),
+    derive(b"monero_serai_wallet_store_authentication"),
+  )
+}
+
+// Version 0 authenticated records with H(key || index || nonce || ciphertext)
+fn legacy_record_tag(key: &[u8; 32], index: u64, nonce: &[u8], ciphertext: &[u8]) -> [u8; 32] {
+  hash(&Zeroizing::new([key.as_ref(), &index.to_le_bytes(), nonce, ciphertext].concat()))
+}
+
+// Decrypt a record written by version 0
+fn legacy_decrypt_record(
+  key: &[u8; 32],
+  index: u64,
+  record: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, StoreError> {
+  if record.len() < (NONCE_LEN + LEGACY_TAG_LEN) {
+    Err(StoreError::InvalidFormat)?;
+  }
+  let (encryption, authentication) = legacy_record_keys(key);
+  let (nonce, ciphertext) = record.split_at(NONCE_LEN);
+  let (ciphertext, tag) = ciphertext.split_at(ciphertext.len() - LEGACY_TAG_LEN);
+  if !bool::from(legacy_record_tag(&authentication, index, nonce, ciphertext).ct_eq(tag)) {
+    Err(StoreError::InvalidEncryptionKey)?;
+  }
+
+  let mut plaintext = Zeroizing::new(ciphertext.to_vec());
+  ChaCha20::new(Cc20Key::from_slice(encryption.as_ref()), Cc20Iv::from_slice(nonce))
+    .apply_keystream(&mut plaintext);
+  Ok(plaintext)
+}
+
+// Read a record, returning None if the reader was exhausted
+fn read_record<R: Read>(
+  r: &mut R,
+  version: u8,
+  key: Option<&Zeroizing<[u8; 32]>>,
+  (header, file_id): (&[u8], &[u8; FILE_ID_LEN]),
+  index: u64,
+) -> Result<Option<Zeroizing<Vec<u8>>>, StoreError> {
+  let mut len = [0; 4];
+  if r.read(&mut len[.. 1]).map_err(io_error)? == 0 {
+    return Ok(None);
+  }
+  r.read_exact(&mut len[1 ..]).map_err(io_error)?;
+  let mut record = Zeroizing::new(vec![0; usize::try_from(u32::from_le_bytes(len)).unwrap()]);
+  r.read_exact(&mut record).map_err(io_error)?;
+
+  let key = match key {
+    Some(key) => key,
+    None => return Ok(Some(record)),
+  };
+  if version == 0 {
+    return legacy_decrypt_record(key, index, &record).map(Some);
+  }
+
+  if record.len() < (NONCE_LEN + TAG_LEN) {
+    Err(StoreError::InvalidFormat)?;
+  }
+  let (nonce, ciphertext) = record.split_at(NONCE_LEN);
+  record_cipher(key, file_id)
+    .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad: &record_aad(header, index) })
+    .map(|plaintext| Some(Zeroizing::new(plaintext)))
+    .map_err(|_| StoreError::InvalidEncryptionKey)
+}
+
+/// A wallet's state, as needed to scan the blockchain and track received outputs.
+///
+/// The store is serialized as a snapshot of the wallet's state followed by a log of changes, one
+/// per scanned block, enabling saving just the changes made since the store was last saved. Every
+/// save ends with a record committing to the amount of records written, so records after the last
+/// commit, as left by an interrupted save, are discarded.
+///
+/// If an encryption key is provided, every record is encrypted with ChaCha20-Poly1305, bound to
+/// its position and to the serialization, which is identified by a random ID chosen whenever the
+/// store is written in full. The key SHOULD be derived from the user's password with a password
+/// hashing function. As a serialization remains valid, a prior serialization of the store may be
+/// provided in place of the latest one, which this cannot detect.
+///
+/// The store also keeps the hashes of the most recently scanned blocks, enabling detecting
+/// reorganizations and rolling back to before them.
+pub struct WalletStore {
+  pair: ViewPair,
+  key: Option<Zeroizing<[u8; 32]>>,
+  subaddresses: Vec<SubaddressIndex>,
+  // The number of the next block to scan
+  height: usize,
+  hashes: VecDeque<[u8; 32]>,
+  outputs: Vec<StoredOutput>,
+  transactions: Vec<StoredTransaction>,
+  metadata: Zeroizing<Vec<u8>>,
+
+  // The ID of the serialization, chosen whenever the store is written in full
+  file_id: [u8; FILE_ID_LEN],
+  // The amount of records written, used to bind each encrypted record to its position
+  records: u64,
+  // The changes not yet saved
+  pending: Vec<Zeroizing<Vec<u8>>>,
+  // If the store must be written in full, as it's never been written, was read from a prior
+  // version, or was left with a partially written record
+  rewrite: bool,
+}
+
+impl WalletStore {
+  /// The current version of the serialization.
+  pub const VERSION: u8 = 2;
+
+  /// Create a new store, which will start scanning from the specified block.
+  pub fn new(pair: ViewPair, height: usize, key: Option<Zeroizing<[u8; 32]>>) -> WalletStore {
+    WalletStore {
+      pair,
+      key,
+      subaddresses: vec![],
+      height,
+      hashes: VecDeque::new(),
+      outputs: vec![],
+      transactions: vec![],
+      metadata: Zeroizing::new(vec![]),
+      file_id: [0; FILE_ID_LEN],
+      records: 0,
+      pending: vec![],
+      rewrite: true,
+    }
+  }
+
+  pub fn view_pair(&self) -> &ViewPair {
+    &self.pair
+  }
+
+  pub fn subaddresses(&self) -> &[SubaddressIndex] {
+    &self.subaddresses
+  }
+
+  /// The number of the next block to scan.
+  pub fn height(&self) -> usize {
+    self.height
+  }
+
+  /// The hash of the specified block, if it was recently scanned.
+  pub fn block_hash(&self, number: usize) -> Option<[u8; 32]> {
+    let first = self.height - self.hashes.len();
+    if (number < first) || (number >= self.height) {
+      None?;
+    }
+    Some(self.hashes[number - first])
+  }
+
+  pub fn outputs(&self) -> &[StoredOutput] {
+    &self.outputs
+  }
+
+  pub fn unspent(&self) -> impl Iterator<Item = &StoredOutput> {
+    self.outputs.iter().filter(|output| output.spent.is_none())
+  }
+
+  pub fn transactions(&self) -> &[StoredTransaction] {
+    &self.transactions
+  }
+
+  /// The metadata set by the application using this store, which is empty if never set.
+  pub fn metadata(&self) -> &[u8] {
+    &self.metadata
+  }
+
+  /// Create a Scanner for this store, with its subaddresses registered and the keys of every
+  /// received output marked as used, preventing the burning bug.
+  ///
+  /// Key images aren't stored and must be imported into the returned Scanner if spent outputs
+  /// should be detected.
+  pub fn scanner(&self) -> Scanner {
+    let mut scanner = Scanner::from_view(
+      self.pair.clone(),
+      Some(self.outputs.iter().map(|output| output.output.key().compress()).collect()),
+    );
+    for subaddress in &self.subaddresses {
+      scanner.register_subaddress(*subaddress);
+    }
+    scanner
+  }
+
+  /// Register a subaddress, which will be registered with every Scanner created for this store.
+  pub fn register_subaddress(&mut self, subaddress: SubaddressIndex) {
+    if !self.subaddresses.contains(&subaddress) {
+      self.change(Change::Subaddress(subaddress));
+    }
+  }
+
+  /// Set the application's metadata, such as the wallet's spend key and labels, which is stored
+  /// (and encrypted) alongside the wallet's state.
+  ///
+  /// The metadata is replaced in full, so it should be kept small.
+  pub fn set_metadata(&mut self, metadata: Zeroizing<Vec<u8>>) {
+    if metadata != self.metadata {
+      self.change(Change::Metadata(metadata));
+    }
+  }
+
+  /// Push the next block, with the outputs received in it, the keys of the outputs spent in it,
+  /// and the transactions relevant to this wallet within it.
+  pub fn push_block(
+    &mut self,
+    hash: [u8; 32],
+    outputs: &[Timelocked<SpendableOutput>],
+    spent: &[EdwardsPoint],
+    transactions: Vec<StoredTransaction>,
+  ) {
+    let block = self.height;
+    assert!(
+      transactions.iter().all(|tx| tx.block == block),
+      "pushing a block with transactions from another block"
+    );
+
+    let outputs = outputs
+      .iter()
+      .flat_map(|outputs| {
+        let timelock = outputs.timelock();
+        outputs.ignore_timelock().into_iter().map(move |output| StoredOutput {
+          output,
+          block,
+          timelock,
+          spent: None,
+        })
+      })
+      .collect();
+    self.change(Change::Block { hash, outputs, spent: spent.to_vec(), transactions });
+  }
+
+  /// Roll back to before the specified block, removing every block from it onwards.
+  ///
+  /// Outputs received in removed blocks are removed, and outputs spent in removed blocks are
+  /// marked as unspent.
+  pub fn rollback(&mut self, block: usize) {
+    if block < self.height {
+      self.change(Change::Rollback(block));
+    }
+  }
+
+  fn change(&mut self, change: Change) {
+    let mut record = Zeroizing::new(vec![]);
+    change.write(&mut *record).unwrap();
+    self.pending.push(record);
+    self.apply(change);
+  }
+
+  fn apply(&mut self, change: Change) {
+    match change {
+      Change::Subaddress(subaddress) => self.subaddresses.push(subaddress),
+      Change::Block { hash, outputs, spent, transactions } => {
+        self.outputs.extend(outputs);
+        for output in &mut self.outputs {
+          if output.spent.is_none() && spent.contains(&output.output.key()) {
+            output.spent = Some(self.height);
+          }
+        }
+        self.transactions.extend(transactions);
+
+        self.hashes.push_back(hash);
+        while self.hashes.len() > RETAINED_HASHES {
+          self.hashes.pop_front();
+        }
+        self.height += 1;
+      }
+      Change::Rollback(block) => {
+        let first = self.height - self.hashes.len();
+        self.hashes.truncate(block.saturating_sub(first));
+        self.height = block;
+
+        self.outputs.retain(|output| output.block < block);
+        for output in &mut self.outputs {
+          if output.spent.map(|spent| spent >= block).unwrap_or(false) {
+            output.spent = None;
+          }
+        }
+        self.transactions.retain(|tx| tx.block < block);
+      }
+      Change::Metadata(metadata) => self.metadata = metadata,
+    }
+  }
+
+  // Check a change read from a serialization is consistent with the store
+  fn valid(&self, change: &Change) -> bool {
+    match change {
+      Change::Subaddress(subaddress) => !self.subaddresses.contains(subaddress),
+      Change::Block { outputs, transactions, .. } => {
+        outputs.iter().all(|output| (output.block == self.height) && output.spent.is_none()) &&
+          transactions.iter().all(|tx| tx.block == self.height)
+      }
+      Change::Rollback(block) => *block < self.height,
+      Change::Metadata(_) => true,
+    }
+  }
+
+  // The header of the serialization
+  fn header(&self) -> Vec<u8> {
+    let mut header = MAGIC.to_vec();
+    header.extend([Self::VERSION, u8::from(self.key.is_some())]);
+    header.extend(self.file_id);
+    header
+  }
+
+  fn write_record<R: RngCore + CryptoRng, W: Write>(
+    &mut self,
+    rng: &mut R,
+    record: &[u8],
+    w: &mut W,
+  ) -> io::Result<()> {
+    let record = if let Some(key) = &self.key {
+      let mut nonce = [0; NONCE_LEN];
+      rng.fill_bytes(&mut nonce);
+      let ciphertext = record_cipher(key, &self.file_id)
+        .encrypt(
+          Nonce::from_slice(&nonce),
+          Payload { msg: record, aad: &record_aad(&self.header(), self.records) },
+        )
+        .map_err(|_| io::Error::new(io::ErrorKind::Other, "couldn't encrypt record"))?;
+      Zeroizing::new([nonce.as_ref(), &ciphertext].concat())
+    } else {
+      Zeroizing::new(record.to_vec())
+    };
+
+    write_len(record.len(), w)?;
+    w.write_all(&record)?;
+    self.records += 1;
+    Ok(())
+  }
+
+  // Commit to every record written so far
+  fn write_commit<R: RngCore + CryptoRng, W: Write>(
+    &mut self,
+    rng: &mut R,
+    w: &mut W,
+  ) -> io::Result<()> {
+    let mut commit = vec![COMMIT];
+    commit.extend(self.records.to_le_bytes());
+    self.write_record(rng, &commit, w)
+  }
+
+  fn write_snapshot<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    w.write_all(&[SNAPSHOT])?;
+    write_point(&self.pair.spend, w)?;
+    write_scalar(&self.pair.view, w)?;
+
+    write_len(self.subaddresses.len(), w)?;
+    for subaddress in &self.subaddresses {
+      write_subaddress(subaddress, w)?;
+    }
+
+    write_usize(self.height, w)?;
+    write_len(self.hashes.len(), w)?;
+    for hash in &self.hashes {
+      w.write_all(hash)?;
+    }
+
+    write_len(self.outputs.len(), w)?;
+    for output in &self.outputs {
+      output.write(w)?;
+    }
+    write_len(self.transactions.len(), w)?;
+    for tx in &self.transactions {
+      tx.write(w)?;
+    }
+    write_len(self.metadata.len(), w)?;
+    w.write_all(&self.metadata)
+  }
+
+  /// Write the entire store, compacting any changes into its snapshot.
+  pub fn write<R: RngCore + CryptoRng, W: Write>(
+    &mut self,
+    rng: &mut R,
+    w: &mut W,
+  ) -> io::Result<()> {
+    self.rewrite = true;
+
+    // Use a new ID, so records from prior serializations aren't valid within this one
+    rng.fill_bytes(&mut self.file_id);
+    let mut store = Zeroizing::new(self.header());
+    let mut snapshot = Zeroizing::new(vec![]);
+    self.write_snapshot(&mut *snapshot)?;
+    self.records = 0;
+    self.write_record(rng, &snapshot, &mut *store)?;
+    self.write_commit(rng, &mut *store)?;
+    w.write_all(&store)?;
+
+    self.pending.clear();
+    self.rewrite = false;
+    Ok(())
+  }
+
+  /// Write the changes made since the store was last read or written, to be appended to that
+  /// serialization.
+  ///
+  /// This will error if the store must be written in full, as it's never been written, was read
+  /// from a prior version of the serialization, or the serialization it was read from ended with
+  /// a partially written record. If this errors, the store must be written in full.
+  pub fn write_changes<R: RngCore + CryptoRng, W: Write>(
+    &mut self,
+    rng: &mut R,
+    w: &mut W,
+  ) -> io::Result<()> {
+    if self.rewrite {
+      Err(io::Error::new(io::ErrorKind::Other, "wallet store must be written in full"))?;
+    }
+
+    if self.pending.is_empty() {
+      return Ok(());
+    }
+
+    // Buffer the changes so they're written at once
+    let mut changes = Zeroizing::new(vec![]);
+    for record in core::mem::take(&mut self.pending) {
+      self.write_record(rng, &record, &mut *changes)?;
+    }
+    self.write_commit(rng, &mut *changes)?;
+    let res = w.write_all(&changes);
+    if res.is_err() {
+      self.rewrite = true;
+    }
+    res
+  }
+
+  fn read_snapshot<R: Read>(
+    r: &mut R,
+    version: u8,
+    key: Option<Zeroizing<[u8; 32]>>,
+  ) -> io::Result<WalletStore> {
+    if read_byte(r)? != SNAPSHOT {
+      Err(io::Error::new(io::ErrorKind::Other, "store didn't start with a snapshot"))?;
+    }
+    let pair = ViewPair::new(read_point(r)?, Zeroizing::new(read_scalar(r)?));
+    let subaddresses = (0 .. read_u32(r)?).map(|_| read_subaddress(r)).collect::<Result<_, _>>()?;
+    let height = read_usize(r)?;
+    let hashes: VecDeque<_> =
+      (0 .. read_u32(r)?).map(|_| read_bytes(r)).collect::<Result<_, _>>()?;
+    let outputs: Vec<StoredOutput> =
+      (0 .. read_u32(r)?).map(|_| StoredOutput::read(r)).collect::<Result<_, _>>()?;
+    let transactions: Vec<StoredTransaction> =
+      (0 .. read_u32(r)?).map(|_| StoredTransaction::read(r)).collect::<Result<_, _>>()?;
+    // Versions prior to 2 didn't have metadata
+    let metadata = if version < 2 { Zeroizing::new(vec![]) } else { read_metadata(r)? };
+
+    if (hashes.len() > height.min(RETAINED_HASHES)) ||
+      outputs.iter().any(|output| {
+        (output.block >= height) ||
+          output.spent.map(|spent| (spent < output.block) || (spent >= height)).unwrap_or(false)
+      }) ||
+      transactions.iter().any(|tx| tx.block >= height)
+    {
+      Err(io::Error::new(io::ErrorKind::Other, "inconsistent snapshot"))?;
+    }
+
+    Ok(WalletStore {
+      pair,
+      key,
+      subaddresses,
+      height,
+      hashes,
+      outputs,
+      transactions,
+      metadata,
+      file_id: [0; FILE_ID_LEN],
+      records: 0,
+      pending: vec![],
+      rewrite: false,
+    })
+  }
+
+  /// Read a store, decrypting it with the specified key if it's encrypted.
+  ///
+  /// Stores written by prior versions are migrated as they're read, and will be written in the
+  /// current version the next time they're written in full. Records after the last commit, as left
+  /// by an interrupted write, are ignored.
+  pub fn read<R: Read>(
+    r: &mut R,
+    key: Option<Zeroizing<[u8; 32]>>,
+  ) -> Result<WalletStore, StoreError> {
+    if &read_bytes::<_, 8>(r).map_err(io_error)? != MAGIC {
+      Err(StoreError::InvalidFormat)?;
+    }
+    let version = read_byte(r).map_err(io_error)?;
+    if version > Self::VERSION {
+      Err(StoreError::UnsupportedVersion(version))?;
+    }
+    let encrypted = read_byte(r).map_err(io_error)?;
+    match (encrypted, key.is_some()) {
+      (0, true) => Err(StoreError::NotEncrypted)?,
+      (1, false) => Err(StoreError::EncryptionKeyRequired)?,
+      (0 | 1, _) => (),
+      _ => Err(StoreError::InvalidFormat)?,
+    }
+    // Version 0 didn't have a file ID
+    let file_id = if version == 0 { [0; FILE_ID_LEN] } else { read_bytes(r).map_err(io_error)? };
+    let mut header = MAGIC.to_vec();
+    header.extend([version, encrypted]);
+    header.extend(file_id);
+    let header = (header.as_slice(), &file_id);
+
+    let snapshot =
+      read_record(r, version, key.as_ref(), header, 0)?.ok_or(StoreError::InvalidFormat)?;
+    let mut store = WalletStore::read_snapshot::<&[u8]>(&mut snapshot.as_ref(), version, key)
+      .map_err(|_| StoreError::InvalidFormat)?;
+    store.file_id = file_id;
+    store.records = 1;
+
+    // The changes read since the last commit, which are only applied once committed to
+    // Version 0 didn't have commits, so its changes are applied as they're read
+    let mut uncommitted = vec![];
+    let mut committed = version == 0;
+    loop {
+      let record = match read_record(r, version, store.key.as_ref(), header, store.records) {
+        Ok(Some(record)) => record,
+        Ok(None) => break,
+        Err(StoreError::Io(io::ErrorKind::UnexpectedEof)) => {
+          store.rewrite = true;
+          break;
+        }
+        // The key was already authenticated by the snapshot
+        Err(StoreError::InvalidEncryptionKey) => Err(StoreError::InvalidFormat)?,
+        Err(e) => Err(e)?,
+      };
+      store.records += 1;
+
+      if (version != 0) && (record.first() == Some(&COMMIT)) {
+        if record[1 ..] != (store.records - 1).to_le_bytes() {
+          Err(StoreError::InvalidFormat)?;
+        }
+        for change in uncommitted.drain(..) {
+          if !store.valid(&change) {
+            Err(StoreError::InvalidFormat)?;
+          }
+          store.apply(change);
+        }
+        committed = true;
+        continue;
+      }
+
+      let change =
+        Change::read::<&[u8]>(&mut record.as_ref()).map_err(|_| StoreError::InvalidFormat)?;
+      if version == 0 {
+        if !store.valid(&change) {
+          Err(StoreError::InvalidFormat)?;
+        }
+        store.apply(change);
+      } else {
+        uncommitted.push(change);
+      }
+    }
+
+    // The snapshot is always committed to, as it's written in full
+    if !committed {
+      Err(StoreError::InvalidFormat)?;
+    }
+    // Discard any records which weren't committed to, requiring the store be written in full to
+    // remove them
+    if !uncommitted.is_empty() {
+      store.rewrite = true;
+    }
+
+    // Migrate stores from prior versions by writing them in full
+    if version != Self::VERSION {
+      store.rewrite = true;
+    }
+    Ok(store)
+  }
+
+  /// Save the store to the specified path, appending the changes made since it was last saved
+  /// when possible.
+  ///
+  /// The path MUST be the path this store was loaded from or last saved to, if any.
+  #[cfg(feature = "std")]
+  pub fn save<R: RngCore + CryptoRng>(
+    &mut self,
+    rng: &mut R,
+    path: &Path,
+  ) -> Result<(), StoreError> {
+    if !(self.rewrite || (self.records >= COMPACT_AFTER) || !path.exists()) {
+      if self.pending.is_empty() {
+        return Ok(());
+      }
+      let mut file = fs::OpenOptions::new().append(true).open(path).map_err(io_error)?;
+      self.write_changes(rng, &mut file).map_err(io_error)?;
+      return file.sync_all().map_err(io_error);
+    }
+
+    // Write to a temporary file and then rename it, so the prior store is never left partially
+    // overwritten
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let mut file = fs::File::create(&tmp).map_err(io_error)?;
+    self.write(rng, &mut file).map_err(io_error)?;
+    file.sync_all().map_err(io_error)?;
+    fs::rename(tmp, path).map_err(io_error)
+  }
+
+  /// Load a store from the specified path.
+  #[cfg(feature = "std")]
+  pub fn load(path: &Path, key: Option<Zeroizing<[u8; 32]>>) -> Result<WalletStore, StoreError> {
+    WalletStore::read(&mut io::BufReader::new(fs::File::open(path).map_err(io_error)?), key)
+  }
+}
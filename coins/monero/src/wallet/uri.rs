@@ -0,0 +1,251 @@
+use core::fmt;
+use std_shims::{
+  vec::Vec,
+  string::{String, ToString},
+};
+
+use crate::wallet::address::{Network, AddressType, AddressError, MoneroAddress};
+
+const SCHEME: &str = "monero:";
+// The amount of decimal places in a XMR amount
+const DECIMALS: usize = 12;
+const COIN: u64 = 1_000_000_000_000;
+
+// Parse a decimal XMR amount into atomic units
+pub(crate) fn parse_amount(amount: &str) -> Result<u64, AddressError> {
+  let (whole, fractional) = match amount.split_once('.') {
+    Some((whole, fractional)) => (whole, fractional.trim_end_matches('0')),
+    None => (amount, ""),
+  };
+  if whole.is_empty() ||
+    (fractional.len() > DECIMALS) ||
+    !whole.chars().chain(fractional.chars()).all(|c| c.is_ascii_digit())
+  {
+    Err(AddressError::InvalidAmount)?;
+  }
+
+  let whole = whole.parse::<u64>().map_err(|_| AddressError::InvalidAmount)?;
+  let mut atomic = 0;
+  if !fractional.is_empty() {
+    atomic = fractional.parse::<u64>().map_err(|_| AddressError::InvalidAmount)? *
+      10u64.pow(u32::try_from(DECIMALS - fractional.len()).unwrap());
+  }
+  whole
+    .checked_mul(COIN)
+    .and_then(|whole| whole.checked_add(atomic))
+    .ok_or(AddressError::InvalidAmount)
+}
+
+// Format an amount of atomic units as a decimal XMR amount
+pub(crate) fn format_amount(amount: u64) -> String {
+  let fractional = amount % COIN;
+  if fractional == 0 {
+    return (amount / COIN).to_string();
+  }
+  let fractional = format!("{fractional:0DECIMALS$}");
+  format!("{}.{}", amount / COIN, fractional.trim_end_matches('0'))
+}
+
+// Apply a short payment ID, as specified alongside an address, to the address
+pub(crate) fn with_payment_id(
+  mut address: MoneroAddress,
+  id: &str,
+) -> Result<MoneroAddress, AddressError> {
+  // Long payment IDs are no longer supported by Monero
+  if (id.len() != 16) || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+    Err(AddressError::InvalidPaymentId)?;
+  }
+  let mut payment_id = [0; 8];
+  for (i, byte) in payment_id.iter_mut().enumerate() {
+    *byte = u8::from_str_radix(&id[(i * 2) .. ((i + 1) * 2)], 16)
+      .map_err(|_| AddressError::InvalidPaymentId)?;
+  }
+
+  address.meta.kind = match address.meta.kind {
+    AddressType::Standard => AddressType::Integrated(payment_id),
+    AddressType::Featured { subaddress, payment_id: None, guaranteed } => {
+      AddressType::Featured { subaddress, payment_id: Some(payment_id), guaranteed }
+    }
+    // Subaddresses can't have payment IDs, and integrated addresses already have one
+    _ => Err(AddressError::InvalidPaymentId)?,
+  };
+  Ok(address)
+}
+
+fn decode(component: &str) -> Result<String, AddressError> {
+  let bytes = component.as_bytes();
+  let mut res = Vec::with_capacity(bytes.len());
+  let mut i = 0;
+  while i < bytes.len() {
+    if bytes[i] == b'%' {
+      let byte = component
+        .get((i + 1) .. (i + 3))
+        .filter(|byte| byte.chars().all(|c| c.is_ascii_hexdigit()))
+        .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+        .ok_or(AddressError::InvalidUri)?;
+      res.push(byte);
+      i += 3;
+    } else {
+      res.push(bytes[i]);
+      i += 1;
+    }
+  }
+  String::from_utf8(res).map_err(|_| AddressError::InvalidUri)
+}
+
+// Split a parameter into its entry for each recipient, where empty entries are None
+fn entries(value: &str, recipients: usize) -> Result<Vec<Option<&str>>, AddressError> {
+  let entries = value.split(';').collect::<Vec<_>>();
+  if entries.len() != recipients {
+    Err(AddressError::InvalidUri)?;
+  }
+  Ok(entries.into_iter().map(|entry| Some(entry).filter(|entry| !entry.is_empty())).collect())
+}
+
+fn encode(component: &str) -> String {
+  let mut res = String::with_capacity(component.len());
+  for byte in component.bytes() {
+    if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+      res.push(char::from(byte));
+    } else {
+      res.push_str(&format!("%{byte:02X}"));
+    }
+  }
+  res
+}
+
+/// A recipient specified by a URI.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct UriRecipient {
+  pub address: MoneroAddress,
+  /// The amount requested, in atomic units.
+  pub amount: Option<u64>,
+  pub name: Option<String>,
+}
+
+/// A `monero:` URI, as specified by
+/// [Monero](https://github.com/monero-project/monero/wiki/URI-Formatting).
+///
+/// Multiple recipients are supported via `;`-separated addresses, with `tx_amount` and
+/// `recipient_name` being `;`-separated lists with an entry per recipient.
+///
+/// A `tx_payment_id` is only supported for a single recipient. As monero-serai doesn't support
+/// separate payment IDs, it's embedded into the recipient's address, converting standard
+/// addresses into integrated addresses. When generated, the payment ID is solely present in the
+/// address.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MoneroUri {
+  pub recipients: Vec<UriRecipient>,
+  pub description: Option<String>,
+  /// Parameters not recognized, in the order they were present.
+  pub unknown_parameters: Vec<(String, String)>,
+}
+
+impl MoneroUri {
+  /// Create a URI for a single recipient.
+  pub fn new(address: MoneroAddress) -> MoneroUri {
+    MoneroUri {
+      recipients: vec![UriRecipient { address, amount: None, name: None }],
+      description: None,
+      unknown_parameters: vec![],
+    }
+  }
+
+  pub fn from_str_raw(s: &str) -> Result<MoneroUri, AddressError> {
+    let s = s.strip_prefix(SCHEME).ok_or(AddressError::InvalidUri)?;
+    let (addresses, query) = match s.split_once('?') {
+      Some((addresses, query)) => (addresses, Some(query)),
+      None => (s, None),
+    };
+
+    let mut recipients = addresses
+      .split(';')
+      .map(|address| {
+        MoneroAddress::from_str_raw(address).map(|address| UriRecipient {
+          address,
+          amount: None,
+          name: None,
+        })
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+    let count = recipients.len();
+
+    let mut uri = MoneroUri { recipients: vec![], description: None, unknown_parameters: vec![] };
+    let mut seen = Vec::new();
+    for parameter in query.into_iter().flat_map(|query| query.split('&')) {
+      let (key, value) = parameter.split_once('=').ok_or(AddressError::InvalidUri)?;
+      let key = decode(key)?;
+      if seen.contains(&key) {
+        Err(AddressError::InvalidUri)?;
+      }
+      seen.push(key.clone());
+
+      match key.as_str() {
+        "tx_amount" => {
+          for (recipient, amount) in recipients.iter_mut().zip(entries(value, count)?) {
+            recipient.amount = amount.map(|amount| parse_amount(&decode(amount)?)).transpose()?;
+          }
+        }
+        "recipient_name" => {
+          for (recipient, name) in recipients.iter_mut().zip(entries(value, count)?) {
+            recipient.name = name.map(decode).transpose()?;
+          }
+        }
+        "tx_payment_id" => {
+          if count != 1 {
+            Err(AddressError::InvalidPaymentId)?;
+          }
+          recipients[0].address = with_payment_id(recipients[0].address, &decode(value)?)?;
+        }
+        "tx_description" => uri.description = Some(decode(value)?),
+        _ => uri.unknown_parameters.push((key, decode(value)?)),
+      }
+    }
+
+    uri.recipients = recipients;
+    Ok(uri)
+  }
+
+  pub fn from_str(network: Network, s: &str) -> Result<MoneroUri, AddressError> {
+    let uri = Self::from_str_raw(s)?;
+    if uri.recipients.iter().any(|recipient| recipient.address.network() != network) {
+      Err(AddressError::DifferentNetwork)?;
+    }
+    Ok(uri)
+  }
+}
+
+impl fmt::Display for MoneroUri {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(SCHEME)?;
+    let addresses = self.recipients.iter().map(|recipient| recipient.address.to_string());
+    f.write_str(&addresses.collect::<Vec<_>>().join(";"))?;
+
+    let mut parameters = vec![];
+    if self.recipients.iter().any(|recipient| recipient.amount.is_some()) {
+      let amounts = self
+        .recipients
+        .iter()
+        .map(|recipient| recipient.amount.map(format_amount).unwrap_or_default());
+      parameters.push(("tx_amount".to_string(), amounts.collect::<Vec<_>>().join(";")));
+    }
+    if self.recipients.iter().any(|recipient| recipient.name.is_some()) {
+      let names = self
+        .recipients
+        .iter()
+        .map(|recipient| recipient.name.as_deref().map(encode).unwrap_or_default());
+      parameters.push(("recipient_name".to_string(), names.collect::<Vec<_>>().join(";")));
+    }
+    if let Some(description) = &self.description {
+      parameters.push(("tx_description".to_string(), encode(description)));
+    }
+    for (key, value) in &self.unknown_parameters {
+      parameters.push((encode(key), encode(value)));
+    }
+
+    for (i, (key, value)) in parameters.iter().enumerate() {
+      write!(f, "{}{key}={value}", if i == 0 { '?' } else { '&' })?;
+    }
+    Ok(())
+  }
+}
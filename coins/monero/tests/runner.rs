@@ -139,7 +139,7 @@ macro_rules! test {
         use core::{ops::Deref, any::Any};
         use std::collections::HashSet;
         #[cfg(feature = "multisig")]
-        use std::collections::HashMap;
+        use std::collections::BTreeMap;
 
         use zeroize::Zeroizing;
         use rand_core::OsRng;
@@ -221,7 +221,7 @@ macro_rules! test {
                 panic!("Multisig branch called without the multisig feature");
                 #[cfg(feature = "multisig")]
                 {
-                  let mut machines = HashMap::new();
+                  let mut machines = BTreeMap::new();
                   for i in (1 ..= THRESHOLD).map(|i| Participant::new(i).unwrap()) {
                     machines.insert(
                       i,
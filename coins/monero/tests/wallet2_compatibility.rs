@@ -1,10 +1,14 @@
+use core::ops::Deref;
 use std::{
   collections::{HashSet, HashMap},
   str::FromStr,
 };
 
+use zeroize::Zeroizing;
 use rand_core::{OsRng, RngCore};
 
+use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+
 use serde::Deserialize;
 use serde_json::json;
 
@@ -18,12 +22,13 @@ use monero_rpc::{
 };
 
 use monero_serai::{
+  random_scalar,
   transaction::Transaction,
   rpc::Rpc,
   wallet::{
     address::{Network, AddressSpec, SubaddressIndex, MoneroAddress},
     extra::{MAX_TX_EXTRA_NONCE_SIZE, Extra},
-    Scanner,
+    ViewPair, Scanner, TxProof, ReserveProof,
   },
 };
 
@@ -108,7 +113,125 @@ async fn from_wallet_rpc_to_self(spec: AddressSpec) {
   assert_eq!(output.commitment().amount, 1000000000000);
 }
 
+async fn proofs_with_wallet_rpc() {
+  #[derive(Deserialize, Debug)]
+  struct SignatureResponse {
+    signature: String,
+  }
+  #[derive(Deserialize, Debug)]
+  struct CheckTxProofResponse {
+    good: bool,
+    received: u64,
+  }
+  #[derive(Deserialize, Debug)]
+  struct CheckReserveProofResponse {
+    good: bool,
+    total: u64,
+    spent: u64,
+  }
+
+  let (wallet_rpc, daemon_rpc, wallet_rpc_addr) = initialize_rpcs().await;
+  let rpc = Rpc::new("http://127.0.0.1:6061".to_string()).unwrap();
+  let wallet_addr =
+    MoneroAddress::from_str(Network::Mainnet, &wallet_rpc_addr.to_string()).unwrap();
+
+  let spend = Zeroizing::new(random_scalar(&mut OsRng));
+  let view = Zeroizing::new(random_scalar(&mut OsRng));
+  let view_pair = ViewPair::new(spend.deref() * &ED25519_BASEPOINT_TABLE, view.clone());
+  let addr = view_pair.address(Network::Mainnet, AddressSpec::Standard);
+
+  wallet_rpc.refresh(None).await.unwrap();
+  let tx = wallet_rpc
+    .transfer(
+      HashMap::from([(Address::from_str(&addr.to_string()).unwrap(), Amount::ONE_XMR)]),
+      monero_rpc::TransferPriority::Default,
+      TransferOptions::default(),
+    )
+    .await
+    .unwrap();
+  let tx_hash: [u8; 32] = tx.tx_hash.0.try_into().unwrap();
+  runner::mine_until_unlocked(&daemon_rpc, &wallet_rpc_addr.to_string(), tx_hash).await;
+  let tx = daemon_rpc.get_transaction(tx_hash).await.unwrap();
+
+  // Proofs created by wallet2 should verify
+  let out_proof = rpc
+    .json_rpc_call::<SignatureResponse>(
+      "get_tx_proof",
+      Some(json!({ "txid": hex::encode(tx_hash), "address": addr.to_string(), "message": "msg" })),
+    )
+    .await
+    .unwrap()
+    .signature;
+  assert!(out_proof.starts_with("OutProofV2"));
+  assert_eq!(out_proof.parse::<TxProof>().unwrap().verify(&tx, &addr, b"msg"), Ok(1000000000000));
+
+  let reserve_proof = rpc
+    .json_rpc_call::<SignatureResponse>(
+      "get_reserve_proof",
+      Some(json!({ "all": true, "message": "msg" })),
+    )
+    .await
+    .unwrap()
+    .signature;
+  let checked = rpc
+    .json_rpc_call::<CheckReserveProofResponse>(
+      "check_reserve_proof",
+      Some(json!({
+        "address": wallet_rpc_addr.to_string(),
+        "message": "msg",
+        "signature": reserve_proof,
+      })),
+    )
+    .await
+    .unwrap();
+  assert!(checked.good);
+  assert_eq!(
+    reserve_proof.parse::<ReserveProof>().unwrap().check(&daemon_rpc, &wallet_addr, b"msg").await,
+    Ok((checked.total, checked.spent))
+  );
+
+  // Proofs created by monero-serai should verify with wallet2
+  let in_proof = TxProof::inbound(&mut OsRng, &tx, &addr, b"msg", &view).unwrap().to_string();
+  assert!(in_proof.starts_with("InProofV2"));
+  let checked = rpc
+    .json_rpc_call::<CheckTxProofResponse>(
+      "check_tx_proof",
+      Some(json!({
+        "txid": hex::encode(tx_hash),
+        "address": addr.to_string(),
+        "message": "msg",
+        "signature": in_proof,
+      })),
+    )
+    .await
+    .unwrap();
+  assert!(checked.good);
+  assert_eq!(checked.received, 1000000000000);
+
+  let mut scanner = Scanner::from_view(view_pair.clone(), Some(HashSet::new()));
+  let outputs = scanner.scan_transaction(&tx).not_locked();
+  let reserve_proof =
+    ReserveProof::new(&mut OsRng, &spend, &view_pair, b"msg", &outputs, &[tx]).unwrap();
+  let checked = rpc
+    .json_rpc_call::<CheckReserveProofResponse>(
+      "check_reserve_proof",
+      Some(json!({
+        "address": addr.to_string(),
+        "message": "msg",
+        "signature": reserve_proof.to_string(),
+      })),
+    )
+    .await
+    .unwrap();
+  assert!(checked.good);
+  assert_eq!((checked.total, checked.spent), (1000000000000, 0));
+}
+
 async_sequential!(
+  async fn wallet2_proofs() {
+    proofs_with_wallet_rpc().await;
+  }
+
   async fn receipt_of_wallet_rpc_tx_standard() {
     from_wallet_rpc_to_self(AddressSpec::Standard).await;
   }
@@ -0,0 +1,161 @@
+use core::{
+  pin::Pin,
+  future::Future,
+  task::{Context, Poll},
+};
+use std::{
+  sync::{Arc, Mutex},
+  collections::VecDeque,
+};
+
+use zeroize::Zeroizing;
+use rand_core::{RngCore, OsRng};
+
+use digest_auth::{Qop, AuthContext, AuthorizationHeader};
+
+use hyper::{
+  header::{AUTHORIZATION, WWW_AUTHENTICATE},
+  Body, Request, Response, StatusCode,
+};
+use tower::{Layer, Service};
+
+const REALM: &str = "monero-rpc";
+// The amount of nonces tracked, after which the oldest nonce is no longer accepted
+const NONCES: usize = 1024;
+
+/// The credentials required to use the server.
+pub(crate) struct Login {
+  username: String,
+  password: Zeroizing<String>,
+}
+
+impl Login {
+  /// Parse credentials specified as `username:password`, as monero-wallet-rpc's `--rpc-login`.
+  pub(crate) fn from_str(login: &str) -> Option<Login> {
+    let (username, password) = login.split_once(':')?;
+    if username.is_empty() || password.is_empty() {
+      None?;
+    }
+    Some(Login { username: username.to_string(), password: Zeroizing::new(password.to_string()) })
+  }
+}
+
+/// A layer requiring HTTP digest authentication, as monero-wallet-rpc does, if a login is set.
+#[derive(Clone)]
+pub(crate) struct AuthLayer {
+  login: Option<Arc<Login>>,
+  // The nonces issued, with the last nonce count used with each, preventing replays
+  nonces: Arc<Mutex<VecDeque<(String, u32)>>>,
+}
+
+impl AuthLayer {
+  pub(crate) fn new(login: Option<Login>) -> AuthLayer {
+    AuthLayer { login: login.map(Arc::new), nonces: Arc::new(Mutex::new(VecDeque::new())) }
+  }
+
+  pub(crate) fn authorized(&self, req: &Request<Body>) -> bool {
+    let login = match &self.login {
+      Some(login) => login,
+      None => return true,
+    };
+
+    let mut header = match req.headers().get(AUTHORIZATION).and_then(|header| header.to_str().ok())
+    {
+      Some(header) => match AuthorizationHeader::parse(header) {
+        Ok(header) => header,
+        Err(_) => return false,
+      },
+      None => return false,
+    };
+    // Require a client nonce, so the response is bound to the nonce count
+    if (header.username != login.username) ||
+      (header.realm != REALM) ||
+      (header.qop != Some(Qop::AUTH)) ||
+      header.cnonce.is_none() ||
+      (Some(header.uri.as_str()) != req.uri().path_and_query().map(|path| path.as_str()))
+    {
+      return false;
+    }
+
+    let mut nonces = self.nonces.lock().unwrap();
+    let nc = match nonces.iter_mut().find(|(nonce, _)| *nonce == header.nonce) {
+      Some((_, nc)) => nc,
+      None => return false,
+    };
+    if header.nc <= *nc {
+      return false;
+    }
+
+    let response = header.response.clone();
+    header.digest(&AuthContext::new_with_method::<_, _, _, &[u8]>(
+      login.username.as_str(),
+      login.password.as_str(),
+      header.uri.clone(),
+      None,
+      req.method().as_str().into(),
+    ));
+    if header.response != response {
+      return false;
+    }
+    *nc = header.nc;
+    true
+  }
+
+  /// A response challenging the client to authenticate, with a new nonce.
+  pub(crate) fn challenge(&self) -> Response<Body> {
+    let mut nonce = [0; 16];
+    OsRng.fill_bytes(&mut nonce);
+    let nonce = hex::encode(nonce);
+
+    let mut nonces = self.nonces.lock().unwrap();
+    nonces.push_back((nonce.clone(), 0));
+    if nonces.len() > NONCES {
+      nonces.pop_front();
+    }
+
+    Response::builder()
+      .status(StatusCode::UNAUTHORIZED)
+      .header(
+        WWW_AUTHENTICATE,
+        format!("Digest realm=\"{REALM}\", qop=\"auth\", algorithm=MD5, nonce=\"{nonce}\""),
+      )
+      .body(Body::empty())
+      .unwrap()
+  }
+}
+
+impl<S> Layer<S> for AuthLayer {
+  type Service = Auth<S>;
+  fn layer(&self, inner: S) -> Auth<S> {
+    Auth { layer: self.clone(), inner }
+  }
+}
+
+#[derive(Clone)]
+pub(crate) struct Auth<S> {
+  layer: AuthLayer,
+  inner: S,
+}
+
+impl<S: Service<Request<Body>, Response = Response<Body>>> Service<Request<Body>> for Auth<S>
+where
+  S::Error: Send + 'static,
+  S::Future: Send + 'static,
+{
+  type Response = Response<Body>;
+  type Error = S::Error;
+  type Future = Pin<Box<dyn Send + Future<Output = Result<Response<Body>, S::Error>>>>;
+
+  fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+    self.inner.poll_ready(cx)
+  }
+
+  fn call(&mut self, req: Request<Body>) -> Self::Future {
+    if self.layer.authorized(&req) {
+      Box::pin(self.inner.call(req))
+    } else {
+      let challenge = self.layer.challenge();
+      Box::pin(async move { Ok(challenge) })
+    }
+  }
+}
@@ -0,0 +1,122 @@
+use std::{env, sync::Arc, path::PathBuf, time::Duration, net::ToSocketAddrs};
+
+use zeroize::Zeroizing;
+use rand_core::OsRng;
+
+use log::{info, error};
+
+use tokio::{sync::RwLock, time::sleep};
+
+use jsonrpsee::server::ServerBuilder;
+
+use monero_serai::{
+  rpc::Rpc,
+  wallet::{
+    address::Network,
+    seed::{Language, Seed},
+  },
+};
+
+mod wallet;
+use wallet::Wallet;
+
+mod auth;
+use auth::{Login, AuthLayer};
+
+mod server;
+use server::State;
+
+#[cfg(test)]
+mod tests;
+
+// The amount of blocks to scan before saving the wallet and releasing its lock
+const SYNC_BATCH: usize = 100;
+
+async fn sync(state: Arc<State>) {
+  loop {
+    let synced = {
+      let mut wallet = state.wallet.write().await;
+      match wallet.sync(&state.rpc, SYNC_BATCH).await {
+        Ok(synced) => {
+          if let Err(e) = wallet.save(state.path.as_ref().unwrap()) {
+            error!("couldn't save the wallet: {e}");
+          }
+          synced
+        }
+        Err(e) => {
+          error!("couldn't sync the wallet: {e}");
+          true
+        }
+      }
+    };
+
+    if synced {
+      sleep(Duration::from_secs(10)).await;
+    }
+  }
+}
+
+#[tokio::main]
+async fn main() {
+  if env::var("RUST_LOG").is_err() {
+    env::set_var("RUST_LOG", "info");
+  }
+  env_logger::init();
+
+  let path = PathBuf::from(env::var("WALLET_FILE").expect("wallet file wasn't specified"));
+  let rpc = Rpc::new(env::var("DAEMON_RPC").expect("daemon rpc wasn't specified"))
+    .expect("invalid daemon rpc");
+
+  // The key the wallet file is encrypted with, as 32 hex-encoded bytes
+  let key = {
+    let encoded = Zeroizing::new(env::var("WALLET_KEY").expect("wallet key wasn't specified"));
+    let decoded = Zeroizing::new(hex::decode(encoded.as_str()).expect("wallet key wasn't hex"));
+    if decoded.len() != 32 {
+      panic!("wallet key wasn't 32 bytes");
+    }
+    let mut key = Zeroizing::new([0; 32]);
+    key.copy_from_slice(&decoded);
+    key
+  };
+
+  let wallet = if path.exists() {
+    Wallet::load(&path, key).expect("couldn't load the wallet")
+  } else {
+    let network = match env::var("NETWORK").as_deref().unwrap_or("mainnet") {
+      "mainnet" => Network::Mainnet,
+      "testnet" => Network::Testnet,
+      "stagenet" => Network::Stagenet,
+      _ => panic!("unknown network"),
+    };
+    let seed = match env::var("WALLET_SEED") {
+      Ok(seed) => Seed::from_string(Zeroizing::new(seed)).expect("invalid seed"),
+      Err(_) => Seed::new(&mut OsRng, Language::English),
+    };
+    let mut wallet = Wallet::new(seed, network, key);
+    wallet.save(&path).expect("couldn't save the new wallet");
+    info!("created a new wallet at {}", path.display());
+    wallet
+  };
+  info!("wallet address: {}", wallet.address(0, 0).to_string());
+
+  let state = Arc::new(State { rpc, wallet: RwLock::new(wallet), path: Some(path) });
+  tokio::spawn(sync(state.clone()));
+
+  let bind = env::var("RPC_BIND").unwrap_or_else(|_| "127.0.0.1:18083".to_string());
+  let login =
+    env::var("RPC_LOGIN").ok().map(|login| Login::from_str(&login).expect("invalid login"));
+  // Anyone able to connect can spend the wallet's funds, so require a login when reachable from
+  // other machines
+  if login.is_none() &&
+    !bind.to_socket_addrs().expect("invalid bind address").all(|addr| addr.ip().is_loopback())
+  {
+    panic!("a login must be specified when not binding to localhost");
+  }
+  let server = ServerBuilder::default()
+    .set_middleware(tower::ServiceBuilder::new().layer(AuthLayer::new(login)))
+    .build(&bind)
+    .await
+    .expect("couldn't bind the server");
+  info!("serving on {bind}");
+  server.start(server::module(state)).expect("couldn't start the server").stopped().await;
+}
@@ -0,0 +1,515 @@
+use std::{sync::Arc, path::PathBuf};
+
+use thiserror::Error;
+
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+
+use log::error;
+
+use tokio::sync::RwLock;
+
+use jsonrpsee::{
+  RpcModule,
+  core::Error as JsonRpcError,
+  types::error::{CallError, ErrorObject},
+};
+
+use monero_serai::{rpc::Rpc, wallet::address::MoneroAddress};
+
+use crate::wallet::{WalletError, TransferKind, Wallet, subaddress};
+
+/// The methods served.
+pub(crate) const METHODS: &[&str] = &[
+  "get_balance",
+  "get_address",
+  "create_address",
+  "create_account",
+  "transfer",
+  "get_transfers",
+  "incoming_transfers",
+  "get_height",
+];
+
+/// The maximum amount of addresses created by a single call to create_address, as with
+/// monero-wallet-rpc.
+const MAX_ADDRESSES: u32 = 64;
+
+#[derive(Debug, Error)]
+pub(crate) enum ServerError {
+  #[error("invalid params ({0})")]
+  InvalidParams(String),
+  #[error("invalid address ({0})")]
+  InvalidAddress(String),
+  #[error("{0}")]
+  Wallet(#[from] WalletError),
+}
+
+impl ServerError {
+  // The error codes used by monero-wallet-rpc, with the standard JSON-RPC code for invalid params
+  fn code(&self) -> i32 {
+    match self {
+      ServerError::InvalidParams(_) => -32602,
+      ServerError::InvalidAddress(_) => -2,
+      ServerError::Wallet(WalletError::UnknownAccount) => -8,
+      ServerError::Wallet(_) => -1,
+    }
+  }
+}
+
+/// The state shared by the server's methods.
+pub(crate) struct State {
+  pub(crate) rpc: Rpc,
+  pub(crate) wallet: RwLock<Wallet>,
+  // Where to save the wallet after it's modified
+  pub(crate) path: Option<PathBuf>,
+}
+
+#[derive(Serialize)]
+struct SubaddressIndex {
+  major: u32,
+  minor: u32,
+}
+
+fn parse<P: DeserializeOwned>(params: Value) -> Result<P, ServerError> {
+  // Methods whose params are all optional may be called without params
+  let params = if params.is_null() { json!({}) } else { params };
+  serde_json::from_value(params).map_err(|e| ServerError::InvalidParams(e.to_string()))
+}
+
+fn response<R: Serialize>(response: R) -> Result<Value, ServerError> {
+  Ok(serde_json::to_value(response).unwrap())
+}
+
+async fn get_balance(state: &State, params: Value) -> Result<Value, ServerError> {
+  #[derive(Deserialize)]
+  struct Params {
+    #[serde(default)]
+    account_index: u32,
+    #[serde(default)]
+    address_indices: Vec<u32>,
+  }
+
+  #[derive(Serialize)]
+  struct PerSubaddress {
+    account_index: u32,
+    address_index: u32,
+    address: String,
+    balance: u64,
+    unlocked_balance: u64,
+    label: String,
+    num_unspent_outputs: usize,
+  }
+
+  #[derive(Serialize)]
+  struct Response {
+    balance: u64,
+    unlocked_balance: u64,
+    multisig_import_needed: bool,
+    per_subaddress: Vec<PerSubaddress>,
+  }
+
+  let params: Params = parse(params)?;
+  let wallet = state.wallet.read().await;
+  let height = wallet.height();
+  let (balance, unlocked_balance) = wallet.balance(params.account_index, None, height);
+
+  let mut per_subaddress = vec![];
+  for (account, address, label) in &wallet.addresses {
+    if (*account != params.account_index) ||
+      (!(params.address_indices.is_empty() || params.address_indices.contains(address)))
+    {
+      continue;
+    }
+    let (balance, unlocked_balance) = wallet.balance(*account, Some(*address), height);
+    if balance == 0 {
+      continue;
+    }
+    per_subaddress.push(PerSubaddress {
+      account_index: *account,
+      address_index: *address,
+      address: wallet.address(*account, *address).to_string(),
+      balance,
+      unlocked_balance,
+      label: label.clone(),
+      num_unspent_outputs: wallet
+        .unspent(*account)
+        .filter(|output| subaddress(output).1 == *address)
+        .count(),
+    });
+  }
+
+  response(Response { balance, unlocked_balance, multisig_import_needed: false, per_subaddress })
+}
+
+#[derive(Serialize)]
+struct Address {
+  address: String,
+  label: String,
+  address_index: u32,
+  used: bool,
+}
+
+async fn get_address(state: &State, params: Value) -> Result<Value, ServerError> {
+  #[derive(Deserialize)]
+  struct Params {
+    #[serde(default)]
+    account_index: u32,
+    #[serde(default)]
+    address_index: Vec<u32>,
+  }
+
+  #[derive(Serialize)]
+  struct Response {
+    address: String,
+    addresses: Vec<Address>,
+  }
+
+  let params: Params = parse(params)?;
+  let wallet = state.wallet.read().await;
+
+  let mut addresses = vec![];
+  for (account, address, label) in &wallet.addresses {
+    if (*account != params.account_index) ||
+      (!(params.address_index.is_empty() || params.address_index.contains(address)))
+    {
+      continue;
+    }
+    addresses.push(Address {
+      address: wallet.address(*account, *address).to_string(),
+      label: label.clone(),
+      address_index: *address,
+      used: wallet.outputs().iter().any(|output| subaddress(output) == (*account, *address)),
+    });
+  }
+  if addresses.is_empty() {
+    Err(WalletError::UnknownAccount)?;
+  }
+
+  response(Response { address: wallet.address(params.account_index, 0).to_string(), addresses })
+}
+
+async fn create_address(state: &State, params: Value) -> Result<Value, ServerError> {
+  #[derive(Deserialize)]
+  struct Params {
+    #[serde(default)]
+    account_index: u32,
+    #[serde(default)]
+    label: String,
+    #[serde(default)]
+    count: Option<u32>,
+  }
+
+  #[derive(Serialize)]
+  struct Response {
+    address: String,
+    address_index: u32,
+    addresses: Vec<String>,
+    address_indices: Vec<u32>,
+  }
+
+  let params: Params = parse(params)?;
+  let count = params.count.unwrap_or(1);
+  if !(1 ..= MAX_ADDRESSES).contains(&count) {
+    Err(ServerError::InvalidParams(format!("count must be within 1 ..= {MAX_ADDRESSES}")))?;
+  }
+
+  let mut wallet = state.wallet.write().await;
+  let mut addresses = vec![];
+  let mut address_indices = vec![];
+  for _ in 0 .. count {
+    let (index, address) = wallet.create_address(params.account_index, params.label.clone())?;
+    addresses.push(address.to_string());
+    address_indices.push(index);
+  }
+  if let Some(path) = &state.path {
+    wallet.save(path)?;
+  }
+
+  response(Response {
+    address: addresses[0].clone(),
+    address_index: address_indices[0],
+    addresses,
+    address_indices,
+  })
+}
+
+async fn create_account(state: &State, params: Value) -> Result<Value, ServerError> {
+  #[derive(Deserialize)]
+  struct Params {
+    #[serde(default)]
+    label: String,
+  }
+
+  #[derive(Serialize)]
+  struct Response {
+    account_index: u32,
+    address: String,
+  }
+
+  let params: Params = parse(params)?;
+  let mut wallet = state.wallet.write().await;
+  let (account_index, address) = wallet.create_account(params.label);
+  if let Some(path) = &state.path {
+    wallet.save(path)?;
+  }
+
+  response(Response { account_index, address: address.to_string() })
+}
+
+async fn transfer(state: &State, params: Value) -> Result<Value, ServerError> {
+  #[derive(Deserialize)]
+  struct Destination {
+    amount: u64,
+    address: String,
+  }
+
+  #[derive(Deserialize)]
+  struct Params {
+    destinations: Vec<Destination>,
+    #[serde(default)]
+    account_index: u32,
+    #[serde(default)]
+    get_tx_key: bool,
+  }
+
+  #[derive(Serialize)]
+  struct Response {
+    amount: u64,
+    fee: u64,
+    tx_hash: String,
+    tx_key: String,
+  }
+
+  let params: Params = parse(params)?;
+  if params.destinations.is_empty() {
+    Err(ServerError::InvalidParams("no destinations".to_string()))?;
+  }
+
+  let mut wallet = state.wallet.write().await;
+  let mut payments = vec![];
+  for destination in params.destinations {
+    payments.push((
+      MoneroAddress::from_str(wallet.network(), &destination.address)
+        .map_err(|_| ServerError::InvalidAddress(destination.address))?,
+      destination.amount,
+    ));
+  }
+
+  let (hash, tx_key, amount, fee) =
+    wallet.transfer(&state.rpc, params.account_index, payments).await?;
+  // The transaction was published, so report it even if the wallet couldn't be saved
+  if let Some(path) = &state.path {
+    if let Err(e) = wallet.save(path) {
+      error!("couldn't save the wallet after publishing {}: {e}", hex::encode(hash));
+    }
+  }
+
+  response(Response {
+    amount,
+    fee,
+    tx_hash: hex::encode(hash),
+    tx_key: if params.get_tx_key { hex::encode(tx_key.to_bytes()) } else { String::new() },
+  })
+}
+
+async fn get_transfers(state: &State, params: Value) -> Result<Value, ServerError> {
+  #[derive(Deserialize)]
+  struct Params {
+    #[serde(default, rename = "in")]
+    incoming: bool,
+    #[serde(default)]
+    out: bool,
+    #[serde(default)]
+    pending: bool,
+    #[serde(default)]
+    account_index: u32,
+    #[serde(default)]
+    subaddr_indices: Vec<u32>,
+    #[serde(default)]
+    filter_by_height: bool,
+    #[serde(default)]
+    min_height: usize,
+    max_height: Option<usize>,
+  }
+
+  #[derive(Serialize)]
+  struct Entry {
+    txid: String,
+    amount: u64,
+    fee: u64,
+    height: usize,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    unlock_time: u64,
+    subaddr_index: SubaddressIndex,
+    address: String,
+    payment_id: String,
+    confirmations: usize,
+  }
+
+  #[derive(Serialize)]
+  struct Response {
+    #[serde(rename = "in", skip_serializing_if = "Vec::is_empty")]
+    incoming: Vec<Entry>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    out: Vec<Entry>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pending: Vec<Entry>,
+  }
+
+  let params: Params = parse(params)?;
+  let wallet = state.wallet.read().await;
+
+  let height = wallet.height();
+  let mut res = Response { incoming: vec![], out: vec![], pending: vec![] };
+  for transfer in &wallet.transfers() {
+    let (account, address) = transfer.subaddress;
+    if (account != params.account_index) ||
+      (!(params.subaddr_indices.is_empty() || params.subaddr_indices.contains(&address)))
+    {
+      continue;
+    }
+    // Heights are only filtered for confirmed transfers, as with monero-wallet-rpc
+    if params.filter_by_height &&
+      (transfer.kind != TransferKind::Pending) &&
+      ((transfer.height < params.min_height) ||
+        params.max_height.map(|max| transfer.height > max).unwrap_or(false))
+    {
+      continue;
+    }
+
+    let (list, kind) = match transfer.kind {
+      TransferKind::Incoming if params.incoming => (&mut res.incoming, "in"),
+      TransferKind::Outgoing if params.out => (&mut res.out, "out"),
+      TransferKind::Pending if params.pending => (&mut res.pending, "pending"),
+      _ => continue,
+    };
+    list.push(Entry {
+      txid: hex::encode(transfer.tx),
+      amount: transfer.amount,
+      fee: transfer.fee,
+      height: if transfer.kind == TransferKind::Pending { 0 } else { transfer.height },
+      kind,
+      unlock_time: transfer.unlock_time(),
+      subaddr_index: SubaddressIndex { major: account, minor: address },
+      address: wallet.address(account, address).to_string(),
+      payment_id: hex::encode(transfer.payment_id),
+      confirmations: if transfer.kind == TransferKind::Pending {
+        0
+      } else {
+        height.saturating_sub(transfer.height)
+      },
+    });
+  }
+
+  response(res)
+}
+
+async fn incoming_transfers(state: &State, params: Value) -> Result<Value, ServerError> {
+  #[derive(Deserialize)]
+  struct Params {
+    transfer_type: String,
+    #[serde(default)]
+    account_index: u32,
+    #[serde(default)]
+    subaddr_indices: Vec<u32>,
+  }
+
+  #[derive(Serialize)]
+  struct Entry {
+    amount: u64,
+    spent: bool,
+    global_index: u64,
+    tx_hash: String,
+    subaddr_index: SubaddressIndex,
+    key_image: String,
+    pubkey: String,
+    block_height: usize,
+    unlocked: bool,
+  }
+
+  #[derive(Serialize)]
+  struct Response {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    transfers: Vec<Entry>,
+  }
+
+  let params: Params = parse(params)?;
+  // "available" outputs are those which are unspent, "unavailable" outputs those spent
+  let spent = match params.transfer_type.as_str() {
+    "all" => None,
+    "available" => Some(false),
+    "unavailable" => Some(true),
+    _ => Err(ServerError::InvalidParams("unknown transfer_type".to_string()))?,
+  };
+
+  let wallet = state.wallet.read().await;
+  let mut transfers = vec![];
+  for output in wallet.outputs() {
+    let (account, address) = subaddress(output);
+    let output_spent = wallet.spent(output);
+    if (account != params.account_index) ||
+      (!(params.subaddr_indices.is_empty() || params.subaddr_indices.contains(&address))) ||
+      spent.map(|spent| spent != output_spent).unwrap_or(false)
+    {
+      continue;
+    }
+    transfers.push(Entry {
+      amount: output.output.commitment().amount,
+      spent: output_spent,
+      global_index: output.output.global_index,
+      tx_hash: hex::encode(output.output.output.absolute.tx),
+      subaddr_index: SubaddressIndex { major: account, minor: address },
+      key_image: hex::encode(wallet.key_image(output).compress().to_bytes()),
+      pubkey: hex::encode(output.output.key().compress().to_bytes()),
+      block_height: output.block,
+      unlocked: Wallet::unlocked(output, wallet.height()),
+    });
+  }
+
+  response(Response { transfers })
+}
+
+async fn get_height(state: &State, _: Value) -> Result<Value, ServerError> {
+  Ok(json!({ "height": state.wallet.read().await.height() }))
+}
+
+/// Handle a call to the specified method.
+pub(crate) async fn handle(
+  state: &State,
+  method: &str,
+  params: Value,
+) -> Result<Value, ServerError> {
+  match method {
+    "get_balance" => get_balance(state, params).await,
+    "get_address" => get_address(state, params).await,
+    "create_address" => create_address(state, params).await,
+    "create_account" => create_account(state, params).await,
+    "transfer" => transfer(state, params).await,
+    "get_transfers" => get_transfers(state, params).await,
+    "incoming_transfers" => incoming_transfers(state, params).await,
+    "get_height" => get_height(state, params).await,
+    _ => Err(ServerError::InvalidParams(format!("unknown method {method}"))),
+  }
+}
+
+/// Create a JSON-RPC module serving every method.
+pub(crate) fn module(state: Arc<State>) -> RpcModule<Arc<State>> {
+  let mut module = RpcModule::new(state);
+  for &method in METHODS {
+    module
+      .register_async_method(method, move |params, state| async move {
+        let params = params.parse::<Value>()?;
+        handle(&state, method, params).await.map_err(|e| {
+          JsonRpcError::Call(CallError::Custom(ErrorObject::owned(
+            e.code(),
+            e.to_string(),
+            None::<()>,
+          )))
+        })
+      })
+      .unwrap();
+  }
+  module
+}
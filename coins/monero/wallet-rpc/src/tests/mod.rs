@@ -0,0 +1,732 @@
+use std::{
+  sync::{Arc, Mutex},
+  collections::HashMap,
+};
+
+use async_trait::async_trait;
+
+use zeroize::Zeroizing;
+use rand_core::{RngCore, OsRng};
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar, edwards::EdwardsPoint};
+
+use serde::{Serialize, Deserialize};
+use serde_json::{Value, json};
+
+use tokio::sync::RwLock;
+
+use digest_auth::AuthContext;
+use hyper::{
+  header::{AUTHORIZATION, WWW_AUTHENTICATE},
+  Body, Request, StatusCode,
+};
+
+use monero_serai::{
+  Commitment, random_scalar, hash_to_scalar,
+  ringct::{RctBase, RctPrunable, RctSignatures},
+  transaction::{Input, Output, Timelock, TransactionPrefix, Transaction},
+  block::{BlockHeader, Block},
+  rpc::{RpcError, RpcConnection, Rpc},
+  wallet::{
+    address::{Network, MoneroAddress},
+    seed::{Seed, Language},
+    StoreError,
+  },
+};
+
+use crate::{
+  wallet::{PENDING_TIMEOUT, WalletError, Wallet, subaddress},
+  auth::{Login, AuthLayer},
+  server::{ServerError, State, handle},
+};
+
+const KEY: [u8; 32] = [7; 32];
+
+fn new_wallet(network: Network) -> Wallet {
+  Wallet::new(Seed::new(&mut OsRng, Language::English), network, Zeroizing::new(KEY))
+}
+
+// Blocks are identified by their number, in place of their hash
+fn block_hash(number: usize) -> [u8; 32] {
+  let mut hash = [0; 32];
+  hash[.. 8].copy_from_slice(&u64::try_from(number).unwrap().to_le_bytes());
+  hash
+}
+
+fn random_point() -> EdwardsPoint {
+  &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE
+}
+
+#[allow(non_snake_case)]
+fn transaction(
+  inputs: Vec<Input>,
+  timelock: Timelock,
+  outputs: Vec<Output>,
+  R: EdwardsPoint,
+) -> Transaction {
+  let mut extra = vec![1];
+  extra.extend(R.compress().to_bytes());
+  Transaction {
+    prefix: TransactionPrefix { version: 2, timelock, inputs, outputs, extra },
+    signatures: vec![],
+    rct_signatures: RctSignatures {
+      base: RctBase { fee: 0, ecdh_info: vec![], commitments: vec![] },
+      prunable: RctPrunable::Null,
+    },
+  }
+}
+
+/// A daemon serving a fixed chain of blocks, each of a miner transaction and any transactions
+/// specified.
+#[derive(Clone, Debug)]
+struct MockDaemon {
+  blocks: Vec<Block>,
+  txs: HashMap<[u8; 32], Transaction>,
+  o_indexes: HashMap<[u8; 32], Vec<u64>>,
+  // Every output, by global index, as (transaction hash, index within the transaction)
+  outputs: Vec<([u8; 32], usize)>,
+  // Transactions published yet not yet mined, shared across clones
+  mempool: Arc<Mutex<Vec<Transaction>>>,
+}
+
+impl MockDaemon {
+  fn new() -> MockDaemon {
+    MockDaemon {
+      blocks: vec![],
+      txs: HashMap::new(),
+      o_indexes: HashMap::new(),
+      outputs: vec![],
+      mempool: Arc::new(Mutex::new(vec![])),
+    }
+  }
+
+  fn add_transaction(&mut self, tx: Transaction) -> [u8; 32] {
+    let hash = tx.hash();
+    let start = self.outputs.len();
+    self.outputs.extend((0 .. tx.prefix.outputs.len()).map(|o| (hash, o)));
+    self
+      .o_indexes
+      .insert(hash, (start .. self.outputs.len()).map(|i| u64::try_from(i).unwrap()).collect());
+    self.txs.insert(hash, tx);
+    hash
+  }
+
+  /// Mine a block including every transaction published since the last call to this function.
+  fn mine_mempool(&mut self) {
+    let txs = self.mempool.lock().unwrap().drain(..).collect();
+    self.mine(None, txs);
+  }
+
+  /// Mine a block whose miner transaction pays the specified address, if any.
+  #[allow(non_snake_case)]
+  fn mine(&mut self, to: Option<(MoneroAddress, u64)>, txs: Vec<Transaction>) {
+    let height = self.blocks.len();
+
+    let r = random_scalar(&mut OsRng);
+    let (R, key, amount) = match to {
+      Some((address, amount)) => {
+        // Subaddresses use a transaction key of rD, instead of rG
+        let R =
+          if address.is_subaddress() { r * address.spend } else { &r * &ED25519_BASEPOINT_TABLE };
+        let shared = hash_to_scalar(
+          &[(r * address.view).mul_by_cofactor().compress().to_bytes().as_ref(), &[0]].concat(),
+        );
+        (R, (&shared * &ED25519_BASEPOINT_TABLE) + address.spend, amount)
+      }
+      None => (&r * &ED25519_BASEPOINT_TABLE, random_point(), 1),
+    };
+
+    let miner_tx = transaction(
+      vec![Input::Gen(u64::try_from(height).unwrap())],
+      Timelock::Block(height + 60),
+      vec![Output { amount, key: key.compress(), view_tag: None }],
+      R,
+    );
+    self.add_transaction(miner_tx.clone());
+
+    let txs = txs.into_iter().map(|tx| self.add_transaction(tx)).collect();
+    self.blocks.push(Block {
+      header: BlockHeader {
+        major_version: 16,
+        minor_version: 16,
+        timestamp: 0,
+        previous: height.checked_sub(1).map(block_hash).unwrap_or([0; 32]),
+        nonce: 0,
+      },
+      miner_tx,
+      txs,
+    });
+  }
+}
+
+#[derive(Deserialize)]
+struct JsonRpcRequest {
+  method: String,
+  #[serde(default)]
+  params: Value,
+}
+
+#[derive(Serialize, Deserialize)]
+struct OIndexesRequest {
+  txid: [u8; 32],
+}
+
+#[derive(Serialize)]
+struct OIndexes {
+  o_indexes: Vec<u64>,
+  status: String,
+  untrusted: bool,
+  credits: usize,
+  top_hash: String,
+}
+
+#[async_trait]
+impl RpcConnection for MockDaemon {
+  async fn post(&self, route: &str, body: Vec<u8>) -> Result<Vec<u8>, RpcError> {
+    // Blocks are identified by their number, in place of their hash
+    let block = |hash: &Value| {
+      let hash = hex::decode(hash.as_str().unwrap()).unwrap();
+      &self.blocks[usize::try_from(u64::from_le_bytes(hash[.. 8].try_into().unwrap())).unwrap()]
+    };
+
+    let res = match route {
+      "get_height" => json!({ "height": self.blocks.len() }),
+      "json_rpc" => {
+        let request: JsonRpcRequest = serde_json::from_slice(&body).unwrap();
+        match request.method.as_str() {
+          "get_block_header_by_height" => {
+            let hash =
+              block_hash(usize::try_from(request.params["height"].as_u64().unwrap()).unwrap());
+            json!({ "result": { "block_header": { "hash": hex::encode(hash) } } })
+          }
+          "get_block" => {
+            json!({ "result": { "blob": hex::encode(block(&request.params["hash"]).serialize()) } })
+          }
+          "get_last_block_header" => {
+            json!({ "result": { "block_header": { "major_version": 16 } } })
+          }
+          "get_fee_estimate" => json!({ "result": { "fee": 20000, "quantization_mask": 10000 } }),
+          "get_output_distribution" => {
+            let from = usize::try_from(request.params["from_height"].as_u64().unwrap()).unwrap();
+            let to = usize::try_from(request.params["to_height"].as_u64().unwrap()).unwrap();
+            let mut distribution = vec![];
+            let mut outputs = 0;
+            for block in &self.blocks[..= to] {
+              outputs += block.miner_tx.prefix.outputs.len() +
+                block.txs.iter().map(|tx| self.txs[tx].prefix.outputs.len()).sum::<usize>();
+              distribution.push(u64::try_from(outputs).unwrap());
+            }
+            json!({ "result": { "distributions": [{ "distribution": distribution[from ..] }] } })
+          }
+          _ => Err(RpcError::InternalError("unknown method"))?,
+        }
+      }
+      "get_transactions" => {
+        let request: Value = serde_json::from_slice(&body).unwrap();
+        let mut txs = vec![];
+        for hash in request["txs_hashes"].as_array().unwrap() {
+          let tx =
+            &self.txs[&<[u8; 32]>::try_from(hex::decode(hash.as_str().unwrap()).unwrap()).unwrap()];
+          txs.push(json!({
+            "tx_hash": hash,
+            "as_hex": hex::encode(tx.serialize()),
+            "pruned_as_hex": "",
+          }));
+        }
+        json!({ "txs": txs })
+      }
+      "get_outs" => {
+        let request: Value = serde_json::from_slice(&body).unwrap();
+        let mut outs = vec![];
+        for output in request["outputs"].as_array().unwrap() {
+          let (hash, o) = self.outputs[usize::try_from(output["index"].as_u64().unwrap()).unwrap()];
+          let tx = &self.txs[&hash];
+          // Miner transactions have their amounts in the clear
+          let mask = match tx.prefix.outputs[o].amount {
+            0 => tx.rct_signatures.base.commitments[o],
+            amount => Commitment::new(Scalar::one(), amount).calculate(),
+          };
+          outs.push(json!({
+            "key": hex::encode(tx.prefix.outputs[o].key.to_bytes()),
+            "mask": hex::encode(mask.compress().to_bytes()),
+            "txid": hex::encode(hash),
+          }));
+        }
+        json!({ "outs": outs })
+      }
+      "is_key_image_spent" => {
+        let request: Value = serde_json::from_slice(&body).unwrap();
+        let spent_by = |tx: &Transaction, key_image: &str| {
+          tx.prefix.inputs.iter().any(|input| match input {
+            Input::ToKey { key_image: spent, .. } => {
+              hex::encode(spent.compress().to_bytes()) == key_image
+            }
+            Input::Gen(_) => false,
+          })
+        };
+        let mut spent_status = vec![];
+        for key_image in request["key_images"].as_array().unwrap() {
+          let key_image = key_image.as_str().unwrap();
+          spent_status.push(if self.txs.values().any(|tx| spent_by(tx, key_image)) {
+            1
+          } else if self.mempool.lock().unwrap().iter().any(|tx| spent_by(tx, key_image)) {
+            2
+          } else {
+            0
+          });
+        }
+        json!({ "spent_status": spent_status })
+      }
+      "send_raw_transaction" => {
+        let request: Value = serde_json::from_slice(&body).unwrap();
+        let tx = hex::decode(request["tx_as_hex"].as_str().unwrap()).unwrap();
+        self.mempool.lock().unwrap().push(Transaction::read(&mut tx.as_slice()).unwrap());
+        json!({
+          "status": "OK",
+          "double_spend": false,
+          "fee_too_low": false,
+          "invalid_input": false,
+          "invalid_output": false,
+          "low_mixin": false,
+          "not_relayed": false,
+          "overspend": false,
+          "too_big": false,
+          "too_few_outputs": false,
+          "reason": "",
+        })
+      }
+      "get_o_indexes.bin" => {
+        let request: OIndexesRequest = monero_epee_bin_serde::from_bytes(body).unwrap();
+        return Ok(
+          monero_epee_bin_serde::to_bytes(&OIndexes {
+            o_indexes: self.o_indexes[&request.txid].clone(),
+            status: "OK".to_string(),
+            untrusted: false,
+            credits: 0,
+            top_hash: String::new(),
+          })
+          .unwrap(),
+        );
+      }
+      _ => Err(RpcError::InternalError("unknown route"))?,
+    };
+    Ok(serde_json::to_vec(&res).unwrap())
+  }
+}
+
+const PRIMARY: u64 = 5_000_000_000_000;
+const SUBADDRESS: u64 = 3_000_000_000_000;
+
+// Create a wallet with a subaddress, and a chain paying both its primary address and subaddress
+async fn setup() -> (State, MockDaemon) {
+  let mut wallet = new_wallet(Network::Mainnet);
+  let (index, subaddress) = wallet.create_address(0, "Subaddress".to_string()).unwrap();
+  assert_eq!(index, 1);
+
+  let mut daemon = MockDaemon::new();
+  daemon.mine(None, vec![]);
+  daemon.mine(Some((wallet.address(0, 0), PRIMARY)), vec![]);
+  daemon.mine(Some((subaddress, SUBADDRESS)), vec![]);
+  for _ in 0 .. 70 {
+    daemon.mine(None, vec![]);
+  }
+
+  // Sync in batches, as the server does
+  let rpc = Rpc::from_connection(daemon.clone());
+  assert!(!wallet.sync(&rpc, 50).await.unwrap());
+  assert_eq!(wallet.height(), 50);
+  assert!(wallet.sync(&rpc, 50).await.unwrap());
+  assert_eq!(wallet.height(), 73);
+
+  (State { rpc, wallet: RwLock::new(wallet), path: None }, daemon)
+}
+
+// Extend the chain so there's enough decoys, and the outputs received are unlocked
+async fn funded() -> (State, MockDaemon) {
+  let (state, mut daemon) = setup().await;
+  for _ in 0 .. 130 {
+    daemon.mine(None, vec![]);
+  }
+  let state = State { rpc: Rpc::from_connection(daemon.clone()), ..state };
+  assert!(state.wallet.write().await.sync(&state.rpc, usize::MAX).await.unwrap());
+  (state, daemon)
+}
+
+#[tokio::test]
+async fn balance() {
+  let (state, _) = setup().await;
+
+  assert_eq!(handle(&state, "get_height", Value::Null).await.unwrap(), json!({ "height": 73 }));
+
+  let balance = handle(&state, "get_balance", Value::Null).await.unwrap();
+  assert_eq!(balance["balance"], PRIMARY + SUBADDRESS);
+  assert_eq!(balance["unlocked_balance"], PRIMARY + SUBADDRESS);
+  let per_subaddress = balance["per_subaddress"].as_array().unwrap();
+  assert_eq!(per_subaddress.len(), 2);
+  assert_eq!(per_subaddress[1]["address_index"], 1);
+  assert_eq!(per_subaddress[1]["balance"], SUBADDRESS);
+  assert_eq!(per_subaddress[1]["num_unspent_outputs"], 1);
+
+  let balance = handle(&state, "get_balance", json!({ "address_indices": [1] })).await.unwrap();
+  assert_eq!(balance["per_subaddress"].as_array().unwrap().len(), 1);
+
+  // Outputs are locked until their timelock expires
+  {
+    let wallet = state.wallet.read().await;
+    assert_eq!(wallet.balance(0, None, 61), (PRIMARY + SUBADDRESS, PRIMARY));
+    assert_eq!(wallet.balance(0, Some(1), 61), (SUBADDRESS, 0));
+  }
+
+  assert!(matches!(
+    handle(&state, "get_balance", json!({ "account_index": "0" })).await,
+    Err(ServerError::InvalidParams(_))
+  ));
+}
+
+#[tokio::test]
+async fn addresses() {
+  let (state, _) = setup().await;
+
+  let res = handle(&state, "get_address", Value::Null).await.unwrap();
+  let wallet = state.wallet.read().await;
+  assert_eq!(res["address"], wallet.address(0, 0).to_string());
+  let addresses = res["addresses"].as_array().unwrap();
+  assert_eq!(addresses.len(), 2);
+  assert_eq!(addresses[1]["address"], wallet.address(0, 1).to_string());
+  assert_eq!(addresses[1]["label"], "Subaddress");
+  assert_eq!(addresses[1]["used"], true);
+  drop(wallet);
+
+  let res = handle(&state, "create_address", json!({ "label": "New", "count": 2 })).await.unwrap();
+  assert_eq!(res["address_index"], 2);
+  assert_eq!(res["address_indices"], json!([2, 3]));
+
+  let res = handle(&state, "get_address", json!({ "address_index": [3] })).await.unwrap();
+  let addresses = res["addresses"].as_array().unwrap();
+  assert_eq!(addresses.len(), 1);
+  assert_eq!(addresses[0]["label"], "New");
+  assert_eq!(addresses[0]["used"], false);
+
+  // As with monero-wallet-rpc, between 1 and 64 addresses may be created at once
+  for count in [0, 65] {
+    assert!(matches!(
+      handle(&state, "create_address", json!({ "count": count })).await,
+      Err(ServerError::InvalidParams(_))
+    ));
+  }
+  let res = handle(&state, "create_address", json!({ "count": 64 })).await.unwrap();
+  assert_eq!(res["address_indices"].as_array().unwrap().len(), 64);
+
+  assert!(matches!(
+    handle(&state, "get_address", json!({ "account_index": 1 })).await,
+    Err(ServerError::Wallet(_))
+  ));
+}
+
+#[tokio::test]
+async fn transfers() {
+  let (state, mut daemon) = setup().await;
+
+  let res = handle(&state, "get_transfers", json!({ "in": true })).await.unwrap();
+  let incoming = res["in"].as_array().unwrap();
+  assert_eq!(incoming.len(), 2);
+  assert_eq!(incoming[0]["amount"], PRIMARY);
+  assert_eq!(incoming[0]["height"], 1);
+  assert_eq!(incoming[0]["confirmations"], 72);
+  assert_eq!(incoming[0]["unlock_time"], 61);
+  assert_eq!(incoming[1]["subaddr_index"], json!({ "major": 0, "minor": 1 }));
+  assert!(res.get("out").is_none());
+
+  let res = handle(
+    &state,
+    "get_transfers",
+    json!({ "in": true, "filter_by_height": true, "min_height": 2 }),
+  )
+  .await
+  .unwrap();
+  assert_eq!(res["in"].as_array().unwrap().len(), 1);
+
+  let res =
+    handle(&state, "incoming_transfers", json!({ "transfer_type": "available" })).await.unwrap();
+  let available = res["transfers"].as_array().unwrap();
+  assert_eq!(available.len(), 2);
+  assert_eq!(available[0]["global_index"], 1);
+  assert_eq!(available[1]["global_index"], 2);
+  assert_eq!(available[0]["unlocked"], true);
+
+  // Spend the primary address's output, which the wallet should detect via its key image
+  let key_image = {
+    let wallet = state.wallet.read().await;
+    wallet.key_image(&wallet.outputs()[0])
+  };
+  daemon.mine(
+    None,
+    vec![transaction(
+      vec![Input::ToKey { amount: 0, key_offsets: vec![1], key_image }],
+      Timelock::None,
+      vec![],
+      random_point(),
+    )],
+  );
+  let rpc = Rpc::from_connection(daemon);
+  assert!(state.wallet.write().await.sync(&rpc, usize::MAX).await.unwrap());
+
+  let res =
+    handle(&state, "incoming_transfers", json!({ "transfer_type": "unavailable" })).await.unwrap();
+  let unavailable = res["transfers"].as_array().unwrap();
+  assert_eq!(unavailable.len(), 1);
+  assert_eq!(unavailable[0]["amount"], PRIMARY);
+  assert_eq!(unavailable[0]["spent"], true);
+
+  let res = handle(&state, "get_transfers", json!({ "out": true })).await.unwrap();
+  let out = res["out"].as_array().unwrap();
+  assert_eq!(out.len(), 1);
+  assert_eq!(out[0]["amount"], PRIMARY);
+  assert_eq!(out[0]["height"], 73);
+
+  let balance = handle(&state, "get_balance", Value::Null).await.unwrap();
+  assert_eq!(balance["balance"], SUBADDRESS);
+
+  assert!(matches!(
+    handle(&state, "incoming_transfers", json!({ "transfer_type": "spent" })).await,
+    Err(ServerError::InvalidParams(_))
+  ));
+}
+
+#[tokio::test]
+async fn transfer() {
+  let (state, _) = setup().await;
+
+  assert!(matches!(
+    handle(&state, "transfer", json!({ "destinations": [] })).await,
+    Err(ServerError::InvalidParams(_))
+  ));
+  assert!(matches!(
+    handle(&state, "transfer", json!({ "destinations": [{ "amount": 1, "address": "invalid" }] }))
+      .await,
+    Err(ServerError::InvalidAddress(_))
+  ));
+
+  // Addresses for other networks are invalid
+  let testnet = new_wallet(Network::Testnet).address(0, 0);
+  assert!(matches!(
+    handle(
+      &state,
+      "transfer",
+      json!({ "destinations": [{ "amount": 1, "address": testnet.to_string() }] })
+    )
+    .await,
+    Err(ServerError::InvalidAddress(_))
+  ));
+
+  assert!(matches!(
+    handle(&state, "unknown", Value::Null).await,
+    Err(ServerError::InvalidParams(_))
+  ));
+}
+
+#[tokio::test]
+async fn send() {
+  const AMOUNT: u64 = 1_000_000_000_000;
+
+  let (state, mut daemon) = funded().await;
+  let mut recipient = new_wallet(Network::Mainnet);
+  let res = handle(
+    &state,
+    "transfer",
+    json!({
+      "destinations": [{ "amount": AMOUNT, "address": recipient.address(0, 0).to_string() }],
+      "get_tx_key": true,
+    }),
+  )
+  .await
+  .unwrap();
+  assert_eq!(res["amount"], AMOUNT);
+  let fee = res["fee"].as_u64().unwrap();
+  assert!(fee != 0);
+  assert_eq!(res["tx_key"].as_str().unwrap().len(), 64);
+
+  let published = daemon.mempool.lock().unwrap().clone();
+  assert_eq!(published.len(), 1);
+  assert_eq!(res["tx_hash"], hex::encode(published[0].hash()));
+  let res = handle(&state, "get_transfers", json!({ "pending": true })).await.unwrap();
+  assert_eq!(res["pending"].as_array().unwrap().len(), 1);
+
+  // Once mined, the transfer should no longer be pending and the change should be received
+  daemon.mine_mempool();
+  let rpc = Rpc::from_connection(daemon.clone());
+  assert!(state.wallet.write().await.sync(&rpc, usize::MAX).await.unwrap());
+  let res = handle(&state, "get_transfers", json!({ "out": true, "pending": true })).await.unwrap();
+  assert!(res.get("pending").is_none());
+  let out = res["out"].as_array().unwrap();
+  assert_eq!(out.len(), 1);
+  assert_eq!(out[0]["amount"], AMOUNT);
+  assert_eq!(out[0]["height"], 203);
+
+  let balance = handle(&state, "get_balance", Value::Null).await.unwrap();
+  let first_balance = PRIMARY + SUBADDRESS - AMOUNT - fee;
+  assert_eq!(balance["balance"], first_balance);
+
+  // The recipient should be able to scan the payment
+  assert!(recipient.sync(&rpc, usize::MAX).await.unwrap());
+  assert_eq!(recipient.balance(0, None, 0).0, AMOUNT);
+
+  // When spending from another account, change should be sent to that account
+  let res = handle(&state, "create_account", json!({ "label": "Account" })).await.unwrap();
+  assert_eq!(res["account_index"], 1);
+  let account = state.wallet.read().await.address(1, 0);
+  assert_eq!(res["address"], account.to_string());
+  daemon.mine(Some((account, PRIMARY)), vec![]);
+  for _ in 0 .. 60 {
+    daemon.mine(None, vec![]);
+  }
+  let state = State { rpc: Rpc::from_connection(daemon.clone()), ..state };
+  assert!(state.wallet.write().await.sync(&state.rpc, usize::MAX).await.unwrap());
+
+  let res = handle(
+    &state,
+    "transfer",
+    json!({
+      "destinations": [{ "amount": AMOUNT, "address": recipient.address(0, 0).to_string() }],
+      "account_index": 1,
+    }),
+  )
+  .await
+  .unwrap();
+  let fee = res["fee"].as_u64().unwrap();
+  daemon.mine_mempool();
+  assert!(state
+    .wallet
+    .write()
+    .await
+    .sync(&Rpc::from_connection(daemon), usize::MAX)
+    .await
+    .unwrap());
+
+  let balance = handle(&state, "get_balance", json!({ "account_index": 1 })).await.unwrap();
+  assert_eq!(balance["balance"], PRIMARY - AMOUNT - fee);
+  assert_eq!(balance["per_subaddress"][0]["address_index"], 0);
+  assert_eq!(balance["per_subaddress"][0]["num_unspent_outputs"], 1);
+  let wallet = state.wallet.read().await;
+  assert_eq!(subaddress(wallet.outputs().last().unwrap()), (1, 0));
+  assert_eq!(wallet.balance(0, None, wallet.height()).0, first_balance);
+}
+
+#[tokio::test]
+async fn dropped() {
+  let (state, mut daemon) = funded().await;
+  let recipient = new_wallet(Network::Mainnet).address(0, 0);
+
+  // The transfer is published, so it's reported even though the wallet can't be saved
+  let dir = std::env::temp_dir().join(format!("monero-serai-wallet-rpc-{}", OsRng.next_u64()));
+  let state = State { path: Some(dir.join("wallet")), ..state };
+  let res = handle(
+    &state,
+    "transfer",
+    json!({ "destinations": [{ "amount": 1_000_000_000_000u64, "address": recipient.to_string() }] }),
+  )
+  .await
+  .unwrap();
+  assert_eq!(res["tx_hash"], hex::encode(daemon.mempool.lock().unwrap()[0].hash()));
+
+  // The inputs are reserved while the transfer is pending, including past the timeout while the
+  // transfer remains in the pool
+  let balance = |state: &State| {
+    let state = state.wallet.try_read().unwrap();
+    state.balance(0, None, state.height()).0
+  };
+  assert!(balance(&state) < (PRIMARY + SUBADDRESS));
+  for _ in 0 .. PENDING_TIMEOUT {
+    daemon.mine(None, vec![]);
+  }
+  let rpc = Rpc::from_connection(daemon.clone());
+  assert!(state.wallet.write().await.sync(&rpc, usize::MAX).await.unwrap());
+  let res = handle(&state, "get_transfers", json!({ "pending": true })).await.unwrap();
+  assert_eq!(res["pending"].as_array().unwrap().len(), 1);
+  assert!(balance(&state) < (PRIMARY + SUBADDRESS));
+
+  // Pending transfers are persisted
+  std::fs::create_dir(&dir).unwrap();
+  let path = dir.join("wallet");
+  state.wallet.write().await.save(&path).unwrap();
+  let state =
+    State { wallet: RwLock::new(Wallet::load(&path, Zeroizing::new(KEY)).unwrap()), ..state };
+  std::fs::remove_dir_all(dir).unwrap();
+  assert!(balance(&state) < (PRIMARY + SUBADDRESS));
+
+  // Once dropped from the pool, the inputs are released
+  daemon.mempool.lock().unwrap().clear();
+  assert!(state.wallet.write().await.sync(&rpc, usize::MAX).await.unwrap());
+  let res = handle(&state, "get_transfers", json!({ "pending": true })).await.unwrap();
+  assert!(res.get("pending").is_none());
+  assert_eq!(balance(&state), PRIMARY + SUBADDRESS);
+}
+
+#[tokio::test]
+async fn persistence() {
+  let (state, _) = setup().await;
+  let mut wallet = state.wallet.write().await;
+
+  let dir = std::env::temp_dir().join(format!("monero-serai-wallet-rpc-{}", OsRng.next_u64()));
+  std::fs::create_dir(&dir).unwrap();
+  let path = dir.join("wallet");
+  wallet.save(&path).unwrap();
+  // The labels, stored alongside the seed, shouldn't be present in the clear
+  assert!(!std::fs::read(&path).unwrap().windows(10).any(|window| window == b"Subaddress"));
+
+  let check = |loaded: Wallet, wallet: &Wallet| {
+    assert_eq!(loaded.network(), wallet.network());
+    assert_eq!(loaded.height(), wallet.height());
+    assert_eq!(loaded.addresses, wallet.addresses);
+    assert_eq!(loaded.outputs(), wallet.outputs());
+    assert_eq!(loaded.transfers(), wallet.transfers());
+    assert_eq!(loaded.address(0, 1), wallet.address(0, 1));
+    assert_eq!(loaded.key_image(&loaded.outputs()[0]), wallet.key_image(&wallet.outputs()[0]));
+  };
+  check(Wallet::load(&path, Zeroizing::new(KEY)).unwrap(), &wallet);
+
+  // Further changes are appended
+  let len = std::fs::metadata(&path).unwrap().len();
+  wallet.create_account("Appended".to_string());
+  wallet.save(&path).unwrap();
+  assert!(std::fs::metadata(&path).unwrap().len() > len);
+  check(Wallet::load(&path, Zeroizing::new(KEY)).unwrap(), &wallet);
+
+  assert!(matches!(
+    Wallet::load(&path, Zeroizing::new([0; 32])),
+    Err(WalletError::Store(StoreError::InvalidEncryptionKey))
+  ));
+  std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn authentication() {
+  let request = |authorization: Option<String>| {
+    let mut request = Request::post("/json_rpc");
+    if let Some(authorization) = authorization {
+      request = request.header(AUTHORIZATION, authorization);
+    }
+    request.body(Body::empty()).unwrap()
+  };
+  assert!(AuthLayer::new(None).authorized(&request(None)));
+
+  assert!(Login::from_str("user").is_none());
+  assert!(Login::from_str("user:").is_none());
+  let layer = AuthLayer::new(Some(Login::from_str("user:pass").unwrap()));
+  assert!(!layer.authorized(&request(None)));
+
+  let respond = |password: &str| {
+    let challenge = layer.challenge();
+    assert_eq!(challenge.status(), StatusCode::UNAUTHORIZED);
+    digest_auth::parse(challenge.headers()[WWW_AUTHENTICATE].to_str().unwrap())
+      .unwrap()
+      .respond(&AuthContext::new_post::<_, _, _, &[u8]>("user", password, "/json_rpc", None))
+      .unwrap()
+      .to_header_string()
+  };
+  let authorization = respond("pass");
+  assert!(layer.authorized(&request(Some(authorization.clone()))));
+  // Replayed responses should be rejected
+  assert!(!layer.authorized(&request(Some(authorization))));
+  assert!(!layer.authorized(&request(Some(respond("password")))));
+}
@@ -0,0 +1,561 @@
+use core::ops::Deref;
+use std::{
+  io::{self, Read, Write},
+  path::Path,
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+use thiserror::Error;
+
+use zeroize::Zeroizing;
+use rand_core::{RngCore, OsRng};
+
+use curve25519_dalek::{
+  constants::ED25519_BASEPOINT_TABLE,
+  scalar::Scalar,
+  edwards::{EdwardsPoint, CompressedEdwardsY},
+};
+
+use log::{info, warn};
+
+use monero_serai::{
+  hash_to_scalar,
+  ringct::generate_key_image,
+  transaction::Timelock,
+  rpc::{RpcError, Rpc},
+  wallet::{
+    address::{Network, SubaddressIndex, AddressSpec, MoneroAddress},
+    seed::Seed,
+    ViewPair, SpendableOutput, TransactionError, Change, SignableTransactionBuilder, StoreError,
+    StoredOutput, WalletStore, ScanError, ChainScanner,
+  },
+};
+
+/// The amount of blocks an output is locked for after its inclusion.
+const LOCK_WINDOW: usize = 10;
+
+/// The amount of blocks after which a pending transfer, if neither on-chain nor in the pool, is
+/// considered dropped and has its inputs released.
+pub(crate) const PENDING_TIMEOUT: usize = 10;
+
+#[derive(Debug, Error)]
+pub(crate) enum WalletError {
+  #[error("rpc error ({0})")]
+  Rpc(#[from] RpcError),
+  #[error("transaction error ({0})")]
+  Transaction(#[from] TransactionError),
+  #[error("scan error ({0})")]
+  Scan(#[from] ScanError),
+  #[error("store error ({0})")]
+  Store(#[from] StoreError),
+  #[error("io error ({0})")]
+  Io(#[from] io::Error),
+  #[error("invalid wallet file ({0})")]
+  InvalidWalletFile(&'static str),
+  #[error("account doesn't exist")]
+  UnknownAccount,
+}
+
+/// The subaddress an output was received to, as (account, address).
+pub(crate) fn subaddress(output: &StoredOutput) -> (u32, u32) {
+  index(output.output.output.metadata.subaddress)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum TransferKind {
+  Incoming,
+  Outgoing,
+  // An outgoing transfer which has been published yet not yet scanned on-chain
+  Pending,
+}
+
+/// A transfer into or out of the wallet.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Transfer {
+  pub(crate) tx: [u8; 32],
+  pub(crate) height: usize,
+  pub(crate) kind: TransferKind,
+  pub(crate) amount: u64,
+  pub(crate) fee: u64,
+  // The subaddress received to, or spent from, as (account, address)
+  pub(crate) subaddress: (u32, u32),
+  pub(crate) payment_id: [u8; 8],
+  pub(crate) timelock: Timelock,
+}
+
+impl Transfer {
+  pub(crate) fn unlock_time(&self) -> u64 {
+    match self.timelock {
+      Timelock::None => 0,
+      Timelock::Block(block) => block.try_into().unwrap(),
+      Timelock::Time(time) => time,
+    }
+  }
+}
+
+// A transfer published by this wallet which hasn't been scanned on-chain
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Pending {
+  tx: [u8; 32],
+  // The height of the chain when this was published
+  height: usize,
+  account: u32,
+  amount: u64,
+  fee: u64,
+  // The keys of the outputs spent
+  inputs: Vec<EdwardsPoint>,
+}
+
+fn index(subaddress: Option<SubaddressIndex>) -> (u32, u32) {
+  subaddress.map(|index| (index.account(), index.address())).unwrap_or((0, 0))
+}
+
+fn read_u32<R: Read>(r: &mut R) -> io::Result<u32> {
+  let mut buf = [0; 4];
+  r.read_exact(&mut buf)?;
+  Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64<R: Read>(r: &mut R) -> io::Result<u64> {
+  let mut buf = [0; 8];
+  r.read_exact(&mut buf)?;
+  Ok(u64::from_le_bytes(buf))
+}
+
+fn read_usize<R: Read>(r: &mut R) -> io::Result<usize> {
+  usize::try_from(read_u64(r)?).map_err(|_| io::Error::new(io::ErrorKind::Other, "usize overflow"))
+}
+
+fn read_point<R: Read>(r: &mut R) -> io::Result<EdwardsPoint> {
+  let mut buf = [0; 32];
+  r.read_exact(&mut buf)?;
+  CompressedEdwardsY(buf)
+    .decompress()
+    .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "invalid point"))
+}
+
+fn read_string<R: Read>(r: &mut R) -> io::Result<Zeroizing<String>> {
+  let mut buf = Zeroizing::new(vec![0; usize::try_from(read_u32(r)?).unwrap()]);
+  r.read_exact(buf.as_mut())?;
+  Ok(Zeroizing::new(
+    String::from_utf8(buf.to_vec())
+      .map_err(|_| io::Error::new(io::ErrorKind::Other, "invalid string"))?,
+  ))
+}
+
+fn write_string<W: Write>(string: &str, w: &mut W) -> io::Result<()> {
+  w.write_all(&u32::try_from(string.len()).unwrap().to_le_bytes())?;
+  w.write_all(string.as_bytes())
+}
+
+// Monero's private spend key is the seed's entropy, and its private view key is its hash
+fn keys(seed: &Seed) -> (Zeroizing<Scalar>, ViewPair) {
+  let spend = Zeroizing::new(Scalar::from_bytes_mod_order(*seed.entropy()));
+  let view = ViewPair::new(
+    spend.deref() * &ED25519_BASEPOINT_TABLE,
+    Zeroizing::new(hash_to_scalar(&spend.to_bytes())),
+  );
+  (spend, view)
+}
+
+/// A wallet, scanning the blockchain and persisted to disk as an encrypted WalletStore.
+///
+/// The seed, network, address labels, and pending transfers are kept as the store's metadata, so
+/// they're encrypted along with the rest of the wallet.
+pub(crate) struct Wallet {
+  seed: Seed,
+  network: Network,
+  spend: Zeroizing<Scalar>,
+  view: ViewPair,
+  chain: ChainScanner,
+  // Every address generated, as (account, address, label), including the primary address
+  pub(crate) addresses: Vec<(u32, u32, String)>,
+  pending: Vec<Pending>,
+}
+
+impl Wallet {
+  const METADATA_VERSION: u8 = 0;
+
+  /// Create a new wallet, whose store is encrypted with the specified key.
+  pub(crate) fn new(seed: Seed, network: Network, key: Zeroizing<[u8; 32]>) -> Wallet {
+    let (spend, view) = keys(&seed);
+    let store = WalletStore::new(view.clone(), 0, Some(key));
+    let mut wallet = Wallet {
+      seed,
+      network,
+      chain: ChainScanner::with_spend_key(store, spend.clone()),
+      spend,
+      view,
+      addresses: vec![(0, 0, "Primary account".to_string())],
+      pending: vec![],
+    };
+    wallet.update_metadata();
+    wallet
+  }
+
+  pub(crate) fn network(&self) -> Network {
+    self.network
+  }
+
+  /// The number of the next block to scan.
+  pub(crate) fn height(&self) -> usize {
+    self.chain.store().height()
+  }
+
+  /// Every output received, including those spent.
+  pub(crate) fn outputs(&self) -> &[StoredOutput] {
+    self.chain.store().outputs()
+  }
+
+  pub(crate) fn key_image(&self, output: &StoredOutput) -> EdwardsPoint {
+    generate_key_image(&Zeroizing::new(self.spend.deref() + output.output.key_offset()))
+  }
+
+  /// If an output was spent, either on-chain or by a pending transfer.
+  pub(crate) fn spent(&self, output: &StoredOutput) -> bool {
+    output.spent.is_some() ||
+      self.pending.iter().any(|pending| pending.inputs.contains(&output.output.key()))
+  }
+
+  pub(crate) fn address(&self, account: u32, address: u32) -> MoneroAddress {
+    self.view.address(
+      self.network,
+      SubaddressIndex::new(account, address).map_or(AddressSpec::Standard, AddressSpec::Subaddress),
+    )
+  }
+
+  /// Create the next account, returning its index and primary address.
+  pub(crate) fn create_account(&mut self, label: String) -> (u32, MoneroAddress) {
+    let account = self.addresses.iter().map(|(account, ..)| account + 1).max().unwrap();
+    self.chain.register_subaddress(SubaddressIndex::new(account, 0).unwrap());
+    self.addresses.push((account, 0, label));
+    self.update_metadata();
+    (account, self.address(account, 0))
+  }
+
+  /// Create the next address within an account.
+  pub(crate) fn create_address(
+    &mut self,
+    account: u32,
+    label: String,
+  ) -> Result<(u32, MoneroAddress), WalletError> {
+    let next = self
+      .addresses
+      .iter()
+      .filter(|(this_account, ..)| *this_account == account)
+      .map(|(_, address, _)| address + 1)
+      .max()
+      .ok_or(WalletError::UnknownAccount)?;
+    self.chain.register_subaddress(SubaddressIndex::new(account, next).unwrap());
+    self.addresses.push((account, next, label));
+    self.update_metadata();
+    Ok((next, self.address(account, next)))
+  }
+
+  /// If an output is unlocked as of the specified chain height.
+  pub(crate) fn unlocked(output: &StoredOutput, height: usize) -> bool {
+    ((output.block + LOCK_WINDOW) <= height) &&
+      match output.timelock {
+        Timelock::None => true,
+        Timelock::Block(block) => block <= height,
+        Timelock::Time(time) => {
+          time <= SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+        }
+      }
+  }
+
+  /// Scan up to the specified amount of blocks, returning if the wallet is now synced.
+  ///
+  /// Reorganizations are handled by rolling back the blocks no longer on the chain.
+  pub(crate) async fn sync(&mut self, rpc: &Rpc, max_blocks: usize) -> Result<bool, WalletError> {
+    let (reorganizations, synced) = self.chain.sync_blocks(rpc, max_blocks).await?;
+    for fork in reorganizations {
+      warn!("reorganization detected, rolled back to block {fork}");
+    }
+    self.resolve_pending(rpc).await?;
+    Ok(synced)
+  }
+
+  // Remove pending transfers which were confirmed, or whose inputs were since spent or reorganized
+  // out. Pending transfers which have been neither on-chain nor in the pool for PENDING_TIMEOUT
+  // blocks are considered dropped, releasing their inputs.
+  async fn resolve_pending(&mut self, rpc: &Rpc) -> Result<(), WalletError> {
+    let store = self.chain.store();
+    let mut resolved = vec![];
+    for pending in &self.pending {
+      if store.transactions().iter().any(|tx| tx.hash == pending.tx) {
+        resolved.push(pending.tx);
+        continue;
+      }
+
+      let inputs = pending
+        .inputs
+        .iter()
+        .map(|key| store.unspent().find(|output| output.output.key() == *key))
+        .collect::<Option<Vec<_>>>();
+      let inputs = match inputs {
+        Some(inputs) => inputs,
+        None => {
+          resolved.push(pending.tx);
+          continue;
+        }
+      };
+
+      if (pending.height + PENDING_TIMEOUT) <= store.height() {
+        let key_images = inputs.iter().map(|output| self.key_image(output)).collect::<Vec<_>>();
+        if !rpc.is_key_image_spent(&key_images).await?.contains(&true) {
+          info!("transfer {} was dropped, releasing its inputs", hex::encode(pending.tx));
+          resolved.push(pending.tx);
+        }
+      }
+    }
+
+    if !resolved.is_empty() {
+      self.pending.retain(|pending| !resolved.contains(&pending.tx));
+      self.update_metadata();
+    }
+    Ok(())
+  }
+
+  /// The balance and unlocked balance of an account, optionally filtered to a subaddress.
+  pub(crate) fn balance(&self, account: u32, address: Option<u32>, height: usize) -> (u64, u64) {
+    let mut balance = 0;
+    let mut unlocked = 0;
+    for output in self.unspent(account) {
+      if address.is_some() && (Some(subaddress(output).1) != address) {
+        continue;
+      }
+      let amount = output.output.commitment().amount;
+      balance += amount;
+      if Self::unlocked(output, height) {
+        unlocked += amount;
+      }
+    }
+    (balance, unlocked)
+  }
+
+  /// The unspent outputs for an account, excluding those spent by pending transfers.
+  pub(crate) fn unspent(&self, account: u32) -> impl Iterator<Item = &StoredOutput> {
+    self
+      .outputs()
+      .iter()
+      .filter(move |output| (!self.spent(output)) && (subaddress(output).0 == account))
+  }
+
+  /// Every transfer into or out of the wallet, including those pending.
+  pub(crate) fn transfers(&self) -> Vec<Transfer> {
+    let store = self.chain.store();
+    let mut transfers = vec![];
+    for tx in store.transactions() {
+      transfers.push(if tx.spent != 0 {
+        // The store only tracks the block an output was spent in, so this is attributed to the
+        // subaddress of an output spent within the transaction's block
+        let spent_from = store
+          .outputs()
+          .iter()
+          .find(|output| output.spent == Some(tx.block))
+          .map(subaddress)
+          .unwrap_or((0, 0));
+        Transfer {
+          tx: tx.hash,
+          height: tx.block,
+          kind: TransferKind::Outgoing,
+          amount: tx.spent.saturating_sub(tx.received).saturating_sub(tx.fee),
+          fee: tx.fee,
+          subaddress: spent_from,
+          payment_id: [0; 8],
+          timelock: tx.timelock,
+        }
+      } else {
+        let received =
+          store.outputs().iter().find(|output| output.output.output.absolute.tx == tx.hash);
+        Transfer {
+          tx: tx.hash,
+          height: tx.block,
+          kind: TransferKind::Incoming,
+          amount: tx.received,
+          fee: tx.fee,
+          subaddress: received.map(subaddress).unwrap_or((0, 0)),
+          payment_id: received
+            .map(|output| output.output.output.metadata.payment_id)
+            .unwrap_or([0; 8]),
+          timelock: tx.timelock,
+        }
+      });
+    }
+
+    for pending in &self.pending {
+      transfers.push(Transfer {
+        tx: pending.tx,
+        height: pending.height,
+        kind: TransferKind::Pending,
+        amount: pending.amount,
+        fee: pending.fee,
+        subaddress: (pending.account, 0),
+        payment_id: [0; 8],
+        timelock: Timelock::None,
+      });
+    }
+    transfers
+  }
+
+  /// Create, sign, and publish a transaction from the specified account, returning the
+  /// transaction hash, the transaction key, the amount sent, and the fee.
+  ///
+  /// The inputs spent are reserved until the transaction is scanned on-chain, or it's been
+  /// dropped from the pool.
+  pub(crate) async fn transfer(
+    &mut self,
+    rpc: &Rpc,
+    account: u32,
+    payments: Vec<(MoneroAddress, u64)>,
+  ) -> Result<([u8; 32], Scalar, u64, u64), WalletError> {
+    if !self.addresses.iter().any(|(this_account, ..)| *this_account == account) {
+      Err(WalletError::UnknownAccount)?;
+    }
+
+    let protocol = rpc.get_protocol().await?;
+    let fee = rpc.get_fee().await?;
+    let height = rpc.get_height().await?;
+
+    let pool = self
+      .unspent(account)
+      .filter(|output| Self::unlocked(output, height))
+      .map(|output| output.output.clone())
+      .collect::<Vec<_>>();
+
+    // Change is sent to the account's primary address, as done by wallet2
+    let change = if account == 0 {
+      Change::new(&self.view, false)
+    } else {
+      Change::fingerprintable(self.address(account, 0))
+    };
+    let mut builder =
+      SignableTransactionBuilder::new(protocol, fee, Some(change)).add_payments(&payments);
+    builder.select_inputs(&pool)?;
+    // Set an r_seed so the transaction keys are deterministic, enabling reporting them
+    let mut r_seed = Zeroizing::new([0; 32]);
+    OsRng.fill_bytes(r_seed.as_mut());
+    builder.set_r_seed(r_seed);
+    let signable = builder.build()?;
+
+    let fee = signable.fee();
+    let amount = payments.iter().map(|(_, amount)| amount).sum();
+    let tx_key = *signable.tx_keys().unwrap().key;
+    let inputs = signable.inputs().iter().map(SpendableOutput::key).collect::<Vec<_>>();
+
+    let tx = signable.sign(&mut OsRng, rpc, &self.spend).await?;
+    rpc.publish_transaction(&tx).await?;
+
+    let hash = tx.hash();
+    self.pending.push(Pending { tx: hash, height, account, amount, fee, inputs });
+    self.update_metadata();
+
+    Ok((hash, tx_key, amount, fee))
+  }
+
+  fn write_metadata<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    w.write_all(&[Self::METADATA_VERSION])?;
+    w.write_all(&[match self.network {
+      Network::Mainnet => 0,
+      Network::Testnet => 1,
+      Network::Stagenet => 2,
+    }])?;
+    write_string(&self.seed.to_string(), w)?;
+
+    w.write_all(&u32::try_from(self.addresses.len()).unwrap().to_le_bytes())?;
+    for (account, address, label) in &self.addresses {
+      w.write_all(&account.to_le_bytes())?;
+      w.write_all(&address.to_le_bytes())?;
+      write_string(label, w)?;
+    }
+
+    w.write_all(&u32::try_from(self.pending.len()).unwrap().to_le_bytes())?;
+    for pending in &self.pending {
+      w.write_all(&pending.tx)?;
+      w.write_all(&u64::try_from(pending.height).unwrap().to_le_bytes())?;
+      w.write_all(&pending.account.to_le_bytes())?;
+      w.write_all(&pending.amount.to_le_bytes())?;
+      w.write_all(&pending.fee.to_le_bytes())?;
+      w.write_all(&u32::try_from(pending.inputs.len()).unwrap().to_le_bytes())?;
+      for input in &pending.inputs {
+        w.write_all(&input.compress().to_bytes())?;
+      }
+    }
+    Ok(())
+  }
+
+  // Update the store's metadata, so it's saved with the store
+  fn update_metadata(&mut self) {
+    let mut metadata = Zeroizing::new(vec![]);
+    self.write_metadata(&mut *metadata).unwrap();
+    self.chain.set_metadata(metadata);
+  }
+
+  fn from_store(store: WalletStore) -> Result<Wallet, WalletError> {
+    let mut r = store.metadata();
+    let mut byte = [0; 1];
+    r.read_exact(&mut byte)?;
+    if byte[0] != Self::METADATA_VERSION {
+      Err(WalletError::InvalidWalletFile("unknown version"))?;
+    }
+    r.read_exact(&mut byte)?;
+    let network = match byte[0] {
+      0 => Network::Mainnet,
+      1 => Network::Testnet,
+      2 => Network::Stagenet,
+      _ => Err(WalletError::InvalidWalletFile("unknown network"))?,
+    };
+    let seed = Seed::from_string(read_string(&mut r)?)
+      .map_err(|_| WalletError::InvalidWalletFile("invalid seed"))?;
+
+    let mut addresses = vec![];
+    for _ in 0 .. read_u32(&mut r)? {
+      let account = read_u32(&mut r)?;
+      let address = read_u32(&mut r)?;
+      addresses.push((account, address, read_string(&mut r)?.to_string()));
+    }
+
+    let mut pending = vec![];
+    for _ in 0 .. read_u32(&mut r)? {
+      let mut tx = [0; 32];
+      r.read_exact(&mut tx)?;
+      let height = read_usize(&mut r)?;
+      let account = read_u32(&mut r)?;
+      let amount = read_u64(&mut r)?;
+      let fee = read_u64(&mut r)?;
+      let inputs =
+        (0 .. read_u32(&mut r)?).map(|_| read_point(&mut r)).collect::<Result<_, _>>()?;
+      pending.push(Pending { tx, height, account, amount, fee, inputs });
+    }
+    if !r.is_empty() {
+      Err(WalletError::InvalidWalletFile("trailing metadata"))?;
+    }
+
+    let (spend, view) = keys(&seed);
+    if view.spend() != store.view_pair().spend() {
+      Err(WalletError::InvalidWalletFile("seed doesn't correspond to the store"))?;
+    }
+    Ok(Wallet {
+      seed,
+      network,
+      chain: ChainScanner::with_spend_key(store, spend.clone()),
+      spend,
+      view,
+      addresses,
+      pending,
+    })
+  }
+
+  /// Save the wallet to the specified path.
+  ///
+  /// The path MUST be the path the wallet was loaded from or last saved to, if any.
+  pub(crate) fn save(&mut self, path: &Path) -> Result<(), WalletError> {
+    Ok(self.chain.save(&mut OsRng, path)?)
+  }
+
+  /// Load the wallet from the specified path, decrypting it with the specified key.
+  pub(crate) fn load(path: &Path, key: Zeroizing<[u8; 32]>) -> Result<Wallet, WalletError> {
+    Wallet::from_store(WalletStore::load(path, Some(key))?)
+  }
+}
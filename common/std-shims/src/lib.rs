@@ -16,6 +16,20 @@ pub mod str {
   pub use std::str::*;
 }
 
+pub mod string {
+  #[cfg(not(feature = "std"))]
+  pub use alloc::string::*;
+  #[cfg(feature = "std")]
+  pub use std::string::*;
+}
+
+pub mod sync {
+  #[cfg(not(feature = "std"))]
+  pub use alloc::sync::*;
+  #[cfg(feature = "std")]
+  pub use std::sync::*;
+}
+
 pub mod vec {
   #[cfg(not(feature = "std"))]
   pub use alloc::vec::*;
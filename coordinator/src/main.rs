@@ -5,6 +5,7 @@
 use core::ops::Deref;
 use std::{
   sync::Arc,
+  io::Read,
   time::{SystemTime, Duration},
   collections::{VecDeque, HashMap},
 };
@@ -159,7 +160,7 @@ pub async fn scan_tributaries<D: Db, Pro: Processor, P: P2p>(
     }
 
     for (spec, reader) in &tributary_readers {
-      tributary::scanner::handle_new_blocks::<_, _>(
+      tributary::scanner::handle_new_blocks::<_, _, P>(
         &mut tributary_db,
         &key,
         &processor,
@@ -202,6 +203,29 @@ pub async fn heartbeat_tributaries<D: Db, P: P2p>(
   }
 }
 
+// The size to batch blocks up to when responding to a heartbeat
+const BLOCKS_MESSAGE_SIZE: usize = 1_000_000;
+
+// Read a range of blocks, each followed by their length-prefixed commit
+fn read_blocks(mut msg: &[u8]) -> Option<Vec<(Block<Transaction>, Vec<u8>)>> {
+  let mut blocks = vec![];
+  while !msg.is_empty() {
+    let block = Block::<Transaction>::read(&mut msg).ok()?;
+
+    let mut commit_len = [0; 4];
+    msg.read_exact(&mut commit_len).ok()?;
+    let commit_len = usize::try_from(u32::from_le_bytes(commit_len)).unwrap();
+    if msg.len() < commit_len {
+      return None;
+    }
+    let commit = msg[.. commit_len].to_vec();
+    msg = &msg[commit_len ..];
+
+    blocks.push((block, commit));
+  }
+  Some(blocks)
+}
+
 #[allow(clippy::type_complexity)]
 pub async fn handle_p2p<D: Db, P: P2p>(
   our_key: <Ristretto as Ciphersuite>::G,
@@ -209,7 +233,7 @@ pub async fn handle_p2p<D: Db, P: P2p>(
   tributaries: Arc<RwLock<HashMap<[u8; 32], ActiveTributary<D, P>>>>,
 ) {
   loop {
-    let mut msg = p2p.receive().await;
+    let msg = p2p.receive().await;
     match msg.kind {
       P2pMessageKind::Tributary(genesis) => {
         let tributaries = tributaries.read().await;
@@ -218,7 +242,7 @@ pub async fn handle_p2p<D: Db, P: P2p>(
           continue;
         };
 
-        if tributary.tributary.write().await.handle_message(&msg.msg).await {
+        if tributary.tributary.write().await.handle_message(msg.sender, &msg.msg).await {
           P2p::broadcast(&p2p, msg.kind, msg.msg).await;
         }
       }
@@ -247,8 +271,10 @@ pub async fn handle_p2p<D: Db, P: P2p>(
         }
         */
 
+        let validators = tributary.spec.validators();
+
         // Have up to three nodes respond
-        let responders = u64::from(tributary.spec.n().min(3));
+        let responders = u64::try_from(validators.len().min(3)).unwrap();
 
         // Decide which nodes will respond by using the latest block's hash as a mutually agreed
         // upon entropy source
@@ -257,11 +283,10 @@ pub async fn handle_p2p<D: Db, P: P2p>(
         // If n = 10, responders = 3, we want start to be 0 ..= 7 (so the highest is 7, 8, 9)
         // entropy % (10 + 1) - 3 = entropy % 8 = 0 ..= 7
         let start =
-          usize::try_from(entropy % (u64::from(tributary.spec.n() + 1) - responders)).unwrap();
+          usize::try_from(entropy % (u64::try_from(validators.len() + 1).unwrap() - responders))
+            .unwrap();
         let mut selected = false;
-        for validator in
-          &tributary.spec.validators()[start .. (start + usize::try_from(responders).unwrap())]
-        {
+        for validator in &validators[start .. (start + usize::try_from(responders).unwrap())] {
           if our_key == validator.0 {
             selected = true;
             break;
@@ -277,23 +302,37 @@ pub async fn handle_p2p<D: Db, P: P2p>(
         let reader = tributary_read.reader();
         drop(tributary_read);
 
+        // Send the blocks after their latest block in ranges, so the syncer can add each range in
+        // a single DB transaction
+        // Only the last block's commit is verified by the syncer, so only it's sent. Since blocks
+        // synced within a range don't have their commits saved, ranges end on a block with one
         let mut latest = msg.msg.try_into().unwrap();
+        let mut range = vec![];
         while let Some(next) = reader.block_after(&latest) {
-          let mut res = reader.block(&next).unwrap().serialize();
-          res.extend(reader.commit(&next).unwrap());
-          p2p.send(msg.sender, P2pMessageKind::Block(tributary.spec.genesis()), res).await;
+          range.extend(reader.block(&next).unwrap().serialize());
           latest = next;
+
+          let commit = reader.commit(&next).filter(|_| {
+            (range.len() >= BLOCKS_MESSAGE_SIZE) || reader.block_after(&next).is_none()
+          });
+          let end = commit.is_some();
+          let commit = commit.unwrap_or_default();
+          range.extend(u32::try_from(commit.len()).unwrap().to_le_bytes());
+          range.extend(commit);
+
+          if end {
+            let range = core::mem::take(&mut range);
+            P2p::send(&p2p, msg.sender, P2pMessageKind::Blocks(tributary.spec.genesis()), range)
+              .await;
+          }
         }
       }
 
-      P2pMessageKind::Block(genesis) => {
-        let mut msg_ref: &[u8] = msg.msg.as_ref();
-        let Ok(block) = Block::<Transaction>::read(&mut msg_ref) else {
-          log::error!("received block message with an invalidly serialized block");
+      P2pMessageKind::Blocks(genesis) => {
+        let Some(blocks) = read_blocks(&msg.msg) else {
+          log::error!("received blocks message with invalidly serialized blocks");
           continue;
         };
-        // Get just the commit
-        msg.msg.drain(.. (msg.msg.len() - msg_ref.len()));
 
         let tributaries = tributaries.read().await;
         let Some(tributary) = tributaries.get(&genesis) else {
@@ -305,8 +344,8 @@ pub async fn handle_p2p<D: Db, P: P2p>(
         // transactions
         // Any tributary with missing provided transactions will cause this P2P loop to halt
         // Make a separate queue for this
-        let res = tributary.tributary.write().await.sync_block(block, msg.msg).await;
-        log::debug!("received block from {:?}, sync_block returned {}", msg.sender, res);
+        let res = tributary.tributary.write().await.sync_blocks(blocks).await;
+        log::debug!("received blocks from {:?}, sync_blocks returned {}", msg.sender, res);
       }
     }
   }
@@ -332,24 +371,33 @@ pub async fn handle_processors<D: Db, Pro: Processor, P: P2p>(
           Some(Transaction::DkgCommitments(id.attempt, commitments, Transaction::empty_signed()))
         }
         key_gen::ProcessorMessage::Shares { id, shares } => {
+          // Order each key share's shares by the recipient's participant index
+          let shares = shares
+            .into_iter()
+            .map(|shares| {
+              let mut shares = shares.into_iter().collect::<Vec<_>>();
+              shares.sort_by_key(|(i, _)| *i);
+              shares.into_iter().map(|(_, share)| share).collect()
+            })
+            .collect();
           Some(Transaction::DkgShares(id.attempt, shares, Transaction::empty_signed()))
         }
         // TODO
         key_gen::ProcessorMessage::GeneratedKeyPair { .. } => todo!(),
       },
       ProcessorMessage::Sign(msg) => match msg {
-        sign::ProcessorMessage::Preprocess { id, preprocess } => {
+        sign::ProcessorMessage::Preprocess { id, preprocesses } => {
           Some(Transaction::SignPreprocess(SignData {
             plan: id.id,
             attempt: id.attempt,
-            data: preprocess,
+            data: preprocesses,
             signed: Transaction::empty_signed(),
           }))
         }
-        sign::ProcessorMessage::Share { id, share } => Some(Transaction::SignShare(SignData {
+        sign::ProcessorMessage::Share { id, shares } => Some(Transaction::SignShare(SignData {
           plan: id.id,
           attempt: id.attempt,
-          data: share,
+          data: shares,
           signed: Transaction::empty_signed(),
         })),
         // TODO
@@ -358,19 +406,19 @@ pub async fn handle_processors<D: Db, Pro: Processor, P: P2p>(
       ProcessorMessage::Coordinator(msg) => match msg {
         // TODO
         coordinator::ProcessorMessage::SubstrateBlockAck { .. } => todo!(),
-        coordinator::ProcessorMessage::BatchPreprocess { id, preprocess } => {
+        coordinator::ProcessorMessage::BatchPreprocess { id, preprocesses } => {
           Some(Transaction::BatchPreprocess(SignData {
             plan: id.id,
             attempt: id.attempt,
-            data: preprocess,
+            data: preprocesses,
             signed: Transaction::empty_signed(),
           }))
         }
-        coordinator::ProcessorMessage::BatchShare { id, share } => {
+        coordinator::ProcessorMessage::BatchShare { id, shares } => {
           Some(Transaction::BatchShare(SignData {
             plan: id.id,
             attempt: id.attempt,
-            data: share.to_vec(),
+            data: shares.into_iter().map(|share| share.to_vec()).collect(),
             signed: Transaction::empty_signed(),
           }))
         }
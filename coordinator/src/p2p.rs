@@ -11,7 +11,7 @@ pub use tributary::P2p as TributaryP2p;
 pub enum P2pMessageKind {
   Tributary([u8; 32]),
   Heartbeat([u8; 32]),
-  Block([u8; 32]),
+  Blocks([u8; 32]),
 }
 
 impl P2pMessageKind {
@@ -27,7 +27,7 @@ impl P2pMessageKind {
         res.extend(genesis);
         res
       }
-      P2pMessageKind::Block(genesis) => {
+      P2pMessageKind::Blocks(genesis) => {
         let mut res = vec![2];
         res.extend(genesis);
         res
@@ -52,7 +52,7 @@ impl P2pMessageKind {
       2 => Some({
         let mut genesis = [0; 32];
         reader.read_exact(&mut genesis).ok()?;
-        P2pMessageKind::Block(genesis)
+        P2pMessageKind::Blocks(genesis)
       }),
       _ => None,
     }
@@ -68,8 +68,6 @@ pub struct Message<P: P2p> {
 
 #[async_trait]
 pub trait P2p: Send + Sync + Clone + Debug + TributaryP2p {
-  type Id: Send + Sync + Clone + Copy + Debug;
-
   async fn send_raw(&self, to: Self::Id, msg: Vec<u8>);
   async fn broadcast_raw(&self, msg: Vec<u8>);
   async fn receive_raw(&self) -> (Self::Id, Vec<u8>);
@@ -121,8 +119,6 @@ impl LocalP2p {
 
 #[async_trait]
 impl P2p for LocalP2p {
-  type Id = usize;
-
   async fn send_raw(&self, to: Self::Id, msg: Vec<u8>) {
     self.1.write().await[to].push_back((self.0, msg));
   }
@@ -149,6 +145,11 @@ impl P2p for LocalP2p {
 
 #[async_trait]
 impl TributaryP2p for LocalP2p {
+  type Id = usize;
+
+  async fn send(&self, genesis: [u8; 32], to: usize, msg: Vec<u8>) {
+    <Self as P2p>::send(self, to, P2pMessageKind::Tributary(genesis), msg).await
+  }
   async fn broadcast(&self, genesis: [u8; 32], msg: Vec<u8>) {
     <Self as P2p>::broadcast(self, P2pMessageKind::Tributary(genesis), msg).await
   }
@@ -1,3 +1,7 @@
+use scale::{Encode, Decode};
+
+use serai_client::validator_sets::primitives::{ValidatorSet, KeyPair};
+
 pub use serai_db::*;
 
 #[derive(Debug)]
@@ -23,6 +27,17 @@ impl<D: Db> SubstrateDb<D> {
     u64::from_le_bytes(self.0.get(Self::block_key()).unwrap_or(vec![0; 8]).try_into().unwrap())
   }
 
+  fn key_pair_key(set: ValidatorSet) -> Vec<u8> {
+    Self::substrate_key(b"key_pair", set.encode())
+  }
+  // The key pair confirmed for a validator set, which it signs with
+  pub fn key_pair<G: Get>(getter: &G, set: ValidatorSet) -> Option<KeyPair> {
+    getter.get(Self::key_pair_key(set)).map(|bytes| KeyPair::decode(&mut bytes.as_ref()).unwrap())
+  }
+  pub fn save_key_pair(txn: &mut D::Transaction<'_>, set: ValidatorSet, key_pair: &KeyPair) {
+    txn.put(Self::key_pair_key(set), key_pair.encode());
+  }
+
   fn event_key(id: &[u8], index: u32) -> Vec<u8> {
     Self::substrate_key(b"event", [id, index.to_le_bytes().as_ref()].concat())
   }
@@ -58,6 +58,9 @@ async fn handle_new_set<
     let spec = TributarySpec::new(block.hash(), block.time().unwrap(), set, set_data);
     create_new_tributary(db, spec.clone());
 
+    let our_i =
+      spec.i(Ristretto::generator() * key.deref()).expect("In set for a set we aren't in set for");
+
     // Trigger a DKG
     // TODO: Check how the processor handles this being fired multiple times
     // We already have a unique event ID based on block, event index (where event index is
@@ -67,14 +70,8 @@ async fn handle_new_set<
       .send(CoordinatorMessage::KeyGen(
         processor_messages::key_gen::CoordinatorMessage::GenerateKey {
           id: KeyGenId { set, attempt: 0 },
-          params: ThresholdParams::new(
-            spec.t(),
-            spec.n(),
-            spec
-              .i(Ristretto::generator() * key.deref())
-              .expect("In set for a set we aren't in set for"),
-          )
-          .unwrap(),
+          params: ThresholdParams::new(spec.t(), spec.n(), our_i.start).unwrap(),
+          shares: u16::from(our_i.end) - u16::from(our_i.start),
         },
       ))
       .await;
@@ -248,12 +245,13 @@ async fn handle_block<
   // If a key pair was confirmed, inform the processor
   for key_gen in serai.get_key_gen_events(hash).await? {
     if !SubstrateDb::<D>::handled_event(&db.0, hash, event_id) {
-      if let ValidatorSetsEvent::KeyGen { set, key_pair } = key_gen {
-        handle_key_gen(key, processor, serai, &block, set, key_pair).await?;
-      } else {
+      let ValidatorSetsEvent::KeyGen { set, key_pair } = key_gen else {
         panic!("KeyGen event wasn't KeyGen: {key_gen:?}");
-      }
+      };
+      handle_key_gen(key, processor, serai, &block, set, key_pair.clone()).await?;
       let mut txn = db.0.txn();
+      // Save the key pair so the Tributary scanner can tell the processor which key to sign with
+      SubstrateDb::<D>::save_key_pair(&mut txn, set, &key_pair);
       SubstrateDb::<D>::handle_event(&mut txn, hash, event_id);
       txn.commit();
     }
@@ -51,10 +51,15 @@ pub fn new_spec<R: RngCore + CryptoRng>(
   let set_data = ValidatorSetData {
     bond: Amount(100),
     network: NETWORKS[&NetworkId::Bitcoin].clone(),
+    // Give the first validator two key shares, so multiple key shares per validator are tested
     participants: keys
       .iter()
-      .map(|key| {
-        (sr25519::Public((<Ristretto as Ciphersuite>::generator() * **key).to_bytes()), Amount(100))
+      .enumerate()
+      .map(|(i, key)| {
+        (
+          sr25519::Public((<Ristretto as Ciphersuite>::generator() * **key).to_bytes()),
+          Amount(if i == 0 { 200 } else { 100 }),
+        )
       })
       .collect::<Vec<_>>()
       .try_into()
@@ -99,7 +104,7 @@ pub async fn run_tributaries(
         match msg.kind {
           P2pMessageKind::Tributary(genesis) => {
             assert_eq!(genesis, tributary.genesis());
-            if tributary.handle_message(&msg.msg).await {
+            if tributary.handle_message(msg.sender, &msg.msg).await {
               p2p.broadcast(msg.kind, msg.msg).await;
             }
           }
@@ -170,7 +175,7 @@ async fn tributary_test() {
         match msg.kind {
           P2pMessageKind::Tributary(genesis) => {
             assert_eq!(genesis, tributary.genesis());
-            tributary.handle_message(&msg.msg).await;
+            tributary.handle_message(msg.sender, &msg.msg).await;
           }
           _ => panic!("unexpected p2p message found"),
         }
@@ -196,7 +201,7 @@ async fn tributary_test() {
       match msg.kind {
         P2pMessageKind::Tributary(genesis) => {
           assert_eq!(genesis, tributary.genesis());
-          tributary.handle_message(&msg.msg).await;
+          tributary.handle_message(msg.sender, &msg.msg).await;
         }
         _ => panic!("unexpected p2p message found"),
       }
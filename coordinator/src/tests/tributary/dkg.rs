@@ -1,5 +1,5 @@
 use core::time::Duration;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use zeroize::Zeroizing;
 use rand_core::{RngCore, OsRng};
@@ -35,12 +35,21 @@ async fn dkg_test() {
   // Run the tributaries in the background
   tokio::spawn(run_tributaries(tributaries.clone()));
 
+  let key_shares = |key: &Zeroizing<<Ristretto as Ciphersuite>::F>| {
+    let range = spec.i(Ristretto::generator() * **key).unwrap();
+    u16::from(range.start) .. u16::from(range.end)
+  };
+
   let mut txs = vec![];
   // Create DKG commitments for each key
   for key in &keys {
     let attempt = 0;
-    let mut commitments = vec![0; 256];
-    OsRng.fill_bytes(&mut commitments);
+    let mut commitments = vec![];
+    for _ in key_shares(key) {
+      let mut key_share_commitments = vec![0; 256];
+      OsRng.fill_bytes(&mut key_share_commitments);
+      commitments.push(key_share_commitments);
+    }
 
     let mut tx = Transaction::DkgCommitments(attempt, commitments, Transaction::empty_signed());
     tx.sign(&mut OsRng, spec.genesis(), key, 0);
@@ -63,10 +72,13 @@ async fn dkg_test() {
     id: KeyGenId { set: spec.set(), attempt: 0 },
     commitments: txs
       .iter()
-      .enumerate()
-      .map(|(i, tx)| {
+      .zip(&keys)
+      .flat_map(|(tx, key)| {
         if let Transaction::DkgCommitments(_, commitments, _) = tx {
-          (Participant::new((i + 1).try_into().unwrap()).unwrap(), commitments.clone())
+          key_shares(key)
+            .zip(commitments.clone())
+            .map(|(i, commitments)| (Participant::new(i).unwrap(), commitments))
+            .collect::<Vec<_>>()
         } else {
           panic!("txs had non-commitments");
         }
@@ -81,7 +93,14 @@ async fn dkg_test() {
   ) -> (TributaryDb<MemDb>, MemProcessor) {
     let mut scanner_db = TributaryDb(MemDb::new());
     let processor = MemProcessor::new();
-    handle_new_blocks(&mut scanner_db, key, &processor, spec, &tributary.reader()).await;
+    handle_new_blocks::<_, _, LocalP2p>(
+      &mut scanner_db,
+      key,
+      &processor,
+      spec,
+      &tributary.reader(),
+    )
+    .await;
     (scanner_db, processor)
   }
 
@@ -96,7 +115,14 @@ async fn dkg_test() {
   sleep(Duration::from_secs(Tributary::<MemDb, Transaction, LocalP2p>::block_time().into())).await;
 
   // Verify the scanner emits a KeyGen::Commitments message
-  handle_new_blocks(&mut scanner_db, &keys[0], &processor, &spec, &tributaries[0].1.reader()).await;
+  handle_new_blocks::<_, _, LocalP2p>(
+    &mut scanner_db,
+    &keys[0],
+    &processor,
+    &spec,
+    &tributaries[0].1.reader(),
+  )
+  .await;
   {
     let mut msgs = processor.0.write().await;
     assert_eq!(msgs.pop_front().unwrap(), expected_commitments);
@@ -116,11 +142,16 @@ async fn dkg_test() {
   for key in &keys {
     let attempt = 0;
 
-    let mut shares = HashMap::new();
-    for i in 0 .. keys.len() {
-      let mut share = vec![0; 256];
-      OsRng.fill_bytes(&mut share);
-      shares.insert(Participant::new((i + 1).try_into().unwrap()).unwrap(), share);
+    // Each key share sends a share to every other key share
+    let mut shares = vec![];
+    for _ in key_shares(key) {
+      let mut key_share_shares = vec![];
+      for _ in 1 .. spec.n() {
+        let mut share = vec![0; 256];
+        OsRng.fill_bytes(&mut share);
+        key_share_shares.push(share);
+      }
+      shares.push(key_share_shares);
     }
 
     let mut tx = Transaction::DkgShares(attempt, shares, Transaction::empty_signed());
@@ -137,7 +168,14 @@ async fn dkg_test() {
   }
 
   // With just 4 sets of shares, nothing should happen yet
-  handle_new_blocks(&mut scanner_db, &keys[0], &processor, &spec, &tributaries[0].1.reader()).await;
+  handle_new_blocks::<_, _, LocalP2p>(
+    &mut scanner_db,
+    &keys[0],
+    &processor,
+    &spec,
+    &tributaries[0].1.reader(),
+  )
+  .await;
   assert!(processor.0.write().await.is_empty());
 
   // Publish the final set of shares
@@ -150,25 +188,41 @@ async fn dkg_test() {
   let shares_for = |i: usize| {
     CoordinatorMessage::KeyGen(key_gen::CoordinatorMessage::Shares {
       id: KeyGenId { set: spec.set(), attempt: 0 },
-      shares: txs
-        .iter()
-        .enumerate()
-        .map(|(l, tx)| {
-          if let Transaction::DkgShares(_, shares, _) = tx {
-            (
-              Participant::new((l + 1).try_into().unwrap()).unwrap(),
-              shares[&Participant::new((i + 1).try_into().unwrap()).unwrap()].clone(),
-            )
-          } else {
-            panic!("txs had non-shares");
-          }
+      shares: key_shares(&keys[i])
+        .map(|recipient| {
+          txs
+            .iter()
+            .zip(&keys)
+            .flat_map(|(tx, key)| {
+              if let Transaction::DkgShares(_, shares, _) = tx {
+                key_shares(key)
+                  .zip(shares)
+                  .filter(|(sender, _)| *sender != recipient)
+                  .map(|(sender, shares)| {
+                    // Each key share's shares skip itself
+                    let index = if recipient < sender { recipient - 1 } else { recipient - 2 };
+                    (Participant::new(sender).unwrap(), shares[usize::from(index)].clone())
+                  })
+                  .collect::<Vec<_>>()
+              } else {
+                panic!("txs had non-shares");
+              }
+            })
+            .collect::<BTreeMap<_, _>>()
         })
-        .collect::<HashMap<_, _>>(),
+        .collect(),
     })
   };
 
   // Any scanner which has handled the prior blocks should only emit the new event
-  handle_new_blocks(&mut scanner_db, &keys[0], &processor, &spec, &tributaries[0].1.reader()).await;
+  handle_new_blocks::<_, _, LocalP2p>(
+    &mut scanner_db,
+    &keys[0],
+    &processor,
+    &spec,
+    &tributaries[0].1.reader(),
+  )
+  .await;
   {
     let mut msgs = processor.0.write().await;
     assert_eq!(msgs.pop_front().unwrap(), shares_for(0));
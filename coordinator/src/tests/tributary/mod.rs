@@ -1,10 +1,7 @@
 use core::fmt::Debug;
-use std::collections::HashMap;
 
 use rand_core::{RngCore, OsRng};
 
-use frost::Participant;
-
 use tributary::{ReadWrite, tests::random_signed};
 
 use crate::tributary::{SignData, Transaction};
@@ -17,6 +14,8 @@ mod tx;
 mod dkg;
 // TODO: Test the other transactions
 
+mod reattempt;
+
 mod handle_p2p;
 mod sync;
 
@@ -31,6 +30,12 @@ fn random_vec<R: RngCore>(rng: &mut R, limit: usize) -> Vec<u8> {
   res
 }
 
+fn random_vec_of_vecs<R: RngCore>(rng: &mut R, limit: usize) -> Vec<Vec<u8>> {
+  // Have between 1 and 16 entries, as done by a validator with up to 16 key shares
+  let len = usize::try_from((rng.next_u64() % 16) + 1).unwrap();
+  (0 .. len).map(|_| random_vec(rng, limit)).collect()
+}
+
 fn random_sign_data<R: RngCore>(rng: &mut R) -> SignData {
   let mut plan = [0; 32];
   rng.fill_bytes(&mut plan);
@@ -39,7 +44,7 @@ fn random_sign_data<R: RngCore>(rng: &mut R) -> SignData {
     plan,
     attempt: random_u32(&mut OsRng),
 
-    data: random_vec(&mut OsRng, 512),
+    data: random_vec_of_vecs(&mut OsRng, 512),
 
     signed: random_signed(&mut OsRng),
   }
@@ -58,7 +63,7 @@ fn serialize_sign_data() {
 fn serialize_transaction() {
   test_read_write(Transaction::DkgCommitments(
     random_u32(&mut OsRng),
-    random_vec(&mut OsRng, 512),
+    random_vec_of_vecs(&mut OsRng, 512),
     random_signed(&mut OsRng),
   ));
 
@@ -66,13 +71,18 @@ fn serialize_transaction() {
     // This supports a variable share length, yet share length is expected to be constant among
     // shares
     let share_len = usize::try_from(OsRng.next_u64() % 512).unwrap();
-    // Create a valid map of shares
-    let mut shares = HashMap::new();
     // Create up to 512 participants
-    for i in 0 .. (OsRng.next_u64() % 512) {
-      let mut share = vec![0; share_len];
-      OsRng.fill_bytes(&mut share);
-      shares.insert(Participant::new(u16::try_from(i + 1).unwrap()).unwrap(), share);
+    let i = OsRng.next_u64() % 512;
+    // Create shares from up to 16 key shares
+    let mut shares = vec![];
+    for _ in 0 .. ((OsRng.next_u64() % 16) + 1) {
+      let mut key_share_shares = vec![];
+      for _ in 0 .. i {
+        let mut share = vec![0; share_len];
+        OsRng.fill_bytes(&mut share);
+        key_share_shares.push(share);
+      }
+      shares.push(key_share_shares);
     }
 
     test_read_write(Transaction::DkgShares(
@@ -0,0 +1,271 @@
+use std::sync::Arc;
+
+use zeroize::Zeroizing;
+use rand_core::{RngCore, OsRng};
+
+use ciphersuite::{Ciphersuite, Ristretto};
+use frost::Participant;
+
+use sp_application_crypto::sr25519;
+
+use serai_db::{DbTxn, Db, MemDb};
+
+use processor_messages::{
+  sign::{self, SignId},
+  coordinator, CoordinatorMessage,
+};
+
+use tributary::{BlockHeader, Block};
+
+use crate::{
+  LocalP2p,
+  processor::MemProcessor,
+  substrate::SubstrateDb,
+  tributary::{
+    TributaryDb, TributarySpec,
+    scanner::{
+      reattempt_delay, Zone, reattempt_signing_set, non_responsive, reattempts, handle_block,
+    },
+  },
+  tests::tributary::{new_keys, new_spec},
+};
+
+#[test]
+fn reattempt_signing_set_test() {
+  let keys = new_keys(&mut OsRng);
+  let spec = new_spec(&mut OsRng, &keys);
+  let validators =
+    keys.iter().map(|key| <Ristretto as Ciphersuite>::generator() * **key).collect::<Vec<_>>();
+
+  // Every attempt should select a threshold, deterministically, with distinct sets selected as
+  // attempts increment
+  let mut sets = vec![];
+  for attempt in 0 .. u32::try_from(validators.len()).unwrap() {
+    let set = reattempt_signing_set(&spec, attempt, &[]);
+    assert!(set.len() >= usize::from(spec.t()));
+    assert_eq!(set, reattempt_signing_set(&spec, attempt, &[]));
+    assert!(!sets.contains(&set));
+    sets.push(set);
+  }
+
+  // An offline validator should never be selected
+  let offline = validators[1];
+  let offline_i = spec.i(offline).unwrap().start;
+  for attempt in 0 .. 10 {
+    let set = reattempt_signing_set(&spec, attempt, &[offline]);
+    assert!(set.len() >= usize::from(spec.t()));
+    assert!(!set.contains(&offline_i));
+  }
+
+  // If excluding the non-responsive validators wouldn't leave a threshold, none are excluded
+  let set = reattempt_signing_set(&spec, 0, &validators[.. 2]);
+  assert!(set.len() >= usize::from(spec.t()));
+  assert!(set.contains(&spec.i(validators[0]).unwrap().start));
+}
+
+#[test]
+fn reattempt_test() {
+  let keys = new_keys(&mut OsRng);
+  let spec = new_spec(&mut OsRng, &keys);
+  let genesis = spec.genesis();
+  let validators =
+    keys.iter().map(|key| <Ristretto as Ciphersuite>::generator() * **key).collect::<Vec<_>>();
+
+  let mut db = MemDb::new();
+  let id = [0xaa; 32];
+  let start = spec.start_time();
+  let (preprocess_label, share_label) = Zone::Sign.data_labels();
+
+  let mut txn = db.txn();
+  TributaryDb::<MemDb>::recognize_id(&mut txn, Zone::Sign.label(), genesis, id);
+  TributaryDb::<MemDb>::add_active_id(&mut txn, Zone::Sign.label(), genesis, id);
+  TributaryDb::<MemDb>::set_attempt_start(&mut txn, genesis, id, start);
+
+  // Have every validator other than the last publish preprocesses, forming the signing set
+  let mut signing_set = vec![];
+  for validator in &validators[.. (validators.len() - 1)] {
+    let weight = spec.weight(*validator).unwrap();
+    TributaryDb::<MemDb>::set_data(
+      preprocess_label,
+      &mut txn,
+      genesis,
+      id,
+      0,
+      *validator,
+      weight,
+      &vec![vec![]; usize::from(weight)],
+    );
+    let i = spec.i(*validator).unwrap();
+    signing_set
+      .extend((u16::from(i.start) .. u16::from(i.end)).map(|i| Participant::new(i).unwrap()));
+  }
+  assert_eq!(signing_set.len(), usize::from(spec.t()));
+  TributaryDb::<MemDb>::set_signing_set(&mut txn, Zone::Sign.label(), genesis, id, 0, &signing_set);
+
+  // Have every member of the signing set other than the one before the last publish shares
+  let offline = validators[validators.len() - 2];
+  for validator in &validators[.. (validators.len() - 2)] {
+    let weight = spec.weight(*validator).unwrap();
+    TributaryDb::<MemDb>::set_data(
+      share_label,
+      &mut txn,
+      genesis,
+      id,
+      0,
+      *validator,
+      weight,
+      &vec![vec![]; usize::from(weight)],
+    );
+  }
+  assert_eq!(non_responsive::<MemDb, _>(&txn, &spec, Zone::Sign, id, 0), vec![offline]);
+
+  // Nothing should be re-attempted before the delay has passed
+  let delay = reattempt_delay::<MemDb, LocalP2p>();
+  assert!(reattempts::<MemDb, LocalP2p>(&mut txn, &spec, start + delay - 1).is_empty());
+  assert_eq!(TributaryDb::<MemDb>::attempt(&txn, genesis, id), 0);
+
+  // Once it has, the ID should be re-attempted with a signing set excluding the offline validator
+  let time = start + delay;
+  assert_eq!(reattempts::<MemDb, LocalP2p>(&mut txn, &spec, time), vec![(Zone::Sign, id, 1)]);
+  assert_eq!(TributaryDb::<MemDb>::attempt(&txn, genesis, id), 1);
+  assert_eq!(TributaryDb::<MemDb>::attempt_start(&txn, genesis, id), Some(time));
+  let signing_set =
+    TributaryDb::<MemDb>::signing_set(&txn, Zone::Sign.label(), genesis, id, 1).unwrap();
+  assert_eq!(signing_set, reattempt_signing_set(&spec, 1, &[offline]));
+  assert!(!signing_set.contains(&spec.i(offline).unwrap().start));
+  // The validator who didn't preprocess, yet is online, should now be included
+  assert!(signing_set.contains(&spec.i(validators[validators.len() - 1]).unwrap().start));
+
+  // The new attempt should have its own delay
+  assert!(reattempts::<MemDb, LocalP2p>(&mut txn, &spec, time + delay - 1).is_empty());
+
+  // Once the attempt completes, it should be recorded and no longer re-attempted
+  TributaryDb::<MemDb>::complete_attempt(&mut txn, Zone::Sign.label(), genesis, id, 1);
+  assert_eq!(
+    TributaryDb::<MemDb>::completed_attempt(&txn, Zone::Sign.label(), genesis, id),
+    Some(1)
+  );
+  assert!(TributaryDb::<MemDb>::active_ids(&txn, Zone::Sign.label(), genesis).is_empty());
+  assert!(reattempts::<MemDb, LocalP2p>(&mut txn, &spec, time + (2 * delay)).is_empty());
+  txn.commit();
+}
+
+// Publish preprocesses from the specified validators, forming the signing set for the first attempt
+fn publish_preprocesses(
+  txn: &mut <MemDb as Db>::Transaction<'_>,
+  spec: &TributarySpec,
+  zone: Zone,
+  id: [u8; 32],
+  validators: &[<Ristretto as Ciphersuite>::G],
+) {
+  let genesis = spec.genesis();
+  let mut signing_set = vec![];
+  for validator in validators {
+    let weight = spec.weight(*validator).unwrap();
+    TributaryDb::<MemDb>::set_data(
+      zone.data_labels().0,
+      txn,
+      genesis,
+      id,
+      0,
+      *validator,
+      weight,
+      &vec![vec![]; usize::from(weight)],
+    );
+    let i = spec.i(*validator).unwrap();
+    signing_set
+      .extend((u16::from(i.start) .. u16::from(i.end)).map(|i| Participant::new(i).unwrap()));
+  }
+  signing_set.sort();
+  TributaryDb::<MemDb>::set_signing_set(txn, zone.label(), genesis, id, 0, &signing_set);
+}
+
+// Scan an empty block with the specified time, returning the messages sent to the processor
+async fn scan(
+  db: &MemDb,
+  key: &Zeroizing<<Ristretto as Ciphersuite>::F>,
+  spec: &TributarySpec,
+  time: u64,
+) -> Vec<CoordinatorMessage> {
+  // Use a random parent so every block scanned is distinct
+  let mut parent = [0; 32];
+  OsRng.fill_bytes(&mut parent);
+  let block = Block { header: BlockHeader { parent, transactions: [0; 32] }, transactions: vec![] };
+
+  let processor = MemProcessor::new();
+  handle_block::<_, _, LocalP2p>(&mut TributaryDb(db.clone()), key, &processor, spec, block, time)
+    .await;
+  Arc::try_unwrap(processor.0).unwrap().into_inner().into()
+}
+
+#[tokio::test]
+async fn handle_block_reattempt_test() {
+  let keys = new_keys(&mut OsRng);
+  let spec = new_spec(&mut OsRng, &keys);
+  let genesis = spec.genesis();
+  let validators =
+    keys.iter().map(|key| <Ristretto as Ciphersuite>::generator() * **key).collect::<Vec<_>>();
+  let start = spec.start_time();
+
+  let mut substrate_key = [0; 32];
+  OsRng.fill_bytes(&mut substrate_key);
+  let mut network_key = vec![0; 33];
+  OsRng.fill_bytes(&mut network_key);
+
+  let batch = [0xaa; 32];
+  let plan = [0xbb; 32];
+  // Every validator but the last preprocesses, yet no shares are published
+  let online = &validators[.. (validators.len() - 1)];
+
+  // The re-attempt's signing set excludes every validator who didn't publish shares, unless that
+  // wouldn't leave a threshold, as here
+  let signing_set = reattempt_signing_set(&spec, 1, online);
+  assert_eq!(signing_set, reattempt_signing_set(&spec, 1, &[]));
+
+  for key in &keys {
+    // Each coordinator has its own DB
+    let mut db = MemDb::new();
+    let mut txn = db.txn();
+    // Confirm the set's key pair, which the processor is told to sign with
+    SubstrateDb::<MemDb>::save_key_pair(
+      &mut txn,
+      spec.set(),
+      &(sr25519::Public(substrate_key), network_key.clone().try_into().unwrap()),
+    );
+    for (zone, id) in [(Zone::Batch, batch), (Zone::Sign, plan)] {
+      TributaryDb::<MemDb>::recognize_id(&mut txn, zone.label(), genesis, id);
+      TributaryDb::<MemDb>::add_active_id(&mut txn, zone.label(), genesis, id);
+      TributaryDb::<MemDb>::set_attempt_start(&mut txn, genesis, id, start);
+      publish_preprocesses(&mut txn, &spec, zone, id, online);
+    }
+    txn.commit();
+
+    // Nothing should be re-attempted before the delay has passed
+    let delay = reattempt_delay::<MemDb, LocalP2p>();
+    assert!(scan(&db, key, &spec, start + delay - 1).await.is_empty());
+    assert_eq!(TributaryDb::<MemDb>::attempt(&db, genesis, batch), 0);
+    assert_eq!(TributaryDb::<MemDb>::attempt(&db, genesis, plan), 0);
+
+    // Once it has, members of the new signing set should have their processor re-attempt both,
+    // with the set's keys
+    let msgs = scan(&db, key, &spec, start + delay).await;
+    let i = spec.i(<Ristretto as Ciphersuite>::generator() * **key).unwrap();
+    if signing_set.contains(&i.start) {
+      assert_eq!(
+        msgs,
+        vec![
+          CoordinatorMessage::Coordinator(coordinator::CoordinatorMessage::BatchReattempt {
+            id: SignId { key: substrate_key.to_vec(), id: batch, attempt: 1 },
+          }),
+          CoordinatorMessage::Sign(sign::CoordinatorMessage::Reattempt {
+            id: SignId { key: network_key.clone(), id: plan, attempt: 1 },
+          }),
+        ]
+      );
+    } else {
+      assert!(msgs.is_empty());
+    }
+    assert_eq!(TributaryDb::<MemDb>::attempt(&db, genesis, batch), 1);
+    assert_eq!(TributaryDb::<MemDb>::attempt(&db, genesis, plan), 1);
+  }
+}
@@ -1,6 +1,7 @@
 use std::io::Read;
 
 use ciphersuite::{group::GroupEncoding, Ciphersuite, Ristretto};
+use frost::Participant;
 
 pub use serai_db::*;
 
@@ -80,6 +81,94 @@ impl<D: Db> TributaryDb<D> {
       getter.get(Self::attempt_key(genesis, id)).unwrap_or(vec![0; 4]).try_into().unwrap(),
     )
   }
+  pub fn set_attempt(txn: &mut D::Transaction<'_>, genesis: [u8; 32], id: [u8; 32], attempt: u32) {
+    txn.put(Self::attempt_key(genesis, id), attempt.to_le_bytes())
+  }
+
+  // The Tributary block time at which the current attempt started
+  fn attempt_start_key(genesis: [u8; 32], id: [u8; 32]) -> Vec<u8> {
+    let genesis_ref: &[u8] = genesis.as_ref();
+    Self::tributary_key(b"attempt_start", [genesis_ref, id.as_ref()].concat())
+  }
+  pub fn attempt_start<G: Get>(getter: &G, genesis: [u8; 32], id: [u8; 32]) -> Option<u64> {
+    getter
+      .get(Self::attempt_start_key(genesis, id))
+      .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+  }
+  pub fn set_attempt_start(
+    txn: &mut D::Transaction<'_>,
+    genesis: [u8; 32],
+    id: [u8; 32],
+    time: u64,
+  ) {
+    txn.put(Self::attempt_start_key(genesis, id), time.to_le_bytes())
+  }
+
+  // The IDs which are currently being signed for, and may need re-attempts
+  fn active_ids_key(label: &'static str, genesis: [u8; 32]) -> Vec<u8> {
+    Self::tributary_key(b"active_ids", [label.as_bytes(), genesis.as_ref()].concat())
+  }
+  pub fn active_ids<G: Get>(getter: &G, label: &'static str, genesis: [u8; 32]) -> Vec<[u8; 32]> {
+    getter
+      .get(Self::active_ids_key(label, genesis))
+      .map(|bytes| bytes.chunks(32).map(|id| id.try_into().unwrap()).collect())
+      .unwrap_or(vec![])
+  }
+  pub fn add_active_id(
+    txn: &mut D::Transaction<'_>,
+    label: &'static str,
+    genesis: [u8; 32],
+    id: [u8; 32],
+  ) {
+    let key = Self::active_ids_key(label, genesis);
+    let mut ids = txn.get(&key).unwrap_or(vec![]);
+    if ids.chunks(32).any(|existing| existing == id) {
+      return;
+    }
+    ids.extend(id);
+    txn.put(key, ids);
+  }
+  fn remove_active_id(
+    txn: &mut D::Transaction<'_>,
+    label: &'static str,
+    genesis: [u8; 32],
+    id: [u8; 32],
+  ) {
+    let key = Self::active_ids_key(label, genesis);
+    let ids = txn.get(&key).unwrap_or(vec![]);
+    txn.put(
+      key,
+      ids.chunks(32).filter(|existing| *existing != id).flatten().copied().collect::<Vec<_>>(),
+    );
+  }
+
+  fn completed_attempt_key(label: &'static str, genesis: [u8; 32], id: [u8; 32]) -> Vec<u8> {
+    Self::tributary_key(
+      b"completed_attempt",
+      [label.as_bytes(), genesis.as_ref(), id.as_ref()].concat(),
+    )
+  }
+  pub fn completed_attempt<G: Get>(
+    getter: &G,
+    label: &'static str,
+    genesis: [u8; 32],
+    id: [u8; 32],
+  ) -> Option<u32> {
+    getter
+      .get(Self::completed_attempt_key(label, genesis, id))
+      .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+  }
+  // Record which attempt completed, and stop re-attempting this ID
+  pub fn complete_attempt(
+    txn: &mut D::Transaction<'_>,
+    label: &'static str,
+    genesis: [u8; 32],
+    id: [u8; 32],
+    attempt: u32,
+  ) {
+    txn.put(Self::completed_attempt_key(label, genesis, id), attempt.to_le_bytes());
+    Self::remove_active_id(txn, label, genesis, id);
+  }
 
   fn data_received_key(
     label: &'static [u8],
@@ -118,9 +207,22 @@ impl<D: Db> TributaryDb<D> {
     id: [u8; 32],
     attempt: u32,
     signer: <Ristretto as Ciphersuite>::G,
-  ) -> Option<Vec<u8>> {
-    getter.get(Self::data_key(label, genesis, id, attempt, signer))
+  ) -> Option<Vec<Vec<u8>>> {
+    getter.get(Self::data_key(label, genesis, id, attempt, signer)).map(|bytes| {
+      let mut res = vec![];
+      let mut bytes_ref: &[u8] = bytes.as_ref();
+      while !bytes_ref.is_empty() {
+        let mut len = [0; 4];
+        bytes_ref.read_exact(&mut len).unwrap();
+        let mut data = vec![0; usize::try_from(u32::from_le_bytes(len)).unwrap()];
+        bytes_ref.read_exact(&mut data).unwrap();
+        res.push(data);
+      }
+      res
+    })
   }
+  // Returns the weight which has now published data for this attempt
+  #[allow(clippy::too_many_arguments)]
   pub fn set_data(
     label: &'static [u8],
     txn: &mut D::Transaction<'_>,
@@ -128,19 +230,92 @@ impl<D: Db> TributaryDb<D> {
     id: [u8; 32],
     attempt: u32,
     signer: <Ristretto as Ciphersuite>::G,
-    data: &[u8],
+    weight: u16,
+    data: &[Vec<u8>],
   ) -> u16 {
     let received_key = Self::data_received_key(label, genesis, id, attempt);
     let mut received =
       u16::from_le_bytes(txn.get(&received_key).unwrap_or(vec![0; 2]).try_into().unwrap());
-    received += 1;
+    received += weight;
+
+    let mut bytes = vec![];
+    for data in data {
+      bytes.extend(u32::try_from(data.len()).unwrap().to_le_bytes());
+      bytes.extend(data);
+    }
 
     txn.put(received_key, received.to_le_bytes());
-    txn.put(Self::data_key(label, genesis, id, attempt, signer), data);
+    txn.put(Self::data_key(label, genesis, id, attempt, signer), bytes);
 
     received
   }
 
+  fn signing_set_key(
+    label: &'static str,
+    genesis: [u8; 32],
+    id: [u8; 32],
+    attempt: u32,
+  ) -> Vec<u8> {
+    Self::tributary_key(
+      b"signing_set",
+      [label.as_bytes(), genesis.as_ref(), id.as_ref(), attempt.to_le_bytes().as_ref()].concat(),
+    )
+  }
+  pub fn signing_set<G: Get>(
+    getter: &G,
+    label: &'static str,
+    genesis: [u8; 32],
+    id: [u8; 32],
+    attempt: u32,
+  ) -> Option<Vec<Participant>> {
+    getter.get(Self::signing_set_key(label, genesis, id, attempt)).map(|bytes| {
+      bytes
+        .chunks(2)
+        .map(|i| Participant::new(u16::from_le_bytes(i.try_into().unwrap())).unwrap())
+        .collect()
+    })
+  }
+  pub fn set_signing_set(
+    txn: &mut D::Transaction<'_>,
+    label: &'static str,
+    genesis: [u8; 32],
+    id: [u8; 32],
+    attempt: u32,
+    signing_set: &[Participant],
+  ) {
+    txn.put(
+      Self::signing_set_key(label, genesis, id, attempt),
+      signing_set.iter().flat_map(Participant::to_bytes).collect::<Vec<_>>(),
+    );
+  }
+
+  fn fatal_slashes_key(genesis: [u8; 32]) -> Vec<u8> {
+    Self::tributary_key(b"fatal_slashes", genesis)
+  }
+  // The validators who published invalid data, and should be fully slashed
+  pub fn fatal_slashes<G: Get>(getter: &G, genesis: [u8; 32]) -> Vec<[u8; 32]> {
+    getter
+      .get(Self::fatal_slashes_key(genesis))
+      .unwrap_or(vec![])
+      .chunks(32)
+      .map(|validator| validator.try_into().unwrap())
+      .collect()
+  }
+  pub fn set_fatally_slashed(
+    txn: &mut D::Transaction<'_>,
+    genesis: [u8; 32],
+    validator: <Ristretto as Ciphersuite>::G,
+  ) {
+    let validator = validator.to_bytes();
+    let key = Self::fatal_slashes_key(genesis);
+    let mut slashes = txn.get(&key).unwrap_or(vec![]);
+    if slashes.chunks(32).any(|existing| existing == validator.as_ref()) {
+      return;
+    }
+    slashes.extend(validator);
+    txn.put(key, slashes);
+  }
+
   fn event_key(id: &[u8], index: u32) -> Vec<u8> {
     Self::tributary_key(b"event", [id, index.to_le_bytes().as_ref()].concat())
   }
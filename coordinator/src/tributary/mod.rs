@@ -1,8 +1,5 @@
-use core::ops::Deref;
-use std::{
-  io::{self, Read, Write},
-  collections::HashMap,
-};
+use core::ops::{Deref, Range};
+use std::io::{self, Read, Write};
 
 use zeroize::Zeroizing;
 use rand_core::{RngCore, CryptoRng};
@@ -81,30 +78,33 @@ impl TributarySpec {
     self.start_time
   }
 
+  // The amount of key shares, which is the sum of every validator's weight
   pub fn n(&self) -> u16 {
-    // TODO: Support multiple key shares
-    // self.validators.iter().map(|(_, weight)| u16::try_from(weight).unwrap()).sum()
-    self.validators().len().try_into().unwrap()
+    self.validators.iter().map(|(_, weight)| u16::try_from(*weight).unwrap()).sum()
   }
 
   pub fn t(&self) -> u16 {
     (2 * (self.n() / 3)) + 1
   }
 
-  pub fn i(&self, key: <Ristretto as Ciphersuite>::G) -> Option<Participant> {
+  // The key shares held by a validator, one per unit of weight
+  pub fn i(&self, key: <Ristretto as Ciphersuite>::G) -> Option<Range<Participant>> {
     let mut i = 1;
-    // TODO: Support multiple key shares
-    for (validator, _weight) in &self.validators {
+    for (validator, weight) in &self.validators {
+      let weight = u16::try_from(*weight).unwrap();
       if validator == &key {
-        // return (i .. (i + weight)).to_vec();
-        return Some(Participant::new(i).unwrap());
+        return Some(Participant::new(i).unwrap() .. Participant::new(i + weight).unwrap());
       }
-      // i += weight;
-      i += 1;
+      i += weight;
     }
     None
   }
 
+  // The weight of a validator, as the amount of key shares it holds
+  pub fn weight(&self, key: <Ristretto as Ciphersuite>::G) -> Option<u16> {
+    self.i(key).map(|range| u16::from(range.end) - u16::from(range.start))
+  }
+
   pub fn validators(&self) -> Vec<(<Ristretto as Ciphersuite>::G, u64)> {
     self.validators.clone()
   }
@@ -168,7 +168,8 @@ pub struct SignData {
   pub plan: [u8; 32],
   pub attempt: u32,
 
-  pub data: Vec<u8>,
+  // One piece of data per key share held by the signer
+  pub data: Vec<Vec<u8>>,
 
   pub signed: Signed,
 }
@@ -183,11 +184,20 @@ impl ReadWrite for SignData {
     let attempt = u32::from_le_bytes(attempt);
 
     let data = {
-      let mut data_len = [0; 2];
-      reader.read_exact(&mut data_len)?;
-      let mut data = vec![0; usize::from(u16::from_le_bytes(data_len))];
-      reader.read_exact(&mut data)?;
-      data
+      let mut data_pieces = [0];
+      reader.read_exact(&mut data_pieces)?;
+      if data_pieces[0] == 0 {
+        Err(io::Error::new(io::ErrorKind::Other, "zero pieces of data in SignData"))?;
+      }
+      let mut all_data = vec![];
+      for _ in 0 .. data_pieces[0] {
+        let mut data_len = [0; 2];
+        reader.read_exact(&mut data_len)?;
+        let mut data = vec![0; usize::from(u16::from_le_bytes(data_len))];
+        reader.read_exact(&mut data)?;
+        all_data.push(data);
+      }
+      all_data
     };
 
     let signed = Signed::read(reader)?;
@@ -199,16 +209,21 @@ impl ReadWrite for SignData {
     writer.write_all(&self.plan)?;
     writer.write_all(&self.attempt.to_le_bytes())?;
 
-    if self.data.len() > u16::MAX.into() {
-      // Currently, the largest sign item would be a Monero transaction
-      // It provides 4 commitments per input (128 bytes), a 64-byte proof for them, along with a
-      // key image and proof (96 bytes)
-      // Even with all of that, we could support 227 inputs in a single TX
-      // Monero is limited to 120 inputs per TX
-      Err(io::Error::new(io::ErrorKind::Other, "signing data exceeded 65535 bytes"))?;
+    // A validator with more than 255 key shares would have to be running multiple instances
+    writer.write_all(&[u8::try_from(self.data.len())
+      .map_err(|_| io::Error::new(io::ErrorKind::Other, "signing data had over 255 pieces"))?])?;
+    for data in &self.data {
+      if data.len() > u16::MAX.into() {
+        // Currently, the largest sign item would be a Monero transaction
+        // It provides 4 commitments per input (128 bytes), a 64-byte proof for them, along with a
+        // key image and proof (96 bytes)
+        // Even with all of that, we could support 227 inputs in a single TX
+        // Monero is limited to 120 inputs per TX
+        Err(io::Error::new(io::ErrorKind::Other, "signing data exceeded 65535 bytes"))?;
+      }
+      writer.write_all(&u16::try_from(data.len()).unwrap().to_le_bytes())?;
+      writer.write_all(data)?;
     }
-    writer.write_all(&u16::try_from(self.data.len()).unwrap().to_le_bytes())?;
-    writer.write_all(&self.data)?;
 
     self.signed.write(writer)
   }
@@ -217,8 +232,11 @@ impl ReadWrite for SignData {
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum Transaction {
   // Once this completes successfully, no more instances should be created.
-  DkgCommitments(u32, Vec<u8>, Signed),
-  DkgShares(u32, HashMap<Participant, Vec<u8>>, Signed),
+  // The commitments for each of the signer's key shares
+  DkgCommitments(u32, Vec<Vec<u8>>, Signed),
+  // For each of the signer's key shares, the secret shares for every other key share, ordered by
+  // the recipient's participant index
+  DkgShares(u32, Vec<Vec<Vec<u8>>>, Signed),
 
   // When an external block is finalized, we can allow the associated batch IDs
   // Commits to the full block so eclipsed nodes don't continue on their eclipsed state
@@ -246,11 +264,21 @@ impl ReadWrite for Transaction {
         let attempt = u32::from_le_bytes(attempt);
 
         let commitments = {
-          let mut commitments_len = [0; 2];
+          let mut commitments_len = [0];
           reader.read_exact(&mut commitments_len)?;
-          let mut commitments = vec![0; usize::from(u16::from_le_bytes(commitments_len))];
-          reader.read_exact(&mut commitments)?;
-          commitments
+          if commitments_len[0] == 0 {
+            Err(io::Error::new(io::ErrorKind::Other, "zero commitments in DkgCommitments"))?;
+          }
+
+          let mut all_commitments = vec![];
+          for _ in 0 .. commitments_len[0] {
+            let mut commitments_len = [0; 2];
+            reader.read_exact(&mut commitments_len)?;
+            let mut commitments = vec![0; usize::from(u16::from_le_bytes(commitments_len))];
+            reader.read_exact(&mut commitments)?;
+            all_commitments.push(commitments);
+          }
+          all_commitments
         };
 
         let signed = Signed::read(reader)?;
@@ -264,6 +292,12 @@ impl ReadWrite for Transaction {
         let attempt = u32::from_le_bytes(attempt);
 
         let shares = {
+          let mut share_senders = [0];
+          reader.read_exact(&mut share_senders)?;
+          if share_senders[0] == 0 {
+            Err(io::Error::new(io::ErrorKind::Other, "zero senders in DkgShares"))?;
+          }
+
           let mut share_quantity = [0; 2];
           reader.read_exact(&mut share_quantity)?;
 
@@ -271,14 +305,17 @@ impl ReadWrite for Transaction {
           reader.read_exact(&mut share_len)?;
           let share_len = usize::from(u16::from_le_bytes(share_len));
 
-          let mut shares = HashMap::new();
-          for i in 0 .. u16::from_le_bytes(share_quantity) {
-            let participant = Participant::new(i + 1).unwrap();
-            let mut share = vec![0; share_len];
-            reader.read_exact(&mut share)?;
-            shares.insert(participant, share);
+          let mut all_shares = vec![];
+          for _ in 0 .. share_senders[0] {
+            let mut shares = vec![];
+            for _ in 0 .. u16::from_le_bytes(share_quantity) {
+              let mut share = vec![0; share_len];
+              reader.read_exact(&mut share)?;
+              shares.push(share);
+            }
+            all_shares.push(shares);
           }
-          shares
+          all_shares
         };
 
         let signed = Signed::read(reader)?;
@@ -313,38 +350,66 @@ impl ReadWrite for Transaction {
       Transaction::DkgCommitments(attempt, commitments, signed) => {
         writer.write_all(&[0])?;
         writer.write_all(&attempt.to_le_bytes())?;
-        if commitments.len() > u16::MAX.into() {
-          // t commitments and an encryption key mean a u16 is fine until a threshold > 2000 occurs
-          Err(io::Error::new(io::ErrorKind::Other, "dkg commitments exceeded 65535 bytes"))?;
+        writer.write_all(&[u8::try_from(commitments.len()).map_err(|_| {
+          io::Error::new(io::ErrorKind::Other, "dkg commitments had over 255 key shares")
+        })?])?;
+        for commitments in commitments {
+          if commitments.len() > u16::MAX.into() {
+            // t commitments and an encryption key mean a u16 is fine until a threshold > 2000
+            // occurs
+            Err(io::Error::new(io::ErrorKind::Other, "dkg commitments exceeded 65535 bytes"))?;
+          }
+          writer.write_all(&u16::try_from(commitments.len()).unwrap().to_le_bytes())?;
+          writer.write_all(commitments)?;
         }
-        writer.write_all(&u16::try_from(commitments.len()).unwrap().to_le_bytes())?;
-        writer.write_all(commitments)?;
         signed.write(writer)
       }
 
       Transaction::DkgShares(attempt, shares, signed) => {
         writer.write_all(&[1])?;
         writer.write_all(&attempt.to_le_bytes())?;
-        // Shares are indexed by non-zero u16s (Participants), so this can't fail
-        writer.write_all(&u16::try_from(shares.len()).unwrap().to_le_bytes())?;
+        writer.write_all(&[u8::try_from(shares.len()).map_err(|_| {
+          io::Error::new(io::ErrorKind::Other, "dkg shares had over 255 key shares")
+        })?])?;
+
+        let mut share_quantity = None;
         let mut share_len = None;
-        for participant in 0 .. shares.len() {
-          let share = &shares[&Participant::new(u16::try_from(participant + 1).unwrap()).unwrap()];
-          if let Some(share_len) = share_len {
-            if share.len() != share_len {
-              panic!("variable length shares");
+        for shares in shares {
+          if let Some(share_quantity) = share_quantity {
+            if shares.len() != share_quantity {
+              panic!("variable amount of shares");
             }
           } else {
-            // For BLS12-381 G2, this would be:
-            // - A 32-byte share
-            // - A 96-byte ephemeral key
-            // - A 128-byte signature
-            // Hence why this has to be u16
-            writer.write_all(&u16::try_from(share.len()).unwrap().to_le_bytes())?;
-            share_len = Some(share.len());
+            // Shares are indexed by non-zero u16s (Participants), so this can't fail
+            writer.write_all(&u16::try_from(shares.len()).unwrap().to_le_bytes())?;
+            share_quantity = Some(shares.len());
+          }
+
+          for share in shares {
+            if let Some(share_len) = share_len {
+              if share.len() != share_len {
+                panic!("variable length shares");
+              }
+            } else {
+              // For BLS12-381 G2, this would be:
+              // - A 32-byte share
+              // - A 96-byte ephemeral key
+              // - A 128-byte signature
+              // Hence why this has to be u16
+              writer.write_all(&u16::try_from(share.len()).unwrap().to_le_bytes())?;
+              share_len = Some(share.len());
+            }
+
+            writer.write_all(share)?;
           }
+        }
 
-          writer.write_all(share)?;
+        // If no shares were written, write the amount and length as 0 so this is still readable
+        if share_quantity.is_none() {
+          writer.write_all(&[0; 2])?;
+        }
+        if share_len.is_none() {
+          writer.write_all(&[0; 2])?;
         }
         signed.write(writer)
       }
@@ -408,8 +473,10 @@ impl TransactionTrait for Transaction {
 
   fn verify(&self) -> Result<(), TransactionError> {
     if let Transaction::BatchShare(data) = self {
-      if data.data.len() != 32 {
-        Err(TransactionError::InvalidContent)?;
+      for data in &data.data {
+        if data.len() != 32 {
+          Err(TransactionError::InvalidContent)?;
+        }
       }
     }
 
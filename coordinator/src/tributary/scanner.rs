@@ -1,11 +1,12 @@
 use core::ops::Deref;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use zeroize::Zeroizing;
 
-use ciphersuite::{Ciphersuite, Ristretto};
+use ciphersuite::{group::GroupEncoding, Ciphersuite, Ristretto};
+use frost::Participant;
 
-use tributary::{Signed, Block, TributaryReader};
+use tributary::{Signed, Block, TributaryReader, Tributary};
 
 use processor_messages::{
   key_gen::{self, KeyGenId},
@@ -13,128 +14,387 @@ use processor_messages::{
   coordinator, CoordinatorMessage,
 };
 
-use serai_db::DbTxn;
+use serai_db::{Get, DbTxn};
 
 use crate::{
   Db,
+  p2p::P2p,
   processor::Processor,
+  substrate::SubstrateDb,
   tributary::{TributaryDb, TributarySpec, Transaction},
 };
 
+// The amount of Tributary blocks an attempt has to complete before it's re-attempted
+pub(crate) const REATTEMPT_DELAY_BLOCKS: u64 = 50;
+
+// The amount of Tributary time, in seconds, an attempt has to complete before it's re-attempted
+pub(crate) fn reattempt_delay<D: Db, P: P2p>() -> u64 {
+  REATTEMPT_DELAY_BLOCKS * u64::from(Tributary::<D, Transaction, P>::block_time())
+}
+
+// Used to determine if an ID is acceptable
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Zone {
+  Dkg,
+  Batch,
+  Sign,
+}
+
+impl Zone {
+  pub(crate) fn label(&self) -> &'static str {
+    match self {
+      Zone::Dkg => {
+        panic!("getting the label for dkg despite dkg code paths not needing a label")
+      }
+      Zone::Batch => "batch",
+      Zone::Sign => "sign",
+    }
+  }
+
+  // The labels preprocesses and shares are stored under
+  pub(crate) fn data_labels(&self) -> (&'static [u8], &'static [u8]) {
+    match self {
+      Zone::Dkg => panic!("getting the data labels for dkg despite dkg not being re-attempted"),
+      Zone::Batch => (b"batch_preprocess", b"batch_share"),
+      Zone::Sign => (b"sign_preprocess", b"sign_share"),
+    }
+  }
+
+  // The key the processor signs with for this zone
+  pub(crate) fn key<D: Db, G: Get>(&self, getter: &G, spec: &TributarySpec) -> Vec<u8> {
+    let (substrate_key, network_key) = SubstrateDb::<D>::key_pair(getter, spec.set())
+      .expect("signing for a set whose key pair wasn't confirmed");
+    match self {
+      Zone::Dkg => panic!("getting the key for dkg despite dkg not signing with a key"),
+      // Batches are signed by the Substrate key
+      Zone::Batch => substrate_key.0.to_vec(),
+      Zone::Sign => network_key.to_vec(),
+    }
+  }
+}
+
+// Deterministically select the signing set for a re-attempt
+// Validators who didn't respond during the prior attempt are excluded, unless that'd leave less
+// than a threshold of key shares. The validators are then rotated by the attempt number, so every
+// re-attempt tries a distinct set
+pub(crate) fn reattempt_signing_set(
+  spec: &TributarySpec,
+  attempt: u32,
+  non_responsive: &[<Ristretto as Ciphersuite>::G],
+) -> Vec<Participant> {
+  let validators =
+    spec.validators().into_iter().map(|(validator, _)| validator).collect::<Vec<_>>();
+  let weight = |validators: &[<Ristretto as Ciphersuite>::G]| {
+    validators.iter().map(|validator| spec.weight(*validator).unwrap()).sum::<u16>()
+  };
+
+  let mut candidates = validators
+    .iter()
+    .copied()
+    .filter(|validator| !non_responsive.contains(validator))
+    .collect::<Vec<_>>();
+  if weight(&candidates) < spec.t() {
+    candidates = validators;
+  }
+
+  let start = usize::try_from(attempt).unwrap() % candidates.len();
+  let mut signing_set = vec![];
+  let mut selected = vec![];
+  for validator in candidates[start ..].iter().chain(&candidates[.. start]) {
+    if weight(&selected) >= spec.t() {
+      break;
+    }
+    selected.push(*validator);
+    let i = spec.i(*validator).unwrap();
+    signing_set
+      .extend((u16::from(i.start) .. u16::from(i.end)).map(|i| Participant::new(i).unwrap()));
+  }
+  signing_set.sort();
+  signing_set
+}
+
+// The validators who didn't publish the data expected of them during an attempt
+pub(crate) fn non_responsive<D: Db, G: Get>(
+  getter: &G,
+  spec: &TributarySpec,
+  zone: Zone,
+  id: [u8; 32],
+  attempt: u32,
+) -> Vec<<Ristretto as Ciphersuite>::G> {
+  let genesis = spec.genesis();
+  let (preprocess_label, share_label) = zone.data_labels();
+  let signing_set = TributaryDb::<D>::signing_set(getter, zone.label(), genesis, id, attempt);
+  spec
+    .validators()
+    .into_iter()
+    .map(|(validator, _)| validator)
+    .filter(|validator| match &signing_set {
+      // Once a signing set exists, solely its members were expected to respond, with shares
+      Some(signing_set) => {
+        signing_set.contains(&spec.i(*validator).unwrap().start) &&
+          TributaryDb::<D>::data(share_label, getter, genesis, id, attempt, *validator).is_none()
+      }
+      // Without a signing set, every validator was expected to publish a preprocess
+      None => {
+        TributaryDb::<D>::data(preprocess_label, getter, genesis, id, attempt, *validator).is_none()
+      }
+    })
+    .collect()
+}
+
+// Re-attempt every active ID whose current attempt has exceeded the re-attempt delay as of the
+// specified Tributary block time, returning the IDs re-attempted along with their new attempts
+pub(crate) fn reattempts<D: Db, P: P2p>(
+  txn: &mut D::Transaction<'_>,
+  spec: &TributarySpec,
+  time: u64,
+) -> Vec<(Zone, [u8; 32], u32)> {
+  let genesis = spec.genesis();
+  let mut res = vec![];
+  for zone in [Zone::Batch, Zone::Sign] {
+    for id in TributaryDb::<D>::active_ids(txn, zone.label(), genesis) {
+      let attempt = TributaryDb::<D>::attempt(txn, genesis, id);
+      let start = TributaryDb::<D>::attempt_start(txn, genesis, id)
+        .expect("active ID didn't have its attempt's start time set");
+      if time < (start + reattempt_delay::<D, P>()) {
+        continue;
+      }
+
+      let non_responsive = non_responsive::<D, _>(txn, spec, zone, id, attempt);
+      let attempt = attempt + 1;
+      TributaryDb::<D>::set_attempt(txn, genesis, id, attempt);
+      TributaryDb::<D>::set_attempt_start(txn, genesis, id, time);
+      TributaryDb::<D>::set_signing_set(
+        txn,
+        zone.label(),
+        genesis,
+        id,
+        attempt,
+        &reattempt_signing_set(spec, attempt, &non_responsive),
+      );
+      res.push((zone, id, attempt));
+    }
+  }
+  res
+}
+
+// Fully slash a validator for publishing invalid data
+fn fatal_slash<D: Db>(
+  txn: &mut D::Transaction<'_>,
+  genesis: [u8; 32],
+  validator: <Ristretto as Ciphersuite>::G,
+  reason: &str,
+) {
+  log::warn!("fatally slashing validator {:?}: {reason}", validator.to_bytes());
+  TributaryDb::<D>::set_fatally_slashed(txn, genesis, validator);
+}
+
 // Handle a specific Tributary block
-async fn handle_block<D: Db, Pro: Processor>(
+pub(crate) async fn handle_block<D: Db, Pro: Processor, P: P2p>(
   db: &mut TributaryDb<D>,
   key: &Zeroizing<<Ristretto as Ciphersuite>::F>,
   processor: &Pro,
   spec: &TributarySpec,
   block: Block<Transaction>,
+  time: u64,
 ) {
   let genesis = spec.genesis();
   let hash = block.hash();
 
+  let our_i =
+    spec.i(Ristretto::generator() * key.deref()).expect("in a tributary we're not a validator for");
+
   let mut event_id = 0;
   #[allow(clippy::explicit_counter_loop)] // event_id isn't TX index. It just currently lines up
   for tx in block.transactions {
     if !TributaryDb::<D>::handled_event(&db.0, hash, event_id) {
       let mut txn = db.0.txn();
 
-      // Used to determine if an ID is acceptable
+      // The weight which must have published data before it's sent to the processor
       #[derive(Clone, Copy, PartialEq, Eq, Debug)]
-      enum Zone {
-        Dkg,
-        Batch,
-        Sign,
+      enum Needed {
+        // Every key share, as needed by the DKG
+        All,
+        // A threshold of key shares, whose holders become this attempt's signing set
+        Threshold,
+        // Every key share within this attempt's signing set
+        SigningSet,
       }
 
-      impl Zone {
-        fn label(&self) -> &'static str {
-          match self {
-            Zone::Dkg => {
-              panic!("getting the label for dkg despite dkg code paths not needing a label")
-            }
-            Zone::Batch => "batch",
-            Zone::Sign => "sign",
+      let mut handle =
+        |zone: Zone, label, needed: Needed, id, attempt, mut data: Vec<Vec<u8>>, signed: Signed| {
+          if zone == Zone::Dkg {
+            // Since Dkg doesn't have an ID, solely attempts, this should just be [0; 32]
+            assert_eq!(id, [0; 32], "DKG, which shouldn't have IDs, had a non-0 ID");
+          } else if !TributaryDb::<D>::recognized_id(&txn, zone.label(), genesis, id) {
+            fatal_slash::<D>(
+              &mut txn,
+              genesis,
+              signed.signer,
+              "published data for an unrecognized ID",
+            );
+            return None;
           }
-        }
-      }
 
-      let mut handle = |zone: Zone,
-                        label,
-                        needed,
-                        id,
-                        attempt,
-                        mut bytes: Vec<u8>,
-                        signed: Signed| {
-        if zone == Zone::Dkg {
-          // Since Dkg doesn't have an ID, solely attempts, this should just be [0; 32]
-          assert_eq!(id, [0; 32], "DKG, which shouldn't have IDs, had a non-0 ID");
-        } else if !TributaryDb::<D>::recognized_id(&txn, zone.label(), genesis, id) {
-          // TODO: Full slash
-          todo!();
-        }
-
-        // If they've already published a TX for this attempt, slash
-        if let Some(data) = TributaryDb::<D>::data(label, &txn, genesis, id, attempt, signed.signer)
-        {
-          if data != bytes {
-            // TODO: Full slash
-            todo!();
+          // Tributary only accepts signed transactions from validators
+          let signer_i = spec.i(signed.signer).expect("signed transaction from a non-validator");
+          let weight = u16::from(signer_i.end) - u16::from(signer_i.start);
+          // Every key share must have data
+          if data.len() != usize::from(weight) {
+            fatal_slash::<D>(
+              &mut txn,
+              genesis,
+              signed.signer,
+              "published data for an amount of key shares other than their own",
+            );
+            return None;
           }
 
-          // TODO: Slash
-          return None;
-        }
+          // If they've already published a TX for this attempt, slash
+          if let Some(existing) =
+            TributaryDb::<D>::data(label, &txn, genesis, id, attempt, signed.signer)
+          {
+            if existing != data {
+              fatal_slash::<D>(&mut txn, genesis, signed.signer, "published conflicting data");
+            }
 
-        // If the attempt is lesser than the blockchain's, slash
-        let curr_attempt = TributaryDb::<D>::attempt(&txn, genesis, id);
-        if attempt < curr_attempt {
-          // TODO: Slash for being late
-          return None;
-        }
-        if attempt > curr_attempt {
-          // TODO: Full slash
-          todo!();
-        }
+            // TODO: Slash
+            return None;
+          }
 
-        // TODO: We can also full slash if shares before all commitments, or share before the
-        // necessary preprocesses
+          // If the attempt is lesser than the blockchain's, slash
+          let curr_attempt = TributaryDb::<D>::attempt(&txn, genesis, id);
+          if attempt < curr_attempt {
+            // TODO: Slash for being late
+            return None;
+          }
+          if attempt > curr_attempt {
+            fatal_slash::<D>(&mut txn, genesis, signed.signer, "published a future attempt's data");
+            return None;
+          }
 
-        // TODO: If this is shares, we need to check they are part of the selected signing set
+          // TODO: We can also full slash if shares before all commitments
 
-        // Store this data
-        let received =
-          TributaryDb::<D>::set_data(label, &mut txn, genesis, id, attempt, signed.signer, &bytes);
+          let signing_set = match needed {
+            Needed::All => None,
+            // Re-attempts have their signing set selected in advance
+            Needed::Threshold => {
+              TributaryDb::<D>::signing_set(&txn, zone.label(), genesis, id, attempt)
+            }
+            Needed::SigningSet => {
+              let Some(signing_set) =
+                TributaryDb::<D>::signing_set(&txn, zone.label(), genesis, id, attempt)
+              else {
+                fatal_slash::<D>(
+                  &mut txn,
+                  genesis,
+                  signed.signer,
+                  "published shares before the necessary preprocesses",
+                );
+                return None;
+              };
+              Some(signing_set)
+            }
+          };
+          if let Some(signing_set) = &signing_set {
+            // Only the signing set should publish data
+            if !signing_set.contains(&signer_i.start) {
+              // TODO: Slash
+              return None;
+            }
+          }
+          let needed_weight = match (needed, &signing_set) {
+            (Needed::All, _) => spec.n(),
+            (_, Some(signing_set)) => u16::try_from(signing_set.len()).unwrap(),
+            (_, None) => spec.t(),
+          };
+
+          // Store this data
+          let received = TributaryDb::<D>::set_data(
+            label,
+            &mut txn,
+            genesis,
+            id,
+            attempt,
+            signed.signer,
+            weight,
+            &data,
+          );
 
-        // If we have all the needed commitments/preprocesses/shares, tell the processor
-        // TODO: This needs to be coded by weight, not by validator count
-        if received == needed {
-          let mut data = HashMap::new();
-          for validator in spec.validators().iter().map(|validator| validator.0) {
-            data.insert(
-              spec.i(validator).unwrap(),
-              if validator == signed.signer {
-                bytes.split_off(0)
+          // If this pushed us to having all the needed commitments/preprocesses/shares, tell the
+          // processor
+          // Since validators have varying weights, the threshold may be exceeded, not exactly met
+          // Any further data after this will be ignored
+          if (received >= needed_weight) && ((received - weight) < needed_weight) {
+            let mut res = BTreeMap::new();
+            for validator in spec.validators().iter().map(|validator| validator.0) {
+              let validator_i = spec.i(validator).unwrap();
+              if let Some(signing_set) = &signing_set {
+                if !signing_set.contains(&validator_i.start) {
+                  continue;
+                }
+              }
+
+              let validator_data = if validator == signed.signer {
+                data.split_off(0)
               } else if let Some(data) =
                 TributaryDb::<D>::data(label, &txn, genesis, id, attempt, validator)
               {
                 data
               } else {
                 continue;
-              },
-            );
-          }
-          assert_eq!(data.len(), usize::from(needed));
+              };
 
-          return Some(data);
-        }
-        None
-      };
+              for (i, data) in
+                (u16::from(validator_i.start) .. u16::from(validator_i.end)).zip(validator_data)
+              {
+                res.insert(Participant::new(i).unwrap(), data);
+              }
+            }
+            assert_eq!(res.len(), usize::from(received));
+
+            // If this was the threshold, the holders of these key shares form the signing set
+            if zone != Zone::Dkg {
+              if signing_set.is_none() {
+                let mut signing_set = res.keys().copied().collect::<Vec<_>>();
+                signing_set.sort();
+                TributaryDb::<D>::set_signing_set(
+                  &mut txn,
+                  zone.label(),
+                  genesis,
+                  id,
+                  attempt,
+                  &signing_set,
+                );
+              }
+
+              // Once the entire signing set has published shares, this attempt will complete
+              if needed == Needed::SigningSet {
+                TributaryDb::<D>::complete_attempt(&mut txn, zone.label(), genesis, id, attempt);
+              }
+
+              // If we weren't selected to sign, there's nothing for the processor to do
+              if !res.contains_key(&our_i.start) {
+                return None;
+              }
+            }
+
+            return Some(res);
+          }
+          None
+        };
 
       match tx {
-        Transaction::DkgCommitments(attempt, bytes, signed) => {
-          if let Some(commitments) =
-            handle(Zone::Dkg, b"dkg_commitments", spec.n(), [0; 32], attempt, bytes, signed)
-          {
+        Transaction::DkgCommitments(attempt, commitments, signed) => {
+          if let Some(commitments) = handle(
+            Zone::Dkg,
+            b"dkg_commitments",
+            Needed::All,
+            [0; 32],
+            attempt,
+            commitments,
+            signed,
+          ) {
             processor
               .send(CoordinatorMessage::KeyGen(key_gen::CoordinatorMessage::Commitments {
                 id: KeyGenId { set: spec.set(), attempt },
@@ -144,29 +404,68 @@ async fn handle_block<D: Db, Pro: Processor>(
           }
         }
 
-        Transaction::DkgShares(attempt, mut shares, signed) => {
-          if shares.len() != usize::from(spec.n()) {
-            // TODO: Full slash
-            todo!();
-          }
-
-          let bytes = shares
-            .remove(
-              &spec
-                .i(Ristretto::generator() * key.deref())
-                .expect("in a tributary we're not a validator for"),
-            )
-            .unwrap();
-
-          if let Some(shares) =
-            handle(Zone::Dkg, b"dkg_shares", spec.n(), [0; 32], attempt, bytes, signed)
+        Transaction::DkgShares(attempt, shares, signed) => {
+          let sender_i = spec.i(signed.signer).expect("signed transaction from a non-validator");
+          let sender_weight = usize::from(u16::from(sender_i.end) - u16::from(sender_i.start));
+          // Every key share sends a share to every key share other than itself
+          if (shares.len() != sender_weight) ||
+            shares.iter().any(|shares| shares.len() != usize::from(spec.n() - 1))
           {
-            processor
-              .send(CoordinatorMessage::KeyGen(key_gen::CoordinatorMessage::Shares {
-                id: KeyGenId { set: spec.set(), attempt },
-                shares,
-              }))
-              .await;
+            fatal_slash::<D>(
+              &mut txn,
+              genesis,
+              signed.signer,
+              "published an invalid amount of DKG shares",
+            );
+          } else {
+            // Extract the shares meant for our key shares, concatenated in the order of our key
+            // shares
+            let bytes = shares
+              .into_iter()
+              .enumerate()
+              .map(|(s, mut shares)| {
+                let sender = u16::from(sender_i.start) + u16::try_from(s).unwrap();
+                let mut our_shares = vec![];
+                for i in u16::from(our_i.start) .. u16::from(our_i.end) {
+                  if i == sender {
+                    continue;
+                  }
+                  // The sender's shares skip itself
+                  let index = if i < sender { i - 1 } else { i - 2 };
+                  our_shares.extend(shares[usize::from(index)].split_off(0));
+                }
+                our_shares
+              })
+              .collect();
+
+            if let Some(shares) =
+              handle(Zone::Dkg, b"dkg_shares", Needed::All, [0; 32], attempt, bytes, signed)
+            {
+              // Split the received shares into a map per our key shares
+              let mut our_shares =
+                vec![BTreeMap::new(); usize::from(u16::from(our_i.end) - u16::from(our_i.start))];
+              for (sender, bytes) in shares {
+                let recipients = (u16::from(our_i.start) .. u16::from(our_i.end))
+                  .filter(|i| *i != u16::from(sender))
+                  .collect::<Vec<_>>();
+                if recipients.is_empty() {
+                  continue;
+                }
+
+                let share_len = bytes.len() / recipients.len();
+                for (r, i) in recipients.into_iter().enumerate() {
+                  our_shares[usize::from(i - u16::from(our_i.start))]
+                    .insert(sender, bytes[(r * share_len) .. ((r + 1) * share_len)].to_vec());
+                }
+              }
+
+              processor
+                .send(CoordinatorMessage::KeyGen(key_gen::CoordinatorMessage::Shares {
+                  id: KeyGenId { set: spec.set(), attempt },
+                  shares: our_shares,
+                }))
+                .await;
+            }
           }
         }
 
@@ -182,6 +481,8 @@ async fn handle_block<D: Db, Pro: Processor>(
           );
 
           TributaryDb::<D>::recognize_id(&mut txn, Zone::Batch.label(), genesis, batch_id);
+          TributaryDb::<D>::add_active_id(&mut txn, Zone::Batch.label(), genesis, batch_id);
+          TributaryDb::<D>::set_attempt_start(&mut txn, genesis, batch_id, time);
         }
 
         Transaction::SubstrateBlock(block) => {
@@ -192,6 +493,8 @@ async fn handle_block<D: Db, Pro: Processor>(
 
           for id in plan_ids {
             TributaryDb::<D>::recognize_id(&mut txn, Zone::Sign.label(), genesis, id);
+            TributaryDb::<D>::add_active_id(&mut txn, Zone::Sign.label(), genesis, id);
+            TributaryDb::<D>::set_attempt_start(&mut txn, genesis, id, time);
           }
         }
 
@@ -199,7 +502,7 @@ async fn handle_block<D: Db, Pro: Processor>(
           if let Some(preprocesses) = handle(
             Zone::Batch,
             b"batch_preprocess",
-            spec.t(),
+            Needed::Threshold,
             data.plan,
             data.attempt,
             data.data,
@@ -208,7 +511,11 @@ async fn handle_block<D: Db, Pro: Processor>(
             processor
               .send(CoordinatorMessage::Coordinator(
                 coordinator::CoordinatorMessage::BatchPreprocesses {
-                  id: SignId { key: todo!(), id: data.plan, attempt: data.attempt },
+                  id: SignId {
+                    key: Zone::Batch.key::<D, _>(&txn, spec),
+                    id: data.plan,
+                    attempt: data.attempt,
+                  },
                   preprocesses,
                 },
               ))
@@ -219,7 +526,7 @@ async fn handle_block<D: Db, Pro: Processor>(
           if let Some(shares) = handle(
             Zone::Batch,
             b"batch_share",
-            spec.t(),
+            Needed::SigningSet,
             data.plan,
             data.attempt,
             data.data,
@@ -227,9 +534,13 @@ async fn handle_block<D: Db, Pro: Processor>(
           ) {
             processor
               .send(CoordinatorMessage::Coordinator(coordinator::CoordinatorMessage::BatchShares {
-                id: SignId { key: todo!(), id: data.plan, attempt: data.attempt },
+                id: SignId {
+                  key: Zone::Batch.key::<D, _>(&txn, spec),
+                  id: data.plan,
+                  attempt: data.attempt,
+                },
                 shares: shares
-                  .drain()
+                  .into_iter()
                   .map(|(validator, share)| (validator, share.try_into().unwrap()))
                   .collect(),
               }))
@@ -241,7 +552,7 @@ async fn handle_block<D: Db, Pro: Processor>(
           if let Some(preprocesses) = handle(
             Zone::Sign,
             b"sign_preprocess",
-            spec.t(),
+            Needed::Threshold,
             data.plan,
             data.attempt,
             data.data,
@@ -249,7 +560,11 @@ async fn handle_block<D: Db, Pro: Processor>(
           ) {
             processor
               .send(CoordinatorMessage::Sign(sign::CoordinatorMessage::Preprocesses {
-                id: SignId { key: todo!(), id: data.plan, attempt: data.attempt },
+                id: SignId {
+                  key: Zone::Sign.key::<D, _>(&txn, spec),
+                  id: data.plan,
+                  attempt: data.attempt,
+                },
                 preprocesses,
               }))
               .await;
@@ -259,7 +574,7 @@ async fn handle_block<D: Db, Pro: Processor>(
           if let Some(shares) = handle(
             Zone::Sign,
             b"sign_share",
-            spec.t(),
+            Needed::SigningSet,
             data.plan,
             data.attempt,
             data.data,
@@ -267,7 +582,11 @@ async fn handle_block<D: Db, Pro: Processor>(
           ) {
             processor
               .send(CoordinatorMessage::Sign(sign::CoordinatorMessage::Shares {
-                id: SignId { key: todo!(), id: data.plan, attempt: data.attempt },
+                id: SignId {
+                  key: Zone::Sign.key::<D, _>(&txn, spec),
+                  id: data.plan,
+                  attempt: data.attempt,
+                },
                 shares,
               }))
               .await;
@@ -281,10 +600,34 @@ async fn handle_block<D: Db, Pro: Processor>(
     event_id += 1;
   }
 
-  // TODO: Trigger any necessary re-attempts
+  // Trigger any necessary re-attempts
+  if !TributaryDb::<D>::handled_event(&db.0, hash, event_id) {
+    let mut txn = db.0.txn();
+    for (zone, id, attempt) in reattempts::<D, P>(&mut txn, spec, time) {
+      // If we weren't selected for this attempt, there's nothing for the processor to do
+      let signing_set = TributaryDb::<D>::signing_set(&txn, zone.label(), genesis, id, attempt)
+        .expect("re-attempt didn't have a signing set");
+      if !signing_set.contains(&our_i.start) {
+        continue;
+      }
+
+      let id = SignId { key: zone.key::<D, _>(&txn, spec), id, attempt };
+      processor
+        .send(match zone {
+          Zone::Dkg => unreachable!("re-attempting a DKG"),
+          Zone::Batch => {
+            CoordinatorMessage::Coordinator(coordinator::CoordinatorMessage::BatchReattempt { id })
+          }
+          Zone::Sign => CoordinatorMessage::Sign(sign::CoordinatorMessage::Reattempt { id }),
+        })
+        .await;
+    }
+    TributaryDb::<D>::handle_event(&mut txn, hash, event_id);
+    txn.commit();
+  }
 }
 
-pub async fn handle_new_blocks<D: Db, Pro: Processor>(
+pub async fn handle_new_blocks<D: Db, Pro: Processor, P: P2p>(
   db: &mut TributaryDb<D>,
   key: &Zeroizing<<Ristretto as Ciphersuite>::F>,
   processor: &Pro,
@@ -295,7 +638,8 @@ pub async fn handle_new_blocks<D: Db, Pro: Processor>(
   let mut last_block = db.last_block(genesis);
   while let Some(next) = tributary.block_after(&last_block) {
     let block = tributary.block(&next).unwrap();
-    handle_block(db, key, processor, spec, block).await;
+    let time = tributary.time_of_block(&next).unwrap();
+    handle_block::<_, _, P>(db, key, processor, spec, block, time).await;
     last_block = next;
     db.set_last_block(genesis, next);
   }
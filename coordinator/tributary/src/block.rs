@@ -8,6 +8,7 @@ use thiserror::Error;
 use blake2::{Digest, Blake2s256};
 
 use ciphersuite::{Ciphersuite, Ristretto};
+use multiexp::BatchVerifier;
 
 #[derive(Clone, PartialEq, Eq, Debug, Error)]
 pub enum BlockError {
@@ -32,11 +33,14 @@ pub enum BlockError {
   /// An included transaction was invalid.
   #[error("included transaction had an error")]
   TransactionError(TransactionError),
+  /// An included transaction had an invalid signature.
+  #[error("included transaction had an invalid signature: {0:?}")]
+  InvalidSignature([u8; 32]),
 }
 
 use crate::{
   BLOCK_SIZE_LIMIT, ReadWrite, TransactionError, Signed, TransactionKind, Transaction, merkle,
-  verify_transaction,
+  batch_verify_transaction,
 };
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -163,6 +167,7 @@ impl<T: Transaction> Block<T> {
 
     let mut found_non_provided = false;
     let mut txs = Vec::with_capacity(self.transactions.len());
+    let mut batch = BatchVerifier::new(self.transactions.len());
     for tx in self.transactions.iter() {
       txs.push(tx.hash());
 
@@ -185,12 +190,15 @@ impl<T: Transaction> Block<T> {
       }
 
       found_non_provided = true;
-      match verify_transaction(tx, genesis, &mut next_nonces) {
+      match batch_verify_transaction(tx, genesis, &mut next_nonces, &mut batch) {
         Ok(()) => {}
         Err(e) => Err(BlockError::TransactionError(e))?,
       }
     }
 
+    // Verify every signature in a single multiexp, only searching for the invalid one on failure
+    batch.verify_with_vartime_blame().map_err(BlockError::InvalidSignature)?;
+
     if merkle(&txs) != self.header.transactions {
       Err(BlockError::InvalidTransactions)?;
     }
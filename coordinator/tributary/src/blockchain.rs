@@ -20,6 +20,9 @@ pub(crate) struct Blockchain<D: Db, T: Transaction> {
 
   provided: ProvidedTransactions<D, T>,
   mempool: Mempool<D, T>,
+
+  // A synced range of blocks, to be added alongside the block after them
+  queued: Vec<Block<T>>,
 }
 
 impl<D: Db, T: Transaction> Blockchain<D, T> {
@@ -38,6 +41,9 @@ impl<D: Db, T: Transaction> Blockchain<D, T> {
   fn block_after_key(genesis: &[u8], hash: &[u8; 32]) -> Vec<u8> {
     D::key(b"tributary_blockchain", b"block_after", [genesis, hash].concat())
   }
+  fn slash_evidence_key(genesis: &[u8], validator: &[u8; 32]) -> Vec<u8> {
+    D::key(b"tributary_blockchain", b"slash_evidence", [genesis, validator].concat())
+  }
   fn next_nonce_key(&self, signer: &<Ristretto as Ciphersuite>::G) -> Vec<u8> {
     D::key(
       b"tributary_blockchain",
@@ -66,6 +72,8 @@ impl<D: Db, T: Transaction> Blockchain<D, T> {
 
       provided: ProvidedTransactions::new(db.clone(), genesis),
       mempool: Mempool::new(db, genesis),
+
+      queued: vec![],
     };
 
     if let Some((block_number, tip)) = {
@@ -110,10 +118,40 @@ impl<D: Db, T: Transaction> Blockchain<D, T> {
     db.get(Self::block_after_key(&genesis, block)).map(|bytes| bytes.try_into().unwrap())
   }
 
+  pub(crate) fn slash_evidence_from_db(
+    db: &D,
+    genesis: [u8; 32],
+    validator: &[u8; 32],
+  ) -> Option<Vec<u8>> {
+    db.get(Self::slash_evidence_key(&genesis, validator))
+  }
+
+  /// Record evidence a validator misbehaved.
+  ///
+  /// Only the first piece of evidence for a validator is kept, as any is sufficient to slash them.
+  pub(crate) fn add_slash_evidence(&mut self, validator: [u8; 32], evidence: Vec<u8>) {
+    let key = Self::slash_evidence_key(&self.genesis, &validator);
+    let mut db = self.db.take().unwrap();
+    if db.get(&key).is_none() {
+      let mut txn = db.txn();
+      txn.put(key, evidence);
+      txn.commit();
+    }
+    self.db = Some(db);
+  }
+
   pub(crate) fn add_transaction(&mut self, internal: bool, tx: T) -> bool {
     self.mempool.add(&self.next_nonces, internal, tx)
   }
 
+  pub(crate) fn mempool_hashes(&self) -> Vec<[u8; 32]> {
+    self.mempool.hashes()
+  }
+
+  pub(crate) fn mempool_transaction(&self, hash: &[u8; 32]) -> Option<T> {
+    self.mempool.transaction(hash).cloned()
+  }
+
   pub(crate) fn provide_transaction(&mut self, tx: T) -> Result<(), ProvidedError> {
     self.provided.provide(tx)
   }
@@ -143,16 +181,88 @@ impl<D: Db, T: Transaction> Blockchain<D, T> {
     )
   }
 
+  /// Verify a chain of blocks, each as if the blocks before it were added.
+  pub(crate) fn verify_blocks(&self, blocks: &[Block<T>]) -> Result<(), BlockError> {
+    let mut tip = self.tip;
+    let mut provided = self.provided.transactions.clone();
+    let mut next_nonces = self.next_nonces.clone();
+    for block in blocks {
+      block.verify(self.genesis, tip, provided.clone(), next_nonces.clone())?;
+
+      // Update our view of the blockchain's state as if this block was added
+      tip = block.hash();
+      for tx in &block.transactions {
+        match tx.kind() {
+          TransactionKind::Provided(order) => {
+            provided.get_mut(order).unwrap().pop_front();
+          }
+          TransactionKind::Unsigned => {}
+          TransactionKind::Signed(Signed { signer, nonce, .. }) => {
+            next_nonces.insert(*signer, nonce + 1);
+          }
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// Queue a synced range of blocks, to be added alongside the block after them.
+  ///
+  /// Only the commit for the block after the range is verified, so the range's commits are never
+  /// saved. Queueing an empty range clears any prior queued range.
+  pub(crate) fn queue_blocks(&mut self, blocks: Vec<Block<T>>) {
+    self.queued = blocks;
+  }
+
+  // The queued blocks to add before this block, if this block is the one after the queued range
+  fn queued_before(&self, block: &Block<T>) -> Vec<Block<T>> {
+    if self.queued.last().map(Block::hash) != Some(block.parent()) {
+      return vec![];
+    }
+    // If consensus added some of the queued blocks, only add those after our tip
+    match self.queued.iter().position(|queued| queued.hash() == self.tip) {
+      Some(i) => self.queued[(i + 1) ..].to_vec(),
+      None => self.queued.clone(),
+    }
+  }
+
   /// Add a block.
+  ///
+  /// If this block is the one after a queued range, the range is added alongside this block,
+  /// atomically, with only this block's commit saved.
   pub(crate) fn add_block(&mut self, block: &Block<T>, commit: Vec<u8>) -> Result<(), BlockError> {
-    self.verify_block(block)?;
-
-    // None of the following assertions should be reachable since we verified the block
+    let mut blocks = self.queued_before(block);
+    blocks.push(block.clone());
+    self.verify_blocks(&blocks)?;
+    let block = blocks.pop().unwrap();
 
     // Take it from the Option so Rust doesn't consider self as mutably borrowed thanks to the
     // existence of the txn
     let mut db = self.db.take().unwrap();
     let mut txn = db.txn();
+    for queued in &blocks {
+      self.apply_block(&mut txn, queued, None);
+    }
+    self.apply_block(&mut txn, &block, Some(commit));
+    txn.commit();
+    self.db = Some(db);
+
+    if !blocks.is_empty() {
+      self.queued = vec![];
+    }
+
+    Ok(())
+  }
+
+  // Apply an already verified block to the blockchain's state, writing it to the passed txn
+  // The commit is only saved if one is passed, as only verified commits are saved
+  fn apply_block(
+    &mut self,
+    txn: &mut D::Transaction<'_>,
+    block: &Block<T>,
+    commit: Option<Vec<u8>>,
+  ) {
+    // None of the following assertions should be reachable since we verified the block
 
     self.tip = block.hash();
     txn.put(self.tip_key(), self.tip);
@@ -161,14 +271,16 @@ impl<D: Db, T: Transaction> Blockchain<D, T> {
     txn.put(self.block_number_key(), self.block_number.to_le_bytes());
 
     txn.put(Self::block_key(&self.genesis, &self.tip), block.serialize());
-    txn.put(Self::commit_key(&self.genesis, &self.tip), commit);
+    if let Some(commit) = commit {
+      txn.put(Self::commit_key(&self.genesis, &self.tip), commit);
+    }
 
     txn.put(Self::block_after_key(&self.genesis, &block.parent()), block.hash());
 
     for tx in &block.transactions {
       match tx.kind() {
         TransactionKind::Provided(order) => {
-          self.provided.complete(&mut txn, order, tx.hash());
+          self.provided.complete(txn, order, tx.hash());
         }
         TransactionKind::Unsigned => {}
         TransactionKind::Signed(Signed { signer, nonce, .. }) => {
@@ -187,10 +299,5 @@ impl<D: Db, T: Transaction> Blockchain<D, T> {
         }
       }
     }
-
-    txn.commit();
-    self.db = Some(db);
-
-    Ok(())
   }
 }
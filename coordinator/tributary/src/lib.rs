@@ -1,4 +1,4 @@
-use core::{marker::PhantomData, fmt::Debug};
+use core::{marker::PhantomData, hash::Hash, fmt::Debug, time::Duration};
 use std::{sync::Arc, io};
 
 use async_trait::async_trait;
@@ -17,7 +17,7 @@ use ::tendermint::{
 
 use serai_db::Db;
 
-use tokio::sync::RwLock;
+use tokio::{sync::RwLock, time::sleep};
 
 mod merkle;
 pub(crate) use merkle::*;
@@ -52,10 +52,23 @@ pub const ACCOUNT_MEMPOOL_LIMIT: u32 = 50;
 // This targets a growth limit of roughly 5 GB a day, under load, in order to prevent a malicious
 // participant from flooding disks and causing out of space errors in order processes.
 pub const BLOCK_SIZE_LIMIT: usize = 350_000;
+/// Amount of transaction hashes which may be announced, or requested, in a single message when
+/// synchronizing mempools.
+pub const MEMPOOL_SYNC_LIMIT: usize = 1_000;
 
 pub(crate) const TENDERMINT_MESSAGE: u8 = 0;
 pub(crate) const BLOCK_MESSAGE: u8 = 1;
 pub(crate) const TRANSACTION_MESSAGE: u8 = 2;
+pub(crate) const MEMPOOL_ANNOUNCEMENT_MESSAGE: u8 = 3;
+pub(crate) const MEMPOOL_REQUEST_MESSAGE: u8 = 4;
+
+// Read a list of hashes, as used by the mempool synchronization messages
+fn read_hashes(msg: &[u8]) -> Option<Vec<[u8; 32]>> {
+  if ((msg.len() % 32) != 0) || ((msg.len() / 32) > MEMPOOL_SYNC_LIMIT) {
+    return None;
+  }
+  Some(msg.chunks(32).map(|hash| hash.try_into().unwrap()).collect())
+}
 
 /// An item which can be read and written.
 pub trait ReadWrite: Sized {
@@ -72,11 +85,19 @@ pub trait ReadWrite: Sized {
 
 #[async_trait]
 pub trait P2p: 'static + Send + Sync + Clone + Debug {
+  type Id: Send + Sync + Clone + Copy + PartialEq + Eq + Hash + Debug;
+
+  async fn send(&self, genesis: [u8; 32], to: Self::Id, msg: Vec<u8>);
   async fn broadcast(&self, genesis: [u8; 32], msg: Vec<u8>);
 }
 
 #[async_trait]
 impl<P: P2p> P2p for Arc<P> {
+  type Id = P::Id;
+
+  async fn send(&self, genesis: [u8; 32], to: Self::Id, msg: Vec<u8>) {
+    (*self).send(genesis, to, msg).await
+  }
   async fn broadcast(&self, genesis: [u8; 32], msg: Vec<u8>) {
     (*self).broadcast(genesis, msg).await
   }
@@ -92,6 +113,19 @@ pub struct Tributary<D: Db, T: Transaction, P: P2p> {
   synced_block: SyncedBlockSender<TendermintNetwork<D, T, P>>,
   synced_block_result: Arc<RwLock<SyncedBlockResultReceiver>>,
   messages: Arc<RwLock<MessageSender<TendermintNetwork<D, T, P>>>>,
+
+  mempool_requests: Arc<RwLock<MempoolRateLimiter<P::Id>>>,
+  mempool_responses: Arc<RwLock<MempoolRateLimiter<P::Id>>>,
+  // The task periodically announcing our mempool, aborted once every handle has been dropped
+  _mempool_announcer: Arc<AbortOnDrop>,
+}
+
+// A task which is aborted when this is dropped
+struct AbortOnDrop(tokio::task::JoinHandle<()>);
+impl Drop for AbortOnDrop {
+  fn drop(&mut self) {
+    self.0.abort();
+  }
 }
 
 impl<D: Db, T: Transaction, P: P2p> Tributary<D, T, P> {
@@ -125,6 +159,18 @@ impl<D: Db, T: Transaction, P: P2p> Tributary<D, T, P> {
       TendermintMachine::new(network.clone(), block_number, start_time, proposal).await;
     tokio::task::spawn(machine.run());
 
+    // Periodically announce our mempool, so peers who missed a transaction's broadcast (such as by
+    // being offline at the time) can request it
+    let mempool_announcer = tokio::task::spawn({
+      let network = network.clone();
+      async move {
+        loop {
+          sleep(Duration::from_secs(TendermintNetwork::<D, T, P>::block_time().into())).await;
+          Self::announce_mempool_with(&network).await;
+        }
+      }
+    });
+
     Some(Self {
       db,
       genesis,
@@ -132,6 +178,9 @@ impl<D: Db, T: Transaction, P: P2p> Tributary<D, T, P> {
       synced_block,
       synced_block_result: Arc::new(RwLock::new(synced_block_result)),
       messages: Arc::new(RwLock::new(messages)),
+      mempool_requests: Arc::new(RwLock::new(MempoolRateLimiter::default())),
+      mempool_responses: Arc::new(RwLock::new(MempoolRateLimiter::default())),
+      _mempool_announcer: Arc::new(AbortOnDrop(mempool_announcer)),
     })
   }
 
@@ -175,8 +224,27 @@ impl<D: Db, T: Transaction, P: P2p> Tributary<D, T, P> {
     res
   }
 
+  async fn announce_mempool_with(network: &TendermintNetwork<D, T, P>) {
+    let hashes = network.blockchain.read().await.mempool_hashes();
+    if hashes.is_empty() {
+      return;
+    }
+
+    let mut msg = vec![MEMPOOL_ANNOUNCEMENT_MESSAGE];
+    for hash in hashes.iter().take(MEMPOOL_SYNC_LIMIT) {
+      msg.extend(hash);
+    }
+    network.p2p.broadcast(network.genesis, msg).await;
+  }
+
+  /// Announce the transactions within our mempool to our peers.
+  ///
+  /// This is automatically done every block time.
+  pub async fn announce_mempool(&self) {
+    Self::announce_mempool_with(&self.network).await;
+  }
+
   // Sync a block.
-  // TODO: Since we have a static validator set, we should only need the tail commit?
   pub async fn sync_block(&mut self, block: Block<T>, commit: Vec<u8>) -> bool {
     let mut result = self.synced_block_result.write().await;
 
@@ -205,8 +273,79 @@ impl<D: Db, T: Transaction, P: P2p> Tributary<D, T, P> {
     result.next().await.unwrap()
   }
 
+  /// Sync a range of blocks, each with its commit.
+  ///
+  /// Since the validator set is static, only the last block's commit is verified. The blocks
+  /// before it are verified to form a chain from our tip to it, and their commits are ignored. As
+  /// they aren't verified, they're never saved nor served to peers.
+  ///
+  /// The range is added, alongside the last block, in a single DB transaction once the Tendermint
+  /// machine accepts the last block, so either every block is added or none are.
+  ///
+  /// Returns true if the blocks were synced.
+  pub async fn sync_blocks(&mut self, mut blocks: Vec<(Block<T>, Vec<u8>)>) -> bool {
+    let Some((last_block, last_commit)) = blocks.pop() else {
+      log::debug!("told to sync an empty range of blocks");
+      return false;
+    };
+    if blocks.is_empty() {
+      return self.sync_block(last_block, last_commit).await;
+    }
+    let mut blocks = blocks.into_iter().map(|(block, _)| block).collect::<Vec<_>>();
+
+    let mut result = self.synced_block_result.write().await;
+
+    let (tip, block_number) = {
+      let blockchain = self.network.blockchain.read().await;
+      (blockchain.tip(), blockchain.block_number())
+    };
+
+    // Verify the chain of parent hashes
+    let mut parent = tip;
+    for block in blocks.iter().chain(core::iter::once(&last_block)) {
+      if block.header.parent != parent {
+        log::debug!("told to sync a range of blocks which weren't a chain from our tip");
+        return false;
+      }
+      parent = block.hash();
+    }
+
+    // Verify the last block's commit, which commits to the entire chain
+    let Ok(last_commit) = Commit::<Arc<Validators>>::decode(&mut last_commit.as_ref()) else {
+      log::error!("sent an invalidly serialized commit");
+      return false;
+    };
+    if !self.network.verify_commit(last_block.hash(), &last_commit) {
+      log::error!("sent an invalid commit");
+      return false;
+    }
+
+    // Verify the blocks before handing them to the Tendermint machine, which halts on invalid
+    // blocks
+    blocks.push(last_block);
+    if let Err(e) = self.network.blockchain.read().await.verify_blocks(&blocks) {
+      log::error!("sent a range of blocks which didn't verify: {e:?}");
+      return false;
+    }
+    let last_block = TendermintBlock(blocks.pop().unwrap().serialize());
+
+    // Queue the range, then have the Tendermint machine add the last block, jumping to after it
+    let number = BlockNumber((block_number + u32::try_from(blocks.len()).unwrap() + 1).into());
+    self.network.blockchain.write().await.queue_blocks(blocks);
+    self
+      .synced_block
+      .send(SyncedBlock { number, block: last_block, commit: last_commit })
+      .await
+      .unwrap();
+    let synced = result.next().await.unwrap();
+    // If the last block wasn't added, the range wasn't either, so clear it
+    self.network.blockchain.write().await.queue_blocks(vec![]);
+    synced
+  }
+
   // Return true if the message should be rebroadcasted.
-  pub async fn handle_message(&mut self, msg: &[u8]) -> bool {
+  // from is the peer who sent this message, to whom any mempool requests/responses are sent.
+  pub async fn handle_message(&mut self, from: P::Id, msg: &[u8]) -> bool {
     match msg.first() {
       Some(&TRANSACTION_MESSAGE) => {
         let Ok(tx) = T::read::<&[u8]>(&mut &msg[1 ..]) else {
@@ -214,17 +353,84 @@ impl<D: Db, T: Transaction, P: P2p> Tributary<D, T, P> {
           return false;
         };
 
-        // TODO: Sync mempools with fellow peers
-        // Can we just rebroadcast transactions not included for at least two blocks?
         let res = self.network.blockchain.write().await.add_transaction(false, tx);
         log::debug!("received transaction message. valid new transaction: {res}");
         res
       }
 
+      Some(&MEMPOOL_ANNOUNCEMENT_MESSAGE) => {
+        let Some(hashes) = read_hashes(&msg[1 ..]) else {
+          log::error!("received invalid mempool announcement");
+          return false;
+        };
+
+        let missing = {
+          let blockchain = self.network.blockchain.read().await;
+          hashes
+            .into_iter()
+            .filter(|hash| blockchain.mempool_transaction(hash).is_none())
+            .collect::<Vec<_>>()
+        };
+        // Don't request transactions we've recently requested from this peer, as they're likely in
+        // flight
+        let missing = self.mempool_requests.write().await.filter(
+          Duration::from_secs(Self::block_time().into()),
+          from,
+          missing,
+        );
+        if missing.is_empty() {
+          return false;
+        }
+
+        log::debug!("requesting {} transactions missing from our mempool", missing.len());
+        let mut request = vec![MEMPOOL_REQUEST_MESSAGE];
+        for hash in missing {
+          request.extend(hash);
+        }
+        self.network.p2p.send(self.genesis, from, request).await;
+        false
+      }
+
+      Some(&MEMPOOL_REQUEST_MESSAGE) => {
+        let Some(hashes) = read_hashes(&msg[1 ..]) else {
+          log::error!("received invalid mempool request");
+          return false;
+        };
+
+        // Don't respond with transactions we've recently sent this peer, as they're likely in
+        // flight
+        let hashes = self.mempool_responses.write().await.filter(
+          Duration::from_secs(Self::block_time().into()),
+          from,
+          hashes,
+        );
+        let mut txs = {
+          let blockchain = self.network.blockchain.read().await;
+          hashes.iter().filter_map(|hash| blockchain.mempool_transaction(hash)).collect::<Vec<_>>()
+        };
+
+        // Send the transactions in nonce order, so they can be added to the requester's mempool
+        let nonce = |tx: &T| {
+          if let TransactionKind::Signed(Signed { nonce, .. }) = tx.kind() {
+            *nonce
+          } else {
+            0
+          }
+        };
+        txs.sort_by_key(nonce);
+
+        for tx in txs {
+          let mut response = vec![TRANSACTION_MESSAGE];
+          tx.write(&mut response).unwrap();
+          self.network.p2p.send(self.genesis, from, response).await;
+        }
+        false
+      }
+
       Some(&TENDERMINT_MESSAGE) => {
-        let Ok(msg) = SignedMessageFor::<TendermintNetwork<D, T, P>>::decode::<&[u8]>(
-          &mut &msg[1 ..]
-        ) else {
+        let Ok(msg) =
+          SignedMessageFor::<TendermintNetwork<D, T, P>>::decode::<&[u8]>(&mut &msg[1 ..])
+        else {
           log::error!("received invalid tendermint message");
           return false;
         };
@@ -271,6 +477,10 @@ impl<D: Db, T: Transaction> TributaryReader<D, T> {
   pub fn block_after(&self, hash: &[u8; 32]) -> Option<[u8; 32]> {
     Blockchain::<D, T>::block_after(&self.0, self.1, hash)
   }
+  // The SCALE-encoded Tendermint evidence of a validator's misbehavior, if any was observed
+  pub fn slash_evidence(&self, validator: &[u8; 32]) -> Option<Vec<u8>> {
+    Blockchain::<D, T>::slash_evidence_from_db(&self.0, self.1, validator)
+  }
   pub fn time_of_block(&self, hash: &[u8; 32]) -> Option<u64> {
     self
       .commit(hash)
@@ -1,10 +1,17 @@
-use std::collections::HashMap;
+use core::{hash::Hash, time::Duration};
+use std::{
+  time::Instant,
+  collections::{HashSet, HashMap},
+};
 
 use ciphersuite::{Ciphersuite, Ristretto};
 
 use serai_db::{DbTxn, Db};
 
-use crate::{ACCOUNT_MEMPOOL_LIMIT, Signed, TransactionKind, Transaction, verify_transaction};
+use crate::{
+  ACCOUNT_MEMPOOL_LIMIT, MEMPOOL_SYNC_LIMIT, Signed, TransactionKind, Transaction,
+  verify_transaction,
+};
 
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub(crate) struct Mempool<D: Db, T: Transaction> {
@@ -176,8 +183,59 @@ impl<D: Db, T: Transaction> Mempool<D, T> {
     self.txs.remove(tx);
   }
 
+  /// Get the hashes of every transaction in the mempool.
+  pub(crate) fn hashes(&self) -> Vec<[u8; 32]> {
+    self.txs.keys().cloned().collect()
+  }
+
+  /// Get a transaction from the mempool.
+  pub(crate) fn transaction(&self, hash: &[u8; 32]) -> Option<&T> {
+    self.txs.get(hash)
+  }
+
   #[cfg(test)]
   pub(crate) fn txs(&self) -> &HashMap<[u8; 32], T> {
     &self.txs
   }
 }
+
+/// Maximum amount of peers the mempool rate limiter will track within an interval.
+///
+/// Peers beyond this limit are ignored until the interval elapses, bounding the limiter's memory.
+pub(crate) const MEMPOOL_RATE_LIMITER_PEERS: usize = 64;
+
+/// Rate limiter for mempool synchronization, tracking which hashes were requested from/responded
+/// to for each peer so peers can't have us repeatedly do so.
+///
+/// Each peer may have at most `MEMPOOL_SYNC_LIMIT` hashes acted upon per interval.
+#[derive(Clone, Debug)]
+pub(crate) struct MempoolRateLimiter<I: Copy + Eq + Hash>(HashMap<I, (Instant, HashSet<[u8; 32]>)>);
+
+impl<I: Copy + Eq + Hash> Default for MempoolRateLimiter<I> {
+  fn default() -> Self {
+    MempoolRateLimiter(HashMap::new())
+  }
+}
+
+impl<I: Copy + Eq + Hash> MempoolRateLimiter<I> {
+  /// Returns the hashes which haven't been acted upon for this peer within the interval, marking
+  /// them as acted upon.
+  pub(crate) fn filter(
+    &mut self,
+    interval: Duration,
+    peer: I,
+    hashes: Vec<[u8; 32]>,
+  ) -> Vec<[u8; 32]> {
+    let now = Instant::now();
+    self.0.retain(|_, (start, _)| now.duration_since(*start) < interval);
+    if (!self.0.contains_key(&peer)) && (self.0.len() >= MEMPOOL_RATE_LIMITER_PEERS) {
+      return vec![];
+    }
+
+    let (_, acted_upon) = self.0.entry(peer).or_insert_with(|| (now, HashSet::new()));
+    hashes
+      .into_iter()
+      .filter(|hash| (acted_upon.len() < MEMPOOL_SYNC_LIMIT) && acted_upon.insert(*hash))
+      .collect()
+  }
+}
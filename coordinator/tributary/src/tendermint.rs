@@ -18,13 +18,16 @@ use ciphersuite::{
   },
   Ciphersuite, Ristretto,
 };
-use schnorr::SchnorrSignature;
+use schnorr::{
+  SchnorrSignature,
+  aggregate::{SchnorrAggregate, SchnorrAggregator},
+};
 
 use serai_db::Db;
 
-use scale::{Encode, Decode};
+use scale::{Encode, Decode, Input, Output};
 use tendermint::{
-  SignedMessageFor,
+  SignedMessageFor, EvidenceFor,
   ext::{
     BlockNumber, RoundNumber, Signer as SignerTrait, SignatureScheme, Weights, Block as BlockTrait,
     BlockError as TendermintBlockError, Commit, Network,
@@ -56,6 +59,96 @@ fn challenge(
   <Ristretto as Ciphersuite>::F::from_bytes_mod_order_wide(&transcript.challenge(b"schnorr").into())
 }
 
+const COMMIT_AGGREGATION_DST: &[u8] = b"Tributary Chain Tendermint Commit Aggregation";
+
+// Legacy commits SCALE-encoded a Vec<[u8; 64]>, prefixed by its compact length. Since commits
+// always have signatures, a legacy commit never starts with a 0 byte, letting a 0 byte mark a
+// versioned encoding
+const VERSIONED_COMMIT: u8 = 0;
+const HALF_AGGREGATED_COMMIT: u8 = 1;
+
+/// The signature for a commit.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum AggregateSignature {
+  /// Every signature, individually. Only read for commits created before half-aggregation.
+  Legacy(Vec<[u8; 64]>),
+  /// A half-aggregated Schnorr signature, as defined in <https://eprint.iacr.org/2021/350>.
+  HalfAggregated(SchnorrAggregate<Ristretto>),
+}
+
+impl Encode for AggregateSignature {
+  fn size_hint(&self) -> usize {
+    match self {
+      AggregateSignature::Legacy(sigs) => sigs.size_hint(),
+      AggregateSignature::HalfAggregated(aggregate) => 2 + 4 + ((aggregate.Rs().len() + 1) * 32),
+    }
+  }
+
+  fn encode_to<O: Output + ?Sized>(&self, dest: &mut O) {
+    match self {
+      AggregateSignature::Legacy(sigs) => sigs.encode_to(dest),
+      AggregateSignature::HalfAggregated(aggregate) => {
+        dest.push_byte(VERSIONED_COMMIT);
+        dest.push_byte(HALF_AGGREGATED_COMMIT);
+        dest.write(&aggregate.serialize());
+      }
+    }
+  }
+}
+
+// An Input which yields a byte already read from the underlying Input before the rest of it
+struct Prefixed<'a, I: Input> {
+  prefix: Option<u8>,
+  input: &'a mut I,
+}
+impl<I: Input> Input for Prefixed<'_, I> {
+  fn remaining_len(&mut self) -> Result<Option<usize>, scale::Error> {
+    Ok(self.input.remaining_len()?.map(|len| len + usize::from(self.prefix.is_some())))
+  }
+
+  fn read(&mut self, into: &mut [u8]) -> Result<(), scale::Error> {
+    if into.is_empty() {
+      return Ok(());
+    }
+    if let Some(prefix) = self.prefix.take() {
+      into[0] = prefix;
+      return self.input.read(&mut into[1 ..]);
+    }
+    self.input.read(into)
+  }
+}
+
+impl Decode for AggregateSignature {
+  fn decode<I: Input>(input: &mut I) -> Result<Self, scale::Error> {
+    let first = input.read_byte()?;
+    if first != VERSIONED_COMMIT {
+      return Ok(AggregateSignature::Legacy(Vec::<[u8; 64]>::decode(&mut Prefixed {
+        prefix: Some(first),
+        input,
+      })?));
+    }
+
+    if input.read_byte()? != HALF_AGGREGATED_COMMIT {
+      Err("unknown commit version")?;
+    }
+
+    let mut len = [0; 4];
+    input.read(&mut len)?;
+    // Read the nonces and the aggregated s one at a time, so a malicious length can't cause a
+    // massive allocation
+    let mut buf = len.to_vec();
+    for _ in 0 ..= u32::from_le_bytes(len) {
+      let mut element = [0; 32];
+      input.read(&mut element)?;
+      buf.extend(element);
+    }
+    Ok(AggregateSignature::HalfAggregated(
+      SchnorrAggregate::read::<&[u8]>(&mut buf.as_ref())
+        .map_err(|_| "invalid half-aggregated signature")?,
+    ))
+  }
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Signer {
   genesis: [u8; 32],
@@ -154,8 +247,7 @@ impl Validators {
 impl SignatureScheme for Validators {
   type ValidatorId = [u8; 32];
   type Signature = [u8; 64];
-  // TODO: Use half-aggregation.
-  type AggregateSignature = Vec<[u8; 64]>;
+  type AggregateSignature = AggregateSignature;
   type Signer = Arc<Signer>;
 
   #[must_use]
@@ -172,8 +264,19 @@ impl SignatureScheme for Validators {
     actual_sig.verify(validator_point, challenge(self.genesis, validator, &sig[.. 32], msg))
   }
 
-  fn aggregate(sigs: &[Self::Signature]) -> Self::AggregateSignature {
-    sigs.to_vec()
+  fn aggregate(
+    &self,
+    validators: &[Self::ValidatorId],
+    msg: &[u8],
+    sigs: &[Self::Signature],
+  ) -> Self::AggregateSignature {
+    let mut aggregator = SchnorrAggregator::<Ristretto>::new(COMMIT_AGGREGATION_DST);
+    for (validator, sig) in validators.iter().zip(sigs) {
+      // These signatures were verified when received, so they'll deserialize
+      let actual_sig = SchnorrSignature::<Ristretto>::read::<&[u8]>(&mut sig.as_ref()).unwrap();
+      aggregator.aggregate(challenge(self.genesis, *validator, &sig[.. 32], msg), actual_sig);
+    }
+    AggregateSignature::HalfAggregated(aggregator.complete().unwrap())
   }
 
   #[must_use]
@@ -183,12 +286,37 @@ impl SignatureScheme for Validators {
     msg: &[u8],
     sig: &Self::AggregateSignature,
   ) -> bool {
-    for (signer, sig) in signers.iter().zip(sig.iter()) {
-      if !self.verify(*signer, msg, sig) {
-        return false;
+    match sig {
+      AggregateSignature::Legacy(sigs) => {
+        if signers.len() != sigs.len() {
+          return false;
+        }
+        for (signer, sig) in signers.iter().zip(sigs.iter()) {
+          if !self.verify(*signer, msg, sig) {
+            return false;
+          }
+        }
+        true
+      }
+      AggregateSignature::HalfAggregated(aggregate) => {
+        if signers.len() != aggregate.Rs().len() {
+          return false;
+        }
+
+        let mut keys_and_challenges = Vec::with_capacity(signers.len());
+        for (signer, nonce) in signers.iter().zip(aggregate.Rs()) {
+          if !self.weights.contains_key(signer) {
+            return false;
+          }
+          let Ok(signer_point) = Ristretto::read_G::<&[u8]>(&mut signer.as_ref()) else {
+            return false;
+          };
+          keys_and_challenges
+            .push((signer_point, challenge(self.genesis, *signer, nonce.to_bytes().as_ref(), msg)));
+        }
+        aggregate.verify(COMMIT_AGGREGATION_DST, &keys_and_challenges)
       }
     }
-    true
   }
 }
 
@@ -262,13 +390,26 @@ impl<D: Db, T: Transaction, P: P2p> Network for TendermintNetwork<D, T, P> {
     to_broadcast.extend(msg.encode());
     self.p2p.broadcast(self.genesis, to_broadcast).await
   }
-  async fn slash(&mut self, validator: Self::ValidatorId) {
-    // TODO: Handle this slash
+  async fn slash(&mut self, validator: Self::ValidatorId, evidence: Option<EvidenceFor<Self>>) {
     log::error!(
       "validator {} triggered a slash event on tributary {}",
       hex::encode(validator),
       hex::encode(self.genesis)
     );
+
+    // Record the evidence so it can be published, where it's verifiable by anyone with the
+    // validator set
+    let Some(evidence) = evidence else { return };
+    // The machine only provides evidence it verified, so this should never fail
+    if evidence.verify(&self.validators) != Some(validator) {
+      log::error!(
+        "evidence against validator {} on tributary {} didn't verify",
+        hex::encode(validator),
+        hex::encode(self.genesis)
+      );
+      return;
+    }
+    self.blockchain.write().await.add_slash_evidence(validator, evidence.encode());
   }
 
   async fn validate(&mut self, block: &Self::Block) -> Result<(), TendermintBlockError> {
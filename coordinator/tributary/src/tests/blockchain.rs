@@ -10,7 +10,7 @@ use ciphersuite::{group::ff::Field, Ciphersuite, Ristretto};
 use serai_db::{DbTxn, Db, MemDb};
 
 use crate::{
-  merkle, Transaction, ProvidedError, ProvidedTransactions, Block, Blockchain,
+  merkle, Transaction, ProvidedError, ProvidedTransactions, BlockError, Block, Blockchain,
   tests::{ProvidedTransaction, SignedTransaction, random_provided_transaction},
 };
 
@@ -127,6 +127,28 @@ fn invalid_block() {
   }
 }
 
+#[test]
+fn batch_verified_signatures() {
+  let genesis = new_genesis();
+
+  let key = Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng));
+  let signer = <Ristretto as Ciphersuite>::generator() * *key;
+  let (_, blockchain) = new_blockchain::<SignedTransaction>(genesis, &[signer]);
+
+  let mut txs = vec![];
+  for nonce in 0 .. 16 {
+    txs.push(crate::tests::signed_transaction(&mut OsRng, genesis, &key, nonce));
+  }
+  blockchain.verify_block(&Block::new(blockchain.tip(), vec![], txs.clone())).unwrap();
+
+  // Invalidate a single signature, which should be identified despite the batch verification
+  txs[11].1.signature.s += <Ristretto as Ciphersuite>::F::ONE;
+  assert_eq!(
+    blockchain.verify_block(&Block::new(blockchain.tip(), vec![], txs.clone())),
+    Err(BlockError::InvalidSignature(txs[11].hash()))
+  );
+}
+
 #[test]
 fn signed_transaction() {
   let genesis = new_genesis();
@@ -218,3 +240,99 @@ fn provided_transaction() {
   // add_block should fail for unverified provided transactions if told to add them
   assert!(blockchain.add_block(&block, vec![]).is_err());
 }
+
+#[test]
+fn multiple_blocks() {
+  let genesis = new_genesis();
+
+  let key = Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng));
+  let signer = <Ristretto as Ciphersuite>::generator() * *key;
+
+  // Build a chain of blocks, each with a signed transaction
+  let (_, mut blockchain) = new_blockchain::<SignedTransaction>(genesis, &[signer]);
+  let mut blocks = vec![];
+  for nonce in 0 .. 5 {
+    let tx = crate::tests::signed_transaction(&mut OsRng, genesis, &key, nonce);
+    assert!(blockchain.add_transaction(true, tx));
+    let block = blockchain.build_block();
+    assert!(blockchain.add_block(&block, vec![]).is_ok());
+    blocks.push(block);
+  }
+  let last = blocks.pop().unwrap();
+
+  // A range which isn't a chain shouldn't be added, and nothing within it should be
+  let (db, mut syncer) = new_blockchain::<SignedTransaction>(genesis, &[signer]);
+  let mut unchained = blocks.clone();
+  unchained.remove(2);
+  syncer.queue_blocks(unchained);
+  assert!(syncer.add_block(&last, vec![1]).is_err());
+  assert_eq!(syncer.tip(), genesis);
+  assert_eq!(syncer.block_number(), 0);
+  assert_eq!(syncer.next_nonce(signer), Some(0));
+  assert!(Blockchain::<MemDb, SignedTransaction>::block_after(&db, genesis, &genesis).is_none());
+
+  // A queued range shouldn't be added alongside a block which isn't after it
+  syncer.queue_blocks(blocks.clone());
+  assert!(syncer.add_block(&blocks[1], vec![1]).is_err());
+  assert_eq!(syncer.tip(), genesis);
+
+  // If some of the range was added by other means, only the rest of the range should be added
+  syncer.add_block(&blocks[0], vec![0]).unwrap();
+  assert_eq!(syncer.block_number(), 1);
+
+  // The range should be added alongside the block after it, with only that block's commit saved
+  syncer.add_block(&last, vec![1]).unwrap();
+  assert_eq!(syncer.tip(), blockchain.tip());
+  assert_eq!(syncer.block_number(), 5);
+  assert_eq!(syncer.next_nonce(signer), Some(5));
+  let mut parent = genesis;
+  for block in &blocks {
+    assert_eq!(
+      Blockchain::<MemDb, SignedTransaction>::block_after(&db, genesis, &parent).unwrap(),
+      block.hash()
+    );
+    parent = block.hash();
+  }
+  assert_eq!(syncer.commit(&blocks[0].hash()), Some(vec![0]));
+  for block in &blocks[1 ..] {
+    assert!(syncer.commit(&block.hash()).is_none());
+  }
+  assert_eq!(syncer.commit(&last.hash()), Some(vec![1]));
+
+  // The state should've been written to the DB
+  let reloaded = Blockchain::<MemDb, SignedTransaction>::new(db, genesis, &[signer]);
+  assert_eq!(reloaded.tip(), blockchain.tip());
+  assert_eq!(reloaded.next_nonce(signer), Some(5));
+}
+
+#[test]
+fn slash_evidence() {
+  let genesis = new_genesis();
+  let (db, mut blockchain) = new_blockchain::<SignedTransaction>(genesis, &[]);
+
+  let validator = [0xaa; 32];
+  assert!(Blockchain::<MemDb, SignedTransaction>::slash_evidence_from_db(&db, genesis, &validator)
+    .is_none());
+
+  // Only the first piece of evidence is kept
+  blockchain.add_slash_evidence(validator, vec![1]);
+  blockchain.add_slash_evidence(validator, vec![2]);
+  assert_eq!(
+    Blockchain::<MemDb, SignedTransaction>::slash_evidence_from_db(&db, genesis, &validator),
+    Some(vec![1])
+  );
+
+  // Evidence is scoped to its validator and tributary
+  assert!(Blockchain::<MemDb, SignedTransaction>::slash_evidence_from_db(
+    &db,
+    genesis,
+    &[0xbb; 32]
+  )
+  .is_none());
+  assert!(Blockchain::<MemDb, SignedTransaction>::slash_evidence_from_db(
+    &db,
+    new_genesis(),
+    &validator
+  )
+  .is_none());
+}
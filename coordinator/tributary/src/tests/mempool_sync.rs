@@ -0,0 +1,171 @@
+use core::time::Duration;
+use std::{sync::Arc, time::SystemTime};
+
+use async_trait::async_trait;
+
+use zeroize::Zeroizing;
+use rand::{RngCore, rngs::OsRng};
+
+use ciphersuite::{group::ff::Field, Ciphersuite, Ristretto};
+
+use serai_db::MemDb;
+
+use tokio::sync::RwLock;
+
+use crate::{
+  TRANSACTION_MESSAGE, MEMPOOL_ANNOUNCEMENT_MESSAGE, MEMPOOL_REQUEST_MESSAGE, MEMPOOL_SYNC_LIMIT,
+  ReadWrite, Transaction, P2p, Tributary,
+  mempool::{MEMPOOL_RATE_LIMITER_PEERS, MempoolRateLimiter},
+  tests::{SignedTransaction, signed_transaction},
+};
+
+// P2p which solely records what was sent, and to whom (None for broadcasts), so messages can be
+// manually delivered
+#[derive(Clone, Debug)]
+#[allow(clippy::type_complexity)]
+struct RecordingP2p(Arc<RwLock<Vec<(Option<usize>, Vec<u8>)>>>);
+
+impl RecordingP2p {
+  fn new() -> Self {
+    RecordingP2p(Arc::new(RwLock::new(vec![])))
+  }
+
+  // Take every recorded message of the specified kind
+  async fn take(&self, kind: u8) -> Vec<(Option<usize>, Vec<u8>)> {
+    let mut msgs = self.0.write().await;
+    let (res, others) = msgs.drain(..).partition(|(_, msg)| msg[0] == kind);
+    *msgs = others;
+    res
+  }
+}
+
+#[async_trait]
+impl P2p for RecordingP2p {
+  type Id = usize;
+
+  async fn send(&self, _: [u8; 32], to: usize, msg: Vec<u8>) {
+    self.0.write().await.push((Some(to), msg));
+  }
+
+  async fn broadcast(&self, _: [u8; 32], msg: Vec<u8>) {
+    self.0.write().await.push((None, msg));
+  }
+}
+
+const A: usize = 0;
+const B: usize = 1;
+
+#[tokio::test]
+async fn mempool_sync() {
+  let mut genesis = [0; 32];
+  OsRng.fill_bytes(&mut genesis);
+  let start_time = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
+
+  let keys = (0 .. 2)
+    .map(|_| Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng)))
+    .collect::<Vec<_>>();
+  let validators =
+    keys.iter().map(|key| (<Ristretto as Ciphersuite>::generator() * **key, 1)).collect::<Vec<_>>();
+
+  let mut tributaries = vec![];
+  for key in &keys {
+    let p2p = RecordingP2p::new();
+    let tributary = Tributary::<MemDb, SignedTransaction, _>::new(
+      MemDb::new(),
+      genesis,
+      start_time,
+      key.clone(),
+      validators.clone(),
+      p2p.clone(),
+    )
+    .await
+    .unwrap();
+    tributaries.push((p2p, tributary));
+  }
+  let (b_p2p, mut b) = tributaries.pop().unwrap();
+  let (a_p2p, mut a) = tributaries.pop().unwrap();
+
+  // Add a transaction to the first Tributary, without delivering its broadcast to the second
+  let tx = signed_transaction(&mut OsRng, genesis, &keys[0], 0);
+  assert!(a.add_transaction(tx.clone()).await);
+  assert_eq!(a_p2p.take(TRANSACTION_MESSAGE).await.len(), 1);
+  assert_eq!(b.next_nonce(validators[0].0).await, Some(0));
+
+  // Have the first Tributary announce its mempool
+  a.announce_mempool().await;
+  let (to, announcement) = a_p2p.take(MEMPOOL_ANNOUNCEMENT_MESSAGE).await.swap_remove(0);
+  assert_eq!(to, None);
+  assert_eq!(announcement, [[MEMPOOL_ANNOUNCEMENT_MESSAGE].as_ref(), &tx.hash()].concat());
+
+  // The second Tributary should request the missing transaction from the announcer, yet only once
+  assert!(!b.handle_message(A, &announcement).await);
+  let request = b_p2p.take(MEMPOOL_REQUEST_MESSAGE).await;
+  assert_eq!(request, vec![(Some(A), [[MEMPOOL_REQUEST_MESSAGE].as_ref(), &tx.hash()].concat())]);
+  assert!(!b.handle_message(A, &announcement).await);
+  assert!(b_p2p.take(MEMPOOL_REQUEST_MESSAGE).await.is_empty());
+
+  // The first Tributary should respond to the requester with the transaction, yet only once
+  let request = &request[0].1;
+  assert!(!a.handle_message(B, request).await);
+  let response = a_p2p.take(TRANSACTION_MESSAGE).await;
+  assert_eq!(response, vec![(Some(B), [[TRANSACTION_MESSAGE].as_ref(), &tx.serialize()].concat())]);
+  assert!(!a.handle_message(B, request).await);
+  assert!(a_p2p.take(TRANSACTION_MESSAGE).await.is_empty());
+
+  // Another peer should still have its request responded to
+  assert!(!a.handle_message(B + 1, request).await);
+  assert_eq!(a_p2p.take(TRANSACTION_MESSAGE).await[0].0, Some(B + 1));
+
+  // The second Tributary should accept the transaction into its mempool
+  assert!(b.handle_message(A, &response[0].1).await);
+  assert_eq!(b.next_nonce(validators[0].0).await, Some(1));
+
+  // Now that both mempools match, an announcement shouldn't cause any requests
+  b.announce_mempool().await;
+  let (_, announcement) = b_p2p.take(MEMPOOL_ANNOUNCEMENT_MESSAGE).await.swap_remove(0);
+  assert!(!a.handle_message(B, &announcement).await);
+  assert!(a_p2p.take(MEMPOOL_REQUEST_MESSAGE).await.is_empty());
+
+  // Malformed announcements should be rejected
+  assert!(!b.handle_message(A, &announcement[.. 20]).await);
+  assert!(b_p2p.take(MEMPOOL_REQUEST_MESSAGE).await.is_empty());
+}
+
+#[test]
+fn mempool_rate_limiter() {
+  let interval = Duration::from_secs(60);
+  let random_hashes = |amount| {
+    (0 .. amount)
+      .map(|_| {
+        let mut hash = [0; 32];
+        OsRng.fill_bytes(&mut hash);
+        hash
+      })
+      .collect::<Vec<_>>()
+  };
+
+  let mut limiter = MempoolRateLimiter::default();
+
+  // A peer announcing fresh hashes may only have MEMPOOL_SYNC_LIMIT acted upon per interval
+  let hashes = random_hashes(MEMPOOL_SYNC_LIMIT);
+  assert_eq!(limiter.filter(interval, 0, hashes[.. 10].to_vec()), hashes[.. 10]);
+  // A hash won't be acted upon twice for the same peer
+  assert_eq!(limiter.filter(interval, 0, hashes.clone()), hashes[10 ..]);
+  assert!(limiter.filter(interval, 0, random_hashes(1)).is_empty());
+
+  // Other peers are limited independently
+  assert_eq!(limiter.filter(interval, 1, hashes[.. 1].to_vec()), hashes[.. 1]);
+
+  // Only a bounded amount of peers are tracked
+  for peer in 2 .. MEMPOOL_RATE_LIMITER_PEERS {
+    assert_eq!(limiter.filter(interval, peer, hashes[.. 1].to_vec()).len(), 1);
+  }
+  assert!(limiter.filter(interval, MEMPOOL_RATE_LIMITER_PEERS, hashes[.. 1].to_vec()).is_empty());
+  // Yet tracked peers can still be acted upon
+  assert_eq!(limiter.filter(interval, 1, hashes[1 .. 2].to_vec()), hashes[1 .. 2]);
+
+  // Once the interval elapses, everything may be acted upon again
+  let interval = Duration::ZERO;
+  assert_eq!(limiter.filter(interval, 0, hashes.clone()), hashes);
+  assert_eq!(limiter.filter(interval, MEMPOOL_RATE_LIMITER_PEERS, hashes.clone()), hashes);
+}
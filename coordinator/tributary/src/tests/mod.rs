@@ -10,3 +10,9 @@ mod block;
 mod blockchain;
 #[cfg(test)]
 mod mempool;
+#[cfg(test)]
+mod mempool_sync;
+#[cfg(test)]
+mod sync;
+#[cfg(test)]
+mod tendermint;
@@ -0,0 +1,156 @@
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+
+use zeroize::Zeroizing;
+use rand::{RngCore, rngs::OsRng};
+
+use ciphersuite::{group::ff::Field, Ciphersuite, Ristretto};
+
+use scale::Encode;
+use ::tendermint::{
+  ext::{Signer as SignerTrait, SignatureScheme, Commit},
+  commit_msg,
+};
+
+use serai_db::MemDb;
+
+use crate::{Signer, Validators, Block, P2p, Tributary, tests::SignedTransaction};
+
+// P2p which drops every message, as the Tributary under test solely syncs blocks
+#[derive(Clone, Debug)]
+struct DroppingP2p;
+
+#[async_trait]
+impl P2p for DroppingP2p {
+  type Id = usize;
+
+  async fn send(&self, _: [u8; 32], _: usize, _: Vec<u8>) {}
+  async fn broadcast(&self, _: [u8; 32], _: Vec<u8>) {}
+}
+
+// Create a commit for the specified block, signed by the specified signers
+async fn commit(
+  validators: &Validators,
+  signers: &[Signer],
+  end_time: u64,
+  block: &Block<SignedTransaction>,
+) -> Vec<u8> {
+  let msg = commit_msg(end_time, block.hash().as_ref());
+  let mut ids = vec![];
+  let mut sigs = vec![];
+  for signer in signers {
+    ids.push(signer.validator_id().await.unwrap());
+    sigs.push(signer.sign(&msg).await);
+  }
+  let signature = validators.aggregate(&ids, &msg, &sigs);
+  Commit::<Validators> { end_time, validators: ids, signature }.encode()
+}
+
+#[tokio::test]
+async fn sync_blocks() {
+  let mut genesis = [0; 32];
+  OsRng.fill_bytes(&mut genesis);
+  // Start in the past so jumping to a synced block doesn't sleep
+  let start_time =
+    SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs() - 1000;
+
+  // Two validators, so the Tributary under test can't produce blocks on its own
+  let keys = (0 .. 2)
+    .map(|_| Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng)))
+    .collect::<Vec<_>>();
+  let validators =
+    keys.iter().map(|key| (<Ristretto as Ciphersuite>::generator() * **key, 1)).collect::<Vec<_>>();
+  let signers = keys.iter().map(|key| Signer::new(genesis, key.clone())).collect::<Vec<_>>();
+  let scheme = Validators::new(genesis, validators.clone()).unwrap();
+
+  let mut tributary = Tributary::<MemDb, SignedTransaction, _>::new(
+    MemDb::new(),
+    genesis,
+    start_time,
+    keys[0].clone(),
+    validators,
+    DroppingP2p,
+  )
+  .await
+  .unwrap();
+
+  // Build a chain of blocks, each validly committed to
+  let mut blocks = vec![];
+  let mut parent = genesis;
+  for i in 0 .. 3 {
+    let block = Block::<SignedTransaction>::new(parent, vec![], vec![]);
+    parent = block.hash();
+    let commit = commit(&scheme, &signers, start_time + i, &block).await;
+    blocks.push((block, commit));
+  }
+
+  let assert_unsynced = |tributary: &Tributary<MemDb, SignedTransaction, DroppingP2p>| {
+    let reader = tributary.reader();
+    for (block, _) in &blocks {
+      assert!(reader.block(&block.hash()).is_none());
+      assert!(reader.commit(&block.hash()).is_none());
+    }
+  };
+
+  // A range which isn't a chain from our tip should be rejected
+  let mut unchained = blocks.clone();
+  unchained.remove(1);
+  assert!(!tributary.sync_blocks(unchained).await);
+  assert_eq!(tributary.tip().await, genesis);
+  assert_eq!(tributary.block_number().await, 0);
+  assert_unsynced(&tributary);
+
+  // As should a range whose last commit is for another block
+  let mut forged = blocks.clone();
+  forged[2].1 = commit(&scheme, &signers, start_time + 2, &blocks[1].0).await;
+  assert!(!tributary.sync_blocks(forged).await);
+  assert_eq!(tributary.tip().await, genesis);
+  assert_unsynced(&tributary);
+
+  // As should a range whose last commit doesn't reach the threshold
+  let mut forged = blocks.clone();
+  forged[2].1 = commit(&scheme, &signers[.. 1], start_time + 2, &blocks[2].0).await;
+  assert!(!tributary.sync_blocks(forged).await);
+  assert_eq!(tributary.tip().await, genesis);
+  assert_unsynced(&tributary);
+
+  // As should a range whose last commit was mutated after signing
+  let mut forged = blocks.clone();
+  let last = forged[2].1.len() - 1;
+  forged[2].1[last] ^= 1;
+  assert!(!tributary.sync_blocks(forged).await);
+  assert_eq!(tributary.tip().await, genesis);
+  assert_unsynced(&tributary);
+
+  // The intermediate commits aren't verified, so a valid range should be synced regardless of them
+  let mut range = blocks.clone();
+  range[0].1 = vec![];
+  range[1].1 = commit(&scheme, &signers[.. 1], start_time + 1, &blocks[0].0).await;
+  assert!(tributary.sync_blocks(range).await);
+  assert_eq!(tributary.tip().await, blocks.last().unwrap().0.hash());
+  assert_eq!(tributary.block_number().await, 3);
+  // Yet only the last commit, which was verified, should be saved
+  let reader = tributary.reader();
+  for (block, _) in &blocks {
+    assert_eq!(reader.block(&block.hash()).unwrap(), *block);
+  }
+  assert!(reader.commit(&blocks[0].0.hash()).is_none());
+  assert!(reader.commit(&blocks[1].0.hash()).is_none());
+  assert_eq!(reader.commit(&blocks[2].0.hash()).unwrap(), blocks[2].1);
+
+  // Having jumped past the range, the Tendermint machine should accept the following block solely
+  // with a valid commit
+  let block = Block::<SignedTransaction>::new(parent, vec![], vec![]);
+  // The first round of this block starts when the last synced block ended
+  let end_time =
+    start_time + 2 + u64::from(Tributary::<MemDb, SignedTransaction, DroppingP2p>::block_time());
+  let forged = commit(&scheme, &signers[.. 1], end_time, &block).await;
+  assert!(!tributary.sync_block(block.clone(), forged).await);
+  assert_eq!(tributary.block_number().await, 3);
+  let commit = commit(&scheme, &signers, end_time, &block).await;
+  assert!(tributary.sync_block(block.clone(), commit.clone()).await);
+  assert_eq!(tributary.tip().await, block.hash());
+  assert_eq!(tributary.block_number().await, 4);
+  assert_eq!(tributary.reader().commit(&block.hash()).unwrap(), commit);
+}
@@ -0,0 +1,70 @@
+use zeroize::Zeroizing;
+use rand::{RngCore, rngs::OsRng};
+
+use ciphersuite::{group::ff::Field, Ciphersuite, Ristretto};
+
+use scale::{Encode, Decode};
+use ::tendermint::ext::{Signer as SignerTrait, SignatureScheme, Commit};
+
+use crate::{Signer, Validators, AggregateSignature};
+
+#[tokio::test]
+async fn half_aggregated_commit() {
+  let mut genesis = [0; 32];
+  OsRng.fill_bytes(&mut genesis);
+
+  let mut signers = vec![];
+  let mut keys = vec![];
+  for _ in 0 .. 3 {
+    let key = Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng));
+    keys.push((<Ristretto as Ciphersuite>::generator() * *key, 1));
+    signers.push(Signer::new(genesis, key));
+  }
+  let validators = Validators::new(genesis, keys).unwrap();
+
+  let msg = b"commit";
+  let mut ids = vec![];
+  let mut sigs = vec![];
+  for signer in &signers {
+    ids.push(signer.validator_id().await.unwrap());
+    sigs.push(signer.sign(msg).await);
+  }
+
+  let signature = validators.aggregate(&ids, msg, &sigs);
+  assert!(matches!(signature, AggregateSignature::HalfAggregated(_)));
+  assert!(validators.verify_aggregate(&ids, msg, &signature));
+
+  // The aggregate should be bound to the message and signers
+  assert!(!validators.verify_aggregate(&ids, b"other", &signature));
+  let mut reordered = ids.clone();
+  reordered.swap(0, 1);
+  assert!(!validators.verify_aggregate(&reordered, msg, &signature));
+  assert!(!validators.verify_aggregate(&ids[.. 2], msg, &signature));
+
+  // The commit should round-trip, and be smaller than a legacy commit
+  let commit = Commit::<Validators> { end_time: 0, validators: ids.clone(), signature };
+  let encoded = commit.encode();
+  assert_eq!(commit.signature.encode().len(), commit.signature.size_hint());
+  assert_eq!(Commit::<Validators>::decode(&mut encoded.as_ref()).unwrap(), commit);
+
+  let legacy = Commit::<Validators> {
+    end_time: 0,
+    validators: ids.clone(),
+    signature: AggregateSignature::Legacy(sigs.clone()),
+  };
+  let legacy_encoded = legacy.encode();
+  // This should be identical to the encoding used before commits were versioned
+  assert_eq!(legacy_encoded, (0u64, ids.clone(), sigs.clone()).encode());
+  assert!(encoded.len() < legacy_encoded.len());
+
+  // Legacy commits should still be readable and verifiable
+  let legacy = Commit::<Validators>::decode(&mut legacy_encoded.as_ref()).unwrap();
+  assert_eq!(legacy.signature, AggregateSignature::Legacy(sigs.clone()));
+  assert!(validators.verify_aggregate(&ids, msg, &legacy.signature));
+  assert!(!validators.verify_aggregate(&ids[.. 2], msg, &legacy.signature));
+
+  // Unknown versions should be rejected
+  let mut unknown = encoded;
+  unknown[8 + ids.encode().len() + 1] = 2;
+  assert!(Commit::<Validators>::decode(&mut unknown.as_ref()).is_err());
+}
@@ -3,9 +3,12 @@ use std::{io, collections::HashMap};
 
 use thiserror::Error;
 
+use rand::rngs::OsRng;
+
 use blake2::{Digest, Blake2b512};
 
 use ciphersuite::{group::GroupEncoding, Ciphersuite, Ristretto};
+use multiexp::BatchVerifier;
 use schnorr::SchnorrSignature;
 
 use crate::{TRANSACTION_SIZE_LIMIT, ReadWrite};
@@ -110,6 +113,28 @@ pub(crate) fn verify_transaction<T: Transaction>(
   tx: &T,
   genesis: [u8; 32],
   next_nonces: &mut HashMap<<Ristretto as Ciphersuite>::G, u32>,
+) -> Result<(), TransactionError> {
+  verify_transaction_internal(tx, genesis, next_nonces, None)
+}
+
+// Verifies everything except the signature, which is queued into the batch verifier under the
+// transaction's hash
+// This will cause mutations if the transaction's signature is invalid, so the batch must be
+// verified before the mutated nonces are used
+pub(crate) fn batch_verify_transaction<T: Transaction>(
+  tx: &T,
+  genesis: [u8; 32],
+  next_nonces: &mut HashMap<<Ristretto as Ciphersuite>::G, u32>,
+  batch: &mut BatchVerifier<[u8; 32], <Ristretto as Ciphersuite>::G>,
+) -> Result<(), TransactionError> {
+  verify_transaction_internal(tx, genesis, next_nonces, Some(batch))
+}
+
+fn verify_transaction_internal<T: Transaction>(
+  tx: &T,
+  genesis: [u8; 32],
+  next_nonces: &mut HashMap<<Ristretto as Ciphersuite>::G, u32>,
+  batch: Option<&mut BatchVerifier<[u8; 32], <Ristretto as Ciphersuite>::G>>,
 ) -> Result<(), TransactionError> {
   if tx.serialize().len() > TRANSACTION_SIZE_LIMIT {
     Err(TransactionError::TooLargeTransaction)?;
@@ -130,8 +155,9 @@ pub(crate) fn verify_transaction<T: Transaction>(
         Err(TransactionError::InvalidSigner)?;
       }
 
-      // TODO: Use Schnorr half-aggregation and a batch verification here
-      if !signature.verify(*signer, tx.sig_hash(genesis)) {
+      if let Some(batch) = batch {
+        signature.batch_verify(&mut OsRng, batch, tx.hash(), *signer, tx.sig_hash(genesis));
+      } else if !signature.verify(*signer, tx.sig_hash(genesis)) {
         Err(TransactionError::InvalidSignature)?;
       }
 
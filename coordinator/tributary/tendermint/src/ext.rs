@@ -6,7 +6,7 @@ use thiserror::Error;
 
 use parity_scale_codec::{Encode, Decode};
 
-use crate::{SignedMessageFor, commit_msg};
+use crate::{SignedMessageFor, EvidenceFor, commit_msg};
 
 /// An alias for a series of traits required for a type to be usable as a validator ID,
 /// automatically implemented for all types satisfying those traits.
@@ -81,7 +81,15 @@ pub trait SignatureScheme: Send + Sync {
   fn verify(&self, validator: Self::ValidatorId, msg: &[u8], sig: &Self::Signature) -> bool;
 
   /// Aggregate signatures.
-  fn aggregate(sigs: &[Self::Signature]) -> Self::AggregateSignature;
+  ///
+  /// The signatures are guaranteed to have been verified, with `sigs[i]` being a signature from
+  /// `validators[i]` for `msg`.
+  fn aggregate(
+    &self,
+    validators: &[Self::ValidatorId],
+    msg: &[u8],
+    sigs: &[Self::Signature],
+  ) -> Self::AggregateSignature;
   /// Verify an aggregate signature for the list of signers.
   #[must_use]
   fn verify_aggregate(
@@ -102,8 +110,13 @@ impl<S: SignatureScheme> SignatureScheme for Arc<S> {
     self.as_ref().verify(validator, msg, sig)
   }
 
-  fn aggregate(sigs: &[Self::Signature]) -> Self::AggregateSignature {
-    S::aggregate(sigs)
+  fn aggregate(
+    &self,
+    validators: &[Self::ValidatorId],
+    msg: &[u8],
+    sigs: &[Self::Signature],
+  ) -> Self::AggregateSignature {
+    self.as_ref().aggregate(validators, msg, sigs)
   }
 
   #[must_use]
@@ -262,9 +275,11 @@ pub trait Network: Send + Sync {
 
   /// Trigger a slash for the validator in question who was definitively malicious.
   ///
+  /// If the validator equivocated, evidence of such is provided. This evidence is self-contained
+  /// and can be verified by anyone with the signature scheme, enabling it to be published.
+  ///
   /// The exact process of triggering a slash is undefined and left to the network as a whole.
-  // TODO: We need to provide some evidence for this.
-  async fn slash(&mut self, validator: Self::ValidatorId);
+  async fn slash(&mut self, validator: Self::ValidatorId, evidence: Option<EvidenceFor<Self>>);
 
   /// Validate a block.
   async fn validate(&mut self, block: &Self::Block) -> Result<(), BlockError>;
@@ -29,7 +29,11 @@ pub(crate) mod message_log;
 pub mod ext;
 use ext::*;
 
-pub(crate) fn commit_msg(end_time: u64, id: &[u8]) -> Vec<u8> {
+#[cfg(test)]
+mod tests;
+
+/// The message validators sign to commit to a block, as verified by `Network::verify_commit`.
+pub fn commit_msg(end_time: u64, id: &[u8]) -> Vec<u8> {
   [&end_time.to_le_bytes(), id].concat().to_vec()
 }
 
@@ -103,9 +107,62 @@ impl<V: ValidatorId, B: Block, S: Signature> SignedMessage<V, B, S> {
   }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
-enum TendermintError<V: ValidatorId> {
-  Malicious(V),
+/// Evidence a validator equivocated, verifiable with solely the signature scheme (and the
+/// validator set it represents).
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub enum Evidence<V: ValidatorId, B: Block, S: Signature> {
+  /// Two distinct messages for the same block, round, and step.
+  ConflictingMessages(SignedMessage<V, B, S>, SignedMessage<V, B, S>),
+  /// Two precommits, for the same block number, for distinct block IDs.
+  ConflictingPrecommit(SignedMessage<V, B, S>, SignedMessage<V, B, S>),
+}
+
+impl<V: ValidatorId, B: Block, S: Signature> Evidence<V, B, S> {
+  /// The validator this evidence claims equivocated.
+  pub fn validator(&self) -> V {
+    match self {
+      Evidence::ConflictingMessages(first, _) => first.msg.sender,
+      Evidence::ConflictingPrecommit(first, _) => first.msg.sender,
+    }
+  }
+
+  /// Verify this evidence, returning the validator who equivocated if it's valid.
+  #[must_use]
+  pub fn verify<Scheme: SignatureScheme<ValidatorId = V, Signature = S>>(
+    &self,
+    signer: &Scheme,
+  ) -> Option<V> {
+    let (first, second) = match self {
+      Evidence::ConflictingMessages(first, second) => (first, second),
+      Evidence::ConflictingPrecommit(first, second) => (first, second),
+    };
+
+    // Both messages must be from the same validator, for the same block
+    if (first.msg.sender != second.msg.sender) || (first.msg.block != second.msg.block) {
+      return None;
+    }
+
+    if !(first.verify_signature(signer) && second.verify_signature(signer)) {
+      return None;
+    }
+
+    let conflicting = match self {
+      Evidence::ConflictingMessages(..) => {
+        (first.msg.round == second.msg.round) &&
+          (first.msg.data.step() == second.msg.data.step()) &&
+          (first.msg.data != second.msg.data)
+      }
+      Evidence::ConflictingPrecommit(..) => match (&first.msg.data, &second.msg.data) {
+        (Data::Precommit(Some((id, _))), Data::Precommit(Some((id2, _)))) => id != id2,
+        _ => false,
+      },
+    };
+    Some(first.msg.sender).filter(|_| conflicting)
+  }
+}
+
+enum TendermintError<N: Network> {
+  Malicious(N::ValidatorId, Option<EvidenceFor<N>>),
   Temporal,
   AlreadyHandled,
 }
@@ -124,6 +181,12 @@ pub type SignedMessageFor<N> = SignedMessage<
   <N as Network>::Block,
   <<N as Network>::SignatureScheme as SignatureScheme>::Signature,
 >;
+/// Type alias to the Evidence type for a given Network
+pub type EvidenceFor<N> = Evidence<
+  <N as Network>::ValidatorId,
+  <N as Network>::Block,
+  <<N as Network>::SignatureScheme as SignatureScheme>::Signature,
+>;
 
 /// A machine executing the Tendermint protocol.
 pub struct TendermintMachine<N: Network> {
@@ -155,6 +218,8 @@ pub type MessageSender<N> = mpsc::UnboundedSender<SignedMessageFor<N>>;
 pub struct TendermintHandle<N: Network> {
   /// Channel to trigger the machine to move to the next block.
   /// Takes in the the previous block's commit, along with the new proposal.
+  /// The synced block may be beyond the machine's current block, if the network has already
+  /// added the blocks in between, in which case the machine will jump to after it.
   pub synced_block: SyncedBlockSender<N>,
   /// A channel to communicate the result of a synced_block message.
   pub synced_block_result: SyncedBlockResultReceiver,
@@ -237,11 +302,38 @@ impl<N: Network + 'static> TendermintMachine<N> {
     self.reset(round, proposal).await;
   }
 
-  async fn slash(&mut self, validator: N::ValidatorId) {
+  // Jump to after a block beyond our current block, as happens when the network syncs multiple
+  // blocks at once
+  async fn reset_by_jump(
+    &mut self,
+    number: BlockNumber,
+    end_time: u64,
+    proposal: Option<N::Block>,
+  ) {
+    // Sleep until the synced block ends
+    let sys_time = sys_time(end_time);
+    sleep(sys_time.duration_since(SystemTime::now()).unwrap_or(Duration::ZERO)).await;
+
+    // Clear our outbound message queue
+    self.queue = VecDeque::new();
+
+    // Create the new block
+    self.block = BlockData::new(
+      self.weights.clone(),
+      BlockNumber(number.0 + 1),
+      self.signer.validator_id().await,
+      proposal,
+    );
+
+    // Start the first round, using the synced block's end time as the start time
+    self.round(RoundNumber(0), Some(CanonicalInstant::new(end_time)));
+  }
+
+  async fn slash(&mut self, validator: N::ValidatorId, evidence: Option<EvidenceFor<N>>) {
     if !self.block.slashes.contains(&validator) {
       log::info!(target: "tendermint", "Slashing validator {:?}", validator);
       self.block.slashes.insert(validator);
-      self.network.slash(validator).await;
+      self.network.slash(validator, evidence).await;
     }
   }
 
@@ -313,13 +405,13 @@ impl<N: Network + 'static> TendermintMachine<N> {
       let mut queue_future =
         if self.queue.is_empty() { Fuse::terminated() } else { future::ready(()).fuse() };
 
-      if let Some((our_message, msg, mut sig)) = futures::select_biased! {
+      if let Some((our_message, signed)) = futures::select_biased! {
         // Handle a new block occuring externally (an external sync loop)
         // Has the highest priority as it makes all other futures here irrelevant
         msg = self.synced_block_recv.next() => {
           if let Some(SyncedBlock { number, block, commit }) = msg {
             // Commit is for a block we've already moved past
-            if number != self.block.number {
+            if number.0 < self.block.number.0 {
               self.synced_block_result_send.send(false).await.unwrap();
               continue;
             }
@@ -331,7 +423,13 @@ impl<N: Network + 'static> TendermintMachine<N> {
             }
 
             let proposal = self.network.add_block(block, commit.clone()).await;
-            self.reset_by_commit(commit, proposal).await;
+            if number == self.block.number {
+              self.reset_by_commit(commit, proposal).await;
+            } else {
+              // The network synced multiple blocks at once, so there's no round within our
+              // current block to reconcile this commit with
+              self.reset_by_jump(number, commit.end_time, proposal).await;
+            }
             self.synced_block_result_send.send(true).await.unwrap();
             None
           } else {
@@ -341,7 +439,11 @@ impl<N: Network + 'static> TendermintMachine<N> {
 
         // Handle our messages
         _ = queue_future => {
-          Some((true, self.queue.pop_front().unwrap(), None))
+          let msg = self.queue.pop_front().unwrap();
+          // Sign our message now so it's logged alongside its signature, as any other message is
+          // This is still only broadcast after we've handled it
+          let sig = self.signer.sign(&msg.encode()).await;
+          Some((true, SignedMessage { msg, sig }))
         },
 
         // Handle any timeouts
@@ -357,7 +459,8 @@ impl<N: Network + 'static> TendermintMachine<N> {
                 // Slash the validator for not proposing when they should've
                 log::debug!(target: "tendermint", "Validator didn't propose when they should have");
                 self.slash(
-                  self.weights.proposer(self.block.number, self.block.round().number)
+                  self.weights.proposer(self.block.number, self.block.round().number),
+                  None,
                 ).await;
                 self.broadcast(Data::Prevote(None));
               },
@@ -377,33 +480,32 @@ impl<N: Network + 'static> TendermintMachine<N> {
             if !msg.verify_signature(&self.validators) {
               continue;
             }
-            Some((false, msg.msg, Some(msg.sig)))
+            Some((false, msg))
           } else {
             break;
           }
         }
       } {
-        let res = self.message(msg.clone()).await;
+        let res = self.message(signed.clone()).await;
         if res.is_err() && our_message {
           panic!("honest node (ourselves) had invalid behavior");
         }
 
         match res {
           Ok(None) => {
-            if let Some(sig) = sig.take() {
-              // If it's our own message, it shouldn't already be signed
-              assert!(!our_message);
-
+            if !our_message {
               // Re-broadcast this since it's an original consensus message
-              self.network.broadcast(SignedMessage { msg: msg.clone(), sig }).await;
+              self.network.broadcast(signed.clone()).await;
             }
           }
           Ok(Some(block)) => {
             let mut validators = vec![];
             let mut sigs = vec![];
             // Get all precommits for this round
-            for (validator, msgs) in &self.block.log.log[&msg.round] {
-              if let Some(Data::Precommit(Some((id, sig)))) = msgs.get(&Step::Precommit) {
+            for (validator, msgs) in &self.block.log.log[&signed.msg.round] {
+              if let Some(Data::Precommit(Some((id, sig)))) =
+                msgs.get(&Step::Precommit).map(|signed| &signed.msg.data)
+              {
                 // If this precommit was for this block, include it
                 if id == &block.id() {
                   validators.push(*validator);
@@ -412,25 +514,27 @@ impl<N: Network + 'static> TendermintMachine<N> {
               }
             }
 
-            let commit = Commit {
-              end_time: self.block.end_time[&msg.round].canonical(),
-              validators,
-              signature: N::SignatureScheme::aggregate(&sigs),
-            };
+            let end_time = self.block.end_time[&signed.msg.round].canonical();
+            let signature = self.validators.aggregate(
+              &validators,
+              &commit_msg(end_time, block.id().as_ref()),
+              &sigs,
+            );
+            let commit = Commit { end_time, validators, signature };
             debug_assert!(self.network.verify_commit(block.id(), &commit));
 
             let proposal = self.network.add_block(block, commit).await;
-            self.reset(msg.round, proposal).await;
+            self.reset(signed.msg.round, proposal).await;
+          }
+          Err(TendermintError::Malicious(validator, evidence)) => {
+            self.slash(validator, evidence).await
           }
-          Err(TendermintError::Malicious(validator)) => self.slash(validator).await,
           Err(TendermintError::Temporal) => (),
           Err(TendermintError::AlreadyHandled) => (),
         }
 
         if our_message {
-          assert!(sig.is_none());
-          let sig = self.signer.sign(&msg.encode()).await;
-          self.network.broadcast(SignedMessage { msg, sig }).await;
+          self.network.broadcast(signed).await;
         }
       }
     }
@@ -444,7 +548,7 @@ impl<N: Network + 'static> TendermintMachine<N> {
     sender: N::ValidatorId,
     round: RoundNumber,
     data: &DataFor<N>,
-  ) -> Result<bool, TendermintError<N::ValidatorId>> {
+  ) -> Result<bool, TendermintError<N>> {
     if let Data::Precommit(Some((id, sig))) = data {
       // Also verify the end_time of the commit
       // Only perform this verification if we already have the end_time
@@ -453,7 +557,7 @@ impl<N: Network + 'static> TendermintMachine<N> {
       if let Some(end_time) = self.block.end_time.get(&round) {
         if !self.validators.verify(sender, &commit_msg(end_time.canonical(), id.as_ref()), sig) {
           log::warn!(target: "tendermint", "Validator produced an invalid commit signature");
-          Err(TendermintError::Malicious(sender))?;
+          Err(TendermintError::Malicious(sender, None))?;
         }
         return Ok(true);
       }
@@ -463,8 +567,9 @@ impl<N: Network + 'static> TendermintMachine<N> {
 
   async fn message(
     &mut self,
-    msg: MessageFor<N>,
-  ) -> Result<Option<N::Block>, TendermintError<N::ValidatorId>> {
+    signed: SignedMessageFor<N>,
+  ) -> Result<Option<N::Block>, TendermintError<N>> {
+    let msg = signed.msg.clone();
     if msg.block != self.block.number {
       Err(TendermintError::Temporal)?;
     }
@@ -477,10 +582,10 @@ impl<N: Network + 'static> TendermintMachine<N> {
       (msg.sender != self.weights.proposer(msg.block, msg.round))
     {
       log::warn!(target: "tendermint", "Validator who wasn't the proposer proposed");
-      Err(TendermintError::Malicious(msg.sender))?;
+      Err(TendermintError::Malicious(msg.sender, None))?;
     };
 
-    if !self.block.log.log(msg.clone())? {
+    if !self.block.log.log(signed)? {
       return Err(TendermintError::AlreadyHandled);
     }
     log::debug!(target: "tendermint", "received new tendermint message");
@@ -519,8 +624,10 @@ impl<N: Network + 'static> TendermintMachine<N> {
         // If this round already has precommit messages, verify their signatures
         let round_msgs = self.block.log.log[&msg.round].clone();
         for (validator, msgs) in &round_msgs {
-          if let Some(data) = msgs.get(&Step::Precommit) {
-            if let Ok(res) = self.verify_precommit_signature(*validator, msg.round, data) {
+          if let Some(signed) = msgs.get(&Step::Precommit) {
+            if let Ok(res) =
+              self.verify_precommit_signature(*validator, msg.round, &signed.msg.data)
+            {
               // Ensure this actually verified the signature instead of believing it shouldn't yet
               debug_assert!(res);
             } else {
@@ -537,7 +644,7 @@ impl<N: Network + 'static> TendermintMachine<N> {
                 .get_mut(validator)
                 .unwrap()
                 .remove(&Step::Precommit);
-              self.slash(*validator).await;
+              self.slash(*validator, None).await;
             }
           }
         }
@@ -595,7 +702,7 @@ impl<N: Network + 'static> TendermintMachine<N> {
         Err(BlockError::Temporal) => (false, Ok(None)),
         Err(BlockError::Fatal) => (false, {
           log::warn!(target: "tendermint", "Validator proposed a fatally invalid block");
-          Err(TendermintError::Malicious(proposer))
+          Err(TendermintError::Malicious(proposer, None))
         }),
       };
       // Create a raw vote which only requires block validity as a basis for the actual vote.
@@ -612,7 +719,7 @@ impl<N: Network + 'static> TendermintMachine<N> {
         // Malformed message
         if vr.0 >= self.block.round().number.0 {
           log::warn!(target: "tendermint", "Validator claimed a round from the future was valid");
-          Err(TendermintError::Malicious(msg.sender))?;
+          Err(TendermintError::Malicious(msg.sender, None))?;
         }
 
         if self.block.log.has_consensus(*vr, Data::Prevote(Some(block.id()))) {
@@ -651,7 +758,7 @@ impl<N: Network + 'static> TendermintMachine<N> {
           Err(BlockError::Temporal) => (),
           Err(BlockError::Fatal) => {
             log::warn!(target: "tendermint", "Validator proposed a fatally invalid block");
-            Err(TendermintError::Malicious(proposer))?
+            Err(TendermintError::Malicious(proposer, None))?
           }
         };
 
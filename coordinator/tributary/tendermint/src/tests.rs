@@ -0,0 +1,217 @@
+use async_trait::async_trait;
+
+use parity_scale_codec::{Encode, Decode};
+
+use crate::{ext::*, Data, Message, SignedMessage, Evidence};
+
+type TestValidatorId = u16;
+type TestBlockId = [u8; 4];
+
+// Signatures are the validator's ID followed by the message, making them trivially forgeable yet
+// sufficient to check evidence is bound to its signatures
+struct TestSigner(u16);
+#[async_trait]
+impl Signer for TestSigner {
+  type ValidatorId = TestValidatorId;
+  type Signature = Vec<u8>;
+
+  async fn validator_id(&self) -> Option<TestValidatorId> {
+    Some(self.0)
+  }
+
+  async fn sign(&self, msg: &[u8]) -> Vec<u8> {
+    [self.0.to_le_bytes().as_ref(), msg].concat()
+  }
+}
+
+struct TestSignatureScheme;
+impl SignatureScheme for TestSignatureScheme {
+  type ValidatorId = TestValidatorId;
+  type Signature = Vec<u8>;
+  type AggregateSignature = Vec<Vec<u8>>;
+  type Signer = TestSigner;
+
+  #[must_use]
+  fn verify(&self, validator: u16, msg: &[u8], sig: &Vec<u8>) -> bool {
+    sig == &[validator.to_le_bytes().as_ref(), msg].concat()
+  }
+
+  fn aggregate(&self, _: &[TestValidatorId], _: &[u8], sigs: &[Vec<u8>]) -> Vec<Vec<u8>> {
+    sigs.to_vec()
+  }
+
+  #[must_use]
+  fn verify_aggregate(&self, signers: &[TestValidatorId], msg: &[u8], sigs: &Vec<Vec<u8>>) -> bool {
+    (signers.len() == sigs.len()) &&
+      signers.iter().zip(sigs.iter()).all(|(signer, sig)| self.verify(*signer, msg, sig))
+  }
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+struct TestBlock(TestBlockId);
+
+impl Block for TestBlock {
+  type Id = TestBlockId;
+
+  fn id(&self) -> TestBlockId {
+    self.0
+  }
+}
+
+type TestSignedMessage = SignedMessage<TestValidatorId, TestBlock, Vec<u8>>;
+type TestEvidence = Evidence<TestValidatorId, TestBlock, Vec<u8>>;
+
+const VALIDATOR: TestValidatorId = 1;
+
+fn sign(
+  sender: TestValidatorId,
+  block: u64,
+  round: u32,
+  data: Data<TestBlock, Vec<u8>>,
+) -> TestSignedMessage {
+  let msg = Message { sender, block: BlockNumber(block), round: RoundNumber(round), data };
+  let sig = [sender.to_le_bytes().as_ref(), &msg.encode()].concat();
+  SignedMessage { msg, sig }
+}
+
+fn prevote(sender: TestValidatorId, block: u64, round: u32, id: u8) -> TestSignedMessage {
+  sign(sender, block, round, Data::Prevote(Some([id; 4])))
+}
+
+fn precommit(sender: TestValidatorId, block: u64, round: u32, id: u8) -> TestSignedMessage {
+  // The precommit's own signature isn't relevant to the evidence
+  sign(sender, block, round, Data::Precommit(Some(([id; 4], vec![]))))
+}
+
+fn verify(evidence: &TestEvidence) -> Option<TestValidatorId> {
+  evidence.verify(&TestSignatureScheme)
+}
+
+#[test]
+fn conflicting_messages() {
+  let evidence =
+    Evidence::ConflictingMessages(prevote(VALIDATOR, 1, 0, 1), prevote(VALIDATOR, 1, 0, 2));
+  assert_eq!(evidence.validator(), VALIDATOR);
+  assert_eq!(verify(&evidence), Some(VALIDATOR));
+
+  // Proposals and precommits conflict as well
+  assert_eq!(
+    verify(&Evidence::ConflictingMessages(
+      sign(VALIDATOR, 1, 0, Data::Proposal(None, TestBlock([1; 4]))),
+      sign(VALIDATOR, 1, 0, Data::Proposal(None, TestBlock([2; 4]))),
+    )),
+    Some(VALIDATOR)
+  );
+  assert_eq!(
+    verify(&Evidence::ConflictingMessages(
+      precommit(VALIDATOR, 1, 0, 1),
+      sign(VALIDATOR, 1, 0, Data::Precommit(None)),
+    )),
+    Some(VALIDATOR)
+  );
+}
+
+#[test]
+fn forged_conflicting_messages() {
+  let first = prevote(VALIDATOR, 1, 0, 1);
+
+  // Identical messages don't conflict
+  assert_eq!(verify(&Evidence::ConflictingMessages(first.clone(), first.clone())), None);
+  // Nor do messages from distinct rounds
+  assert_eq!(
+    verify(&Evidence::ConflictingMessages(first.clone(), prevote(VALIDATOR, 1, 1, 2))),
+    None
+  );
+  // Nor do messages from distinct steps
+  assert_eq!(
+    verify(&Evidence::ConflictingMessages(first.clone(), precommit(VALIDATOR, 1, 0, 2))),
+    None
+  );
+  // Nor do messages from distinct blocks
+  assert_eq!(
+    verify(&Evidence::ConflictingMessages(first.clone(), prevote(VALIDATOR, 2, 0, 2))),
+    None
+  );
+  // Nor do messages from distinct validators
+  assert_eq!(
+    verify(&Evidence::ConflictingMessages(first.clone(), prevote(VALIDATOR + 1, 1, 0, 2))),
+    None
+  );
+
+  // Messages must be validly signed
+  let mut forged = prevote(VALIDATOR, 1, 0, 2);
+  // Signed by another validator
+  forged.sig = [(VALIDATOR + 1).to_le_bytes().as_ref(), &forged.msg.encode()].concat();
+  assert_eq!(verify(&Evidence::ConflictingMessages(first.clone(), forged.clone())), None);
+  assert_eq!(verify(&Evidence::ConflictingMessages(forged, first)), None);
+}
+
+#[test]
+fn conflicting_precommit() {
+  // Precommits for distinct blocks conflict even when from distinct rounds
+  let evidence =
+    Evidence::ConflictingPrecommit(precommit(VALIDATOR, 1, 0, 1), precommit(VALIDATOR, 1, 1, 2));
+  assert_eq!(evidence.validator(), VALIDATOR);
+  assert_eq!(verify(&evidence), Some(VALIDATOR));
+}
+
+#[test]
+fn forged_conflicting_precommit() {
+  let first = precommit(VALIDATOR, 1, 0, 1);
+
+  // Precommits for the same block don't conflict
+  assert_eq!(
+    verify(&Evidence::ConflictingPrecommit(first.clone(), precommit(VALIDATOR, 1, 1, 1))),
+    None
+  );
+  // Nor do precommits for nil
+  assert_eq!(
+    verify(&Evidence::ConflictingPrecommit(
+      first.clone(),
+      sign(VALIDATOR, 1, 1, Data::Precommit(None))
+    )),
+    None
+  );
+  // Nor do messages which aren't precommits
+  assert_eq!(
+    verify(&Evidence::ConflictingPrecommit(first.clone(), prevote(VALIDATOR, 1, 1, 2))),
+    None
+  );
+  // Nor do precommits from distinct blocks
+  assert_eq!(
+    verify(&Evidence::ConflictingPrecommit(first.clone(), precommit(VALIDATOR, 2, 1, 2))),
+    None
+  );
+  // Nor do precommits from distinct validators
+  assert_eq!(
+    verify(&Evidence::ConflictingPrecommit(first.clone(), precommit(VALIDATOR + 1, 1, 1, 2))),
+    None
+  );
+
+  // Precommits must be validly signed
+  let mut forged = precommit(VALIDATOR, 1, 1, 2);
+  forged.sig[0] ^= 1;
+  assert_eq!(verify(&Evidence::ConflictingPrecommit(first.clone(), forged.clone())), None);
+  assert_eq!(verify(&Evidence::ConflictingPrecommit(forged, first)), None);
+}
+
+#[test]
+fn evidence_encoding() {
+  for evidence in [
+    Evidence::ConflictingMessages(prevote(VALIDATOR, 1, 0, 1), prevote(VALIDATOR, 1, 0, 2)),
+    Evidence::ConflictingPrecommit(precommit(VALIDATOR, 1, 0, 1), precommit(VALIDATOR, 1, 1, 2)),
+  ] {
+    let decoded = TestEvidence::decode(&mut evidence.encode().as_ref()).unwrap();
+    assert_eq!(decoded, evidence);
+    assert_eq!(verify(&decoded), Some(VALIDATOR));
+  }
+
+  // Evidence of one kind doesn't verify when re-encoded as the other
+  let mut encoded =
+    Evidence::ConflictingMessages(prevote(VALIDATOR, 1, 0, 1), prevote(VALIDATOR, 1, 0, 2))
+      .encode();
+  encoded[0] = 1;
+  let decoded = TestEvidence::decode(&mut encoded.as_ref()).unwrap();
+  assert!(matches!(decoded, Evidence::ConflictingPrecommit(..)));
+  assert_eq!(verify(&decoded), None);
+}
@@ -11,8 +11,8 @@ use futures::SinkExt;
 use tokio::{sync::RwLock, time::sleep};
 
 use tendermint_machine::{
-  ext::*, SignedMessageFor, SyncedBlockSender, SyncedBlockResultReceiver, MessageSender,
-  TendermintMachine, TendermintHandle,
+  ext::*, SignedMessageFor, EvidenceFor, SyncedBlockSender, SyncedBlockResultReceiver,
+  MessageSender, TendermintMachine, TendermintHandle,
 };
 
 type TestValidatorId = u16;
@@ -48,7 +48,7 @@ impl SignatureScheme for TestSignatureScheme {
     (sig[.. 2] == validator.to_le_bytes()) && (sig[2 ..] == [msg, &[0; 30]].concat()[.. 30])
   }
 
-  fn aggregate(sigs: &[[u8; 32]]) -> Vec<[u8; 32]> {
+  fn aggregate(&self, _: &[TestValidatorId], _: &[u8], sigs: &[[u8; 32]]) -> Vec<[u8; 32]> {
     sigs.to_vec()
   }
 
@@ -131,7 +131,7 @@ impl Network for TestNetwork {
     }
   }
 
-  async fn slash(&mut self, _: TestValidatorId) {
+  async fn slash(&mut self, _: TestValidatorId, _: Option<EvidenceFor<Self>>) {
     dbg!("Slash");
     todo!()
   }
@@ -1,7 +1,5 @@
 use core::{ops::Deref, fmt};
-use std::{io, collections::HashMap};
-
-use thiserror::Error;
+use std_shims::{vec::Vec, string::String, io, collections::BTreeMap};
 
 use zeroize::{Zeroize, Zeroizing};
 use rand_core::{RngCore, CryptoRng};
@@ -329,11 +327,12 @@ fn encryption_key_transcript(context: &str) -> RecommendedTranscript {
   transcript
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Error)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
 pub(crate) enum DecryptionError {
-  #[error("accused provided an invalid signature")]
+  #[cfg_attr(feature = "std", error("accused provided an invalid signature"))]
   InvalidSignature,
-  #[error("accuser provided an invalid decryption key")]
+  #[cfg_attr(feature = "std", error("accuser provided an invalid decryption key"))]
   InvalidProof,
 }
 
@@ -341,10 +340,12 @@ pub(crate) enum DecryptionError {
 #[derive(Clone)]
 pub(crate) struct Encryption<C: Ciphersuite> {
   context: String,
+  // The context messages sent to us are encrypted under, if distinct from our own
+  sender_context: Option<String>,
   i: Participant,
   enc_key: Zeroizing<C::F>,
   enc_pub_key: C::G,
-  enc_keys: HashMap<Participant, C::G>,
+  enc_keys: BTreeMap<Participant, C::G>,
 }
 
 impl<C: Ciphersuite> fmt::Debug for Encryption<C> {
@@ -352,6 +353,7 @@ impl<C: Ciphersuite> fmt::Debug for Encryption<C> {
     fmt
       .debug_struct("Encryption")
       .field("context", &self.context)
+      .field("sender_context", &self.sender_context)
       .field("i", &self.i)
       .field("enc_pub_key", &self.enc_pub_key)
       .field("enc_keys", &self.enc_keys)
@@ -363,7 +365,7 @@ impl<C: Ciphersuite> Zeroize for Encryption<C> {
   fn zeroize(&mut self) {
     self.enc_key.zeroize();
     self.enc_pub_key.zeroize();
-    for (_, mut value) in self.enc_keys.drain() {
+    for (_, mut value) in core::mem::take(&mut self.enc_keys) {
       value.zeroize();
     }
   }
@@ -374,13 +376,25 @@ impl<C: Ciphersuite> Encryption<C> {
     let enc_key = Zeroizing::new(C::random_nonzero_F(rng));
     Self {
       context,
+      sender_context: None,
       i,
       enc_pub_key: C::generator() * enc_key.deref(),
       enc_key,
-      enc_keys: HashMap::new(),
+      enc_keys: BTreeMap::new(),
     }
   }
 
+  // Receive messages encrypted under a distinct context, as used when the senders are indexed
+  // independently of the recipients
+  pub(crate) fn with_sender_context(mut self, context: String) -> Self {
+    self.sender_context = Some(context);
+    self
+  }
+
+  fn sender_context(&self) -> &str {
+    self.sender_context.as_deref().unwrap_or(&self.context)
+  }
+
   pub(crate) fn registration<M: Message>(&self, msg: M) -> EncryptionKeyMessage<C, M> {
     EncryptionKeyMessage { msg, enc_key: self.enc_pub_key }
   }
@@ -421,11 +435,11 @@ impl<C: Ciphersuite> Encryption<C> {
       batch,
       batch_id,
       msg.key,
-      pop_challenge::<C>(&self.context, msg.pop.R, msg.key, from, msg.msg.deref().as_ref()),
+      pop_challenge::<C>(self.sender_context(), msg.pop.R, msg.key, from, msg.msg.deref().as_ref()),
     );
 
     let key = ecdh::<C>(&self.enc_key, msg.key);
-    cipher::<C>(&self.context, &key).apply_keystream(msg.msg.as_mut().as_mut());
+    cipher::<C>(self.sender_context(), &key).apply_keystream(msg.msg.as_mut().as_mut());
     (
       msg.msg,
       EncryptionKeyProof {
@@ -452,7 +466,7 @@ impl<C: Ciphersuite> Encryption<C> {
   ) -> Result<Zeroizing<E>, DecryptionError> {
     if !msg.pop.verify(
       msg.key,
-      pop_challenge::<C>(&self.context, msg.pop.R, msg.key, from, msg.msg.deref().as_ref()),
+      pop_challenge::<C>(self.sender_context(), msg.pop.R, msg.key, from, msg.msg.deref().as_ref()),
     ) {
       Err(DecryptionError::InvalidSignature)?;
     }
@@ -468,7 +482,7 @@ impl<C: Ciphersuite> Encryption<C> {
         )
         .map_err(|_| DecryptionError::InvalidProof)?;
 
-      cipher::<C>(&self.context, &proof.key).apply_keystream(msg.msg.as_mut().as_mut());
+      cipher::<C>(self.sender_context(), &proof.key).apply_keystream(msg.msg.as_mut().as_mut());
       Ok(msg.msg)
     } else {
       Err(DecryptionError::InvalidProof)
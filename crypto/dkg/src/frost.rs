@@ -1,7 +1,9 @@
 use core::{marker::PhantomData, ops::Deref, fmt};
-use std::{
+use std_shims::{
+  vec::Vec,
+  string::String,
   io::{self, Read, Write},
-  collections::HashMap,
+  collections::BTreeMap,
 };
 
 use rand_core::{RngCore, CryptoRng};
@@ -150,7 +152,7 @@ impl<C: Ciphersuite> KeyGenMachine<C> {
   }
 }
 
-fn polynomial<F: PrimeField + Zeroize>(
+pub(crate) fn polynomial<F: PrimeField + Zeroize>(
   coefficients: &[Zeroizing<F>],
   l: Participant,
 ) -> Zeroizing<F> {
@@ -177,7 +179,7 @@ fn polynomial<F: PrimeField + Zeroize>(
 // The encryption system also explicitly uses Zeroizing<M> so it can ensure anything being
 // encrypted is within Zeroizing. Accordingly, internally having Zeroizing would be redundant.
 #[derive(Clone, PartialEq, Eq)]
-pub struct SecretShare<F: PrimeField>(F::Repr);
+pub struct SecretShare<F: PrimeField>(pub(crate) F::Repr);
 impl<F: PrimeField> AsRef<[u8]> for SecretShare<F> {
   fn as_ref(&self) -> &[u8] {
     self.0.as_ref()
@@ -249,8 +251,8 @@ impl<C: Ciphersuite> SecretShareMachine<C> {
   fn verify_r1<R: RngCore + CryptoRng>(
     &mut self,
     rng: &mut R,
-    mut commitments: HashMap<Participant, EncryptionKeyMessage<C, Commitments<C>>>,
-  ) -> Result<HashMap<Participant, Vec<C::G>>, FrostError<C>> {
+    commitments: BTreeMap<Participant, EncryptionKeyMessage<C, Commitments<C>>>,
+  ) -> Result<BTreeMap<Participant, Vec<C::G>>, FrostError<C>> {
     validate_map(
       &commitments,
       &(1 ..= self.params.n()).map(Participant).collect::<Vec<_>>(),
@@ -259,7 +261,7 @@ impl<C: Ciphersuite> SecretShareMachine<C> {
 
     let mut batch = BatchVerifier::<Participant, C::G>::new(commitments.len());
     let mut commitments = commitments
-      .drain()
+      .into_iter()
       .map(|(l, msg)| {
         let mut msg = self.encryption.register(l, msg);
 
@@ -275,7 +277,7 @@ impl<C: Ciphersuite> SecretShareMachine<C> {
 
         (l, msg.commitments.drain(..).collect::<Vec<_>>())
       })
-      .collect::<HashMap<_, _>>();
+      .collect::<BTreeMap<_, _>>();
 
     batch.verify_vartime_with_vartime_blame().map_err(FrostError::InvalidProofOfKnowledge)?;
 
@@ -285,7 +287,7 @@ impl<C: Ciphersuite> SecretShareMachine<C> {
 
   /// Continue generating a key.
   ///
-  /// Takes in everyone else's commitments. Returns a HashMap of encrypted secret shares to be sent
+  /// Takes in everyone else's commitments. Returns a map of encrypted secret shares to be sent
   /// over authenticated channels to their relevant counterparties.
   ///
   /// If any participant sends multiple secret shares to another participant, they are faulty.
@@ -293,15 +295,15 @@ impl<C: Ciphersuite> SecretShareMachine<C> {
   pub fn generate_secret_shares<R: RngCore + CryptoRng>(
     mut self,
     rng: &mut R,
-    commitments: HashMap<Participant, EncryptionKeyMessage<C, Commitments<C>>>,
+    commitments: BTreeMap<Participant, EncryptionKeyMessage<C, Commitments<C>>>,
   ) -> Result<
-    (KeyMachine<C>, HashMap<Participant, EncryptedMessage<C, SecretShare<C::F>>>),
+    (KeyMachine<C>, BTreeMap<Participant, EncryptedMessage<C, SecretShare<C::F>>>),
     FrostError<C>,
   > {
     let commitments = self.verify_r1(&mut *rng, commitments)?;
 
     // Step 1: Generate secret shares for all other parties
-    let mut res = HashMap::new();
+    let mut res = BTreeMap::new();
     for l in (1 ..= self.params.n()).map(Participant) {
       // Don't insert our own shares to the byte buffer which is meant to be sent around
       // An app developer could accidentally send it. Best to keep this black boxed
@@ -334,7 +336,7 @@ impl<C: Ciphersuite> SecretShareMachine<C> {
 pub struct KeyMachine<C: Ciphersuite> {
   params: ThresholdParams,
   secret: Zeroizing<C::F>,
-  commitments: HashMap<Participant, Vec<C::G>>,
+  commitments: BTreeMap<Participant, Vec<C::G>>,
   encryption: Encryption<C>,
 }
 
@@ -363,7 +365,7 @@ impl<C: Ciphersuite> Zeroize for KeyMachine<C> {
 // Calculate the exponent for a given participant and apply it to a series of commitments
 // Initially used with the actual commitments to verify the secret share, later used with
 // stripes to generate the verification shares
-fn exponential<C: Ciphersuite>(i: Participant, values: &[C::G]) -> Vec<(C::F, C::G)> {
+pub(crate) fn exponential<C: Ciphersuite>(i: Participant, values: &[C::G]) -> Vec<(C::F, C::G)> {
   let i = C::F::from(u16::from(i).into());
   let mut res = Vec::with_capacity(values.len());
   (0 .. values.len()).fold(C::F::ONE, |exp, l| {
@@ -373,7 +375,7 @@ fn exponential<C: Ciphersuite>(i: Participant, values: &[C::G]) -> Vec<(C::F, C:
   res
 }
 
-fn share_verification_statements<C: Ciphersuite>(
+pub(crate) fn share_verification_statements<C: Ciphersuite>(
   target: Participant,
   commitments: &[C::G],
   mut share: Zeroizing<C::F>,
@@ -395,7 +397,7 @@ fn share_verification_statements<C: Ciphersuite>(
 }
 
 #[derive(Clone, Copy, Hash, Debug, Zeroize)]
-enum BatchId {
+pub(crate) enum BatchId {
   Decryption(Participant),
   Share(Participant),
 }
@@ -409,7 +411,7 @@ impl<C: Ciphersuite> KeyMachine<C> {
   pub fn calculate_share<R: RngCore + CryptoRng>(
     mut self,
     rng: &mut R,
-    mut shares: HashMap<Participant, EncryptedMessage<C, SecretShare<C::F>>>,
+    shares: BTreeMap<Participant, EncryptedMessage<C, SecretShare<C::F>>>,
   ) -> Result<BlameMachine<C>, FrostError<C>> {
     validate_map(
       &shares,
@@ -418,8 +420,8 @@ impl<C: Ciphersuite> KeyMachine<C> {
     )?;
 
     let mut batch = BatchVerifier::new(shares.len());
-    let mut blames = HashMap::new();
-    for (l, share_bytes) in shares.drain() {
+    let mut blames = BTreeMap::new();
+    for (l, share_bytes) in shares.into_iter() {
       let (mut share_bytes, blame) =
         self.encryption.decrypt(rng, &mut batch, BatchId::Decryption(l), l, share_bytes);
       let share =
@@ -454,7 +456,7 @@ impl<C: Ciphersuite> KeyMachine<C> {
     }
 
     // Calculate each user's verification share
-    let mut verification_shares = HashMap::new();
+    let mut verification_shares = BTreeMap::new();
     for i in (1 ..= self.params.n()).map(Participant) {
       verification_shares.insert(
         i,
@@ -482,9 +484,9 @@ impl<C: Ciphersuite> KeyMachine<C> {
 
 /// A machine capable of handling blame proofs.
 pub struct BlameMachine<C: Ciphersuite> {
-  commitments: HashMap<Participant, Vec<C::G>>,
-  encryption: Encryption<C>,
-  result: ThresholdCore<C>,
+  pub(crate) commitments: BTreeMap<Participant, Vec<C::G>>,
+  pub(crate) encryption: Encryption<C>,
+  pub(crate) result: ThresholdCore<C>,
 }
 
 impl<C: Ciphersuite> fmt::Debug for BlameMachine<C> {
@@ -1,13 +1,15 @@
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 #![doc = include_str!("../README.md")]
+#![cfg_attr(not(feature = "std"), no_std)]
 
 use core::{
   fmt::{self, Debug},
   ops::Deref,
 };
-use std::{io, sync::Arc, collections::HashMap};
-
-use thiserror::Error;
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
+use std_shims::{vec::Vec, io, sync::Arc, collections::BTreeMap};
 
 use zeroize::{Zeroize, Zeroizing};
 
@@ -29,6 +31,15 @@ pub mod frost;
 /// Promote keys between ciphersuites.
 pub mod promote;
 
+/// Refresh the shares of existing keys without changing the group key.
+pub mod refresh;
+
+/// Reshare existing keys to a new set of participants, with a new threshold.
+pub mod resharing;
+
+/// Authenticated encryption of keys for storage at rest.
+pub mod storage;
+
 /// Tests for application-provided curves and algorithms.
 #[cfg(any(test, feature = "tests"))]
 pub mod tests;
@@ -67,42 +78,50 @@ impl fmt::Display for Participant {
 }
 
 /// Various errors possible during key generation.
-#[derive(Clone, PartialEq, Eq, Debug, Error)]
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
 pub enum DkgError<B: Clone + PartialEq + Eq + Debug> {
   /// A parameter was zero.
-  #[error("a parameter was 0 (threshold {0}, participants {1})")]
+  #[cfg_attr(feature = "std", error("a parameter was 0 (threshold {0}, participants {1})"))]
   ZeroParameter(u16, u16),
   /// The threshold exceeded the amount of participants.
-  #[error("invalid threshold (max {1}, got {0})")]
+  #[cfg_attr(feature = "std", error("invalid threshold (max {1}, got {0})"))]
   InvalidThreshold(u16, u16),
   /// Invalid participant identifier.
-  #[error("invalid participant (0 < participant <= {0}, yet participant is {1})")]
+  #[cfg_attr(
+    feature = "std",
+    error("invalid participant (0 < participant <= {0}, yet participant is {1})")
+  )]
   InvalidParticipant(u16, Participant),
 
   /// Invalid signing set.
-  #[error("invalid signing set")]
+  #[cfg_attr(feature = "std", error("invalid signing set"))]
   InvalidSigningSet,
   /// Invalid amount of participants.
-  #[error("invalid participant quantity (expected {0}, got {1})")]
+  #[cfg_attr(feature = "std", error("invalid participant quantity (expected {0}, got {1})"))]
   InvalidParticipantQuantity(usize, usize),
   /// A participant was duplicated.
-  #[error("duplicated participant ({0})")]
+  #[cfg_attr(feature = "std", error("duplicated participant ({0})"))]
   DuplicatedParticipant(Participant),
   /// A participant was missing.
-  #[error("missing participant {0}")]
+  #[cfg_attr(feature = "std", error("missing participant {0}"))]
   MissingParticipant(Participant),
 
+  /// The group key didn't match the expected group key.
+  #[cfg_attr(feature = "std", error("the group key didn't match the expected group key"))]
+  InvalidGroupKey,
+
   /// An invalid proof of knowledge was provided.
-  #[error("invalid proof of knowledge (participant {0})")]
+  #[cfg_attr(feature = "std", error("invalid proof of knowledge (participant {0})"))]
   InvalidProofOfKnowledge(Participant),
   /// An invalid DKG share was provided.
-  #[error("invalid share (participant {participant}, blame {blame})")]
+  #[cfg_attr(feature = "std", error("invalid share (participant {participant}, blame {blame})"))]
   InvalidShare { participant: Participant, blame: Option<B> },
 }
 
 // Validate a map of values to have the expected included participants
 pub(crate) fn validate_map<T, B: Clone + PartialEq + Eq + Debug>(
-  map: &HashMap<Participant, T>,
+  map: &BTreeMap<Participant, T>,
   included: &[Participant],
   ours: Participant,
 ) -> Result<(), DkgError<B>> {
@@ -205,7 +224,7 @@ pub struct ThresholdCore<C: Ciphersuite> {
   /// Group key.
   group_key: C::G,
   /// Verification shares.
-  verification_shares: HashMap<Participant, C::G>,
+  verification_shares: BTreeMap<Participant, C::G>,
 }
 
 impl<C: Ciphersuite> fmt::Debug for ThresholdCore<C> {
@@ -234,7 +253,7 @@ impl<C: Ciphersuite> ThresholdCore<C> {
   pub(crate) fn new(
     params: ThresholdParams,
     secret_share: Zeroizing<C::F>,
-    verification_shares: HashMap<Participant, C::G>,
+    verification_shares: BTreeMap<Participant, C::G>,
   ) -> ThresholdCore<C> {
     let t = (1 ..= params.t).map(Participant).collect::<Vec<_>>();
     ThresholdCore {
@@ -260,7 +279,8 @@ impl<C: Ciphersuite> ThresholdCore<C> {
     self.group_key
   }
 
-  pub(crate) fn verification_shares(&self) -> HashMap<Participant, C::G> {
+  /// Verification shares for these keys.
+  pub fn verification_shares(&self) -> BTreeMap<Participant, C::G> {
     self.verification_shares.clone()
   }
 
@@ -323,7 +343,7 @@ impl<C: Ciphersuite> ThresholdCore<C> {
 
     let secret_share = Zeroizing::new(C::read_F(reader)?);
 
-    let mut verification_shares = HashMap::new();
+    let mut verification_shares = BTreeMap::new();
     for l in (1 ..= n).map(Participant) {
       verification_shares.insert(l, <C as Ciphersuite>::read_G(reader)?);
     }
@@ -357,8 +377,8 @@ pub struct ThresholdView<C: Ciphersuite> {
   group_key: C::G,
   included: Vec<Participant>,
   secret_share: Zeroizing<C::F>,
-  original_verification_shares: HashMap<Participant, C::G>,
-  verification_shares: HashMap<Participant, C::G>,
+  original_verification_shares: BTreeMap<Participant, C::G>,
+  verification_shares: BTreeMap<Participant, C::G>,
 }
 
 impl<C: Ciphersuite> fmt::Debug for ThresholdView<C> {
@@ -430,7 +450,7 @@ impl<C: Ciphersuite> ThresholdKeys<C> {
   }
 
   /// Return all participants' verification shares without any offsetting.
-  pub(crate) fn verification_shares(&self) -> HashMap<Participant, C::G> {
+  pub fn verification_shares(&self) -> BTreeMap<Participant, C::G> {
     self.core.verification_shares()
   }
 
@@ -441,46 +461,75 @@ impl<C: Ciphersuite> ThresholdKeys<C> {
 
   /// Obtain a view of these keys, with any offset applied, interpolated for the specified signing
   /// set.
-  pub fn view(&self, mut included: Vec<Participant>) -> Result<ThresholdView<C>, DkgError<()>> {
-    if (included.len() < self.params().t.into()) || (usize::from(self.params().n) < included.len())
-    {
+  pub fn view(&self, included: Vec<Participant>) -> Result<ThresholdView<C>, DkgError<()>> {
+    let mut view = ThresholdView::public(
+      self.params(),
+      self.core.group_key,
+      self.verification_shares(),
+      self.offset,
+      included,
+    )?;
+
+    *view.secret_share =
+      lagrange::<C::F>(self.params().i, &view.included) * self.secret_share().deref();
+    // The offset is included by adding it to the participant with the lowest ID
+    if view.included[0] == self.params().i() {
+      *view.secret_share += view.offset;
+    }
+
+    Ok(view)
+  }
+}
+
+impl<C: Ciphersuite> From<ThresholdCore<C>> for ThresholdKeys<C> {
+  fn from(keys: ThresholdCore<C>) -> ThresholdKeys<C> {
+    ThresholdKeys::new(keys)
+  }
+}
+
+impl<C: Ciphersuite> ThresholdView<C> {
+  /// Obtain a view of a multisig from its public components, with the offset applied,
+  /// interpolated for the specified signing set.
+  ///
+  /// The group key and verification shares should be without any offset. As this view is for
+  /// parties who verify signature shares without holding a key share themselves, its secret share
+  /// is zero.
+  pub fn public(
+    params: ThresholdParams,
+    group_key: C::G,
+    mut verification_shares: BTreeMap<Participant, C::G>,
+    offset: Option<C::F>,
+    mut included: Vec<Participant>,
+  ) -> Result<ThresholdView<C>, DkgError<()>> {
+    if (included.len() < params.t.into()) || (usize::from(params.n) < included.len()) {
       Err(DkgError::InvalidSigningSet)?;
     }
     included.sort();
+    for l in &included {
+      if !verification_shares.contains_key(l) {
+        Err(DkgError::InvalidSigningSet)?;
+      }
+    }
 
-    let mut secret_share =
-      Zeroizing::new(lagrange::<C::F>(self.params().i, &included) * self.secret_share().deref());
-
-    let mut verification_shares = self.verification_shares();
+    let original_verification_shares = verification_shares.clone();
     for (i, share) in verification_shares.iter_mut() {
       *share *= lagrange::<C::F>(*i, &included);
     }
 
     // The offset is included by adding it to the participant with the lowest ID
-    let offset = self.offset.unwrap_or(C::F::ZERO);
-    if included[0] == self.params().i() {
-      *secret_share += offset;
-    }
+    let offset = offset.unwrap_or(C::F::ZERO);
     *verification_shares.get_mut(&included[0]).unwrap() += C::generator() * offset;
 
     Ok(ThresholdView {
       offset,
-      group_key: self.group_key(),
-      secret_share,
-      original_verification_shares: self.verification_shares(),
+      group_key: group_key + (C::generator() * offset),
+      secret_share: Zeroizing::new(C::F::ZERO),
+      original_verification_shares,
       verification_shares,
       included,
     })
   }
-}
 
-impl<C: Ciphersuite> From<ThresholdCore<C>> for ThresholdKeys<C> {
-  fn from(keys: ThresholdCore<C>) -> ThresholdKeys<C> {
-    ThresholdKeys::new(keys)
-  }
-}
-
-impl<C: Ciphersuite> ThresholdView<C> {
   /// Return the offset for this view.
   pub fn offset(&self) -> C::F {
     self.offset
@@ -1,8 +1,9 @@
 use core::{marker::PhantomData, ops::Deref};
-use std::{
+use std_shims::{
+  vec::Vec,
   io::{self, Read, Write},
   sync::Arc,
-  collections::HashMap,
+  collections::BTreeMap,
 };
 
 use rand_core::{RngCore, CryptoRng};
@@ -97,14 +98,14 @@ where
   /// Complete promotion by taking in the proofs from all other participants.
   pub fn complete(
     self,
-    proofs: &HashMap<Participant, GeneratorProof<C1>>,
+    proofs: &BTreeMap<Participant, GeneratorProof<C1>>,
   ) -> Result<ThresholdKeys<C2>, DkgError<()>> {
     let params = self.base.params();
     validate_map(proofs, &(1 ..= params.n).map(Participant).collect::<Vec<_>>(), params.i)?;
 
     let original_shares = self.base.verification_shares();
 
-    let mut verification_shares = HashMap::new();
+    let mut verification_shares = BTreeMap::new();
     verification_shares.insert(params.i, self.proof.share);
     for (i, proof) in proofs {
       let i = *i;
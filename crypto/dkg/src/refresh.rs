@@ -0,0 +1,420 @@
+use core::{ops::Deref, fmt};
+use std_shims::{
+  vec::Vec,
+  string::String,
+  io::{self, Read, Write},
+  collections::BTreeMap,
+};
+
+use rand_core::{RngCore, CryptoRng};
+
+use zeroize::{Zeroize, Zeroizing};
+
+use transcript::{Transcript, RecommendedTranscript};
+
+use ciphersuite::{
+  group::{
+    ff::{Field, PrimeField},
+    Group, GroupEncoding,
+  },
+  Ciphersuite,
+};
+use multiexp::{multiexp_vartime, BatchVerifier};
+
+use schnorr::SchnorrSignature;
+
+use crate::{
+  Participant, DkgError, ThresholdParams, ThresholdCore, validate_map,
+  encryption::{ReadWrite, EncryptionKeyMessage, EncryptedMessage, Encryption, EncryptionKeyProof},
+  frost::{
+    SecretShare, BlameMachine, BatchId, polynomial, exponential, share_verification_statements,
+  },
+};
+
+type RefreshError<C> = DkgError<EncryptionKeyProof<C>>;
+
+#[allow(non_snake_case)]
+fn challenge<C: Ciphersuite>(
+  context: &str,
+  group_key: C::G,
+  l: Participant,
+  R: &[u8],
+  Am: &[u8],
+) -> C::F {
+  let mut transcript = RecommendedTranscript::new(b"DKG Refresh v0.2");
+  transcript.domain_separate(b"share_proof_of_knowledge");
+  transcript.append_message(b"context", context.as_bytes());
+  transcript.append_message(b"group_key", group_key.to_bytes());
+  transcript.append_message(b"participant", l.to_bytes());
+  transcript.append_message(b"nonce", R);
+  transcript.append_message(b"commitments", Am);
+  C::hash_to_F(b"DKG-Refresh-proof_of_knowledge-0", &transcript.challenge(b"schnorr"))
+}
+
+/// The commitments message for a refresh, intended to be broadcast to all other parties.
+///
+/// This commits to a polynomial whose constant term is zero, so the constant term's commitment
+/// is omitted. Instead of a proof of knowledge for the constant term, the commitments are signed
+/// with the participant's existing secret share.
+///
+/// Every participant should only provide one set of commitments to all parties. If any
+/// participant sends multiple sets of commitments, they are faulty and should be presumed
+/// malicious. As this library does not handle networking, it is unable to detect if any
+/// participant is so faulty. That responsibility lies with the caller.
+#[derive(Clone, PartialEq, Eq, Debug, Zeroize)]
+pub struct RefreshCommitments<C: Ciphersuite> {
+  commitments: Vec<C::G>,
+  cached_msg: Vec<u8>,
+  sig: SchnorrSignature<C>,
+}
+
+impl<C: Ciphersuite> ReadWrite for RefreshCommitments<C> {
+  fn read<R: Read>(reader: &mut R, params: ThresholdParams) -> io::Result<Self> {
+    let mut commitments = Vec::with_capacity(usize::from(params.t()) - 1);
+    let mut cached_msg = vec![];
+
+    #[allow(non_snake_case)]
+    let mut read_G = || -> io::Result<C::G> {
+      let mut buf = <C::G as GroupEncoding>::Repr::default();
+      reader.read_exact(buf.as_mut())?;
+      let point = C::read_G(&mut buf.as_ref())?;
+      cached_msg.extend(buf.as_ref());
+      Ok(point)
+    };
+
+    for _ in 1 .. params.t() {
+      commitments.push(read_G()?);
+    }
+
+    Ok(RefreshCommitments { commitments, cached_msg, sig: SchnorrSignature::read(reader)? })
+  }
+
+  fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    writer.write_all(&self.cached_msg)?;
+    self.sig.write(writer)
+  }
+}
+
+/// State machine to begin refreshing a set of keys.
+///
+/// Refreshing has every participant share a random polynomial whose constant term is zero. Adding
+/// the resulting shares to the existing shares produces new shares, and new verification shares,
+/// for the same group key. Shares from before the refresh cannot be combined with shares from
+/// after it, so a refresh recovers from the leakage of less than t shares.
+///
+/// All n participants must partake in a refresh. If any participant is unavailable, the refresh
+/// must be aborted.
+pub struct RefreshMachine<C: Ciphersuite> {
+  keys: ThresholdCore<C>,
+  context: String,
+}
+
+impl<C: Ciphersuite> fmt::Debug for RefreshMachine<C> {
+  fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt
+      .debug_struct("RefreshMachine")
+      .field("keys", &self.keys)
+      .field("context", &self.context)
+      .finish_non_exhaustive()
+  }
+}
+
+impl<C: Ciphersuite> Zeroize for RefreshMachine<C> {
+  fn zeroize(&mut self) {
+    self.keys.zeroize();
+    self.context.zeroize();
+  }
+}
+
+impl<C: Ciphersuite> RefreshMachine<C> {
+  /// Create a new machine to refresh a set of keys.
+  ///
+  /// The context string should be unique among refreshes, and distinct from the context used when
+  /// generating the keys.
+  pub fn new(keys: ThresholdCore<C>, context: String) -> RefreshMachine<C> {
+    RefreshMachine { keys, context }
+  }
+
+  /// Start refreshing the keys.
+  ///
+  /// Returns a commitments message to be sent to all parties over an authenticated channel. If any
+  /// party submits multiple sets of commitments, they MUST be treated as malicious.
+  pub fn generate_coefficients<R: RngCore + CryptoRng>(
+    self,
+    rng: &mut R,
+  ) -> (RefreshShareMachine<C>, EncryptionKeyMessage<C, RefreshCommitments<C>>) {
+    let params = self.keys.params();
+    let t = usize::from(params.t());
+    let mut coefficients = Vec::with_capacity(t);
+    let mut commitments = Vec::with_capacity(t);
+    let mut cached_msg = vec![];
+
+    // The constant term is zero, so the group key is left unchanged
+    coefficients.push(Zeroizing::new(C::F::ZERO));
+    commitments.push(C::G::identity());
+    for i in 1 .. t {
+      coefficients.push(Zeroizing::new(C::random_nonzero_F(&mut *rng)));
+      commitments.push(C::generator() * coefficients[i].deref());
+      cached_msg.extend(commitments[i].to_bytes().as_ref());
+    }
+
+    // Sign the commitments with our existing share, proving we're a member of this multisig
+    let r = Zeroizing::new(C::random_nonzero_F(&mut *rng));
+    let nonce = C::generator() * r.deref();
+    let sig = SchnorrSignature::<C>::sign(
+      self.keys.secret_share(),
+      r,
+      challenge::<C>(
+        &self.context,
+        self.keys.group_key(),
+        params.i(),
+        nonce.to_bytes().as_ref(),
+        &cached_msg,
+      ),
+    );
+
+    let encryption = Encryption::new(self.context.clone(), params.i(), rng);
+
+    let msg = encryption.registration(RefreshCommitments {
+      commitments: commitments[1 ..].to_vec(),
+      cached_msg,
+      sig,
+    });
+    (
+      RefreshShareMachine {
+        keys: self.keys,
+        context: self.context,
+        coefficients,
+        our_commitments: commitments,
+        encryption,
+      },
+      msg,
+    )
+  }
+}
+
+/// Advancement of the refresh state machine.
+pub struct RefreshShareMachine<C: Ciphersuite> {
+  keys: ThresholdCore<C>,
+  context: String,
+  coefficients: Vec<Zeroizing<C::F>>,
+  our_commitments: Vec<C::G>,
+  encryption: Encryption<C>,
+}
+
+impl<C: Ciphersuite> fmt::Debug for RefreshShareMachine<C> {
+  fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt
+      .debug_struct("RefreshShareMachine")
+      .field("keys", &self.keys)
+      .field("context", &self.context)
+      .field("our_commitments", &self.our_commitments)
+      .field("encryption", &self.encryption)
+      .finish_non_exhaustive()
+  }
+}
+
+impl<C: Ciphersuite> Zeroize for RefreshShareMachine<C> {
+  fn zeroize(&mut self) {
+    self.keys.zeroize();
+    self.context.zeroize();
+    self.coefficients.zeroize();
+    self.our_commitments.zeroize();
+    self.encryption.zeroize();
+  }
+}
+
+impl<C: Ciphersuite> RefreshShareMachine<C> {
+  /// Verify the data from the previous round (canonicity, signatures, message authenticity)
+  #[allow(clippy::type_complexity)]
+  fn verify_r1<R: RngCore + CryptoRng>(
+    &mut self,
+    rng: &mut R,
+    commitments: BTreeMap<Participant, EncryptionKeyMessage<C, RefreshCommitments<C>>>,
+  ) -> Result<BTreeMap<Participant, Vec<C::G>>, RefreshError<C>> {
+    let params = self.keys.params();
+    validate_map(
+      &commitments,
+      &(1 ..= params.n()).map(Participant).collect::<Vec<_>>(),
+      params.i(),
+    )?;
+
+    let verification_shares = self.keys.verification_shares();
+    let mut batch = BatchVerifier::<Participant, C::G>::new(commitments.len());
+    let mut commitments = commitments
+      .into_iter()
+      .map(|(l, msg)| {
+        let msg = self.encryption.register(l, msg);
+
+        msg.sig.batch_verify(
+          rng,
+          &mut batch,
+          l,
+          verification_shares[&l],
+          challenge::<C>(
+            &self.context,
+            self.keys.group_key(),
+            l,
+            msg.sig.R.to_bytes().as_ref(),
+            &msg.cached_msg,
+          ),
+        );
+
+        // Restore the omitted commitment to the zero constant term
+        let mut full = Vec::with_capacity(usize::from(params.t()));
+        full.push(C::G::identity());
+        full.extend(&msg.commitments);
+        (l, full)
+      })
+      .collect::<BTreeMap<_, _>>();
+
+    batch.verify_vartime_with_vartime_blame().map_err(RefreshError::InvalidProofOfKnowledge)?;
+
+    commitments.insert(params.i(), self.our_commitments.drain(..).collect());
+    Ok(commitments)
+  }
+
+  /// Continue refreshing the keys.
+  ///
+  /// Takes in everyone else's commitments. Returns a map of encrypted secret shares to be sent
+  /// over authenticated channels to their relevant counterparties.
+  ///
+  /// If any participant sends multiple secret shares to another participant, they are faulty.
+  #[allow(clippy::type_complexity)]
+  pub fn generate_secret_shares<R: RngCore + CryptoRng>(
+    mut self,
+    rng: &mut R,
+    commitments: BTreeMap<Participant, EncryptionKeyMessage<C, RefreshCommitments<C>>>,
+  ) -> Result<
+    (RefreshKeyMachine<C>, BTreeMap<Participant, EncryptedMessage<C, SecretShare<C::F>>>),
+    RefreshError<C>,
+  > {
+    let commitments = self.verify_r1(&mut *rng, commitments)?;
+
+    let params = self.keys.params();
+    let mut res = BTreeMap::new();
+    for l in (1 ..= params.n()).map(Participant) {
+      if l == params.i() {
+        continue;
+      }
+
+      let mut share = polynomial(&self.coefficients, l);
+      let share_bytes = Zeroizing::new(SecretShare::<C::F>(share.to_repr()));
+      share.zeroize();
+      res.insert(l, self.encryption.encrypt(rng, l, share_bytes));
+    }
+
+    // Add our own share of zero to our existing share
+    let mut secret = polynomial(&self.coefficients, params.i());
+    *secret += self.keys.secret_share().deref();
+    self.coefficients.zeroize();
+
+    let RefreshShareMachine { keys, encryption, .. } = self;
+    Ok((RefreshKeyMachine { keys, secret, commitments, encryption }, res))
+  }
+}
+
+/// Advancement of the refresh secret share state machine.
+///
+/// This machine will 'complete' the protocol, by a local perspective. In order to be secure,
+/// the parties must confirm having successfully completed the protocol (an effort out of scope to
+/// this library), yet this is modeled by one more state transition (BlameMachine).
+pub struct RefreshKeyMachine<C: Ciphersuite> {
+  keys: ThresholdCore<C>,
+  secret: Zeroizing<C::F>,
+  commitments: BTreeMap<Participant, Vec<C::G>>,
+  encryption: Encryption<C>,
+}
+
+impl<C: Ciphersuite> fmt::Debug for RefreshKeyMachine<C> {
+  fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt
+      .debug_struct("RefreshKeyMachine")
+      .field("keys", &self.keys)
+      .field("commitments", &self.commitments)
+      .field("encryption", &self.encryption)
+      .finish_non_exhaustive()
+  }
+}
+
+impl<C: Ciphersuite> Zeroize for RefreshKeyMachine<C> {
+  fn zeroize(&mut self) {
+    self.keys.zeroize();
+    self.secret.zeroize();
+    for (_, commitments) in self.commitments.iter_mut() {
+      commitments.zeroize();
+    }
+    self.encryption.zeroize();
+  }
+}
+
+impl<C: Ciphersuite> RefreshKeyMachine<C> {
+  /// Calculate our refreshed share given the shares sent to us.
+  ///
+  /// Returns a BlameMachine usable to determine if faults in the protocol occurred.
+  ///
+  /// This will error on, and return a blame proof for, the first-observed case of faulty behavior.
+  pub fn calculate_share<R: RngCore + CryptoRng>(
+    mut self,
+    rng: &mut R,
+    shares: BTreeMap<Participant, EncryptedMessage<C, SecretShare<C::F>>>,
+  ) -> Result<BlameMachine<C>, RefreshError<C>> {
+    let params = self.keys.params();
+    validate_map(&shares, &(1 ..= params.n()).map(Participant).collect::<Vec<_>>(), params.i())?;
+
+    let mut batch = BatchVerifier::new(shares.len());
+    let mut blames = BTreeMap::new();
+    for (l, share_bytes) in shares.into_iter() {
+      let (mut share_bytes, blame) =
+        self.encryption.decrypt(rng, &mut batch, BatchId::Decryption(l), l, share_bytes);
+      let share =
+        Zeroizing::new(Option::<C::F>::from(C::F::from_repr(share_bytes.0)).ok_or_else(|| {
+          RefreshError::InvalidShare { participant: l, blame: Some(blame.clone()) }
+        })?);
+      share_bytes.zeroize();
+      *self.secret += share.deref();
+
+      blames.insert(l, blame);
+      batch.queue(
+        rng,
+        BatchId::Share(l),
+        share_verification_statements::<C>(params.i(), &self.commitments[&l], share),
+      );
+    }
+    batch.verify_with_vartime_blame().map_err(|id| {
+      let (l, blame) = match id {
+        BatchId::Decryption(l) => (l, None),
+        BatchId::Share(l) => (l, Some(blames.remove(&l).unwrap())),
+      };
+      RefreshError::InvalidShare { participant: l, blame }
+    })?;
+
+    // Stripe the commitments, as done during key generation
+    let mut stripes = Vec::with_capacity(usize::from(params.t()));
+    for t in 0 .. usize::from(params.t()) {
+      stripes.push(self.commitments.values().map(|commitments| commitments[t]).sum());
+    }
+
+    // Each verification share is offset by that participant's share of zero
+    let mut verification_shares = self.keys.verification_shares();
+    for (i, share) in verification_shares.iter_mut() {
+      *share = if *i == params.i() {
+        C::generator() * self.secret.deref()
+      } else {
+        *share + multiexp_vartime(&exponential::<C>(*i, &stripes))
+      };
+    }
+
+    let RefreshKeyMachine { keys, secret, commitments, encryption } = self;
+    Ok(BlameMachine {
+      commitments,
+      encryption,
+      result: ThresholdCore {
+        params,
+        secret_share: secret,
+        group_key: keys.group_key(),
+        verification_shares,
+      },
+    })
+  }
+}
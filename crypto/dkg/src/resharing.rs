@@ -0,0 +1,505 @@
+use core::{ops::Deref, fmt};
+use std_shims::{
+  vec::Vec,
+  string::String,
+  io::{self, Read, Write},
+  collections::BTreeMap,
+};
+
+use rand_core::{RngCore, CryptoRng};
+
+use zeroize::{Zeroize, Zeroizing};
+
+use transcript::{Transcript, RecommendedTranscript};
+
+use ciphersuite::{
+  group::{
+    ff::{Field, PrimeField},
+    GroupEncoding,
+  },
+  Ciphersuite,
+};
+use multiexp::{multiexp_vartime, BatchVerifier};
+
+use schnorr::SchnorrSignature;
+
+use crate::{
+  Participant, DkgError, ThresholdParams, ThresholdCore, ThresholdKeys, validate_map,
+  encryption::{ReadWrite, EncryptionKeyMessage, EncryptedMessage, Encryption, EncryptionKeyProof},
+  frost::{
+    SecretShare, BlameMachine, BatchId, polynomial, exponential, share_verification_statements,
+  },
+};
+
+type ResharingError<C> = DkgError<EncryptionKeyProof<C>>;
+
+#[allow(non_snake_case)]
+fn challenge<C: Ciphersuite>(context: &str, l: Participant, R: &[u8], Am: &[u8]) -> C::F {
+  let mut transcript = RecommendedTranscript::new(b"DKG Resharing v0.2");
+  transcript.domain_separate(b"schnorr_proof_of_knowledge");
+  transcript.append_message(b"context", context.as_bytes());
+  transcript.append_message(b"dealer", l.to_bytes());
+  transcript.append_message(b"nonce", R);
+  transcript.append_message(b"commitments", Am);
+  C::hash_to_F(b"DKG-Resharing-proof_of_knowledge-0", &transcript.challenge(b"schnorr"))
+}
+
+// The dealers and the new participants are indexed independently, so the encryption keys and
+// messages of each are bound to distinct contexts
+pub(crate) fn dealer_context(context: &str) -> String {
+  format!("{context} (dealers)")
+}
+
+fn participant_context(context: &str) -> String {
+  format!("{context} (new participants)")
+}
+
+// Validate a map of values to have exactly the expected participants
+fn validate_exact_map<T, B: Clone + PartialEq + Eq + fmt::Debug>(
+  map: &BTreeMap<Participant, T>,
+  included: impl ExactSizeIterator<Item = Participant>,
+) -> Result<(), DkgError<B>> {
+  if map.len() != included.len() {
+    Err(DkgError::InvalidParticipantQuantity(included.len(), map.len()))?;
+  }
+  for included in included {
+    if !map.contains_key(&included) {
+      Err(DkgError::MissingParticipant(included))?;
+    }
+  }
+  Ok(())
+}
+
+/// The commitments message from a dealer, intended to be broadcast to all new participants.
+///
+/// The commitment to the constant term is omitted, as it's the dealer's interpolated verification
+/// share under the existing keys.
+///
+/// Every dealer should only provide one set of commitments to all parties. If any dealer sends
+/// multiple sets of commitments, they are faulty and should be presumed malicious. As this library
+/// does not handle networking, it is unable to detect if any dealer is so faulty. That
+/// responsibility lies with the caller.
+#[derive(Clone, PartialEq, Eq, Debug, Zeroize)]
+pub struct ResharingCommitments<C: Ciphersuite> {
+  commitments: Vec<C::G>,
+  cached_msg: Vec<u8>,
+  sig: SchnorrSignature<C>,
+}
+
+impl<C: Ciphersuite> ResharingCommitments<C> {
+  /// Read a commitments message, where the parameters are those of the new participants.
+  pub fn read<R: Read>(reader: &mut R, params: ThresholdParams) -> io::Result<Self> {
+    let mut commitments = Vec::with_capacity(usize::from(params.t()) - 1);
+    let mut cached_msg = vec![];
+
+    #[allow(non_snake_case)]
+    let mut read_G = || -> io::Result<C::G> {
+      let mut buf = <C::G as GroupEncoding>::Repr::default();
+      reader.read_exact(buf.as_mut())?;
+      let point = C::read_G(&mut buf.as_ref())?;
+      cached_msg.extend(buf.as_ref());
+      Ok(point)
+    };
+
+    for _ in 1 .. params.t() {
+      commitments.push(read_G()?);
+    }
+
+    Ok(ResharingCommitments { commitments, cached_msg, sig: SchnorrSignature::read(reader)? })
+  }
+
+  /// Write a commitments message.
+  pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    writer.write_all(&self.cached_msg)?;
+    self.sig.write(writer)
+  }
+
+  /// Serialize a commitments message to a `Vec<u8>`.
+  pub fn serialize(&self) -> Vec<u8> {
+    let mut buf = vec![];
+    self.write(&mut buf).unwrap();
+    buf
+  }
+}
+
+/// The registration message from a new participant, only used to carry their encryption key.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ResharingRegistration;
+
+impl Zeroize for ResharingRegistration {
+  fn zeroize(&mut self) {}
+}
+
+impl ReadWrite for ResharingRegistration {
+  fn read<R: Read>(_: &mut R, _: ThresholdParams) -> io::Result<Self> {
+    Ok(ResharingRegistration)
+  }
+
+  fn write<W: Write>(&self, _: &mut W) -> io::Result<()> {
+    Ok(())
+  }
+}
+
+/// State machine for a dealer, a participant of the existing keys, to reshare them.
+///
+/// Every dealer in the specified set of dealers, which must be at least t of the existing
+/// participants, shares their interpolated share of the existing keys among the new participants.
+/// The new participants sum these sub-shares, producing shares of the same group key under new
+/// parameters. Any offset applied to the existing keys is included in the reshared group key.
+///
+/// The old and new participants are indexed independently. A dealer who is also a new participant
+/// should run both this machine and a `ResharedMachine`.
+pub struct ResharingMachine<C: Ciphersuite> {
+  keys: ThresholdKeys<C>,
+  dealers: Vec<Participant>,
+  params: ThresholdParams,
+  context: String,
+}
+
+impl<C: Ciphersuite> fmt::Debug for ResharingMachine<C> {
+  fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt
+      .debug_struct("ResharingMachine")
+      .field("keys", &self.keys)
+      .field("dealers", &self.dealers)
+      .field("params", &self.params)
+      .field("context", &self.context)
+      .finish_non_exhaustive()
+  }
+}
+
+impl<C: Ciphersuite> ResharingMachine<C> {
+  /// Create a new machine to reshare a set of keys to a new set of participants.
+  ///
+  /// The threshold and amount of participants are those of the new participants.
+  ///
+  /// The context string should be unique among resharings.
+  pub fn new(
+    keys: ThresholdKeys<C>,
+    dealers: Vec<Participant>,
+    t: u16,
+    n: u16,
+    context: String,
+  ) -> Result<ResharingMachine<C>, DkgError<()>> {
+    // Check the dealers form a valid signing set including us
+    keys.view(dealers.clone())?;
+    if !dealers.contains(&keys.params().i()) {
+      Err(DkgError::InvalidSigningSet)?;
+    }
+    // The participant index is irrelevant, solely checking the parameters themselves
+    let params = ThresholdParams::new(t, n, Participant(1))?;
+    Ok(ResharingMachine { keys, dealers, params, context })
+  }
+
+  /// Start resharing the keys.
+  ///
+  /// Returns a commitments message to be sent to all new participants over an authenticated
+  /// channel. If any dealer submits multiple sets of commitments, they MUST be treated as
+  /// malicious.
+  pub fn generate_coefficients<R: RngCore + CryptoRng>(
+    self,
+    rng: &mut R,
+  ) -> (ResharingShareMachine<C>, ResharingCommitments<C>) {
+    let i = self.keys.params().i();
+    let view = self.keys.view(self.dealers).unwrap();
+
+    let t = usize::from(self.params.t());
+    let mut coefficients = Vec::with_capacity(t);
+    let mut commitments = Vec::with_capacity(t - 1);
+    let mut cached_msg = vec![];
+
+    // The constant term is our interpolated share, so the new shares sum to the same group key
+    coefficients.push(Zeroizing::new(*view.secret_share().deref()));
+    for _ in 1 .. t {
+      let coefficient = Zeroizing::new(C::random_nonzero_F(&mut *rng));
+      let commitment = C::generator() * coefficient.deref();
+      coefficients.push(coefficient);
+      cached_msg.extend(commitment.to_bytes().as_ref());
+      commitments.push(commitment);
+    }
+
+    // Prove knowledge of the constant term, which also binds it to our interpolated verification
+    // share
+    let r = Zeroizing::new(C::random_nonzero_F(&mut *rng));
+    let nonce = C::generator() * r.deref();
+    let sig = SchnorrSignature::<C>::sign(
+      &coefficients[0],
+      r,
+      challenge::<C>(&self.context, i, nonce.to_bytes().as_ref(), &cached_msg),
+    );
+
+    let encryption = Encryption::new(dealer_context(&self.context), i, rng);
+    (
+      ResharingShareMachine { params: self.params, coefficients, encryption },
+      ResharingCommitments { commitments, cached_msg, sig },
+    )
+  }
+}
+
+/// Advancement of the resharing state machine for a dealer.
+pub struct ResharingShareMachine<C: Ciphersuite> {
+  params: ThresholdParams,
+  coefficients: Vec<Zeroizing<C::F>>,
+  encryption: Encryption<C>,
+}
+
+impl<C: Ciphersuite> fmt::Debug for ResharingShareMachine<C> {
+  fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt
+      .debug_struct("ResharingShareMachine")
+      .field("params", &self.params)
+      .field("encryption", &self.encryption)
+      .finish_non_exhaustive()
+  }
+}
+
+impl<C: Ciphersuite> Zeroize for ResharingShareMachine<C> {
+  fn zeroize(&mut self) {
+    self.params.zeroize();
+    self.coefficients.zeroize();
+    self.encryption.zeroize();
+  }
+}
+
+impl<C: Ciphersuite> ResharingShareMachine<C> {
+  /// Finish dealing.
+  ///
+  /// Takes in every new participant's registration. Returns a map of encrypted secret shares
+  /// to be sent over authenticated channels to their relevant counterparties.
+  ///
+  /// If any dealer sends multiple secret shares to a new participant, they are faulty.
+  #[allow(clippy::type_complexity)]
+  pub fn generate_secret_shares<R: RngCore + CryptoRng>(
+    mut self,
+    rng: &mut R,
+    registrations: BTreeMap<Participant, EncryptionKeyMessage<C, ResharingRegistration>>,
+  ) -> Result<BTreeMap<Participant, EncryptedMessage<C, SecretShare<C::F>>>, ResharingError<C>> {
+    validate_exact_map(&registrations, (1 ..= self.params.n()).map(Participant))?;
+    for (l, msg) in registrations.into_iter() {
+      self.encryption.register(l, msg);
+    }
+
+    let mut res = BTreeMap::new();
+    for l in (1 ..= self.params.n()).map(Participant) {
+      let mut share = polynomial(&self.coefficients, l);
+      let share_bytes = Zeroizing::new(SecretShare::<C::F>(share.to_repr()));
+      share.zeroize();
+      res.insert(l, self.encryption.encrypt(rng, l, share_bytes));
+    }
+    self.coefficients.zeroize();
+
+    Ok(res)
+  }
+}
+
+/// State machine for a new participant to receive reshared keys.
+pub struct ResharedMachine<C: Ciphersuite> {
+  params: ThresholdParams,
+  group_key: C::G,
+  dealers: BTreeMap<Participant, C::G>,
+  context: String,
+}
+
+impl<C: Ciphersuite> fmt::Debug for ResharedMachine<C> {
+  fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt
+      .debug_struct("ResharedMachine")
+      .field("params", &self.params)
+      .field("group_key", &self.group_key)
+      .field("dealers", &self.dealers)
+      .field("context", &self.context)
+      .finish_non_exhaustive()
+  }
+}
+
+impl<C: Ciphersuite> ResharedMachine<C> {
+  /// Create a new machine to receive reshared keys.
+  ///
+  /// `group_key` is the group key being reshared, including any offset. `dealers` maps each
+  /// dealer to their verification share, as interpolated for the set of dealers (and offset, if
+  /// applicable) by `ThresholdView::verification_share`. These must sum to the group key.
+  ///
+  /// The context string should be unique among resharings.
+  pub fn new(
+    params: ThresholdParams,
+    group_key: C::G,
+    dealers: BTreeMap<Participant, C::G>,
+    context: String,
+  ) -> Result<ResharedMachine<C>, DkgError<()>> {
+    if dealers.is_empty() {
+      Err(DkgError::InvalidSigningSet)?;
+    }
+    if dealers.values().copied().sum::<C::G>() != group_key {
+      Err(DkgError::InvalidGroupKey)?;
+    }
+    Ok(ResharedMachine { params, group_key, dealers, context })
+  }
+
+  /// Generate an encryption key for the dealers to encrypt our shares to.
+  ///
+  /// Returns a registration message to be sent to all dealers and new participants over an
+  /// authenticated channel.
+  pub fn generate_encryption_key<R: RngCore + CryptoRng>(
+    self,
+    rng: &mut R,
+  ) -> (ResharedKeyMachine<C>, EncryptionKeyMessage<C, ResharingRegistration>) {
+    let encryption = Encryption::new(participant_context(&self.context), self.params.i(), rng)
+      .with_sender_context(dealer_context(&self.context));
+    let msg = encryption.registration(ResharingRegistration);
+    (
+      ResharedKeyMachine {
+        params: self.params,
+        group_key: self.group_key,
+        dealers: self.dealers,
+        context: self.context,
+        encryption,
+      },
+      msg,
+    )
+  }
+}
+
+/// Advancement of the state machine for receiving reshared keys.
+///
+/// This machine will 'complete' the protocol, by a local perspective. In order to be secure,
+/// the parties must confirm having successfully completed the protocol (an effort out of scope to
+/// this library), yet this is modeled by one more state transition (BlameMachine).
+pub struct ResharedKeyMachine<C: Ciphersuite> {
+  params: ThresholdParams,
+  group_key: C::G,
+  dealers: BTreeMap<Participant, C::G>,
+  context: String,
+  encryption: Encryption<C>,
+}
+
+impl<C: Ciphersuite> fmt::Debug for ResharedKeyMachine<C> {
+  fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt
+      .debug_struct("ResharedKeyMachine")
+      .field("params", &self.params)
+      .field("group_key", &self.group_key)
+      .field("dealers", &self.dealers)
+      .field("context", &self.context)
+      .field("encryption", &self.encryption)
+      .finish_non_exhaustive()
+  }
+}
+
+impl<C: Ciphersuite> Zeroize for ResharedKeyMachine<C> {
+  fn zeroize(&mut self) {
+    self.params.zeroize();
+    self.group_key.zeroize();
+    for (_, share) in self.dealers.iter_mut() {
+      share.zeroize();
+    }
+    self.context.zeroize();
+    self.encryption.zeroize();
+  }
+}
+
+impl<C: Ciphersuite> ResharedKeyMachine<C> {
+  /// Calculate our share given everyone else's registrations and the dealers' commitments and
+  /// shares.
+  ///
+  /// Returns a BlameMachine usable to determine if faults in the protocol occurred.
+  ///
+  /// This will error on, and return a blame proof for, the first-observed case of faulty behavior.
+  pub fn calculate_share<R: RngCore + CryptoRng>(
+    mut self,
+    rng: &mut R,
+    registrations: BTreeMap<Participant, EncryptionKeyMessage<C, ResharingRegistration>>,
+    commitments: BTreeMap<Participant, ResharingCommitments<C>>,
+    shares: BTreeMap<Participant, EncryptedMessage<C, SecretShare<C::F>>>,
+  ) -> Result<BlameMachine<C>, ResharingError<C>> {
+    validate_map(
+      &registrations,
+      &(1 ..= self.params.n()).map(Participant).collect::<Vec<_>>(),
+      self.params.i(),
+    )?;
+    validate_exact_map(&commitments, self.dealers.keys().copied())?;
+    validate_exact_map(&shares, self.dealers.keys().copied())?;
+
+    // Register the other new participants' keys, enabling verifying blame
+    for (l, msg) in registrations.into_iter() {
+      self.encryption.register(l, msg);
+    }
+
+    // Validate each proof of knowledge, against the dealer's expected constant term
+    let mut batch = BatchVerifier::<Participant, C::G>::new(commitments.len());
+    let commitments = commitments
+      .into_iter()
+      .map(|(l, msg)| {
+        msg.sig.batch_verify(
+          rng,
+          &mut batch,
+          l,
+          self.dealers[&l],
+          challenge::<C>(&self.context, l, msg.sig.R.to_bytes().as_ref(), &msg.cached_msg),
+        );
+
+        let mut full = Vec::with_capacity(usize::from(self.params.t()));
+        full.push(self.dealers[&l]);
+        full.extend(&msg.commitments);
+        (l, full)
+      })
+      .collect::<BTreeMap<_, _>>();
+    batch.verify_vartime_with_vartime_blame().map_err(ResharingError::InvalidProofOfKnowledge)?;
+
+    let mut secret = Zeroizing::new(C::F::ZERO);
+    let mut batch = BatchVerifier::new(shares.len());
+    let mut blames = BTreeMap::new();
+    for (l, share_bytes) in shares.into_iter() {
+      let (mut share_bytes, blame) =
+        self.encryption.decrypt(rng, &mut batch, BatchId::Decryption(l), l, share_bytes);
+      let share =
+        Zeroizing::new(Option::<C::F>::from(C::F::from_repr(share_bytes.0)).ok_or_else(|| {
+          ResharingError::InvalidShare { participant: l, blame: Some(blame.clone()) }
+        })?);
+      share_bytes.zeroize();
+      *secret += share.deref();
+
+      blames.insert(l, blame);
+      batch.queue(
+        rng,
+        BatchId::Share(l),
+        share_verification_statements::<C>(self.params.i(), &commitments[&l], share),
+      );
+    }
+    batch.verify_with_vartime_blame().map_err(|id| {
+      let (l, blame) = match id {
+        BatchId::Decryption(l) => (l, None),
+        BatchId::Share(l) => (l, Some(blames.remove(&l).unwrap())),
+      };
+      ResharingError::InvalidShare { participant: l, blame }
+    })?;
+
+    // Stripe the commitments, as done during key generation
+    let mut stripes = Vec::with_capacity(usize::from(self.params.t()));
+    for t in 0 .. usize::from(self.params.t()) {
+      stripes.push(commitments.values().map(|commitments| commitments[t]).sum());
+    }
+
+    let mut verification_shares = BTreeMap::new();
+    for i in (1 ..= self.params.n()).map(Participant) {
+      verification_shares.insert(
+        i,
+        if i == self.params.i() {
+          C::generator() * secret.deref()
+        } else {
+          multiexp_vartime(&exponential::<C>(i, &stripes))
+        },
+      );
+    }
+
+    Ok(BlameMachine {
+      commitments,
+      encryption: self.encryption,
+      result: ThresholdCore {
+        params: self.params,
+        secret_share: secret,
+        group_key: self.group_key,
+        verification_shares,
+      },
+    })
+  }
+}
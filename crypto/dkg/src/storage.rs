@@ -0,0 +1,235 @@
+use core::ops::Deref;
+use std_shims::{
+  vec::Vec,
+  io::{self, Read, Write},
+};
+
+use rand_core::{RngCore, CryptoRng};
+
+use zeroize::{Zeroize, Zeroizing};
+
+use chacha20poly1305::{
+  aead::{Aead, KeyInit, Payload},
+  Key, Nonce, ChaCha20Poly1305,
+};
+
+use transcript::{Transcript, RecommendedTranscript};
+
+use ciphersuite::{group::GroupEncoding, Ciphersuite};
+
+use crate::{ThresholdCore, ThresholdKeys};
+
+// The current version of the encrypted format.
+const VERSION: u8 = 1;
+
+const NONCE_LEN: usize = 12;
+
+// Bounds on the scrypt parameters, preventing malformed data from causing excessive resource usage
+const MAX_SCRYPT_LOG_N: u8 = 22;
+const MAX_SCRYPT_R: u32 = 32;
+const MAX_SCRYPT_P: u32 = 16;
+
+/// The key derivation function used to derive the encryption key for keys stored at rest.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Kdf {
+  /// The secret is a uniformly random key of at least 32 bytes, used as-is.
+  Key,
+  /// The secret is a password, stretched with scrypt under the specified parameters.
+  ///
+  /// `log_n` may be at most 22, `r` at most 32, and `p` at most 16.
+  Scrypt {
+    /// The base 2 logarithm of the CPU/memory cost.
+    log_n: u8,
+    /// The block size.
+    r: u32,
+    /// The parallelization factor.
+    p: u32,
+  },
+}
+
+impl Kdf {
+  /// scrypt with the parameters recommended by its authors for interactive use.
+  pub fn scrypt() -> Kdf {
+    Kdf::Scrypt { log_n: 17, r: 8, p: 1 }
+  }
+
+  fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    match self {
+      Kdf::Key => writer.write_all(&[0]),
+      Kdf::Scrypt { log_n, r, p } => {
+        writer.write_all(&[1, *log_n])?;
+        writer.write_all(&r.to_le_bytes())?;
+        writer.write_all(&p.to_le_bytes())
+      }
+    }
+  }
+
+  fn read<R: Read>(reader: &mut R) -> io::Result<Kdf> {
+    let mut kind = [0; 1];
+    reader.read_exact(&mut kind)?;
+    match kind[0] {
+      0 => Ok(Kdf::Key),
+      1 => {
+        let mut log_n = [0; 1];
+        reader.read_exact(&mut log_n)?;
+        let mut read_u32 = || -> io::Result<u32> {
+          let mut value = [0; 4];
+          reader.read_exact(&mut value)?;
+          Ok(u32::from_le_bytes(value))
+        };
+        Ok(Kdf::Scrypt { log_n: log_n[0], r: read_u32()?, p: read_u32()? })
+      }
+      _ => Err(io::Error::new(io::ErrorKind::Other, "unknown KDF")),
+    }
+  }
+
+  fn derive(&self, secret: &[u8], salt: &[u8]) -> io::Result<Zeroizing<Vec<u8>>> {
+    match self {
+      Kdf::Key => {
+        if secret.len() < 32 {
+          Err(io::Error::new(io::ErrorKind::Other, "key was less than 32 bytes"))?;
+        }
+        Ok(Zeroizing::new(secret.to_vec()))
+      }
+      Kdf::Scrypt { log_n, r, p } => {
+        if (*log_n > MAX_SCRYPT_LOG_N) || (*r > MAX_SCRYPT_R) || (*p > MAX_SCRYPT_P) {
+          Err(io::Error::new(
+            io::ErrorKind::Other,
+            "scrypt parameters exceeded the supported bounds",
+          ))?;
+        }
+        let params = scrypt::Params::new(*log_n, *r, *p)
+          .map_err(|_| io::Error::new(io::ErrorKind::Other, "invalid scrypt parameters"))?;
+        let mut key = Zeroizing::new(vec![0; 32]);
+        scrypt::scrypt(secret, salt, &params, key.as_mut()).unwrap();
+        Ok(key)
+      }
+    }
+  }
+}
+
+// Derive the cipher from the key derived by the KDF, binding it to the ciphersuite
+fn cipher<C: Ciphersuite>(key: Zeroizing<Vec<u8>>) -> ChaCha20Poly1305 {
+  let mut transcript = RecommendedTranscript::new(b"DKG Keys at Rest v0.3");
+  transcript.append_message(b"ciphersuite", C::ID);
+  transcript.append_message(b"key", key.deref());
+  drop(key);
+
+  let mut challenge = transcript.challenge(b"encryption_key");
+  let cipher = ChaCha20Poly1305::new(Key::from_slice(&challenge[.. 32]));
+  let zeroize = |buf: &mut [u8]| buf.zeroize();
+  zeroize(challenge.as_mut());
+  cipher
+}
+
+impl<C: Ciphersuite> ThresholdCore<C> {
+  /// Write these keys, encrypted and authenticated under a key derived from the provided secret.
+  ///
+  /// The group key is written in the clear, yet is authenticated.
+  pub fn write_encrypted<W: Write, R: RngCore + CryptoRng>(
+    &self,
+    writer: &mut W,
+    rng: &mut R,
+    kdf: Kdf,
+    secret: &[u8],
+  ) -> io::Result<()> {
+    let mut salt = [0; 32];
+    rng.fill_bytes(&mut salt);
+    let mut nonce = [0; NONCE_LEN];
+    rng.fill_bytes(&mut nonce);
+
+    let mut header = vec![VERSION];
+    kdf.write(&mut header)?;
+    header.extend(salt);
+    header.extend(nonce);
+    header.extend(self.group_key.to_bytes().as_ref());
+
+    // The header is authenticated as the associated data
+    let ciphertext = cipher::<C>(kdf.derive(secret, &salt)?)
+      .encrypt(Nonce::from_slice(&nonce), Payload { msg: &self.serialize(), aad: &header })
+      .map_err(|_| io::Error::new(io::ErrorKind::Other, "couldn't encrypt keys"))?;
+
+    writer.write_all(&header)?;
+    writer.write_all(&u32::try_from(ciphertext.len()).unwrap().to_le_bytes())?;
+    writer.write_all(&ciphertext)
+  }
+
+  /// Serialize these keys, encrypted and authenticated under a key derived from the provided
+  /// secret, to a `Vec<u8>`.
+  pub fn serialize_encrypted<R: RngCore + CryptoRng>(
+    &self,
+    rng: &mut R,
+    kdf: Kdf,
+    secret: &[u8],
+  ) -> io::Result<Vec<u8>> {
+    let mut serialized = vec![];
+    self.write_encrypted(&mut serialized, rng, kdf, secret)?;
+    Ok(serialized)
+  }
+
+  /// Read keys written by `write_encrypted`, decrypting them with the provided secret.
+  ///
+  /// This verifies the secret share against its verification share.
+  pub fn read_encrypted<R: Read>(reader: &mut R, secret: &[u8]) -> io::Result<ThresholdCore<C>> {
+    let mut version = [0; 1];
+    reader.read_exact(&mut version)?;
+    if version[0] != VERSION {
+      Err(io::Error::new(io::ErrorKind::Other, "unknown version of encrypted keys"))?;
+    }
+
+    let kdf = Kdf::read(reader)?;
+    let mut salt = [0; 32];
+    reader.read_exact(&mut salt)?;
+    let mut nonce = [0; NONCE_LEN];
+    reader.read_exact(&mut nonce)?;
+    let group_key = C::read_G(reader)?;
+
+    let mut header = version.to_vec();
+    kdf.write(&mut header)?;
+    header.extend(salt);
+    header.extend(nonce);
+    header.extend(group_key.to_bytes().as_ref());
+
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+    let mut ciphertext = vec![0; usize::try_from(u32::from_le_bytes(len)).unwrap()];
+    reader.read_exact(&mut ciphertext)?;
+
+    let plaintext = Zeroizing::new(
+      cipher::<C>(kdf.derive(secret, &salt)?)
+        .decrypt(Nonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: &header })
+        .map_err(|_| io::Error::new(io::ErrorKind::Other, "invalid secret or corrupted keys"))?,
+    );
+
+    let mut plaintext_ref = plaintext.as_slice();
+    let keys = ThresholdCore::<C>::read(&mut plaintext_ref)?;
+    if !plaintext_ref.is_empty() {
+      Err(io::Error::new(io::ErrorKind::Other, "trailing data within encrypted keys"))?;
+    }
+    if keys.group_key != group_key {
+      Err(io::Error::new(io::ErrorKind::Other, "encrypted keys had a distinct group key"))?;
+    }
+    if (C::generator() * keys.secret_share.deref()) != keys.verification_shares[&keys.params.i] {
+      Err(io::Error::new(
+        io::ErrorKind::Other,
+        "secret share didn't match its verification share",
+      ))?;
+    }
+    Ok(keys)
+  }
+}
+
+impl<C: Ciphersuite> ThresholdKeys<C> {
+  /// Serialize these keys, encrypted and authenticated under a key derived from the provided
+  /// secret, to a `Vec<u8>`.
+  ///
+  /// As with `serialize`, the offset is not included.
+  pub fn serialize_encrypted<R: RngCore + CryptoRng>(
+    &self,
+    rng: &mut R,
+    kdf: Kdf,
+    secret: &[u8],
+  ) -> io::Result<Vec<u8>> {
+    self.core.serialize_encrypted(rng, kdf, secret)
+  }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std_shims::{string::ToString, collections::BTreeMap};
 
 use rand_core::{RngCore, CryptoRng};
 
@@ -11,7 +11,7 @@ use crate::{
 
 // Needed so rustfmt doesn't fail to format on line length issues
 type FrostEncryptedMessage<C> = EncryptedMessage<C, SecretShare<<C as Ciphersuite>::F>>;
-type FrostSecretShares<C> = HashMap<Participant, FrostEncryptedMessage<C>>;
+type FrostSecretShares<C> = BTreeMap<Participant, FrostEncryptedMessage<C>>;
 
 const CONTEXT: &str = "DKG Test Key Generation";
 
@@ -20,13 +20,13 @@ const CONTEXT: &str = "DKG Test Key Generation";
 fn commit_enc_keys_and_shares<R: RngCore + CryptoRng, C: Ciphersuite>(
   rng: &mut R,
 ) -> (
-  HashMap<Participant, KeyMachine<C>>,
-  HashMap<Participant, C::G>,
-  HashMap<Participant, FrostSecretShares<C>>,
+  BTreeMap<Participant, KeyMachine<C>>,
+  BTreeMap<Participant, C::G>,
+  BTreeMap<Participant, FrostSecretShares<C>>,
 ) {
-  let mut machines = HashMap::new();
-  let mut commitments = HashMap::new();
-  let mut enc_keys = HashMap::new();
+  let mut machines = BTreeMap::new();
+  let mut commitments = BTreeMap::new();
+  let mut enc_keys = BTreeMap::new();
   for i in (1 ..= PARTICIPANTS).map(Participant) {
     let params = ThresholdParams::new(THRESHOLD, PARTICIPANTS, i).unwrap();
     let machine = KeyGenMachine::<C>::new(params, CONTEXT.to_string());
@@ -41,14 +41,14 @@ fn commit_enc_keys_and_shares<R: RngCore + CryptoRng, C: Ciphersuite>(
     enc_keys.insert(i, commitments[&i].enc_key());
   }
 
-  let mut secret_shares = HashMap::new();
+  let mut secret_shares = BTreeMap::new();
   let machines = machines
-    .drain()
+    .into_iter()
     .map(|(l, machine)| {
-      let (machine, mut shares) =
+      let (machine, shares) =
         machine.generate_secret_shares(rng, clone_without(&commitments, &l)).unwrap();
       let shares = shares
-        .drain()
+        .into_iter()
         .map(|(l, share)| {
           (
             l,
@@ -60,20 +60,20 @@ fn commit_enc_keys_and_shares<R: RngCore + CryptoRng, C: Ciphersuite>(
             .unwrap(),
           )
         })
-        .collect::<HashMap<_, _>>();
+        .collect::<BTreeMap<_, _>>();
       secret_shares.insert(l, shares);
       (l, machine)
     })
-    .collect::<HashMap<_, _>>();
+    .collect::<BTreeMap<_, _>>();
 
   (machines, enc_keys, secret_shares)
 }
 
-fn generate_secret_shares<C: Ciphersuite>(
-  shares: &HashMap<Participant, FrostSecretShares<C>>,
+pub(crate) fn generate_secret_shares<C: Ciphersuite>(
+  shares: &BTreeMap<Participant, FrostSecretShares<C>>,
   recipient: Participant,
 ) -> FrostSecretShares<C> {
-  let mut our_secret_shares = HashMap::new();
+  let mut our_secret_shares = BTreeMap::new();
   for (i, shares) in shares {
     if recipient == *i {
       continue;
@@ -86,13 +86,13 @@ fn generate_secret_shares<C: Ciphersuite>(
 /// Fully perform the FROST key generation algorithm.
 pub fn frost_gen<R: RngCore + CryptoRng, C: Ciphersuite>(
   rng: &mut R,
-) -> HashMap<Participant, ThresholdCore<C>> {
-  let (mut machines, _, secret_shares) = commit_enc_keys_and_shares::<_, C>(rng);
+) -> BTreeMap<Participant, ThresholdCore<C>> {
+  let (machines, _, secret_shares) = commit_enc_keys_and_shares::<_, C>(rng);
 
   let mut verification_shares = None;
   let mut group_key = None;
   machines
-    .drain()
+    .into_iter()
     .map(|(i, machine)| {
       let our_secret_shares = generate_secret_shares(&secret_shares, i);
       let these_keys = machine.calculate_share(rng, our_secret_shares).unwrap().complete();
@@ -111,7 +111,7 @@ pub fn frost_gen<R: RngCore + CryptoRng, C: Ciphersuite>(
 
       (i, these_keys)
     })
-    .collect::<HashMap<_, _>>()
+    .collect::<BTreeMap<_, _>>()
 }
 
 #[cfg(test)]
@@ -143,15 +143,14 @@ mod literal {
   // TODO: Write a macro which expands to the following
   #[test]
   fn invalid_encryption_pop_blame() {
-    let (mut machines, _, mut secret_shares) =
-      commit_enc_keys_and_shares::<_, Ristretto>(&mut OsRng);
+    let (machines, _, mut secret_shares) = commit_enc_keys_and_shares::<_, Ristretto>(&mut OsRng);
 
     // Mutate the PoP of the encrypted message from 1 to 2
     secret_shares.get_mut(&ONE).unwrap().get_mut(&TWO).unwrap().invalidate_pop();
 
     let mut blame = None;
     let machines = machines
-      .drain()
+      .into_iter()
       .filter_map(|(i, machine)| {
         let our_secret_shares = generate_secret_shares(&secret_shares, i);
         let machine = machine.calculate_share(&mut OsRng, our_secret_shares);
@@ -172,8 +171,7 @@ mod literal {
 
   #[test]
   fn invalid_ecdh_blame() {
-    let (mut machines, _, mut secret_shares) =
-      commit_enc_keys_and_shares::<_, Ristretto>(&mut OsRng);
+    let (machines, _, mut secret_shares) = commit_enc_keys_and_shares::<_, Ristretto>(&mut OsRng);
 
     // Mutate the share to trigger a blame event
     // Mutates from 2 to 1, as 1 is expected to end up malicious for test_blame to pass
@@ -190,7 +188,7 @@ mod literal {
 
     let mut blame = None;
     let machines = machines
-      .drain()
+      .into_iter()
       .filter_map(|(i, machine)| {
         let our_secret_shares = generate_secret_shares(&secret_shares, i);
         let machine = machine.calculate_share(&mut OsRng, our_secret_shares);
@@ -213,8 +211,7 @@ mod literal {
   // This should be largely equivalent to the prior test
   #[test]
   fn invalid_dleq_blame() {
-    let (mut machines, _, mut secret_shares) =
-      commit_enc_keys_and_shares::<_, Ristretto>(&mut OsRng);
+    let (machines, _, mut secret_shares) = commit_enc_keys_and_shares::<_, Ristretto>(&mut OsRng);
 
     secret_shares
       .get_mut(&TWO)
@@ -225,7 +222,7 @@ mod literal {
 
     let mut blame = None;
     let machines = machines
-      .drain()
+      .into_iter()
       .filter_map(|(i, machine)| {
         let our_secret_shares = generate_secret_shares(&secret_shares, i);
         let machine = machine.calculate_share(&mut OsRng, our_secret_shares);
@@ -247,7 +244,7 @@ mod literal {
 
   #[test]
   fn invalid_share_serialization_blame() {
-    let (mut machines, enc_keys, mut secret_shares) =
+    let (machines, enc_keys, mut secret_shares) =
       commit_enc_keys_and_shares::<_, Ristretto>(&mut OsRng);
 
     secret_shares.get_mut(&ONE).unwrap().get_mut(&TWO).unwrap().invalidate_share_serialization(
@@ -259,7 +256,7 @@ mod literal {
 
     let mut blame = None;
     let machines = machines
-      .drain()
+      .into_iter()
       .filter_map(|(i, machine)| {
         let our_secret_shares = generate_secret_shares(&secret_shares, i);
         let machine = machine.calculate_share(&mut OsRng, our_secret_shares);
@@ -280,7 +277,7 @@ mod literal {
 
   #[test]
   fn invalid_share_value_blame() {
-    let (mut machines, enc_keys, mut secret_shares) =
+    let (machines, enc_keys, mut secret_shares) =
       commit_enc_keys_and_shares::<_, Ristretto>(&mut OsRng);
 
     secret_shares.get_mut(&ONE).unwrap().get_mut(&TWO).unwrap().invalidate_share_value(
@@ -292,7 +289,7 @@ mod literal {
 
     let mut blame = None;
     let machines = machines
-      .drain()
+      .into_iter()
       .filter_map(|(i, machine)| {
         let our_secret_shares = generate_secret_shares(&secret_shares, i);
         let machine = machine.calculate_share(&mut OsRng, our_secret_shares);
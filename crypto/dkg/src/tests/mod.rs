@@ -1,5 +1,5 @@
 use core::ops::Deref;
-use std::collections::HashMap;
+use std_shims::{vec::Vec, collections::BTreeMap};
 
 use rand_core::{RngCore, CryptoRng};
 
@@ -15,16 +15,28 @@ use frost::frost_gen;
 mod promote;
 use promote::test_generator_promotion;
 
+// Refresh test.
+mod refresh;
+use refresh::test_refresh;
+
+// Resharing test.
+mod resharing;
+use resharing::test_resharing;
+
+// Encrypted storage test.
+mod storage;
+use storage::test_storage;
+
 /// Constant amount of participants to use when testing.
 pub const PARTICIPANTS: u16 = 5;
 /// Constant threshold of participants to use when testing.
 pub const THRESHOLD: u16 = ((PARTICIPANTS / 3) * 2) + 1;
 
 /// Clone a map without a specific value.
-pub fn clone_without<K: Clone + std::cmp::Eq + std::hash::Hash, V: Clone>(
-  map: &HashMap<K, V>,
+pub fn clone_without<K: Clone + Ord, V: Clone>(
+  map: &BTreeMap<K, V>,
   without: &K,
-) -> HashMap<K, V> {
+) -> BTreeMap<K, V> {
   let mut res = map.clone();
   res.remove(without).unwrap();
   res
@@ -33,7 +45,7 @@ pub fn clone_without<K: Clone + std::cmp::Eq + std::hash::Hash, V: Clone>(
 /// Recover the secret from a collection of keys.
 ///
 /// This will panic if no keys, an insufficient amount of keys, or the wrong keys are provided.
-pub fn recover_key<C: Ciphersuite>(keys: &HashMap<Participant, ThresholdKeys<C>>) -> C::F {
+pub fn recover_key<C: Ciphersuite>(keys: &BTreeMap<Participant, ThresholdKeys<C>>) -> C::F {
   let first = keys.values().next().expect("no keys provided");
   assert!(keys.len() >= first.params().t().into(), "not enough keys provided");
   let included = keys.keys().cloned().collect::<Vec<_>>();
@@ -48,9 +60,9 @@ pub fn recover_key<C: Ciphersuite>(keys: &HashMap<Participant, ThresholdKeys<C>>
 /// Generate threshold keys for tests.
 pub fn key_gen<R: RngCore + CryptoRng, C: Ciphersuite>(
   rng: &mut R,
-) -> HashMap<Participant, ThresholdKeys<C>> {
+) -> BTreeMap<Participant, ThresholdKeys<C>> {
   let res = frost_gen(rng)
-    .drain()
+    .into_iter()
     .map(|(i, core)| {
       assert_eq!(
         &ThresholdCore::<C>::read::<&[u8]>(&mut core.serialize().as_ref()).unwrap(),
@@ -67,9 +79,32 @@ pub fn key_gen<R: RngCore + CryptoRng, C: Ciphersuite>(
 pub fn test_ciphersuite<R: RngCore + CryptoRng, C: Ciphersuite>(rng: &mut R) {
   key_gen::<_, C>(rng);
   test_generator_promotion::<_, C>(rng);
+  test_refresh::<_, C>(rng);
+  test_resharing::<_, C>(rng);
+  test_storage::<_, C>(rng);
 }
 
 #[test]
 fn test_with_ristretto() {
   test_ciphersuite::<_, ciphersuite::Ristretto>(&mut rand_core::OsRng);
 }
+
+#[test]
+fn test_with_ed25519() {
+  test_ciphersuite::<_, ciphersuite::Ed25519>(&mut rand_core::OsRng);
+}
+
+#[test]
+fn test_with_secp256k1() {
+  test_ciphersuite::<_, ciphersuite::Secp256k1>(&mut rand_core::OsRng);
+}
+
+#[test]
+fn test_with_p256() {
+  test_ciphersuite::<_, ciphersuite::P256>(&mut rand_core::OsRng);
+}
+
+#[test]
+fn test_with_ed448() {
+  test_ciphersuite::<_, ciphersuite::Ed448>(&mut rand_core::OsRng);
+}
@@ -1,5 +1,5 @@
 use core::{marker::PhantomData, ops::Deref};
-use std::collections::HashMap;
+use std_shims::collections::BTreeMap;
 
 use rand_core::{RngCore, CryptoRng};
 
@@ -37,8 +37,8 @@ impl<C: Ciphersuite> Ciphersuite for AltGenerator<C> {
 pub(crate) fn test_generator_promotion<R: RngCore + CryptoRng, C: Ciphersuite>(rng: &mut R) {
   let keys = key_gen::<_, C>(&mut *rng);
 
-  let mut promotions = HashMap::new();
-  let mut proofs = HashMap::new();
+  let mut promotions = BTreeMap::new();
+  let mut proofs = BTreeMap::new();
   for (i, keys) in &keys {
     let (promotion, proof) =
       GeneratorPromotion::<_, AltGenerator<C>>::promote(&mut *rng, keys.clone());
@@ -47,7 +47,7 @@ pub(crate) fn test_generator_promotion<R: RngCore + CryptoRng, C: Ciphersuite>(r
   }
 
   let new_group_key = AltGenerator::<C>::generator() * recover_key(&keys);
-  for (i, promoting) in promotions.drain() {
+  for (i, promoting) in promotions.into_iter() {
     let promoted = promoting.complete(&clone_without(&proofs, &i)).unwrap();
     assert_eq!(keys[&i].params(), promoted.params());
     assert_eq!(keys[&i].secret_share(), promoted.secret_share());
@@ -0,0 +1,353 @@
+use core::ops::Deref;
+use std_shims::{string::ToString, collections::BTreeMap};
+
+use rand_core::{RngCore, CryptoRng};
+
+use ciphersuite::Ciphersuite;
+
+use crate::{
+  Participant, ThresholdParams, ThresholdCore, ThresholdKeys,
+  frost::SecretShare,
+  encryption::{EncryptionKeyMessage, EncryptedMessage},
+  refresh::{RefreshCommitments, RefreshMachine, RefreshShareMachine, RefreshKeyMachine},
+  tests::{
+    THRESHOLD, PARTICIPANTS, clone_without, recover_key,
+    frost::{frost_gen, generate_secret_shares},
+  },
+};
+
+// Needed so rustfmt doesn't fail to format on line length issues
+type RefreshEncryptedMessage<C> = EncryptedMessage<C, SecretShare<<C as Ciphersuite>::F>>;
+type RefreshSecretShares<C> = BTreeMap<Participant, RefreshEncryptedMessage<C>>;
+
+const CONTEXT: &str = "DKG Test Key Refresh";
+
+// Only t/n actually matters when reading, so hardcode i to 1 here
+const PARAMS: ThresholdParams =
+  ThresholdParams { t: THRESHOLD, n: PARTICIPANTS, i: Participant(1) };
+
+// Start refreshing the keys, returning the machines and their commitments
+#[allow(clippy::type_complexity)]
+fn commit<R: RngCore + CryptoRng, C: Ciphersuite>(
+  rng: &mut R,
+  keys: &BTreeMap<Participant, ThresholdCore<C>>,
+) -> (
+  BTreeMap<Participant, RefreshShareMachine<C>>,
+  BTreeMap<Participant, EncryptionKeyMessage<C, RefreshCommitments<C>>>,
+) {
+  let mut machines = BTreeMap::new();
+  let mut commitments = BTreeMap::new();
+  for (i, keys) in keys {
+    let (machine, these_commitments) =
+      RefreshMachine::new(keys.clone(), CONTEXT.to_string()).generate_coefficients(&mut *rng);
+    machines.insert(*i, machine);
+    commitments.insert(
+      *i,
+      EncryptionKeyMessage::read::<&[u8]>(&mut these_commitments.serialize().as_ref(), PARAMS)
+        .unwrap(),
+    );
+  }
+  (machines, commitments)
+}
+
+// Commit, then return the existing keys, enc keys, and shares
+#[allow(clippy::type_complexity)]
+fn commit_enc_keys_and_shares<R: RngCore + CryptoRng, C: Ciphersuite>(
+  rng: &mut R,
+) -> (
+  BTreeMap<Participant, ThresholdCore<C>>,
+  BTreeMap<Participant, RefreshKeyMachine<C>>,
+  BTreeMap<Participant, C::G>,
+  BTreeMap<Participant, RefreshSecretShares<C>>,
+) {
+  let keys = frost_gen::<_, C>(&mut *rng);
+  let (machines, commitments) = commit(&mut *rng, &keys);
+  let enc_keys = commitments.iter().map(|(i, commitments)| (*i, commitments.enc_key())).collect();
+
+  let mut secret_shares = BTreeMap::new();
+  let machines = machines
+    .into_iter()
+    .map(|(l, machine)| {
+      let (machine, shares) =
+        machine.generate_secret_shares(&mut *rng, clone_without(&commitments, &l)).unwrap();
+      let shares = shares
+        .into_iter()
+        .map(|(i, share)| {
+          (i, EncryptedMessage::read::<&[u8]>(&mut share.serialize().as_ref(), PARAMS).unwrap())
+        })
+        .collect::<BTreeMap<_, _>>();
+      secret_shares.insert(l, shares);
+      (l, machine)
+    })
+    .collect::<BTreeMap<_, _>>();
+
+  (keys, machines, enc_keys, secret_shares)
+}
+
+// Test refreshing threshold keys
+pub(crate) fn test_refresh<R: RngCore + CryptoRng, C: Ciphersuite>(rng: &mut R) {
+  let (keys, machines, _, secret_shares) = commit_enc_keys_and_shares::<_, C>(&mut *rng);
+
+  let refreshed = machines
+    .into_iter()
+    .map(|(i, machine)| {
+      let our_secret_shares = generate_secret_shares(&secret_shares, i);
+      let core = machine.calculate_share(&mut *rng, our_secret_shares).unwrap();
+      (i, core.complete())
+    })
+    .collect::<BTreeMap<Participant, ThresholdCore<C>>>();
+
+  let group_key = keys[&Participant(1)].group_key();
+  let verification_shares = refreshed[&Participant(1)].verification_shares();
+  for (i, core) in &refreshed {
+    // The group key is unchanged, and everyone agrees on the new verification shares
+    assert_eq!(core.group_key(), group_key);
+    assert_eq!(core.verification_shares(), verification_shares);
+    assert_eq!(C::generator() * core.secret_share().deref(), verification_shares[i]);
+    // Yet the shares themselves have changed
+    assert!(core.secret_share() != keys[i].secret_share());
+    assert!(verification_shares[i] != keys[i].verification_shares()[i]);
+    assert_eq!(&ThresholdCore::<C>::read::<&[u8]>(&mut core.serialize().as_ref()).unwrap(), core);
+  }
+
+  let refreshed = refreshed
+    .into_iter()
+    .map(|(i, core)| (i, ThresholdKeys::new(core)))
+    .collect::<BTreeMap<_, _>>();
+  assert_eq!(C::generator() * recover_key(&refreshed), group_key);
+}
+
+#[cfg(test)]
+mod literal {
+  use rand_core::OsRng;
+
+  use ciphersuite::{group::GroupEncoding, Ristretto};
+
+  use crate::{DkgError, encryption::EncryptionKeyProof, frost::BlameMachine};
+
+  use super::*;
+
+  const ONE: Participant = Participant(1);
+  const TWO: Participant = Participant(2);
+
+  fn test_blame(
+    machines: Vec<BlameMachine<Ristretto>>,
+    msg: RefreshEncryptedMessage<Ristretto>,
+    blame: Option<EncryptionKeyProof<Ristretto>>,
+  ) {
+    for machine in machines {
+      let (additional, blamed) = machine.blame(ONE, TWO, msg.clone(), blame.clone());
+      assert_eq!(blamed, ONE);
+      // Verify additional blame also works
+      assert_eq!(additional.blame(ONE, TWO, msg.clone(), blame.clone()), ONE);
+    }
+  }
+
+  #[test]
+  fn invalid_commitments_signature() {
+    let keys = frost_gen::<_, Ristretto>(&mut OsRng);
+    let (machines, mut commitments) = commit(&mut OsRng, &keys);
+
+    // Replace the first commitment with another point, leaving the signature over the original
+    // commitments
+    let mut serialized = commitments[&ONE].serialize();
+    let generator = Ristretto::generator().to_bytes();
+    serialized[.. generator.len()].copy_from_slice(generator.as_ref());
+    commitments
+      .insert(ONE, EncryptionKeyMessage::read::<&[u8]>(&mut serialized.as_ref(), PARAMS).unwrap());
+
+    for (i, machine) in machines {
+      if i == ONE {
+        continue;
+      }
+      assert_eq!(
+        machine.generate_secret_shares(&mut OsRng, clone_without(&commitments, &i)).err(),
+        Some(DkgError::InvalidProofOfKnowledge(ONE))
+      );
+    }
+  }
+
+  #[test]
+  fn commitments_signed_by_non_member() {
+    let keys = frost_gen::<_, Ristretto>(&mut OsRng);
+    let (machines, mut commitments) = commit(&mut OsRng, &keys);
+
+    // Have participant one sign their commitments with a key from an unrelated multisig
+    let other_keys = frost_gen::<_, Ristretto>(&mut OsRng);
+    commitments.insert(ONE, commit(&mut OsRng, &other_keys).1.remove(&ONE).unwrap());
+
+    for (i, machine) in machines {
+      if i == ONE {
+        continue;
+      }
+      assert_eq!(
+        machine.generate_secret_shares(&mut OsRng, clone_without(&commitments, &i)).err(),
+        Some(DkgError::InvalidProofOfKnowledge(ONE))
+      );
+    }
+  }
+
+  #[test]
+  fn invalid_encryption_pop_blame() {
+    let (_, machines, _, mut secret_shares) =
+      commit_enc_keys_and_shares::<_, Ristretto>(&mut OsRng);
+
+    // Mutate the PoP of the encrypted message from 1 to 2
+    secret_shares.get_mut(&ONE).unwrap().get_mut(&TWO).unwrap().invalidate_pop();
+
+    let mut blame = None;
+    let machines = machines
+      .into_iter()
+      .filter_map(|(i, machine)| {
+        let our_secret_shares = generate_secret_shares(&secret_shares, i);
+        let machine = machine.calculate_share(&mut OsRng, our_secret_shares);
+        if i == TWO {
+          assert_eq!(machine.err(), Some(DkgError::InvalidShare { participant: ONE, blame: None }));
+          // An invalid PoP is self-explainable, so there's no blame proof
+          blame = Some(None);
+          None
+        } else {
+          Some(machine.unwrap())
+        }
+      })
+      .collect::<Vec<_>>();
+
+    test_blame(machines, secret_shares[&ONE][&TWO].clone(), blame.unwrap());
+  }
+
+  #[test]
+  fn invalid_ecdh_blame() {
+    let (_, machines, _, mut secret_shares) =
+      commit_enc_keys_and_shares::<_, Ristretto>(&mut OsRng);
+
+    // 2 sends 1 a message which decrypts to gibberish, so 1 creates a blame proof
+    // We then malleate 1's blame proof, so 1 ends up malicious
+    secret_shares
+      .get_mut(&TWO)
+      .unwrap()
+      .get_mut(&ONE)
+      .unwrap()
+      .invalidate_msg(&mut OsRng, CONTEXT, TWO);
+
+    let mut blame = None;
+    let machines = machines
+      .into_iter()
+      .filter_map(|(i, machine)| {
+        let our_secret_shares = generate_secret_shares(&secret_shares, i);
+        let machine = machine.calculate_share(&mut OsRng, our_secret_shares);
+        if i == ONE {
+          blame = Some(match machine.err() {
+            Some(DkgError::InvalidShare { participant: TWO, blame: Some(blame) }) => Some(blame),
+            _ => panic!(),
+          });
+          None
+        } else {
+          Some(machine.unwrap())
+        }
+      })
+      .collect::<Vec<_>>();
+
+    blame.as_mut().unwrap().as_mut().unwrap().invalidate_key();
+    test_blame(machines, secret_shares[&TWO][&ONE].clone(), blame.unwrap());
+  }
+
+  #[test]
+  fn invalid_dleq_blame() {
+    let (_, machines, _, mut secret_shares) =
+      commit_enc_keys_and_shares::<_, Ristretto>(&mut OsRng);
+
+    secret_shares
+      .get_mut(&TWO)
+      .unwrap()
+      .get_mut(&ONE)
+      .unwrap()
+      .invalidate_msg(&mut OsRng, CONTEXT, TWO);
+
+    let mut blame = None;
+    let machines = machines
+      .into_iter()
+      .filter_map(|(i, machine)| {
+        let our_secret_shares = generate_secret_shares(&secret_shares, i);
+        let machine = machine.calculate_share(&mut OsRng, our_secret_shares);
+        if i == ONE {
+          blame = Some(match machine.err() {
+            Some(DkgError::InvalidShare { participant: TWO, blame: Some(blame) }) => Some(blame),
+            _ => panic!(),
+          });
+          None
+        } else {
+          Some(machine.unwrap())
+        }
+      })
+      .collect::<Vec<_>>();
+
+    blame.as_mut().unwrap().as_mut().unwrap().invalidate_dleq();
+    test_blame(machines, secret_shares[&TWO][&ONE].clone(), blame.unwrap());
+  }
+
+  #[test]
+  fn invalid_share_serialization_blame() {
+    let (_, machines, enc_keys, mut secret_shares) =
+      commit_enc_keys_and_shares::<_, Ristretto>(&mut OsRng);
+
+    secret_shares.get_mut(&ONE).unwrap().get_mut(&TWO).unwrap().invalidate_share_serialization(
+      &mut OsRng,
+      CONTEXT,
+      ONE,
+      enc_keys[&TWO],
+    );
+
+    let mut blame = None;
+    let machines = machines
+      .into_iter()
+      .filter_map(|(i, machine)| {
+        let our_secret_shares = generate_secret_shares(&secret_shares, i);
+        let machine = machine.calculate_share(&mut OsRng, our_secret_shares);
+        if i == TWO {
+          blame = Some(match machine.err() {
+            Some(DkgError::InvalidShare { participant: ONE, blame: Some(blame) }) => Some(blame),
+            _ => panic!(),
+          });
+          None
+        } else {
+          Some(machine.unwrap())
+        }
+      })
+      .collect::<Vec<_>>();
+
+    test_blame(machines, secret_shares[&ONE][&TWO].clone(), blame.unwrap());
+  }
+
+  #[test]
+  fn invalid_share_value_blame() {
+    let (_, machines, enc_keys, mut secret_shares) =
+      commit_enc_keys_and_shares::<_, Ristretto>(&mut OsRng);
+
+    secret_shares.get_mut(&ONE).unwrap().get_mut(&TWO).unwrap().invalidate_share_value(
+      &mut OsRng,
+      CONTEXT,
+      ONE,
+      enc_keys[&TWO],
+    );
+
+    let mut blame = None;
+    let machines = machines
+      .into_iter()
+      .filter_map(|(i, machine)| {
+        let our_secret_shares = generate_secret_shares(&secret_shares, i);
+        let machine = machine.calculate_share(&mut OsRng, our_secret_shares);
+        if i == TWO {
+          blame = Some(match machine.err() {
+            Some(DkgError::InvalidShare { participant: ONE, blame: Some(blame) }) => Some(blame),
+            _ => panic!(),
+          });
+          None
+        } else {
+          Some(machine.unwrap())
+        }
+      })
+      .collect::<Vec<_>>();
+
+    test_blame(machines, secret_shares[&ONE][&TWO].clone(), blame.unwrap());
+  }
+}
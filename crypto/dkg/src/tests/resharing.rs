@@ -0,0 +1,319 @@
+use core::ops::Deref;
+use std_shims::{vec::Vec, string::ToString, collections::BTreeMap};
+
+use rand_core::{RngCore, CryptoRng};
+
+use ciphersuite::{group::ff::Field, Ciphersuite};
+
+use crate::{
+  Participant, ThresholdParams, ThresholdCore, ThresholdKeys,
+  frost::SecretShare,
+  encryption::{EncryptionKeyMessage, EncryptedMessage},
+  resharing::{
+    ResharingCommitments, ResharingRegistration, ResharingMachine, ResharedMachine,
+    ResharedKeyMachine,
+  },
+  tests::{THRESHOLD, PARTICIPANTS, clone_without, key_gen, recover_key},
+};
+
+// Needed so rustfmt doesn't fail to format on line length issues
+type ResharingEncryptedMessage<C> = EncryptedMessage<C, SecretShare<<C as Ciphersuite>::F>>;
+type ResharingSecretShares<C> = BTreeMap<Participant, ResharingEncryptedMessage<C>>;
+
+const CONTEXT: &str = "DKG Test Key Resharing";
+
+const NEW_PARTICIPANTS: u16 = PARTICIPANTS + 2;
+const NEW_THRESHOLD: u16 = ((NEW_PARTICIPANTS / 3) * 2) + 1;
+
+// Only t/n actually matters when reading, so hardcode i to 1 here
+const PARAMS: ThresholdParams =
+  ThresholdParams { t: NEW_THRESHOLD, n: NEW_PARTICIPANTS, i: Participant(1) };
+
+// A resharing, up to the new participants calculating their shares
+struct Resharing<C: Ciphersuite> {
+  group_key: C::G,
+  dealer_shares: BTreeMap<Participant, C::G>,
+  commitments: BTreeMap<Participant, ResharingCommitments<C>>,
+  registrations: BTreeMap<Participant, EncryptionKeyMessage<C, ResharingRegistration>>,
+  receiving: BTreeMap<Participant, ResharedKeyMachine<C>>,
+  // The secret shares sent by each dealer
+  secret_shares: BTreeMap<Participant, ResharingSecretShares<C>>,
+}
+
+// Reshare (offset) keys from the last t participants to a larger set of participants with a
+// higher threshold
+fn reshare<R: RngCore + CryptoRng, C: Ciphersuite>(rng: &mut R) -> Resharing<C> {
+  // Offset the keys to ensure offsets are carried over
+  let keys = key_gen::<_, C>(&mut *rng)
+    .into_iter()
+    .map(|(i, keys)| (i, keys.offset(C::F::ONE)))
+    .collect::<BTreeMap<_, _>>();
+  let group_key = keys[&Participant(1)].group_key();
+
+  let dealers: Vec<_> =
+    (((PARTICIPANTS - THRESHOLD) + 1) ..= PARTICIPANTS).map(Participant).collect();
+  let view = keys[&Participant(PARTICIPANTS)].view(dealers.clone()).unwrap();
+  let dealer_shares =
+    view.included().iter().map(|l| (*l, view.verification_share(*l))).collect::<BTreeMap<_, _>>();
+
+  let mut dealing = BTreeMap::new();
+  let mut commitments = BTreeMap::new();
+  for l in &dealers {
+    let machine = ResharingMachine::new(
+      keys[l].clone(),
+      dealers.clone(),
+      NEW_THRESHOLD,
+      NEW_PARTICIPANTS,
+      CONTEXT.to_string(),
+    )
+    .unwrap();
+    let (machine, these_commitments) = machine.generate_coefficients(&mut *rng);
+    dealing.insert(*l, machine);
+    commitments.insert(
+      *l,
+      ResharingCommitments::<C>::read::<&[u8]>(&mut these_commitments.serialize().as_ref(), PARAMS)
+        .unwrap(),
+    );
+  }
+
+  let mut receiving = BTreeMap::new();
+  let mut registrations = BTreeMap::new();
+  for i in (1 ..= NEW_PARTICIPANTS).map(Participant) {
+    let machine = ResharedMachine::<C>::new(
+      ThresholdParams::new(NEW_THRESHOLD, NEW_PARTICIPANTS, i).unwrap(),
+      group_key,
+      dealer_shares.clone(),
+      CONTEXT.to_string(),
+    )
+    .unwrap();
+    let (machine, registration) = machine.generate_encryption_key(&mut *rng);
+    receiving.insert(i, machine);
+    registrations.insert(
+      i,
+      EncryptionKeyMessage::read::<&[u8]>(&mut registration.serialize().as_ref(), PARAMS).unwrap(),
+    );
+  }
+
+  let mut secret_shares = BTreeMap::new();
+  for (l, machine) in dealing.into_iter() {
+    let shares = machine
+      .generate_secret_shares(&mut *rng, registrations.clone())
+      .unwrap()
+      .into_iter()
+      .map(|(i, share)| {
+        (i, EncryptedMessage::read::<&[u8]>(&mut share.serialize().as_ref(), PARAMS).unwrap())
+      })
+      .collect::<BTreeMap<_, _>>();
+    secret_shares.insert(l, shares);
+  }
+
+  Resharing { group_key, dealer_shares, commitments, registrations, receiving, secret_shares }
+}
+
+fn received_shares<C: Ciphersuite>(
+  shares: &BTreeMap<Participant, ResharingSecretShares<C>>,
+  recipient: Participant,
+) -> ResharingSecretShares<C> {
+  shares.iter().map(|(l, shares)| (*l, shares[&recipient].clone())).collect()
+}
+
+// Test resharing threshold keys to a larger set of participants with a higher threshold
+pub(crate) fn test_resharing<R: RngCore + CryptoRng, C: Ciphersuite>(rng: &mut R) {
+  let Resharing { group_key, dealer_shares, commitments, registrations, receiving, secret_shares } =
+    reshare::<_, C>(&mut *rng);
+  // The dealers' shares, as interpolated for the dealing set, sum to the group key
+  assert_eq!(dealer_shares.values().copied().sum::<C::G>(), group_key);
+
+  let reshared = receiving
+    .into_iter()
+    .map(|(i, machine)| {
+      let core = machine
+        .calculate_share(
+          &mut *rng,
+          clone_without(&registrations, &i),
+          commitments.clone(),
+          received_shares(&secret_shares, i),
+        )
+        .unwrap()
+        .complete();
+      (i, core)
+    })
+    .collect::<BTreeMap<Participant, ThresholdCore<C>>>();
+
+  let verification_shares = reshared[&Participant(1)].verification_shares();
+  for (i, core) in &reshared {
+    assert_eq!(core.params(), ThresholdParams::new(NEW_THRESHOLD, NEW_PARTICIPANTS, *i).unwrap());
+    // The group key is unchanged, and everyone agrees on the verification shares
+    assert_eq!(core.group_key(), group_key);
+    assert_eq!(core.verification_shares(), verification_shares);
+    assert_eq!(C::generator() * core.secret_share().deref(), verification_shares[i]);
+    assert_eq!(&ThresholdCore::<C>::read::<&[u8]>(&mut core.serialize().as_ref()).unwrap(), core);
+  }
+
+  let reshared =
+    reshared.into_iter().map(|(i, core)| (i, ThresholdKeys::new(core))).collect::<BTreeMap<_, _>>();
+  assert_eq!(C::generator() * recover_key(&reshared), group_key);
+
+  // Any t of the new participants suffice
+  let subset = reshared
+    .iter()
+    .filter(|(i, _)| u16::from(**i) > (NEW_PARTICIPANTS - NEW_THRESHOLD))
+    .map(|(i, keys)| (*i, keys.clone()))
+    .collect::<BTreeMap<_, _>>();
+  assert_eq!(subset.len(), usize::from(NEW_THRESHOLD));
+  assert_eq!(C::generator() * recover_key(&subset), group_key);
+}
+
+#[cfg(test)]
+mod literal {
+  use rand_core::OsRng;
+
+  use ciphersuite::Ristretto;
+
+  use crate::{
+    DkgError, encryption::EncryptionKeyProof, frost::BlameMachine, resharing::dealer_context,
+  };
+
+  use super::*;
+
+  // A dealer, who is also the new participant with the same index
+  const DEALER: Participant = Participant(PARTICIPANTS);
+  const RECIPIENT: Participant = Participant(1);
+
+  fn test_blame(
+    machines: Vec<BlameMachine<Ristretto>>,
+    msg: ResharingEncryptedMessage<Ristretto>,
+    blame: Option<EncryptionKeyProof<Ristretto>>,
+    faulty: Participant,
+  ) {
+    for machine in machines {
+      let (additional, blamed) = machine.blame(DEALER, RECIPIENT, msg.clone(), blame.clone());
+      assert_eq!(blamed, faulty);
+      // Verify additional blame also works
+      assert_eq!(additional.blame(DEALER, RECIPIENT, msg.clone(), blame.clone()), faulty);
+    }
+  }
+
+  // Calculate every new participant's share, returning the recipient's result and everyone
+  // else's BlameMachine
+  #[allow(clippy::type_complexity)]
+  fn calculate_shares(
+    resharing: Resharing<Ristretto>,
+  ) -> (
+    Result<BlameMachine<Ristretto>, DkgError<EncryptionKeyProof<Ristretto>>>,
+    Vec<BlameMachine<Ristretto>>,
+  ) {
+    let Resharing { commitments, registrations, receiving, secret_shares, .. } = resharing;
+    let mut recipient = None;
+    let machines = receiving
+      .into_iter()
+      .filter_map(|(i, machine)| {
+        let machine = machine.calculate_share(
+          &mut OsRng,
+          clone_without(&registrations, &i),
+          commitments.clone(),
+          received_shares(&secret_shares, i),
+        );
+        if i == RECIPIENT {
+          recipient = Some(machine);
+          None
+        } else {
+          Some(machine.unwrap())
+        }
+      })
+      .collect();
+    (recipient.unwrap(), machines)
+  }
+
+  #[test]
+  fn invalid_group_key() {
+    let Resharing { group_key, dealer_shares, .. } = reshare::<_, Ristretto>(&mut OsRng);
+    assert_eq!(
+      ResharedMachine::<Ristretto>::new(
+        ThresholdParams::new(NEW_THRESHOLD, NEW_PARTICIPANTS, RECIPIENT).unwrap(),
+        group_key + Ristretto::generator(),
+        dealer_shares,
+        CONTEXT.to_string(),
+      )
+      .err(),
+      Some(DkgError::InvalidGroupKey)
+    );
+  }
+
+  #[test]
+  fn invalid_dealer_proof_of_knowledge() {
+    let mut resharing = reshare::<_, Ristretto>(&mut OsRng);
+
+    // Have the dealer claim another dealer's commitments
+    let other = *resharing.commitments.keys().find(|l| **l != DEALER).unwrap();
+    let commitments = resharing.commitments[&other].clone();
+    resharing.commitments.insert(DEALER, commitments);
+
+    let Resharing { commitments, registrations, receiving, secret_shares, .. } = resharing;
+    for (i, machine) in receiving {
+      assert_eq!(
+        machine
+          .calculate_share(
+            &mut OsRng,
+            clone_without(&registrations, &i),
+            commitments.clone(),
+            received_shares(&secret_shares, i),
+          )
+          .err(),
+        Some(DkgError::InvalidProofOfKnowledge(DEALER))
+      );
+    }
+  }
+
+  #[test]
+  fn invalid_encryption_pop_blame() {
+    let mut resharing = reshare::<_, Ristretto>(&mut OsRng);
+    resharing.secret_shares.get_mut(&DEALER).unwrap().get_mut(&RECIPIENT).unwrap().invalidate_pop();
+    let msg = resharing.secret_shares[&DEALER][&RECIPIENT].clone();
+
+    let (recipient, machines) = calculate_shares(resharing);
+    assert_eq!(recipient.err(), Some(DkgError::InvalidShare { participant: DEALER, blame: None }));
+    test_blame(machines, msg, None, DEALER);
+  }
+
+  #[test]
+  fn invalid_ecdh_blame() {
+    let mut resharing = reshare::<_, Ristretto>(&mut OsRng);
+    resharing.secret_shares.get_mut(&DEALER).unwrap().get_mut(&RECIPIENT).unwrap().invalidate_msg(
+      &mut OsRng,
+      &dealer_context(CONTEXT),
+      DEALER,
+    );
+    let msg = resharing.secret_shares[&DEALER][&RECIPIENT].clone();
+
+    let (recipient, machines) = calculate_shares(resharing);
+    let mut blame = match recipient.err() {
+      Some(DkgError::InvalidShare { participant: DEALER, blame: Some(blame) }) => blame,
+      _ => panic!(),
+    };
+    // The dealer sent gibberish, yet the recipient's malleated blame proof makes them faulty
+    blame.invalidate_key();
+    test_blame(machines, msg, Some(blame), RECIPIENT);
+  }
+
+  #[test]
+  fn invalid_share_value_blame() {
+    let mut resharing = reshare::<_, Ristretto>(&mut OsRng);
+    let enc_key = resharing.registrations[&RECIPIENT].enc_key();
+    resharing
+      .secret_shares
+      .get_mut(&DEALER)
+      .unwrap()
+      .get_mut(&RECIPIENT)
+      .unwrap()
+      .invalidate_share_value(&mut OsRng, &dealer_context(CONTEXT), DEALER, enc_key);
+    let msg = resharing.secret_shares[&DEALER][&RECIPIENT].clone();
+
+    let (recipient, machines) = calculate_shares(resharing);
+    let blame = match recipient.err() {
+      Some(DkgError::InvalidShare { participant: DEALER, blame: Some(blame) }) => blame,
+      _ => panic!(),
+    };
+    test_blame(machines, msg, Some(blame), DEALER);
+  }
+}
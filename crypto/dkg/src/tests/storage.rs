@@ -0,0 +1,46 @@
+use rand_core::{RngCore, CryptoRng};
+
+use ciphersuite::Ciphersuite;
+
+use crate::{
+  Participant, ThresholdCore,
+  storage::Kdf,
+  tests::{frost::frost_gen, PARTICIPANTS},
+};
+
+// Test encrypting keys for storage at rest
+pub(crate) fn test_storage<R: RngCore + CryptoRng, C: Ciphersuite>(rng: &mut R) {
+  let keys = frost_gen::<_, C>(&mut *rng);
+  let core = &keys[&Participant(PARTICIPANTS)];
+
+  let mut key = [0; 32];
+  rng.fill_bytes(&mut key);
+  // Use weak parameters so the test runs quickly
+  for (kdf, secret) in
+    [(Kdf::Key, key.as_ref()), (Kdf::Scrypt { log_n: 4, r: 8, p: 1 }, b"password".as_ref())]
+  {
+    let encrypted = core.serialize_encrypted(&mut *rng, kdf, secret).unwrap();
+    // Encryption is randomized
+    assert!(encrypted != core.serialize_encrypted(&mut *rng, kdf, secret).unwrap());
+
+    assert_eq!(
+      &ThresholdCore::<C>::read_encrypted::<&[u8]>(&mut encrypted.as_ref(), secret).unwrap(),
+      core
+    );
+
+    // The wrong secret fails to decrypt
+    let mut wrong = secret.to_vec();
+    wrong[0] ^= 1;
+    assert!(ThresholdCore::<C>::read_encrypted::<&[u8]>(&mut encrypted.as_ref(), &wrong).is_err());
+
+    // As does any modification, including to the authenticated group key and KDF parameters
+    for i in 0 .. encrypted.len() {
+      let mut modified = encrypted.clone();
+      modified[i] ^= 1;
+      assert!(ThresholdCore::<C>::read_encrypted::<&[u8]>(&mut modified.as_ref(), secret).is_err());
+    }
+  }
+
+  // Keys which aren't uniform and at least 32 bytes are rejected
+  assert!(core.serialize_encrypted(&mut *rng, Kdf::Key, &key[.. 16]).is_err());
+}
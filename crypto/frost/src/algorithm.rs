@@ -1,5 +1,8 @@
 use core::{marker::PhantomData, fmt::Debug};
-use std::io::{self, Read, Write};
+use std_shims::{
+  vec::Vec,
+  io::{self, Read, Write},
+};
 
 use zeroize::Zeroizing;
 use rand_core::{RngCore, CryptoRng};
@@ -71,6 +74,18 @@ pub trait Algorithm<C: Curve>: Send + Sync + Clone {
     msg: &[u8],
   ) -> C::F;
 
+  /// Establish the state needed to verify shares and signatures, without signing a share.
+  ///
+  /// This is used by parties who verify shares yet don't hold a key share, such as a ROAST
+  /// coordinator. The view's secret share will accordingly be zero. `sign_share` is expected to
+  /// establish the same state.
+  fn prepare_verification(
+    &mut self,
+    params: &ThresholdView<C>,
+    nonce_sums: &[Vec<C::G>],
+    msg: &[u8],
+  );
+
   /// Verify a signature.
   #[must_use]
   fn verify(&self, group_key: C::G, nonces: &[Vec<C::G>], sum: C::F) -> Option<Self::Signature>;
@@ -196,9 +211,17 @@ impl<C: Curve, T: Sync + Clone + Debug + Transcript, H: Hram<C>> Algorithm<C> fo
     mut nonces: Vec<Zeroizing<C::F>>,
     msg: &[u8],
   ) -> C::F {
-    let c = H::hram(&nonce_sums[0][0], &params.group_key(), msg);
-    self.c = Some(c);
-    SchnorrSignature::<C>::sign(params.secret_share(), nonces.swap_remove(0), c).s
+    self.prepare_verification(params, nonce_sums, msg);
+    SchnorrSignature::<C>::sign(params.secret_share(), nonces.swap_remove(0), self.c.unwrap()).s
+  }
+
+  fn prepare_verification(
+    &mut self,
+    params: &ThresholdView<C>,
+    nonce_sums: &[Vec<C::G>],
+    msg: &[u8],
+  ) {
+    self.c = Some(H::hram(&nonce_sums[0][0], &params.group_key(), msg));
   }
 
   #[must_use]
@@ -0,0 +1,240 @@
+use core::{ops::Deref, fmt::Debug};
+use std_shims::{
+  vec::Vec,
+  io::{self, Read},
+};
+
+use zeroize::Zeroizing;
+use rand_core::{RngCore, CryptoRng};
+
+use digest::Digest;
+use transcript::Transcript;
+
+use ciphersuite::{
+  group::{ff::PrimeField, Group, GroupEncoding},
+  Ciphersuite,
+};
+
+use crate::{
+  curve::Secp256k1,
+  Participant, ThresholdKeys, ThresholdView, FrostError,
+  algorithm::{Algorithm, SchnorrSignature},
+};
+
+type G = <Secp256k1 as Ciphersuite>::G;
+type F = <Secp256k1 as Ciphersuite>::F;
+type H = <Secp256k1 as Ciphersuite>::H;
+
+/// Whether or not a point has an even y coordinate.
+pub fn is_even(point: &G) -> bool {
+  // The SEC1 tag for a compressed point is 0x02 if even, 0x03 if odd
+  point.to_bytes()[0] != 0x03
+}
+
+/// Get the x coordinate of a non-infinity point, as used for BIP-340 x-only keys.
+///
+/// A point and its negation share an x coordinate, so this is defined for points of either parity.
+/// Panics on the point at infinity.
+pub fn x(point: &G) -> [u8; 32] {
+  assert!(!bool::from(point.is_identity()), "x coordinate of the point at infinity");
+  point.to_bytes()[1 ..].try_into().unwrap()
+}
+
+/// Lift an x coordinate to the point with an even y coordinate, if one exists.
+pub fn lift_x(x: &[u8; 32]) -> Option<G> {
+  let mut repr = <G as GroupEncoding>::Repr::default();
+  repr[0] = 0x02;
+  repr[1 ..].copy_from_slice(x);
+  Option::<G>::from(G::from_bytes(&repr)).filter(|point| !bool::from(point.is_identity()))
+}
+
+// The hash defined by BIP-340, domain separated by the hash of the tag
+fn tagged_hash(tag: &[u8], data: &[&[u8]]) -> [u8; 32] {
+  let tag = H::digest(tag);
+  let mut hash = H::new();
+  hash.update(tag);
+  hash.update(tag);
+  for data in data {
+    hash.update(data);
+  }
+  hash.finalize().into()
+}
+
+// Reduce a big-endian 32-byte value modulo the order of the curve
+fn reduce(bytes: [u8; 32]) -> F {
+  let high = u128::from_be_bytes(bytes[.. 16].try_into().unwrap());
+  let low = u128::from_be_bytes(bytes[16 ..].try_into().unwrap());
+  (F::from_u128(high) * F::from_u128(1 << 64).square()) + F::from_u128(low)
+}
+
+fn challenge(r: &[u8], public_key: &[u8; 32], msg: &[u8]) -> F {
+  reduce(tagged_hash(b"BIP0340/challenge", &[r, public_key, msg]))
+}
+
+/// Verify a BIP-340 signature for the specified x-only public key.
+pub fn verify(public_key: &[u8; 32], msg: &[u8], sig: &[u8; 64]) -> bool {
+  let Some(public_key_point) = lift_x(public_key) else { return false };
+
+  let mut s = <F as PrimeField>::Repr::default();
+  s.copy_from_slice(&sig[32 ..]);
+  let Some(s) = Option::<F>::from(F::from_repr(s)) else { return false };
+
+  #[allow(non_snake_case)]
+  let R =
+    (Secp256k1::generator() * s) - (public_key_point * challenge(&sig[.. 32], public_key, msg));
+  // x will panic on the point at infinity, yet it's rejected here first
+  (!bool::from(R.is_identity())) && is_even(&R) && (x(&R) == sig[.. 32])
+}
+
+// The scalar an internal key is tweaked by to produce a Taproot output key
+pub(crate) fn taproot_tweak_scalar(internal_key: &[u8; 32], merkle_root: Option<[u8; 32]>) -> F {
+  let mut tweak = <F as PrimeField>::Repr::default();
+  tweak.copy_from_slice(&tagged_hash(
+    b"TapTweak",
+    &[internal_key, merkle_root.as_ref().map(AsRef::as_ref).unwrap_or(&[])],
+  ));
+  Option::<F>::from(F::from_repr(tweak)).expect("Taproot tweak exceeded the order")
+}
+
+/// Tweak keys to the Taproot output key for the specified script tree, per BIP-341.
+///
+/// The returned keys' group key has the x coordinate of the output key, yet not necessarily its
+/// parity, which the `Bip340` algorithm handles when signing. The group key of the passed keys is
+/// used as the internal key, regardless of its own parity.
+///
+/// Panics if the tweak isn't a valid scalar, which has a negligible probability of occurring.
+pub fn taproot_tweak(
+  keys: &ThresholdKeys<Secp256k1>,
+  merkle_root: Option<[u8; 32]>,
+) -> ThresholdKeys<Secp256k1> {
+  let internal_key = keys.group_key();
+  let tweak = taproot_tweak_scalar(&x(&internal_key), merkle_root);
+
+  // The output key is lift_x(P) + tG
+  // If P is odd, lift_x(P) is -P, so offset by -t to produce -(lift_x(P) + tG), which shares an x
+  // coordinate with the output key
+  keys.offset(if is_even(&internal_key) { tweak } else { -tweak })
+}
+
+/// BIP-340 Schnorr signature algorithm, producing signatures valid for Bitcoin's Taproot.
+///
+/// BIP-340 defines keys and nonces as x coordinates, implicitly having an even y coordinate. This
+/// algorithm accepts keys and nonces of either parity, negating them as needed while signing, so
+/// the signature verifies against the x coordinate of the group key (as returned by `x`).
+#[derive(Clone)]
+pub struct Bip340<T: Sync + Clone + Debug + Transcript> {
+  transcript: T,
+  c: Option<F>,
+  negate_nonce: bool,
+  negate_key: bool,
+}
+
+impl<T: Sync + Clone + Debug + Transcript> Bip340<T> {
+  /// Construct a BIP-340 algorithm continuing the specified transcript.
+  pub fn new(transcript: T) -> Bip340<T> {
+    Bip340 { transcript, c: None, negate_nonce: false, negate_key: false }
+  }
+}
+
+impl<T: Sync + Clone + Debug + Transcript> Algorithm<Secp256k1> for Bip340<T> {
+  type Transcript = T;
+  type Addendum = ();
+  type Signature = [u8; 64];
+
+  fn transcript(&mut self) -> &mut Self::Transcript {
+    &mut self.transcript
+  }
+
+  fn nonces(&self) -> Vec<Vec<G>> {
+    vec![vec![Secp256k1::generator()]]
+  }
+
+  fn preprocess_addendum<R: RngCore + CryptoRng>(
+    &mut self,
+    _: &mut R,
+    _: &ThresholdKeys<Secp256k1>,
+  ) {
+  }
+
+  fn read_addendum<R: Read>(&self, _: &mut R) -> io::Result<Self::Addendum> {
+    Ok(())
+  }
+
+  fn process_addendum(
+    &mut self,
+    _: &ThresholdView<Secp256k1>,
+    _: Participant,
+    _: (),
+  ) -> Result<(), FrostError> {
+    Ok(())
+  }
+
+  fn sign_share(
+    &mut self,
+    params: &ThresholdView<Secp256k1>,
+    nonce_sums: &[Vec<G>],
+    mut nonces: Vec<Zeroizing<F>>,
+    msg: &[u8],
+  ) -> F {
+    self.prepare_verification(params, nonce_sums, msg);
+
+    let mut nonce = nonces.swap_remove(0);
+    if self.negate_nonce {
+      *nonce = -*nonce;
+    }
+    let mut key = Zeroizing::new(*params.secret_share().deref());
+    if self.negate_key {
+      *key = -*key;
+    }
+    SchnorrSignature::<Secp256k1>::sign(&key, nonce, self.c.unwrap()).s
+  }
+
+  fn prepare_verification(
+    &mut self,
+    params: &ThresholdView<Secp256k1>,
+    nonce_sums: &[Vec<G>],
+    msg: &[u8],
+  ) {
+    #[allow(non_snake_case)]
+    let R = nonce_sums[0][0];
+    let group_key = params.group_key();
+
+    // If the nonce or key is odd, sign with its negation, which is even and shares an x coordinate
+    self.negate_nonce = !is_even(&R);
+    self.negate_key = !is_even(&group_key);
+
+    self.c = Some(challenge(&x(&R), &x(&group_key), msg));
+  }
+
+  #[must_use]
+  fn verify(&self, group_key: G, nonces: &[Vec<G>], sum: F) -> Option<Self::Signature> {
+    #[allow(non_snake_case)]
+    let R = if self.negate_nonce { -nonces[0][0] } else { nonces[0][0] };
+    let group_key = if self.negate_key { -group_key } else { group_key };
+    if (Secp256k1::generator() * sum) != (R + (group_key * self.c.unwrap())) {
+      None?;
+    }
+
+    let mut sig = [0; 64];
+    sig[.. 32].copy_from_slice(&x(&R));
+    sig[32 ..].copy_from_slice(sum.to_repr().as_ref());
+    Some(sig)
+  }
+
+  fn verify_share(
+    &self,
+    verification_share: G,
+    nonces: &[Vec<G>],
+    share: F,
+  ) -> Result<Vec<(F, G)>, ()> {
+    let verification_share = if self.negate_key { -verification_share } else { verification_share };
+    Ok(
+      SchnorrSignature::<Secp256k1> {
+        R: if self.negate_nonce { -nonces[0][0] } else { nonces[0][0] },
+        s: share,
+      }
+      .batch_statements(verification_share, self.c.unwrap())
+      .to_vec(),
+    )
+  }
+}
@@ -1,5 +1,5 @@
 use core::ops::Deref;
-use std::io::{self, Read};
+use std_shims::io::{self, Read};
 
 use rand_core::{RngCore, CryptoRng};
 
@@ -1,10 +1,12 @@
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 #![doc = include_str!("../README.md")]
+#![cfg_attr(not(feature = "std"), no_std)]
 
 use core::fmt::Debug;
-use std::collections::HashMap;
-
-use thiserror::Error;
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
+use std_shims::collections::BTreeMap;
 
 /// Distributed key generation protocol.
 pub use dkg::{self, Participant, ThresholdParams, ThresholdCore, ThresholdKeys, ThresholdView};
@@ -18,34 +20,46 @@ pub mod algorithm;
 mod nonce;
 /// Threshold signing protocol.
 pub mod sign;
+/// ROAST, a robust wrapper around the threshold signing protocol.
+pub mod roast;
+/// BIP-340 Schnorr signatures, as used by Bitcoin's Taproot.
+#[cfg(feature = "secp256k1")]
+pub mod bip340;
 
 /// Tests for application-provided curves and algorithms.
 #[cfg(any(test, feature = "tests"))]
 pub mod tests;
 
 /// Various errors possible during signing.
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Error)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
 pub enum FrostError {
-  #[error("invalid participant (0 < participant <= {0}, yet participant is {1})")]
+  #[cfg_attr(
+    feature = "std",
+    error("invalid participant (0 < participant <= {0}, yet participant is {1})")
+  )]
   InvalidParticipant(u16, Participant),
-  #[error("invalid signing set ({0})")]
+  #[cfg_attr(feature = "std", error("invalid signing set ({0})"))]
   InvalidSigningSet(&'static str),
-  #[error("invalid participant quantity (expected {0}, got {1})")]
+  #[cfg_attr(feature = "std", error("invalid participant quantity (expected {0}, got {1})"))]
   InvalidParticipantQuantity(usize, usize),
-  #[error("duplicated participant ({0})")]
+  #[cfg_attr(feature = "std", error("duplicated participant ({0})"))]
   DuplicatedParticipant(Participant),
-  #[error("missing participant {0}")]
+  #[cfg_attr(feature = "std", error("missing participant {0}"))]
   MissingParticipant(Participant),
 
-  #[error("invalid preprocess (participant {0})")]
+  #[cfg_attr(feature = "std", error("invalid preprocess (participant {0})"))]
   InvalidPreprocess(Participant),
-  #[error("invalid share (participant {0})")]
+  #[cfg_attr(feature = "std", error("invalid share (participant {0})"))]
   InvalidShare(Participant),
+
+  #[cfg_attr(feature = "std", error("verification shares weren't consistent with the group key"))]
+  InvalidVerificationShares,
 }
 
 /// Validate a map of values to have the expected participants.
 pub fn validate_map<T>(
-  map: &HashMap<Participant, T>,
+  map: &BTreeMap<Participant, T>,
   included: &[Participant],
   ours: Participant,
 ) -> Result<(), FrostError> {
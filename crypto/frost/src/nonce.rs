@@ -10,9 +10,10 @@
 // confirm their integrity
 
 use core::ops::Deref;
-use std::{
+use std_shims::{
+  vec::Vec,
   io::{self, Read, Write},
-  collections::HashMap,
+  collections::BTreeMap,
 };
 
 use rand_core::{RngCore, CryptoRng};
@@ -248,7 +249,7 @@ pub(crate) struct IndividualBinding<C: Curve> {
   binding_factors: Option<Vec<C::F>>,
 }
 
-pub(crate) struct BindingFactor<C: Curve>(pub(crate) HashMap<Participant, IndividualBinding<C>>);
+pub(crate) struct BindingFactor<C: Curve>(pub(crate) BTreeMap<Participant, IndividualBinding<C>>);
 
 impl<C: Curve> BindingFactor<C> {
   pub(crate) fn insert(&mut self, i: Participant, commitments: Commitments<C>) {
@@ -0,0 +1,312 @@
+use std_shims::{
+  vec::Vec,
+  io::{self, Read},
+  collections::{VecDeque, BTreeSet, BTreeMap},
+};
+
+use transcript::Transcript;
+
+use ciphersuite::group::{ff::Field, Group};
+use multiexp::multiexp_vartime;
+
+use crate::{
+  curve::Curve,
+  Participant, FrostError, ThresholdParams, ThresholdView,
+  algorithm::{Addendum, Algorithm},
+  sign::{Preprocess, SignatureShare, Commitments, BindingFactor, process_preprocesses},
+};
+
+/// A signing session launched by a RoastCoordinator.
+///
+/// The preprocesses should be sent to every participant within the session. Each participant
+/// signs with the machine which produced their preprocess, as present in this session, and
+/// reports their share back to the coordinator with this session's ID.
+#[derive(Clone, PartialEq)]
+pub struct RoastSession<C: Curve, A: Addendum> {
+  /// The ID of this session.
+  pub id: u32,
+  /// The preprocesses of every participant included within this session.
+  pub preprocesses: BTreeMap<Participant, Preprocess<C, A>>,
+}
+
+// Coordinator-side state for an open session
+#[allow(non_snake_case)]
+struct Session<C: Curve, A: Algorithm<C>> {
+  algorithm: A,
+  view: ThresholdView<C>,
+  B: BindingFactor<C>,
+  Rs: Vec<Vec<C::G>>,
+  shares: BTreeMap<Participant, C::F>,
+}
+
+/// Coordinator for ROAST, a robust wrapper around FROST.
+///
+/// FROST requires every participant chosen at preprocess time to respond, with any non-responder
+/// forcing the protocol to restart. ROAST instead keeps a pool of fresh preprocesses per
+/// participant, launching a session for any t participants which have one available and aren't
+/// being waited on. The first session to complete yields the signature. Participants who submit
+/// invalid shares are blamed and excluded from all future sessions.
+///
+/// The coordinator only needs the multisig's public components, with the algorithm's state for
+/// verifying shares established by `Algorithm::prepare_verification`. The coordinator does not
+/// need to be a participant in the multisig.
+pub struct RoastCoordinator<C: Curve, A: Algorithm<C>> {
+  algorithm: A,
+  params: ThresholdParams,
+  group_key: C::G,
+  verification_shares: BTreeMap<Participant, C::G>,
+  offset: Option<C::F>,
+  commitments_challenge: <A::Transcript as Transcript>::Challenge,
+  msg: Vec<u8>,
+
+  preprocesses: BTreeMap<Participant, VecDeque<Preprocess<C, A::Addendum>>>,
+  // Participants included in an open session who have yet to respond
+  awaiting: BTreeSet<Participant>,
+  malicious: BTreeSet<Participant>,
+
+  next_session: u32,
+  sessions: BTreeMap<u32, Session<C, A>>,
+}
+
+impl<C: Curve, A: Algorithm<C>> RoastCoordinator<C, A> {
+  /// Create a new coordinator to produce a signature for the specified message.
+  ///
+  /// The group key and verification shares should be without any offset, and are checked to be
+  /// consistent with each other. The `i` of the parameters is ignored.
+  pub fn new(
+    mut algorithm: A,
+    params: ThresholdParams,
+    group_key: C::G,
+    verification_shares: BTreeMap<Participant, C::G>,
+    msg: &[u8],
+  ) -> Result<RoastCoordinator<C, A>, FrostError> {
+    let n = params.n();
+    if verification_shares.len() != usize::from(n) {
+      Err(FrostError::InvalidParticipantQuantity(usize::from(n), verification_shares.len()))?;
+    }
+    let participants = (1 ..= n).map(|l| Participant::new(l).unwrap()).collect::<Vec<_>>();
+    for l in &participants {
+      if !verification_shares.contains_key(l) {
+        Err(FrostError::MissingParticipant(*l))?;
+      }
+    }
+
+    // Every set of t verification shares must interpolate to the group key, as otherwise a
+    // session's valid shares may not form a valid signature
+    let t = usize::from(params.t());
+    for l in &participants[(t - 1) ..] {
+      let mut included = participants[.. (t - 1)].to_vec();
+      included.push(*l);
+      let interpolated = included
+        .iter()
+        .map(|i| verification_shares[i] * dkg::lagrange::<C::F>(*i, &included))
+        .sum::<C::G>();
+      if interpolated != group_key {
+        Err(FrostError::InvalidVerificationShares)?;
+      }
+    }
+
+    // Obtain the challenge used for the commitments' proofs, advancing the transcript as every
+    // participant does
+    let commitments_challenge = algorithm.transcript().challenge(b"commitments");
+    Ok(RoastCoordinator {
+      algorithm,
+      params,
+      group_key,
+      verification_shares,
+      offset: None,
+      commitments_challenge,
+      msg: msg.to_vec(),
+
+      preprocesses: BTreeMap::new(),
+      awaiting: BTreeSet::new(),
+      malicious: BTreeSet::new(),
+
+      next_session: 0,
+      sessions: BTreeMap::new(),
+    })
+  }
+
+  /// Offset the keys being signed for, as `ThresholdKeys::offset` would.
+  ///
+  /// This accumulates, so calling offset multiple times will produce a offset of the offsets' sum.
+  #[must_use]
+  pub fn offset(mut self, offset: C::F) -> RoastCoordinator<C, A> {
+    self.offset = Some(offset + self.offset.unwrap_or(C::F::ZERO));
+    self
+  }
+
+  /// Read a Preprocess message.
+  pub fn read_preprocess<R: Read>(&self, reader: &mut R) -> io::Result<Preprocess<C, A::Addendum>> {
+    Ok(Preprocess {
+      commitments: Commitments::read::<_, A::Transcript>(
+        reader,
+        &self.algorithm.nonces(),
+        self.commitments_challenge.as_ref(),
+      )?,
+      addendum: self.algorithm.read_addendum(reader)?,
+    })
+  }
+
+  /// Read a SignatureShare message.
+  pub fn read_share<R: Read>(&self, reader: &mut R) -> io::Result<SignatureShare<C>> {
+    Ok(SignatureShare(C::read_F(reader)?))
+  }
+
+  /// The participants who have been blamed for submitting invalid preprocesses or shares.
+  ///
+  /// If more than n - t participants have been blamed, no session will ever be launched again.
+  pub fn malicious(&self) -> &BTreeSet<Participant> {
+    &self.malicious
+  }
+
+  fn blame(&mut self, l: Participant) {
+    self.malicious.insert(l);
+    self.preprocesses.remove(&l);
+  }
+
+  /// Add a fresh preprocess for a participant to the pool.
+  ///
+  /// A participant's preprocesses are used in the order they're added. Preprocesses from
+  /// participants who have been blamed are ignored.
+  pub fn add_preprocess(
+    &mut self,
+    l: Participant,
+    preprocess: Preprocess<C, A::Addendum>,
+  ) -> Result<(), FrostError> {
+    let n = self.params.n();
+    if u16::from(l) > n {
+      Err(FrostError::InvalidParticipant(n, l))?;
+    }
+    if !self.malicious.contains(&l) {
+      self.preprocesses.entry(l).or_default().push_back(preprocess);
+    }
+    Ok(())
+  }
+
+  /// Launch as many sessions as possible with the currently available preprocesses.
+  ///
+  /// Participants who submitted a preprocess which failed to process are blamed.
+  pub fn launch(&mut self) -> Result<Vec<RoastSession<C, A::Addendum>>, FrostError> {
+    let t = usize::from(self.params.t());
+
+    let mut sessions = vec![];
+    loop {
+      let mut included = self
+        .preprocesses
+        .iter()
+        .filter(|(l, preprocesses)| !(preprocesses.is_empty() || self.awaiting.contains(*l)))
+        .map(|(l, _)| *l)
+        .collect::<Vec<_>>();
+      if included.len() < t {
+        break;
+      }
+      included.sort_unstable();
+      included.truncate(t);
+
+      let preprocesses = included
+        .iter()
+        .map(|l| (*l, self.preprocesses.get_mut(l).unwrap().pop_front().unwrap()))
+        .collect::<BTreeMap<_, _>>();
+
+      let mut algorithm = self.algorithm.clone();
+      let view = ThresholdView::public(
+        self.params,
+        self.group_key,
+        self.verification_shares.clone(),
+        self.offset,
+        included.clone(),
+      )
+      .unwrap();
+      #[allow(non_snake_case)]
+      let (B, Rs) = match process_preprocesses(
+        &mut algorithm,
+        self.offset,
+        &view,
+        preprocesses.clone(),
+        &self.msg,
+      ) {
+        Ok(res) => res,
+        Err(e) => {
+          // Return everyone else's preprocesses to the pool
+          for (l, preprocess) in preprocesses {
+            if e != FrostError::InvalidPreprocess(l) {
+              self.preprocesses.get_mut(&l).unwrap().push_front(preprocess);
+            }
+          }
+          match e {
+            FrostError::InvalidPreprocess(l) => self.blame(l),
+            _ => Err(e)?,
+          }
+          continue;
+        }
+      };
+
+      // Establish the algorithm's state for verifying shares
+      algorithm.prepare_verification(&view, &Rs, &self.msg);
+
+      for l in &included {
+        self.awaiting.insert(*l);
+      }
+
+      let id = self.next_session;
+      self.next_session += 1;
+      self.sessions.insert(id, Session { algorithm, view, B, Rs, shares: BTreeMap::new() });
+      sessions.push(RoastSession { id, preprocesses });
+    }
+    Ok(sessions)
+  }
+
+  /// Add a participant's signature share for a session.
+  ///
+  /// Returns the signature once any session has received valid shares from all of its
+  /// participants. If the share is invalid, the participant is blamed and the session is
+  /// abandoned.
+  pub fn add_share(
+    &mut self,
+    id: u32,
+    l: Participant,
+    share: SignatureShare<C>,
+  ) -> Result<Option<A::Signature>, FrostError> {
+    let session =
+      self.sessions.get_mut(&id).ok_or(FrostError::InvalidSigningSet("unknown session"))?;
+    if !session.view.included().contains(&l) {
+      Err(FrostError::InvalidSigningSet("participant wasn't included in this session"))?;
+    }
+    if session.shares.contains_key(&l) {
+      Err(FrostError::DuplicatedParticipant(l))?;
+    }
+    self.awaiting.remove(&l);
+
+    let valid = session
+      .algorithm
+      .verify_share(session.view.verification_share(l), &session.B.bound(l), share.0)
+      .map(|statements| bool::from(multiexp_vartime(&statements).is_identity()))
+      .unwrap_or(false);
+    if !valid {
+      // This session can no longer complete, so stop waiting on its other participants
+      let session = self.sessions.remove(&id).unwrap();
+      for i in session.view.included() {
+        if !session.shares.contains_key(i) {
+          self.awaiting.remove(i);
+        }
+      }
+      self.blame(l);
+      return Err(FrostError::InvalidShare(l));
+    }
+
+    session.shares.insert(l, share.0);
+    if session.shares.len() != session.view.included().len() {
+      return Ok(None);
+    }
+
+    let session = self.sessions.remove(&id).unwrap();
+    let sum = session.shares.values().sum();
+    Ok(Some(
+      session
+        .algorithm
+        .verify(session.view.group_key(), &session.Rs, sum)
+        .expect("every share was valid yet the signature was still invalid"),
+    ))
+  }
+}
@@ -1,7 +1,8 @@
 use core::{ops::Deref, fmt::Debug};
-use std::{
+use std_shims::{
+  vec::Vec,
   io::{self, Read, Write},
-  collections::HashMap,
+  collections::BTreeMap,
 };
 
 use rand_core::{RngCore, CryptoRng, SeedableRng};
@@ -9,7 +10,7 @@ use rand_chacha::ChaCha20Rng;
 
 use zeroize::{Zeroize, Zeroizing};
 
-use transcript::Transcript;
+use transcript::{Transcript, RecommendedTranscript};
 
 use ciphersuite::group::{ff::PrimeField, GroupEncoding};
 use multiexp::BatchVerifier;
@@ -45,23 +46,95 @@ impl<T: Writable> Writable for Vec<T> {
 
 // Pairing of an Algorithm with a ThresholdKeys instance.
 #[derive(Clone, Zeroize)]
-struct Params<C: Curve, A: Algorithm<C>> {
+pub(crate) struct Params<C: Curve, A: Algorithm<C>> {
   // Skips the algorithm due to being too large a bound to feasibly enforce on users
   #[zeroize(skip)]
-  algorithm: A,
-  keys: ThresholdKeys<C>,
+  pub(crate) algorithm: A,
+  pub(crate) keys: ThresholdKeys<C>,
 }
 
 impl<C: Curve, A: Algorithm<C>> Params<C, A> {
-  fn new(algorithm: A, keys: ThresholdKeys<C>) -> Params<C, A> {
+  pub(crate) fn new(algorithm: A, keys: ThresholdKeys<C>) -> Params<C, A> {
     Params { algorithm, keys }
   }
 
-  fn multisig_params(&self) -> ThresholdParams {
+  pub(crate) fn multisig_params(&self) -> ThresholdParams {
     self.keys.params()
   }
 }
 
+// Process the preprocesses of every included participant, advancing the algorithm's transcript
+// and returning the binding factors and nonces for this signing session
+#[allow(non_snake_case, clippy::type_complexity)]
+pub(crate) fn process_preprocesses<C: Curve, A: Algorithm<C>>(
+  algorithm: &mut A,
+  offset: Option<C::F>,
+  view: &ThresholdView<C>,
+  mut preprocesses: BTreeMap<Participant, Preprocess<C, A::Addendum>>,
+  msg: &[u8],
+) -> Result<(BindingFactor<C>, Vec<Vec<C::G>>), FrostError> {
+  {
+    // Domain separate FROST
+    algorithm.transcript().domain_separate(b"FROST");
+  }
+
+  let nonces = algorithm.nonces();
+  let mut B = BindingFactor(BTreeMap::<Participant, _>::new());
+  {
+    // Parse the preprocesses
+    for l in view.included() {
+      {
+        algorithm
+          .transcript()
+          .append_message(b"participant", C::F::from(u64::from(u16::from(*l))).to_repr());
+      }
+
+      let preprocess = preprocesses.remove(l).unwrap();
+      preprocess.commitments.transcript(algorithm.transcript());
+      {
+        let mut buf = vec![];
+        preprocess.addendum.write(&mut buf).unwrap();
+        algorithm.transcript().append_message(b"addendum", buf);
+      }
+
+      B.insert(*l, preprocess.commitments);
+      algorithm.process_addendum(view, *l, preprocess.addendum)?;
+    }
+
+    // Re-format into the FROST-expected rho transcript
+    let mut rho_transcript = A::Transcript::new(b"FROST_rho");
+    rho_transcript.append_message(b"message", C::hash_msg(msg));
+    rho_transcript.append_message(
+      b"preprocesses",
+      &C::hash_commitments(algorithm.transcript().challenge(b"preprocesses").as_ref()),
+    );
+
+    // Include the offset, if one exists
+    // While this isn't part of the FROST-expected rho transcript, the offset being here
+    // coincides with another specification (despite the transcript format still being distinct)
+    if let Some(offset) = offset {
+      // Transcript as a point
+      // Under a coordinated model, the coordinater can be the only party to know the discrete
+      // log of the offset. This removes the ability for any signer to provide the discrete log,
+      // proving a key is related to another, slightly increasing security
+      // While further code edits would still be required for such a model (having the offset
+      // communicated as a point along with only a single party applying the offset), this means
+      // it wouldn't require a transcript change as well
+      rho_transcript.append_message(b"offset", (C::generator() * offset).to_bytes());
+    }
+
+    // Generate the per-signer binding factors
+    B.calculate_binding_factors(&mut rho_transcript);
+
+    // Merge the rho transcript back into the global one to ensure its advanced, while
+    // simultaneously committing to everything
+    algorithm.transcript().append_message(b"rho_transcript", rho_transcript.challenge(b"merge"));
+  }
+
+  let Rs = B.nonces(&nonces);
+  Ok((B, Rs))
+}
+
 /// Preprocess for an instance of the FROST signing protocol.
 #[derive(Clone, PartialEq, Eq)]
 pub struct Preprocess<C: Curve, A: Addendum> {
@@ -115,6 +188,41 @@ impl<C: Curve, A: Algorithm<C>> AlgorithmMachine<C, A> {
     AlgorithmMachine { params: Params::new(algorithm, keys) }
   }
 
+  /// Perform the preprocessing round with nonces derived from the secret share, the algorithm's
+  /// transcript, and the specified counter, as opposed to an RNG.
+  ///
+  /// Calling this again, with the same algorithm, keys, and counter, rebuilds the exact same
+  /// preprocess and machine. This lets a signer recover from a restart without storing its nonces.
+  /// Any context for the message being signed should be appended to the algorithm's transcript
+  /// before this is called, as the message itself isn't yet known.
+  ///
+  /// The counter MUST be persisted as used before the preprocess is published, and MUST NOT be
+  /// used again except to rebuild this same signing session. Signing two distinct messages with
+  /// the same nonces enables recovery of your private key share.
+  pub fn deterministic_preprocess(
+    self,
+    counter: u64,
+  ) -> (AlgorithmSignMachine<C, A>, Preprocess<C, A::Addendum>) {
+    let mut transcript = RecommendedTranscript::new(b"FROST Deterministic Preprocess v0.7");
+    transcript.append_message(b"context", C::CONTEXT);
+    transcript.append_message(b"group_key", self.params.keys.group_key().to_bytes());
+    transcript
+      .append_message(b"participant", u16::from(self.params.keys.params().i()).to_le_bytes());
+    // Challenge a clone of the algorithm's transcript so its own transcript isn't advanced,
+    // keeping the resulting machine identical to one created from a cache of the seed
+    transcript.append_message(
+      b"algorithm",
+      self.params.algorithm.clone().transcript().challenge(b"deterministic_preprocess"),
+    );
+    transcript.append_message(b"counter", counter.to_le_bytes());
+
+    let mut secret_share = self.params.keys.secret_share().to_repr();
+    transcript.append_message(b"secret_share", secret_share.as_ref());
+    secret_share.as_mut().zeroize();
+
+    self.seeded_preprocess(CachedPreprocess(Zeroizing::new(transcript.rng_seed(b"preprocess"))))
+  }
+
   fn seeded_preprocess(
     self,
     seed: CachedPreprocess,
@@ -189,7 +297,7 @@ impl<C: Curve, A: Algorithm<C>> PreprocessMachine for AlgorithmMachine<C, A> {
 
 /// Share of a signature produced via FROST.
 #[derive(Clone, PartialEq, Eq)]
-pub struct SignatureShare<C: Curve>(C::F);
+pub struct SignatureShare<C: Curve>(pub(crate) C::F);
 impl<C: Curve> Writable for SignatureShare<C> {
   fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
     writer.write_all(self.0.to_repr().as_ref())
@@ -241,7 +349,7 @@ pub trait SignMachine<S>: Send + Sync + Sized {
   /// become the signing set for this session.
   fn sign(
     self,
-    commitments: HashMap<Participant, Self::Preprocess>,
+    commitments: BTreeMap<Participant, Self::Preprocess>,
     msg: &[u8],
   ) -> Result<(Self::SignatureMachine, Self::SignatureShare), FrostError>;
 }
@@ -294,7 +402,7 @@ impl<C: Curve, A: Algorithm<C>> SignMachine<A::Signature> for AlgorithmSignMachi
 
   fn sign(
     mut self,
-    mut preprocesses: HashMap<Participant, Preprocess<C, A::Addendum>>,
+    mut preprocesses: BTreeMap<Participant, Preprocess<C, A::Addendum>>,
     msg: &[u8],
   ) -> Result<(Self::SignatureMachine, SignatureShare<C>), FrostError> {
     let multisig_params = self.params.multisig_params();
@@ -324,90 +432,16 @@ impl<C: Curve, A: Algorithm<C>> SignMachine<A::Signature> for AlgorithmSignMachi
     let view = self.params.keys.view(included.clone()).unwrap();
     validate_map(&preprocesses, &included, multisig_params.i())?;
 
-    {
-      // Domain separate FROST
-      self.params.algorithm.transcript().domain_separate(b"FROST");
-    }
-
-    let nonces = self.params.algorithm.nonces();
-    #[allow(non_snake_case)]
-    let mut B = BindingFactor(HashMap::<Participant, _>::with_capacity(included.len()));
-    {
-      // Parse the preprocesses
-      for l in &included {
-        {
-          self
-            .params
-            .algorithm
-            .transcript()
-            .append_message(b"participant", C::F::from(u64::from(u16::from(*l))).to_repr());
-        }
-
-        if *l == self.params.keys.params().i() {
-          let commitments = self.preprocess.commitments.clone();
-          commitments.transcript(self.params.algorithm.transcript());
-
-          let addendum = self.preprocess.addendum.clone();
-          {
-            let mut buf = vec![];
-            addendum.write(&mut buf).unwrap();
-            self.params.algorithm.transcript().append_message(b"addendum", buf);
-          }
-
-          B.insert(*l, commitments);
-          self.params.algorithm.process_addendum(&view, *l, addendum)?;
-        } else {
-          let preprocess = preprocesses.remove(l).unwrap();
-          preprocess.commitments.transcript(self.params.algorithm.transcript());
-          {
-            let mut buf = vec![];
-            preprocess.addendum.write(&mut buf).unwrap();
-            self.params.algorithm.transcript().append_message(b"addendum", buf);
-          }
-
-          B.insert(*l, preprocess.commitments);
-          self.params.algorithm.process_addendum(&view, *l, preprocess.addendum)?;
-        }
-      }
-
-      // Re-format into the FROST-expected rho transcript
-      let mut rho_transcript = A::Transcript::new(b"FROST_rho");
-      rho_transcript.append_message(b"message", C::hash_msg(msg));
-      rho_transcript.append_message(
-        b"preprocesses",
-        &C::hash_commitments(
-          self.params.algorithm.transcript().challenge(b"preprocesses").as_ref(),
-        ),
-      );
-
-      // Include the offset, if one exists
-      // While this isn't part of the FROST-expected rho transcript, the offset being here
-      // coincides with another specification (despite the transcript format still being distinct)
-      if let Some(offset) = self.params.keys.current_offset() {
-        // Transcript as a point
-        // Under a coordinated model, the coordinater can be the only party to know the discrete
-        // log of the offset. This removes the ability for any signer to provide the discrete log,
-        // proving a key is related to another, slightly increasing security
-        // While further code edits would still be required for such a model (having the offset
-        // communicated as a point along with only a single party applying the offset), this means
-        // it wouldn't require a transcript change as well
-        rho_transcript.append_message(b"offset", (C::generator() * offset).to_bytes());
-      }
-
-      // Generate the per-signer binding factors
-      B.calculate_binding_factors(&mut rho_transcript);
-
-      // Merge the rho transcript back into the global one to ensure its advanced, while
-      // simultaneously committing to everything
-      self
-        .params
-        .algorithm
-        .transcript()
-        .append_message(b"rho_transcript", rho_transcript.challenge(b"merge"));
-    }
-
+    // Our own preprocess is processed alongside everyone else's
+    preprocesses.insert(multisig_params.i(), self.preprocess.clone());
     #[allow(non_snake_case)]
-    let Rs = B.nonces(&nonces);
+    let (B, Rs) = process_preprocesses(
+      &mut self.params.algorithm,
+      self.params.keys.current_offset(),
+      &view,
+      preprocesses,
+      msg,
+    )?;
 
     let our_binding_factors = B.binding_factors(multisig_params.i());
     let nonces = self
@@ -448,7 +482,7 @@ pub trait SignatureMachine<S>: Send + Sync {
 
   /// Complete signing.
   /// Takes in everyone elses' shares. Returns the signature.
-  fn complete(self, shares: HashMap<Participant, Self::SignatureShare>) -> Result<S, FrostError>;
+  fn complete(self, shares: BTreeMap<Participant, Self::SignatureShare>) -> Result<S, FrostError>;
 }
 
 /// Final step of the state machine for the signing process.
@@ -473,15 +507,15 @@ impl<C: Curve, A: Algorithm<C>> SignatureMachine<A::Signature> for AlgorithmSign
 
   fn complete(
     self,
-    mut shares: HashMap<Participant, SignatureShare<C>>,
+    shares: BTreeMap<Participant, SignatureShare<C>>,
   ) -> Result<A::Signature, FrostError> {
     let params = self.params.multisig_params();
     validate_map(&shares, self.view.included(), params.i())?;
 
-    let mut responses = HashMap::new();
+    let mut responses = BTreeMap::new();
     responses.insert(params.i(), self.share);
     let mut sum = self.share;
-    for (l, share) in shares.drain() {
+    for (l, share) in shares.into_iter() {
       responses.insert(l, share.0);
       sum += share.0;
     }
@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+
+use rand_core::OsRng;
+
+use ciphersuite::{
+  group::ff::{Field, PrimeField},
+  Ciphersuite,
+};
+
+use transcript::{Transcript, RecommendedTranscript};
+
+use crate::{
+  curve::Secp256k1,
+  Participant, ThresholdKeys,
+  bip340::{x, lift_x, verify, taproot_tweak_scalar, taproot_tweak, Bip340},
+  tests::{key_gen, recover_key, algorithm_machines, sign},
+};
+
+type Scalar = <Secp256k1 as Ciphersuite>::F;
+
+// Generate keys whose group key is the specified secret times the generator
+fn keys_for(secret: Scalar) -> BTreeMap<Participant, ThresholdKeys<Secp256k1>> {
+  let keys = key_gen::<_, Secp256k1>(&mut OsRng);
+  let offset = secret - recover_key(&keys);
+  keys.into_iter().map(|(i, keys)| (i, keys.offset(offset))).collect()
+}
+
+fn group_key(keys: &BTreeMap<Participant, ThresholdKeys<Secp256k1>>) -> [u8; 32] {
+  x(&keys[&Participant::new(1).unwrap()].group_key())
+}
+
+fn sign_with(keys: &BTreeMap<Participant, ThresholdKeys<Secp256k1>>, msg: &[u8]) -> [u8; 64] {
+  let algo = Bip340::new(RecommendedTranscript::new(b"FROST BIP-340 Test"));
+  sign(&mut OsRng, algo.clone(), keys.clone(), algorithm_machines(&mut OsRng, algo, keys), msg)
+}
+
+#[test]
+fn bip340_vectors() {
+  let vectors = include_str!("vectors/bip340.csv");
+  for vector in vectors.lines().skip(1) {
+    let vector = vector.split(',').collect::<Vec<_>>();
+    let index = vector[0];
+    let public_key: [u8; 32] = hex::decode(vector[2]).unwrap().try_into().unwrap();
+    let msg = hex::decode(vector[4]).unwrap();
+    let sig: [u8; 64] = hex::decode(vector[5]).unwrap().try_into().unwrap();
+    let valid = vector[6] == "TRUE";
+
+    assert_eq!(verify(&public_key, &msg, &sig), valid, "vector {index} failed verification");
+
+    // FROST nonces aren't derived as BIP-340 specifies, so the signatures themselves won't match
+    // Instead, check the key matches and that the produced signatures verify
+    if !vector[1].is_empty() {
+      let mut secret = <Scalar as PrimeField>::Repr::default();
+      secret.copy_from_slice(&hex::decode(vector[1]).unwrap());
+      let secret = Scalar::from_repr(secret).unwrap();
+
+      // Sign with both the secret and its negation, ensuring keys of either parity are handled
+      for secret in [secret, -secret] {
+        let keys = keys_for(secret);
+        assert_eq!(group_key(&keys), public_key, "vector {index} had an invalid public key");
+        let sig = sign_with(&keys, &msg);
+        assert!(verify(&public_key, &msg, &sig), "vector {index} produced an invalid signature");
+      }
+    }
+  }
+}
+
+#[test]
+fn taproot_tweak_vector() {
+  // Key path spend from BIP-341's wallet test vectors, with no script tree
+  let internal_key: [u8; 32] =
+    hex::decode("d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d")
+      .unwrap()
+      .try_into()
+      .unwrap();
+  let tweak = taproot_tweak_scalar(&internal_key, None);
+  assert_eq!(
+    hex::encode(tweak.to_repr()),
+    "b86e7be8f39bab32a6f2c0443abbc210f0edac0e2c53d501b36b64437d9c6c70"
+  );
+  assert_eq!(
+    hex::encode(x(&(lift_x(&internal_key).unwrap() + (Secp256k1::generator() * tweak)))),
+    "53a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343"
+  );
+}
+
+#[test]
+fn taproot_tweak_signing() {
+  const MESSAGE: &[u8] = b"Hello, World!";
+
+  for merkle_root in [None, Some([0xff; 32])] {
+    let secret = Scalar::random(&mut OsRng);
+    // Sign with both the internal key and its negation, ensuring keys of either parity are handled
+    for secret in [secret, -secret] {
+      let keys = keys_for(secret);
+      let internal_key = group_key(&keys);
+      let tweaked = keys
+        .iter()
+        .map(|(i, keys)| (*i, taproot_tweak(keys, merkle_root)))
+        .collect::<BTreeMap<_, _>>();
+
+      let output_key = x(&(lift_x(&internal_key).unwrap() +
+        (Secp256k1::generator() * taproot_tweak_scalar(&internal_key, merkle_root))));
+      assert_eq!(group_key(&tweaked), output_key);
+      assert!(verify(&output_key, MESSAGE, &sign_with(&tweaked, MESSAGE)));
+    }
+  }
+}
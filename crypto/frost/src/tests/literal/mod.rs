@@ -4,3 +4,5 @@ mod dalek;
 mod kp256;
 #[cfg(feature = "ed448")]
 mod ed448;
+#[cfg(feature = "secp256k1")]
+mod bip340;
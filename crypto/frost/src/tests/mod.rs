@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use rand_core::{RngCore, CryptoRng};
 
@@ -14,6 +14,10 @@ use crate::{
 pub mod nonces;
 use nonces::{test_multi_nonce, test_invalid_commitment, test_invalid_dleq_proof};
 
+/// Tests for the ROAST coordinator.
+pub mod roast;
+use roast::test_roast;
+
 /// Vectorized test suite to ensure consistency.
 pub mod vectors;
 
@@ -27,10 +31,10 @@ pub const PARTICIPANTS: u16 = 5;
 pub const THRESHOLD: u16 = ((PARTICIPANTS / 3) * 2) + 1;
 
 /// Clone a map without a specific value.
-pub fn clone_without<K: Clone + std::cmp::Eq + std::hash::Hash, V: Clone>(
-  map: &HashMap<K, V>,
+pub fn clone_without<K: Clone + Ord, V: Clone>(
+  map: &BTreeMap<K, V>,
   without: &K,
-) -> HashMap<K, V> {
+) -> BTreeMap<K, V> {
   let mut res = map.clone();
   res.remove(without).unwrap();
   res
@@ -40,8 +44,8 @@ pub fn clone_without<K: Clone + std::cmp::Eq + std::hash::Hash, V: Clone>(
 pub fn algorithm_machines<R: RngCore, C: Curve, A: Algorithm<C>>(
   rng: &mut R,
   algorithm: A,
-  keys: &HashMap<Participant, ThresholdKeys<C>>,
-) -> HashMap<Participant, AlgorithmMachine<C, A>> {
+  keys: &BTreeMap<Participant, ThresholdKeys<C>>,
+) -> BTreeMap<Participant, AlgorithmMachine<C, A>> {
   let mut included = vec![];
   while included.len() < usize::from(keys[&Participant::new(1).unwrap()].params().t()) {
     let n = Participant::new(
@@ -70,15 +74,15 @@ pub fn algorithm_machines<R: RngCore, C: Curve, A: Algorithm<C>>(
 pub(crate) fn preprocess<
   R: RngCore + CryptoRng,
   M: PreprocessMachine,
-  F: FnMut(&mut R, &mut HashMap<Participant, M::SignMachine>),
+  F: FnMut(&mut R, &mut BTreeMap<Participant, M::SignMachine>),
 >(
   rng: &mut R,
-  mut machines: HashMap<Participant, M>,
+  machines: BTreeMap<Participant, M>,
   mut cache: F,
-) -> (HashMap<Participant, M::SignMachine>, HashMap<Participant, M::Preprocess>) {
-  let mut commitments = HashMap::new();
+) -> (BTreeMap<Participant, M::SignMachine>, BTreeMap<Participant, M::Preprocess>) {
+  let mut commitments = BTreeMap::new();
   let mut machines = machines
-    .drain()
+    .into_iter()
     .map(|(i, machine)| {
       let (machine, preprocess) = machine.preprocess(rng);
       commitments.insert(i, {
@@ -88,7 +92,7 @@ pub(crate) fn preprocess<
       });
       (i, machine)
     })
-    .collect::<HashMap<_, _>>();
+    .collect::<BTreeMap<_, _>>();
 
   cache(rng, &mut machines);
 
@@ -100,21 +104,21 @@ pub(crate) fn preprocess<
 pub(crate) fn preprocess_and_shares<
   R: RngCore + CryptoRng,
   M: PreprocessMachine,
-  F: FnMut(&mut R, &mut HashMap<Participant, M::SignMachine>),
+  F: FnMut(&mut R, &mut BTreeMap<Participant, M::SignMachine>),
 >(
   rng: &mut R,
-  machines: HashMap<Participant, M>,
+  machines: BTreeMap<Participant, M>,
   cache: F,
   msg: &[u8],
 ) -> (
-  HashMap<Participant, <M::SignMachine as SignMachine<M::Signature>>::SignatureMachine>,
-  HashMap<Participant, <M::SignMachine as SignMachine<M::Signature>>::SignatureShare>,
+  BTreeMap<Participant, <M::SignMachine as SignMachine<M::Signature>>::SignatureMachine>,
+  BTreeMap<Participant, <M::SignMachine as SignMachine<M::Signature>>::SignatureShare>,
 ) {
-  let (mut machines, commitments) = preprocess(rng, machines, cache);
+  let (machines, commitments) = preprocess(rng, machines, cache);
 
-  let mut shares = HashMap::new();
+  let mut shares = BTreeMap::new();
   let machines = machines
-    .drain()
+    .into_iter()
     .map(|(i, machine)| {
       let (machine, share) = machine.sign(clone_without(&commitments, &i), msg).unwrap();
       shares.insert(i, {
@@ -124,7 +128,7 @@ pub(crate) fn preprocess_and_shares<
       });
       (i, machine)
     })
-    .collect::<HashMap<_, _>>();
+    .collect::<BTreeMap<_, _>>();
 
   (machines, shares)
 }
@@ -132,17 +136,17 @@ pub(crate) fn preprocess_and_shares<
 fn sign_internal<
   R: RngCore + CryptoRng,
   M: PreprocessMachine,
-  F: FnMut(&mut R, &mut HashMap<Participant, M::SignMachine>),
+  F: FnMut(&mut R, &mut BTreeMap<Participant, M::SignMachine>),
 >(
   rng: &mut R,
-  machines: HashMap<Participant, M>,
+  machines: BTreeMap<Participant, M>,
   cache: F,
   msg: &[u8],
 ) -> M::Signature {
-  let (mut machines, shares) = preprocess_and_shares(rng, machines, cache, msg);
+  let (machines, shares) = preprocess_and_shares(rng, machines, cache, msg);
 
   let mut signature = None;
-  for (i, machine) in machines.drain() {
+  for (i, machine) in machines.into_iter() {
     let sig = machine.complete(clone_without(&shares, &i)).unwrap();
     if signature.is_none() {
       signature = Some(sig.clone());
@@ -157,7 +161,7 @@ fn sign_internal<
 /// caching.
 pub fn sign_without_caching<R: RngCore + CryptoRng, M: PreprocessMachine>(
   rng: &mut R,
-  machines: HashMap<Participant, M>,
+  machines: BTreeMap<Participant, M>,
   msg: &[u8],
 ) -> M::Signature {
   sign_internal(rng, machines, |_, _| {}, msg)
@@ -168,8 +172,8 @@ pub fn sign_without_caching<R: RngCore + CryptoRng, M: PreprocessMachine>(
 pub fn sign<R: RngCore + CryptoRng, M: PreprocessMachine>(
   rng: &mut R,
   params: <M::SignMachine as SignMachine<M::Signature>>::Params,
-  mut keys: HashMap<Participant, <M::SignMachine as SignMachine<M::Signature>>::Keys>,
-  machines: HashMap<Participant, M>,
+  mut keys: BTreeMap<Participant, <M::SignMachine as SignMachine<M::Signature>>::Keys>,
+  machines: BTreeMap<Participant, M>,
   msg: &[u8],
 ) -> M::Signature {
   sign_internal(
@@ -230,9 +234,9 @@ pub fn test_schnorr_blame<R: RngCore + CryptoRng, C: Curve, H: Hram<C>>(rng: &mu
   let keys = key_gen(&mut *rng);
   let machines = algorithm_machines(&mut *rng, IetfSchnorr::<C, H>::ietf(), &keys);
 
-  let (mut machines, shares) = preprocess_and_shares(&mut *rng, machines, |_, _| {}, MSG);
+  let (machines, shares) = preprocess_and_shares(&mut *rng, machines, |_, _| {}, MSG);
 
-  for (i, machine) in machines.drain() {
+  for (i, machine) in machines.into_iter() {
     let mut shares = clone_without(&shares, &i);
 
     // Select a random participant to give an invalid share
@@ -245,11 +249,54 @@ pub fn test_schnorr_blame<R: RngCore + CryptoRng, C: Curve, H: Hram<C>>(rng: &mu
   }
 }
 
+/// Test deterministically preprocessing, and rebuilding the preprocess as if after a restart.
+pub fn test_deterministic_preprocess<R: RngCore + CryptoRng, C: Curve, H: Hram<C>>(rng: &mut R) {
+  const MSG: &[u8] = b"Hello, World!";
+
+  let keys = key_gen(&mut *rng);
+  let machines = algorithm_machines(&mut *rng, IetfSchnorr::<C, H>::ietf(), &keys);
+
+  let mut sign_machines = BTreeMap::new();
+  let mut preprocesses = BTreeMap::new();
+  for (i, machine) in machines {
+    let (_, preprocess) = machine.deterministic_preprocess(0);
+
+    // Rebuilding with the same counter produces the same preprocess
+    let (machine, rebuilt) = AlgorithmMachine::new(IetfSchnorr::<C, H>::ietf(), keys[&i].clone())
+      .deterministic_preprocess(0);
+    assert!(preprocess == rebuilt);
+
+    // A distinct counter produces a distinct preprocess
+    let (_, distinct) = AlgorithmMachine::new(IetfSchnorr::<C, H>::ietf(), keys[&i].clone())
+      .deterministic_preprocess(1);
+    assert!(preprocess != distinct);
+
+    sign_machines.insert(i, machine);
+    preprocesses.insert(i, preprocess);
+  }
+
+  let mut signature_machines = BTreeMap::new();
+  let mut shares = BTreeMap::new();
+  for (i, machine) in sign_machines {
+    let (machine, share) = machine.sign(clone_without(&preprocesses, &i), MSG).unwrap();
+    signature_machines.insert(i, machine);
+    shares.insert(i, share);
+  }
+
+  let group_key = keys[&Participant::new(1).unwrap()].group_key();
+  for (i, machine) in signature_machines {
+    let sig = machine.complete(clone_without(&shares, &i)).unwrap();
+    assert!(sig.verify(group_key, H::hram(&sig.R, &group_key, MSG)));
+  }
+}
+
 /// Run a variety of tests against a ciphersuite.
 pub fn test_ciphersuite<R: RngCore + CryptoRng, C: Curve, H: Hram<C>>(rng: &mut R) {
   test_schnorr::<R, C, H>(rng);
   test_offset_schnorr::<R, C, H>(rng);
   test_schnorr_blame::<R, C, H>(rng);
+  test_roast::<R, C, H>(rng);
+  test_deterministic_preprocess::<R, C, H>(rng);
 
   test_multi_nonce::<R, C>(rng);
   test_invalid_commitment::<R, C>(rng);
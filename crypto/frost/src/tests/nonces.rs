@@ -89,24 +89,34 @@ impl<C: Curve> Algorithm<C> for MultiNonce<C> {
 
   fn sign_share(
     &mut self,
-    _: &ThresholdView<C>,
+    view: &ThresholdView<C>,
     nonce_sums: &[Vec<C::G>],
     nonces: Vec<Zeroizing<C::F>>,
-    _: &[u8],
+    msg: &[u8],
   ) -> C::F {
-    // Verify the nonce sums are as expected
-    verify_nonces::<C>(nonce_sums);
-
     // Verify we actually have two nonces and that they're distinct
     assert_eq!(nonces.len(), 2);
     assert!(nonces[0] != nonces[1]);
 
-    // Save the nonce sums for later so we can check they're consistent with the call to verify
-    assert!(self.nonces.is_none());
-    self.nonces = Some(nonce_sums.to_vec());
+    self.prepare_verification(view, nonce_sums, msg);
 
     // Sum the nonces so we can later check they actually have a relationship to nonce_sums
     let mut res = C::F::ZERO;
+    let mut rng = ChaCha20Rng::from_seed(self.transcript.clone().rng_seed(b"weight"));
+
+    for nonce in nonces {
+      res += *nonce * C::F::random(&mut rng);
+    }
+    res
+  }
+
+  fn prepare_verification(&mut self, _: &ThresholdView<C>, nonce_sums: &[Vec<C::G>], _: &[u8]) {
+    // Verify the nonce sums are as expected
+    verify_nonces::<C>(nonce_sums);
+
+    // Save the nonce sums for later so we can check they're consistent with the call to verify
+    assert!(self.nonces.is_none());
+    self.nonces = Some(nonce_sums.to_vec());
 
     // Weight each nonce
     // This is probably overkill, since their unweighted forms would practically still require
@@ -117,12 +127,6 @@ impl<C: Curve> Algorithm<C> for MultiNonce<C> {
         self.transcript.append_message(b"commitment", commitment.to_bytes());
       }
     }
-    let mut rng = ChaCha20Rng::from_seed(self.transcript.clone().rng_seed(b"weight"));
-
-    for nonce in nonces {
-      res += *nonce * C::F::random(&mut rng);
-    }
-    res
   }
 
   #[must_use]
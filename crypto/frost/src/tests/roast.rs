@@ -0,0 +1,117 @@
+use std::collections::{VecDeque, BTreeMap};
+
+use rand_core::{RngCore, CryptoRng};
+
+use ciphersuite::group::ff::Field;
+
+use crate::{
+  Curve, Participant, FrostError,
+  algorithm::{Hram, IetfSchnorr},
+  sign::{Writable, PreprocessMachine, SignMachine, AlgorithmMachine, AlgorithmSignMachine},
+  roast::RoastCoordinator,
+  tests::{key_gen, clone_without},
+};
+
+fn roast<R: RngCore + CryptoRng, C: Curve, H: Hram<C>>(rng: &mut R, offset: Option<C::F>) {
+  const MSG: &[u8] = b"Hello, World!";
+
+  let mut keys = key_gen::<_, C>(&mut *rng);
+  let params = keys[&Participant::new(1).unwrap()].params();
+
+  // The lowest participants are selected first, so these will both be in the first session
+  let unresponsive = Participant::new(1).unwrap();
+  let malicious = Participant::new(2).unwrap();
+
+  // The coordinator only uses the multisig's public components
+  let group_key = keys[&Participant::new(1).unwrap()].group_key();
+  let verification_shares = keys[&Participant::new(1).unwrap()].verification_shares();
+  let coordinator = |verification_shares| {
+    RoastCoordinator::new(IetfSchnorr::<C, H>::ietf(), params, group_key, verification_shares, MSG)
+  };
+
+  // Verification shares inconsistent with the group key should be rejected
+  let mut inconsistent = verification_shares.clone();
+  *inconsistent.get_mut(&Participant::new(params.n()).unwrap()).unwrap() += C::generator();
+  assert!(matches!(coordinator(inconsistent), Err(FrostError::InvalidVerificationShares)));
+  let mut missing = verification_shares.clone();
+  missing.remove(&Participant::new(params.n()).unwrap());
+  assert!(coordinator(missing).is_err());
+
+  let mut coordinator = coordinator(verification_shares).unwrap();
+  if let Some(offset) = offset {
+    coordinator = coordinator.offset(offset);
+    for keys in keys.values_mut() {
+      *keys = keys.offset(offset);
+    }
+  }
+
+  // Each participant's machines, in the order their preprocesses were added
+  let mut machines: BTreeMap<_, VecDeque<AlgorithmSignMachine<C, IetfSchnorr<C, H>>>> =
+    BTreeMap::new();
+  let preprocess = |rng: &mut R,
+                    coordinator: &mut RoastCoordinator<_, _>,
+                    machines: &mut BTreeMap<_, VecDeque<_>>,
+                    i| {
+    let (machine, preprocess) =
+      AlgorithmMachine::new(IetfSchnorr::<C, H>::ietf(), keys[&i].clone()).preprocess(&mut *rng);
+    machines.entry(i).or_default().push_back(machine);
+    let preprocess =
+      coordinator.read_preprocess::<&[u8]>(&mut preprocess.serialize().as_ref()).unwrap();
+    coordinator.add_preprocess(i, preprocess).unwrap();
+  };
+
+  for i in 1 ..= params.n() {
+    preprocess(&mut *rng, &mut coordinator, &mut machines, Participant::new(i).unwrap());
+  }
+
+  let mut signature = None;
+  while signature.is_none() {
+    let sessions = coordinator.launch().unwrap();
+    assert!(!sessions.is_empty());
+
+    for session in sessions {
+      let mut included = session.preprocesses.keys().cloned().collect::<Vec<_>>();
+      included.sort();
+      for i in included {
+        if i == unresponsive {
+          continue;
+        }
+
+        let machine = machines.get_mut(&i).unwrap().pop_front().unwrap();
+        let (_, mut share) = machine.sign(clone_without(&session.preprocesses, &i), MSG).unwrap();
+        if i == malicious {
+          share.invalidate();
+        }
+        let share = coordinator.read_share::<&[u8]>(&mut share.serialize().as_ref()).unwrap();
+
+        match coordinator.add_share(session.id, i, share) {
+          Ok(Some(sig)) => {
+            signature = Some(sig);
+            break;
+          }
+          Ok(None) => {}
+          Err(FrostError::InvalidShare(l)) => assert_eq!(l, malicious),
+          // The session was abandoned due to the malicious participant
+          Err(FrostError::InvalidSigningSet(_)) => {}
+          Err(e) => panic!("unexpected error: {e:?}"),
+        }
+
+        // Provide a fresh preprocess after responding
+        preprocess(&mut *rng, &mut coordinator, &mut machines, i);
+      }
+    }
+  }
+
+  let sig = signature.unwrap();
+  let group_key = keys[&Participant::new(1).unwrap()].group_key();
+  assert!(sig.verify(group_key, H::hram(&sig.R, &group_key, MSG)));
+  assert_eq!(coordinator.malicious().iter().collect::<Vec<_>>(), vec![&malicious]);
+}
+
+/// Test ROAST completes despite an unresponsive participant and a participant sending invalid
+/// shares, with and without an offset.
+pub fn test_roast<R: RngCore + CryptoRng, C: Curve, H: Hram<C>>(rng: &mut R) {
+  roast::<R, C, H>(&mut *rng, None);
+  let offset = C::F::random(&mut *rng);
+  roast::<R, C, H>(&mut *rng, Some(offset));
+}
@@ -1,6 +1,6 @@
 use core::ops::Deref;
 
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 #[cfg(test)]
 use std::str::FromStr;
 
@@ -107,7 +107,9 @@ impl From<serde_json::Value> for Vectors {
 }
 
 // Load these vectors into ThresholdKeys using a custom serialization it'll deserialize
-fn vectors_to_multisig_keys<C: Curve>(vectors: &Vectors) -> HashMap<Participant, ThresholdKeys<C>> {
+fn vectors_to_multisig_keys<C: Curve>(
+  vectors: &Vectors,
+) -> BTreeMap<Participant, ThresholdKeys<C>> {
   let shares = vectors
     .shares
     .iter()
@@ -115,7 +117,7 @@ fn vectors_to_multisig_keys<C: Curve>(vectors: &Vectors) -> HashMap<Participant,
     .collect::<Vec<_>>();
   let verification_shares = shares.iter().map(|secret| C::generator() * secret).collect::<Vec<_>>();
 
-  let mut keys = HashMap::new();
+  let mut keys = BTreeMap::new();
   for i in 1 ..= u16::try_from(shares.len()).unwrap() {
     // Manually re-implement the serialization for ThresholdCore to import this data
     let mut serialized = vec![];
@@ -165,7 +167,7 @@ pub fn test_with_vectors<R: RngCore + CryptoRng, C: Curve, H: Hram<C>>(
       machines.push((i, AlgorithmMachine::new(IetfSchnorr::<C, H>::ietf(), keys[i].clone())));
     }
 
-    let mut commitments = HashMap::new();
+    let mut commitments = BTreeMap::new();
     let mut machines = machines
       .drain(..)
       .enumerate()
@@ -223,8 +225,8 @@ pub fn test_with_vectors<R: RngCore + CryptoRng, C: Curve, H: Hram<C>>(
       })
       .collect::<Vec<_>>();
 
-    let mut shares = HashMap::new();
-    let mut machines = machines
+    let mut shares = BTreeMap::new();
+    let machines = machines
       .drain(..)
       .enumerate()
       .map(|(c, (i, machine))| {
@@ -242,9 +244,9 @@ pub fn test_with_vectors<R: RngCore + CryptoRng, C: Curve, H: Hram<C>>(
         shares.insert(*i, machine.read_share::<&[u8]>(&mut share.as_ref()).unwrap());
         (i, machine)
       })
-      .collect::<HashMap<_, _>>();
+      .collect::<BTreeMap<_, _>>();
 
-    for (i, machine) in machines.drain() {
+    for (i, machine) in machines.into_iter() {
       let sig = machine.complete(clone_without(&shares, i)).unwrap();
       let mut serialized = sig.R.to_bytes().as_ref().to_vec();
       serialized.extend(sig.s.to_repr().as_ref());
@@ -88,6 +88,12 @@ impl<C: Ciphersuite> SchnorrAggregate<C> {
     Ok(SchnorrAggregate { Rs, s: C::read_F(reader)? })
   }
 
+  /// The nonces of the aggregated signatures, in the order they were aggregated.
+  #[allow(non_snake_case)]
+  pub fn Rs(&self) -> &[C::G] {
+    &self.Rs
+  }
+
   /// Write a SchnorrAggregate to something implementing Write.
   ///
   /// This will panic if more than 4 billion signatures were aggregated.
@@ -69,6 +69,16 @@ impl Schnorrkel {
       msg: None,
     }
   }
+
+  // The message signed by the underlying Schnorr algorithm, prefixed by the context
+  fn contextualized(&self, msg: &[u8]) -> Vec<u8> {
+    [
+      &u32::try_from(self.context.len()).expect("context exceeded 2^32 bytes").to_le_bytes(),
+      self.context,
+      msg,
+    ]
+    .concat()
+  }
 }
 
 impl Algorithm<Ristretto> for Schnorrkel {
@@ -112,17 +122,17 @@ impl Algorithm<Ristretto> for Schnorrkel {
     msg: &[u8],
   ) -> Scalar {
     self.msg = Some(msg.to_vec());
-    self.schnorr.sign_share(
-      params,
-      nonce_sums,
-      nonces,
-      &[
-        &u32::try_from(self.context.len()).expect("context exceeded 2^32 bytes").to_le_bytes(),
-        self.context,
-        msg,
-      ]
-      .concat(),
-    )
+    self.schnorr.sign_share(params, nonce_sums, nonces, &self.contextualized(msg))
+  }
+
+  fn prepare_verification(
+    &mut self,
+    params: &ThresholdView<Ristretto>,
+    nonce_sums: &[Vec<RistrettoPoint>],
+    msg: &[u8],
+  ) {
+    self.msg = Some(msg.to_vec());
+    self.schnorr.prepare_verification(params, nonce_sums, &self.contextualized(msg))
   }
 
   #[must_use]
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use zeroize::Zeroize;
 
@@ -29,12 +29,15 @@ pub mod key_gen {
   #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
   pub enum CoordinatorMessage {
     // Instructs the Processor to begin the key generation process.
+    // The Processor will run the protocol with `shares` key shares, the first of which is
+    // params.i().
     // TODO: Should this be moved under Substrate?
-    GenerateKey { id: KeyGenId, params: ThresholdParams },
+    GenerateKey { id: KeyGenId, params: ThresholdParams, shares: u16 },
     // Received commitments for the specified key generation protocol.
-    Commitments { id: KeyGenId, commitments: HashMap<Participant, Vec<u8>> },
-    // Received shares for the specified key generation protocol.
-    Shares { id: KeyGenId, shares: HashMap<Participant, Vec<u8>> },
+    // This includes the commitments for our own key shares.
+    Commitments { id: KeyGenId, commitments: BTreeMap<Participant, Vec<u8>> },
+    // Received shares for the specified key generation protocol, one map per our key shares.
+    Shares { id: KeyGenId, shares: Vec<BTreeMap<Participant, Vec<u8>>> },
   }
 
   impl CoordinatorMessage {
@@ -45,10 +48,10 @@ pub mod key_gen {
 
   #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
   pub enum ProcessorMessage {
-    // Created commitments for the specified key generation protocol.
-    Commitments { id: KeyGenId, commitments: Vec<u8> },
-    // Created shares for the specified key generation protocol.
-    Shares { id: KeyGenId, shares: HashMap<Participant, Vec<u8>> },
+    // Created commitments for the specified key generation protocol, one per key share.
+    Commitments { id: KeyGenId, commitments: Vec<Vec<u8>> },
+    // Created shares for the specified key generation protocol, one map per key share.
+    Shares { id: KeyGenId, shares: Vec<BTreeMap<Participant, Vec<u8>>> },
     // Resulting keys from the specified key generation protocol.
     GeneratedKeyPair { id: KeyGenId, substrate_key: [u8; 32], coin_key: Vec<u8> },
   }
@@ -67,9 +70,11 @@ pub mod sign {
   #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
   pub enum CoordinatorMessage {
     // Received preprocesses for the specified signing protocol.
-    Preprocesses { id: SignId, preprocesses: HashMap<Participant, Vec<u8>> },
+    // This includes the preprocesses for our own key shares.
+    Preprocesses { id: SignId, preprocesses: BTreeMap<Participant, Vec<u8>> },
     // Received shares for the specified signing protocol.
-    Shares { id: SignId, shares: HashMap<Participant, Vec<u8>> },
+    // This includes the shares for our own key shares.
+    Shares { id: SignId, shares: BTreeMap<Participant, Vec<u8>> },
     // Re-attempt a signing protocol.
     Reattempt { id: SignId },
     // Completed a signing protocol already.
@@ -93,10 +98,10 @@ pub mod sign {
 
   #[derive(Clone, PartialEq, Eq, Debug, Zeroize, Serialize, Deserialize)]
   pub enum ProcessorMessage {
-    // Created preprocess for the specified signing protocol.
-    Preprocess { id: SignId, preprocess: Vec<u8> },
-    // Signed share for the specified signing protocol.
-    Share { id: SignId, share: Vec<u8> },
+    // Created preprocesses for the specified signing protocol, one per key share.
+    Preprocess { id: SignId, preprocesses: Vec<Vec<u8>> },
+    // Signed shares for the specified signing protocol, one per key share.
+    Share { id: SignId, shares: Vec<Vec<u8>> },
     // Completed a signing protocol already.
     // TODO: Move this to SignId
     Completed { key: Vec<u8>, id: [u8; 32], tx: Vec<u8> },
@@ -109,8 +114,8 @@ pub mod coordinator {
   #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
   pub enum CoordinatorMessage {
     // Uses Vec<u8> instead of [u8; 64] since serde Deserialize isn't implemented for [u8; 64]
-    BatchPreprocesses { id: SignId, preprocesses: HashMap<Participant, Vec<u8>> },
-    BatchShares { id: SignId, shares: HashMap<Participant, [u8; 32]> },
+    BatchPreprocesses { id: SignId, preprocesses: BTreeMap<Participant, Vec<u8>> },
+    BatchShares { id: SignId, shares: BTreeMap<Participant, [u8; 32]> },
     // Re-attempt a batch signing protocol.
     BatchReattempt { id: SignId },
   }
@@ -136,8 +141,8 @@ pub mod coordinator {
   #[derive(Clone, PartialEq, Eq, Debug, Zeroize, Serialize, Deserialize)]
   pub enum ProcessorMessage {
     SubstrateBlockAck { network: NetworkId, block: u64, plans: Vec<[u8; 32]> },
-    BatchPreprocess { id: SignId, preprocess: Vec<u8> },
-    BatchShare { id: SignId, share: [u8; 32] },
+    BatchPreprocess { id: SignId, preprocesses: Vec<Vec<u8>> },
+    BatchShare { id: SignId, shares: Vec<[u8; 32]> },
   }
 }
 
@@ -179,7 +179,6 @@ impl Eventuality for OutPoint {
 
 #[derive(Clone, Debug)]
 pub struct SignableTransaction {
-  keys: ThresholdKeys<Secp256k1>,
   transcript: RecommendedTranscript,
   actual: BSignableTransaction,
 }
@@ -448,7 +447,6 @@ impl Coin for Bitcoin {
 
   async fn prepare_send(
     &self,
-    keys: ThresholdKeys<Secp256k1>,
     _: usize,
     mut plan: Plan<Self>,
     fee: Fee,
@@ -508,7 +506,6 @@ impl Coin for Bitcoin {
     Ok((
       Some((
         SignableTransaction {
-          keys,
           transcript: plan.transcript(),
           actual: signable(&plan, Some(tx_fee)).unwrap(),
         },
@@ -520,15 +517,10 @@ impl Coin for Bitcoin {
 
   async fn attempt_send(
     &self,
+    keys: ThresholdKeys<Secp256k1>,
     transaction: Self::SignableTransaction,
   ) -> Result<Self::TransactionMachine, CoinError> {
-    Ok(
-      transaction
-        .actual
-        .clone()
-        .multisig(transaction.keys.clone(), transaction.transcript)
-        .expect("used the wrong keys"),
-    )
+    Ok(transaction.actual.multisig(keys, transaction.transcript).expect("used the wrong keys"))
   }
 
   async fn publish_transaction(&self, tx: &Self::Transaction) -> Result<(), CoinError> {
@@ -318,7 +318,6 @@ pub trait Coin: 'static + Send + Sync + Clone + PartialEq + Eq + Debug {
   #[rustfmt::skip]
   async fn prepare_send(
     &self,
-    keys: ThresholdKeys<Self::Curve>,
     block_number: usize,
     plan: Plan<Self>,
     fee: Self::Fee,
@@ -327,9 +326,12 @@ pub trait Coin: 'static + Send + Sync + Clone + PartialEq + Eq + Debug {
     CoinError
   >;
 
-  /// Attempt to sign a SignableTransaction.
+  /// Attempt to sign a SignableTransaction with the specified keys.
+  ///
+  /// A machine is created per call, allowing the holder of multiple key shares to sign with each.
   async fn attempt_send(
     &self,
+    keys: ThresholdKeys<Self::Curve>,
     transaction: Self::SignableTransaction,
   ) -> Result<Self::TransactionMachine, CoinError>;
 
@@ -127,7 +127,6 @@ impl EventualityTrait for Eventuality {
 
 #[derive(Clone, Debug)]
 pub struct SignableTransaction {
-  keys: ThresholdKeys<Ed25519>,
   transcript: RecommendedTranscript,
   // Monero height, defined as the length of the chain
   height: usize,
@@ -174,6 +173,9 @@ impl Monero {
     Monero { rpc: Rpc::new(url).unwrap() }
   }
 
+  // TODO: This view key is a hash of a constant, making it public and shared by every multisig
+  // Derive a seed secret to each set during key gen, persist it alongside the keys, and use
+  // ViewPair::derived with it
   fn view_pair(spend: EdwardsPoint) -> ViewPair {
     ViewPair::new(spend.0, Zeroizing::new(additional_key::<Monero>(0).0))
   }
@@ -369,7 +371,6 @@ impl Coin for Monero {
 
   async fn prepare_send(
     &self,
-    keys: ThresholdKeys<Ed25519>,
     block_number: usize,
     mut plan: Plan<Self>,
     fee: Fee,
@@ -482,7 +483,6 @@ impl Coin for Monero {
     let branch_outputs = amortize_fee(&mut plan, tx_fee);
 
     let signable = SignableTransaction {
-      keys,
       transcript: plan.transcript(),
       height: block_number + 1,
       actual: match signable(&mut plan, Some(tx_fee))? {
@@ -496,17 +496,12 @@ impl Coin for Monero {
 
   async fn attempt_send(
     &self,
+    keys: ThresholdKeys<Ed25519>,
     transaction: SignableTransaction,
   ) -> Result<Self::TransactionMachine, CoinError> {
     transaction
       .actual
-      .clone()
-      .multisig(
-        &self.rpc,
-        transaction.keys.clone(),
-        transaction.transcript.clone(),
-        transaction.height,
-      )
+      .multisig(&self.rpc, keys, transaction.transcript, transaction.height)
       .await
       .map_err(|_| CoinError::ConnectionError)
   }
@@ -1,16 +1,19 @@
 use core::marker::PhantomData;
-use std::collections::HashMap;
+use std::collections::{HashMap, BTreeMap};
 
 use zeroize::Zeroizing;
 
-use rand_core::SeedableRng;
+use rand_core::{RngCore, CryptoRng, SeedableRng, OsRng};
 use rand_chacha::ChaCha20Rng;
 
 use transcript::{Transcript, RecommendedTranscript};
 use group::GroupEncoding;
 use frost::{
   curve::{Ciphersuite, Ristretto},
-  dkg::{Participant, ThresholdParams, ThresholdCore, ThresholdKeys, encryption::*, frost::*},
+  dkg::{
+    Participant, ThresholdParams, ThresholdCore, ThresholdKeys, encryption::*, frost::*,
+    storage::Kdf,
+  },
 };
 
 use log::info;
@@ -22,8 +25,9 @@ use crate::{Get, DbTxn, Db, coins::Coin};
 
 #[derive(Debug)]
 pub struct KeyConfirmed<C: Ciphersuite> {
-  pub substrate_keys: ThresholdKeys<Ristretto>,
-  pub coin_keys: ThresholdKeys<C>,
+  // One set of keys per key share held
+  pub substrate_keys: Vec<ThresholdKeys<Ristretto>>,
+  pub coin_keys: Vec<ThresholdKeys<C>>,
 }
 
 #[derive(Clone, Debug)]
@@ -36,10 +40,15 @@ impl<C: Coin, D: Db> KeyGenDb<C, D> {
   fn params_key(set: &ValidatorSet) -> Vec<u8> {
     Self::key_gen_key(b"params", bincode::serialize(set).unwrap())
   }
-  fn save_params(txn: &mut D::Transaction<'_>, set: &ValidatorSet, params: &ThresholdParams) {
-    txn.put(Self::params_key(set), bincode::serialize(params).unwrap());
+  fn save_params(
+    txn: &mut D::Transaction<'_>,
+    set: &ValidatorSet,
+    params: &ThresholdParams,
+    shares: u16,
+  ) {
+    txn.put(Self::params_key(set), bincode::serialize(&(params, shares)).unwrap());
   }
-  fn params<G: Get>(getter: &G, set: &ValidatorSet) -> ThresholdParams {
+  fn params<G: Get>(getter: &G, set: &ValidatorSet) -> (ThresholdParams, u16) {
     // Directly unwraps the .get() as this will only be called after being set
     bincode::deserialize(&getter.get(Self::params_key(set)).unwrap()).unwrap()
   }
@@ -53,12 +62,12 @@ impl<C: Coin, D: Db> KeyGenDb<C, D> {
   fn save_commitments(
     txn: &mut D::Transaction<'_>,
     id: &KeyGenId,
-    commitments: &HashMap<Participant, Vec<u8>>,
+    commitments: &BTreeMap<Participant, Vec<u8>>,
   ) {
     txn.put(Self::commitments_key(id), bincode::serialize(commitments).unwrap());
   }
-  fn commitments<G: Get>(getter: &G, id: &KeyGenId) -> HashMap<Participant, Vec<u8>> {
-    bincode::deserialize::<HashMap<Participant, Vec<u8>>>(
+  fn commitments<G: Get>(getter: &G, id: &KeyGenId) -> BTreeMap<Participant, Vec<u8>> {
+    bincode::deserialize::<BTreeMap<Participant, Vec<u8>>>(
       &getter.get(Self::commitments_key(id)).unwrap(),
     )
     .unwrap()
@@ -67,18 +76,20 @@ impl<C: Coin, D: Db> KeyGenDb<C, D> {
   fn generated_keys_key(set: ValidatorSet, key_pair: (&[u8], &[u8])) -> Vec<u8> {
     Self::key_gen_key(b"generated_keys", bincode::serialize(&(set, key_pair)).unwrap())
   }
-  fn save_keys(
+  fn save_keys<R: RngCore + CryptoRng>(
     txn: &mut D::Transaction<'_>,
     id: &KeyGenId,
-    substrate_keys: &ThresholdCore<Ristretto>,
-    coin_keys: &ThresholdKeys<C::Curve>,
+    rng: &mut R,
+    storage_key: &[u8],
+    substrate_keys: &[ThresholdCore<Ristretto>],
+    coin_keys: &[ThresholdCore<C::Curve>],
+    coin_key: &<C::Curve as Ciphersuite>::G,
   ) {
-    let mut keys = substrate_keys.serialize();
-    keys.extend(coin_keys.serialize().iter());
+    let keys = Self::write_keys(rng, storage_key, substrate_keys, coin_keys);
     txn.put(
       Self::generated_keys_key(
         id.set,
-        (substrate_keys.group_key().to_bytes().as_ref(), coin_keys.group_key().to_bytes().as_ref()),
+        (substrate_keys[0].group_key().to_bytes().as_ref(), coin_key.to_bytes().as_ref()),
       ),
       keys,
     );
@@ -87,45 +98,117 @@ impl<C: Coin, D: Db> KeyGenDb<C, D> {
   fn keys_key(key: &<C::Curve as Ciphersuite>::G) -> Vec<u8> {
     Self::key_gen_key(b"keys", key.to_bytes())
   }
+  // Read a set of keys, returning if they were saved in the clear
+  fn read_core<K: Ciphersuite>(
+    keys_ref: &mut &[u8],
+    storage_key: &[u8],
+  ) -> (ThresholdCore<K>, bool) {
+    // Keys saved prior to their encryption at rest were written in the clear
+    // Since ThresholdCore::read checks the ciphersuite's ID, encrypted keys won't parse as such
+    let mut legacy_ref = *keys_ref;
+    if let Ok(keys) = ThresholdCore::read(&mut legacy_ref) {
+      *keys_ref = legacy_ref;
+      return (keys, true);
+    }
+    (
+      ThresholdCore::read_encrypted(keys_ref, storage_key)
+        .expect("couldn't decrypt keys (was the storage key changed?)"),
+      false,
+    )
+  }
   #[allow(clippy::type_complexity)]
   fn read_keys<G: Get>(
     getter: &G,
     key: &[u8],
-  ) -> (Vec<u8>, (ThresholdKeys<Ristretto>, ThresholdKeys<C::Curve>)) {
+    storage_key: &[u8],
+  ) -> (bool, (Vec<ThresholdCore<Ristretto>>, Vec<ThresholdCore<C::Curve>>)) {
     let keys_vec = getter.get(key).unwrap();
     let mut keys_ref: &[u8] = keys_vec.as_ref();
-    let substrate_keys = ThresholdKeys::new(ThresholdCore::read(&mut keys_ref).unwrap());
-    let mut coin_keys = ThresholdKeys::new(ThresholdCore::read(&mut keys_ref).unwrap());
-    C::tweak_keys(&mut coin_keys);
-    (keys_vec, (substrate_keys, coin_keys))
+
+    let mut legacy = false;
+    let mut substrate_keys = vec![];
+    let mut coin_keys = vec![];
+    while !keys_ref.is_empty() {
+      let (these_substrate_keys, substrate_legacy) = Self::read_core(&mut keys_ref, storage_key);
+      let (these_coin_keys, coin_legacy) = Self::read_core(&mut keys_ref, storage_key);
+      legacy |= substrate_legacy | coin_legacy;
+      substrate_keys.push(these_substrate_keys);
+      coin_keys.push(these_coin_keys);
+    }
+    (legacy, (substrate_keys, coin_keys))
+  }
+  fn write_keys<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    storage_key: &[u8],
+    substrate_keys: &[ThresholdCore<Ristretto>],
+    coin_keys: &[ThresholdCore<C::Curve>],
+  ) -> Vec<u8> {
+    let mut keys = vec![];
+    for (substrate_keys, coin_keys) in substrate_keys.iter().zip(coin_keys) {
+      keys.extend(substrate_keys.serialize_encrypted(&mut *rng, Kdf::Key, storage_key).unwrap());
+      keys.extend(coin_keys.serialize_encrypted(&mut *rng, Kdf::Key, storage_key).unwrap());
+    }
+    keys
+  }
+  #[allow(clippy::type_complexity)]
+  fn into_keys(
+    (substrate_keys, coin_keys): (Vec<ThresholdCore<Ristretto>>, Vec<ThresholdCore<C::Curve>>),
+  ) -> (Vec<ThresholdKeys<Ristretto>>, Vec<ThresholdKeys<C::Curve>>) {
+    (
+      substrate_keys.into_iter().map(ThresholdKeys::new).collect(),
+      coin_keys
+        .into_iter()
+        .map(|keys| {
+          let mut keys = ThresholdKeys::new(keys);
+          C::tweak_keys(&mut keys);
+          keys
+        })
+        .collect(),
+    )
+  }
+  fn save_confirmed_keys(
+    txn: &mut D::Transaction<'_>,
+    storage_key: &[u8],
+    key: &<C::Curve as Ciphersuite>::G,
+    (substrate_keys, coin_keys): &(Vec<ThresholdCore<Ristretto>>, Vec<ThresholdCore<C::Curve>>),
+  ) {
+    txn.put(
+      Self::keys_key(key),
+      Self::write_keys(&mut OsRng, storage_key, substrate_keys, coin_keys),
+    );
   }
+  #[allow(clippy::type_complexity)]
   fn confirm_keys(
     txn: &mut D::Transaction<'_>,
+    storage_key: &[u8],
     set: ValidatorSet,
     key_pair: KeyPair,
-  ) -> (ThresholdKeys<Ristretto>, ThresholdKeys<C::Curve>) {
-    let (keys_vec, keys) = Self::read_keys(
+  ) -> (Vec<ThresholdKeys<Ristretto>>, Vec<ThresholdKeys<C::Curve>>) {
+    let (_, cores) = Self::read_keys(
       txn,
       &Self::generated_keys_key(set, (key_pair.0.as_ref(), key_pair.1.as_ref())),
+      storage_key,
     );
-    assert_eq!(key_pair.0 .0, keys.0.group_key().to_bytes());
+    let keys = Self::into_keys(cores.clone());
+    assert_eq!(key_pair.0 .0, keys.0[0].group_key().to_bytes());
     assert_eq!(
       {
         let coin_key: &[u8] = key_pair.1.as_ref();
         coin_key
       },
-      keys.1.group_key().to_bytes().as_ref(),
+      keys.1[0].group_key().to_bytes().as_ref(),
     );
-    txn.put(Self::keys_key(&keys.1.group_key()), keys_vec);
+    // This re-encrypts the keys, migrating any generated while keys were saved in the clear
+    Self::save_confirmed_keys(txn, storage_key, &keys.1[0].group_key(), &cores);
     keys
   }
+  #[allow(clippy::type_complexity)]
   fn keys<G: Get>(
     getter: &G,
+    storage_key: &[u8],
     key: &<C::Curve as Ciphersuite>::G,
-  ) -> (ThresholdKeys<Ristretto>, ThresholdKeys<C::Curve>) {
-    let res = Self::read_keys(getter, &Self::keys_key(key)).1;
-    assert_eq!(&res.1.group_key(), key);
-    res
+  ) -> (bool, (Vec<ThresholdCore<Ristretto>>, Vec<ThresholdCore<C::Curve>>)) {
+    Self::read_keys(getter, &Self::keys_key(key), storage_key)
   }
 }
 
@@ -136,29 +219,53 @@ impl<C: Coin, D: Db> KeyGenDb<C, D> {
 pub struct KeyGen<C: Coin, D: Db> {
   db: D,
   entropy: Zeroizing<[u8; 32]>,
+  // The key used to encrypt generated keys at rest
+  storage_key: Zeroizing<[u8; 32]>,
 
+  #[allow(clippy::type_complexity)]
   active_commit:
-    HashMap<ValidatorSet, (SecretShareMachine<Ristretto>, SecretShareMachine<C::Curve>)>,
-  active_share: HashMap<ValidatorSet, (KeyMachine<Ristretto>, KeyMachine<C::Curve>)>,
+    HashMap<ValidatorSet, (Vec<SecretShareMachine<Ristretto>>, Vec<SecretShareMachine<C::Curve>>)>,
+  #[allow(clippy::type_complexity)]
+  active_share: HashMap<ValidatorSet, (Vec<KeyMachine<Ristretto>>, Vec<KeyMachine<C::Curve>>)>,
 }
 
 impl<C: Coin, D: Db> KeyGen<C, D> {
   #[allow(clippy::new_ret_no_self)]
-  pub fn new(db: D, entropy: Zeroizing<[u8; 32]>) -> KeyGen<C, D> {
-    KeyGen { db, entropy, active_commit: HashMap::new(), active_share: HashMap::new() }
+  pub fn new(
+    db: D,
+    entropy: Zeroizing<[u8; 32]>,
+    storage_key: Zeroizing<[u8; 32]>,
+  ) -> KeyGen<C, D> {
+    KeyGen { db, entropy, storage_key, active_commit: HashMap::new(), active_share: HashMap::new() }
   }
 
+  #[allow(clippy::type_complexity)]
   pub fn keys(
     &self,
     key: &<C::Curve as Ciphersuite>::G,
-  ) -> (ThresholdKeys<Ristretto>, ThresholdKeys<C::Curve>) {
+  ) -> (Vec<ThresholdKeys<Ristretto>>, Vec<ThresholdKeys<C::Curve>>) {
     // This is safe, despite not having a txn, since it's a static value
     // The only concern is it may not be set when expected, or it may be set unexpectedly
     // Since this unwraps, it being unset when expected to be set will cause a panic
     // The only other concern is if it's set when it's not safe to use
     // The keys are only written on confirmation, and the transaction writing them is atomic to
     // every associated operation
-    KeyGenDb::<C, D>::keys(&self.db, key)
+    let res = KeyGenDb::<C, D>::into_keys(
+      KeyGenDb::<C, D>::keys(&self.db, self.storage_key.as_ref(), key).1,
+    );
+    assert_eq!(&res.1[0].group_key(), key);
+    res
+  }
+
+  /// Encrypt keys confirmed before keys were encrypted at rest.
+  ///
+  /// This should be called, for every active key, on boot.
+  pub fn migrate_keys(&self, txn: &mut D::Transaction<'_>, key: &<C::Curve as Ciphersuite>::G) {
+    let (legacy, cores) = KeyGenDb::<C, D>::keys(txn, self.storage_key.as_ref(), key);
+    if legacy {
+      info!("encrypting keys {} at rest", hex::encode(key.to_bytes()));
+      KeyGenDb::<C, D>::save_confirmed_keys(txn, self.storage_key.as_ref(), key, &cores);
+    }
   }
 
   pub async fn handle(
@@ -184,31 +291,63 @@ impl<C: Coin, D: Db> KeyGen<C, D> {
     let secret_shares_rng = |id| rng(b"Key Gen Secret Shares", id);
     let share_rng = |id| rng(b"Key Gen Share", id);
 
-    let key_gen_machines = |id, params| {
+    // The params for each of our key shares
+    let share_params = |params: ThresholdParams, shares: u16| {
+      (0 .. shares)
+        .map(|s| {
+          ThresholdParams::new(
+            params.t(),
+            params.n(),
+            Participant::new(u16::from(params.i()) + s).unwrap(),
+          )
+          .unwrap()
+        })
+        .collect::<Vec<_>>()
+    };
+
+    let key_gen_machines = |id, params: ThresholdParams, shares| {
       let mut rng = coefficients_rng(id);
-      let substrate = KeyGenMachine::new(params, context(&id)).generate_coefficients(&mut rng);
-      let coin = KeyGenMachine::new(params, context(&id)).generate_coefficients(&mut rng);
-      ((substrate.0, coin.0), (substrate.1, coin.1))
+      let mut machines = (vec![], vec![]);
+      let mut commitments = vec![];
+      for params in share_params(params, shares) {
+        let substrate =
+          KeyGenMachine::<Ristretto>::new(params, context(&id)).generate_coefficients(&mut rng);
+        let coin =
+          KeyGenMachine::<C::Curve>::new(params, context(&id)).generate_coefficients(&mut rng);
+        machines.0.push(substrate.0);
+        machines.1.push(coin.0);
+        let mut serialized = substrate.1.serialize();
+        serialized.extend(coin.1.serialize());
+        commitments.push(serialized);
+      }
+      (machines, commitments)
+    };
+
+    // The commitments from everyone other than the specified key share
+    let commitments_for = |commitments: &BTreeMap<Participant, Vec<u8>>, i: Participant| {
+      commitments
+        .iter()
+        .filter(|(l, _)| **l != i)
+        .map(|(l, commitments)| (*l, commitments.clone()))
+        .collect::<BTreeMap<_, _>>()
     };
 
     match msg {
-      CoordinatorMessage::GenerateKey { id, params } => {
-        info!("Generating new key. ID: {:?} Params: {:?}", id, params);
+      CoordinatorMessage::GenerateKey { id, params, shares } => {
+        info!("Generating new key. ID: {:?} Params: {:?} Shares: {}", id, params, shares);
 
         // Remove old attempts
         if self.active_commit.remove(&id.set).is_none() &&
           self.active_share.remove(&id.set).is_none()
         {
           // If we haven't handled this set before, save the params
-          KeyGenDb::<C, D>::save_params(txn, &id.set, &params);
+          KeyGenDb::<C, D>::save_params(txn, &id.set, &params, shares);
         }
 
-        let (machines, commitments) = key_gen_machines(id, params);
-        let mut serialized = commitments.0.serialize();
-        serialized.extend(commitments.1.serialize());
+        let (machines, commitments) = key_gen_machines(id, params, shares);
         self.active_commit.insert(id.set, machines);
 
-        ProcessorMessage::Commitments { id, commitments: serialized }
+        ProcessorMessage::Commitments { id, commitments }
       }
 
       CoordinatorMessage::Commitments { id, commitments } => {
@@ -221,28 +360,28 @@ impl<C: Coin, D: Db> KeyGen<C, D> {
           panic!("commitments when already handled commitments");
         }
 
-        let params = KeyGenDb::<C, D>::params(txn, &id.set);
+        let (params, share_quantity) = KeyGenDb::<C, D>::params(txn, &id.set);
 
         // Unwrap the machines, rebuilding them if we didn't have them in our cache
         // We won't if the processor rebooted
         // This *may* be inconsistent if we receive a KeyGen for attempt x, then commitments for
         // attempt y
         // The coordinator is trusted to be proper in this regard
-        let machines =
-          self.active_commit.remove(&id.set).unwrap_or_else(|| key_gen_machines(id, params).0);
+        let machines = self
+          .active_commit
+          .remove(&id.set)
+          .unwrap_or_else(|| key_gen_machines(id, params, share_quantity).0);
 
         let mut rng = secret_shares_rng(id);
 
-        let mut commitments_ref: HashMap<Participant, &[u8]> =
-          commitments.iter().map(|(i, commitments)| (*i, commitments.as_ref())).collect();
-
         #[allow(clippy::type_complexity)]
         fn handle_machine<C: Ciphersuite>(
           rng: &mut ChaCha20Rng,
           params: ThresholdParams,
           machine: SecretShareMachine<C>,
-          commitments_ref: &mut HashMap<Participant, &[u8]>,
-        ) -> (KeyMachine<C>, HashMap<Participant, EncryptedMessage<C, SecretShare<C::F>>>) {
+          commitments_ref: &mut BTreeMap<Participant, &[u8]>,
+        ) -> (KeyMachine<C>, BTreeMap<Participant, EncryptedMessage<C, SecretShare<C::F>>>)
+        {
           // Parse the commitments
           let parsed = match commitments_ref
             .iter_mut()
@@ -262,25 +401,39 @@ impl<C: Coin, D: Db> KeyGen<C, D> {
           }
         }
 
-        let (substrate_machine, mut substrate_shares) =
-          handle_machine::<Ristretto>(&mut rng, params, machines.0, &mut commitments_ref);
-        let (coin_machine, coin_shares) =
-          handle_machine(&mut rng, params, machines.1, &mut commitments_ref);
+        let mut key_machines = (vec![], vec![]);
+        let mut shares = vec![];
+        for ((params, substrate_machine), coin_machine) in
+          share_params(params, share_quantity).into_iter().zip(machines.0).zip(machines.1)
+        {
+          let commitments = commitments_for(&commitments, params.i());
+          let mut commitments_ref: BTreeMap<Participant, &[u8]> =
+            commitments.iter().map(|(i, commitments)| (*i, commitments.as_ref())).collect();
+
+          let (substrate_machine, substrate_shares) =
+            handle_machine::<Ristretto>(&mut rng, params, substrate_machine, &mut commitments_ref);
+          let (coin_machine, coin_shares) =
+            handle_machine(&mut rng, params, coin_machine, &mut commitments_ref);
 
-        for (_, commitments) in commitments_ref {
-          if !commitments.is_empty() {
-            todo!("malicious signer: extra bytes");
+          for (_, commitments) in commitments_ref {
+            if !commitments.is_empty() {
+              todo!("malicious signer: extra bytes");
+            }
           }
-        }
 
-        self.active_share.insert(id.set, (substrate_machine, coin_machine));
+          key_machines.0.push(substrate_machine);
+          key_machines.1.push(coin_machine);
 
-        let mut shares: HashMap<_, _> =
-          substrate_shares.drain().map(|(i, share)| (i, share.serialize())).collect();
-        for (i, share) in shares.iter_mut() {
-          share.extend(coin_shares[i].serialize());
+          let mut these_shares: BTreeMap<_, _> =
+            substrate_shares.into_iter().map(|(i, share)| (i, share.serialize())).collect();
+          for (i, share) in these_shares.iter_mut() {
+            share.extend(coin_shares[i].serialize());
+          }
+          shares.push(these_shares);
         }
 
+        self.active_share.insert(id.set, key_machines);
+
         KeyGenDb::<C, D>::save_commitments(txn, &id, &commitments);
 
         ProcessorMessage::Shares { id, shares }
@@ -289,21 +442,18 @@ impl<C: Coin, D: Db> KeyGen<C, D> {
       CoordinatorMessage::Shares { id, shares } => {
         info!("Received shares for {:?}", id);
 
-        let params = KeyGenDb::<C, D>::params(txn, &id.set);
+        let (params, share_quantity) = KeyGenDb::<C, D>::params(txn, &id.set);
 
         // Same commentary on inconsistency as above exists
         let machines = self.active_share.remove(&id.set).unwrap_or_else(|| {
-          let machines = key_gen_machines(id, params).0;
+          let machines = key_gen_machines(id, params, share_quantity).0;
           let mut rng = secret_shares_rng(id);
           let commitments = KeyGenDb::<C, D>::commitments(txn, &id);
 
-          let mut commitments_ref: HashMap<Participant, &[u8]> =
-            commitments.iter().map(|(i, commitments)| (*i, commitments.as_ref())).collect();
-
           fn parse_commitments<C: Ciphersuite>(
             params: ThresholdParams,
-            commitments_ref: &mut HashMap<Participant, &[u8]>,
-          ) -> HashMap<Participant, EncryptionKeyMessage<C, Commitments<C>>> {
+            commitments_ref: &mut BTreeMap<Participant, &[u8]>,
+          ) -> BTreeMap<Participant, EncryptionKeyMessage<C, Commitments<C>>> {
             commitments_ref
               .iter_mut()
               .map(|(i, commitments)| {
@@ -312,30 +462,37 @@ impl<C: Coin, D: Db> KeyGen<C, D> {
               .collect()
           }
 
-          (
-            machines
-              .0
-              .generate_secret_shares(&mut rng, parse_commitments(params, &mut commitments_ref))
-              .unwrap()
-              .0,
-            machines
-              .1
-              .generate_secret_shares(&mut rng, parse_commitments(params, &mut commitments_ref))
-              .unwrap()
-              .0,
-          )
+          let mut key_machines = (vec![], vec![]);
+          for ((params, substrate_machine), coin_machine) in
+            share_params(params, share_quantity).into_iter().zip(machines.0).zip(machines.1)
+          {
+            let commitments = commitments_for(&commitments, params.i());
+            let mut commitments_ref: BTreeMap<Participant, &[u8]> =
+              commitments.iter().map(|(i, commitments)| (*i, commitments.as_ref())).collect();
+
+            key_machines.0.push(
+              substrate_machine
+                .generate_secret_shares(&mut rng, parse_commitments(params, &mut commitments_ref))
+                .unwrap()
+                .0,
+            );
+            key_machines.1.push(
+              coin_machine
+                .generate_secret_shares(&mut rng, parse_commitments(params, &mut commitments_ref))
+                .unwrap()
+                .0,
+            );
+          }
+          key_machines
         });
 
         let mut rng = share_rng(id);
 
-        let mut shares_ref: HashMap<Participant, &[u8]> =
-          shares.iter().map(|(i, shares)| (*i, shares.as_ref())).collect();
-
         fn handle_machine<C: Ciphersuite>(
           rng: &mut ChaCha20Rng,
           params: ThresholdParams,
           machine: KeyMachine<C>,
-          shares_ref: &mut HashMap<Participant, &[u8]>,
+          shares_ref: &mut BTreeMap<Participant, &[u8]>,
         ) -> ThresholdCore<C> {
           // Parse the shares
           let shares = match shares_ref
@@ -357,24 +514,52 @@ impl<C: Coin, D: Db> KeyGen<C, D> {
           .complete()
         }
 
-        let substrate_keys = handle_machine(&mut rng, params, machines.0, &mut shares_ref);
-        let coin_keys = handle_machine(&mut rng, params, machines.1, &mut shares_ref);
+        if shares.len() != usize::from(share_quantity) {
+          panic!("coordinator sent shares for {} key shares, not {share_quantity}", shares.len());
+        }
+
+        let mut substrate_keys = vec![];
+        let mut coin_keys = vec![];
+        for (((params, substrate_machine), coin_machine), shares) in
+          share_params(params, share_quantity)
+            .into_iter()
+            .zip(machines.0)
+            .zip(machines.1)
+            .zip(&shares)
+        {
+          let mut shares_ref: BTreeMap<Participant, &[u8]> =
+            shares.iter().map(|(i, shares)| (*i, shares.as_ref())).collect();
+
+          substrate_keys.push(handle_machine(&mut rng, params, substrate_machine, &mut shares_ref));
+          coin_keys.push(handle_machine(&mut rng, params, coin_machine, &mut shares_ref));
 
-        for (_, shares) in shares_ref {
-          if !shares.is_empty() {
-            todo!("malicious signer: extra bytes");
+          for (_, shares) in shares_ref {
+            if !shares.is_empty() {
+              todo!("malicious signer: extra bytes");
+            }
           }
         }
 
-        let mut coin_keys = ThresholdKeys::new(coin_keys);
-        C::tweak_keys(&mut coin_keys);
-
-        KeyGenDb::<C, D>::save_keys(txn, &id, &substrate_keys, &coin_keys);
+        // The coin keys are saved untweaked, as the tweak is reapplied whenever they're read
+        let coin_key = {
+          let mut coin_key = ThresholdKeys::new(coin_keys[0].clone());
+          C::tweak_keys(&mut coin_key);
+          coin_key.group_key()
+        };
+        KeyGenDb::<C, D>::save_keys(
+          txn,
+          &id,
+          &mut rng,
+          self.storage_key.as_ref(),
+          &substrate_keys,
+          &coin_keys,
+          &coin_key,
+        );
 
         ProcessorMessage::GeneratedKeyPair {
           id,
-          substrate_key: substrate_keys.group_key().to_bytes(),
-          coin_key: coin_keys.group_key().to_bytes().as_ref().to_vec(),
+          substrate_key: substrate_keys[0].group_key().to_bytes(),
+          coin_key: coin_key.to_bytes().as_ref().to_vec(),
         }
       }
     }
@@ -386,12 +571,13 @@ impl<C: Coin, D: Db> KeyGen<C, D> {
     set: ValidatorSet,
     key_pair: KeyPair,
   ) -> KeyConfirmed<C::Curve> {
-    let (substrate_keys, coin_keys) = KeyGenDb::<C, D>::confirm_keys(txn, set, key_pair);
+    let (substrate_keys, coin_keys) =
+      KeyGenDb::<C, D>::confirm_keys(txn, self.storage_key.as_ref(), set, key_pair);
 
     info!(
       "Confirmed key pair {} {} for set {:?}",
-      hex::encode(substrate_keys.group_key().to_bytes()),
-      hex::encode(coin_keys.group_key().to_bytes()),
+      hex::encode(substrate_keys[0].group_key().to_bytes()),
+      hex::encode(coin_keys[0].group_key().to_bytes()),
       set,
     );
 
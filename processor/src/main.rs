@@ -8,7 +8,7 @@ use zeroize::{Zeroize, Zeroizing};
 
 use transcript::{Transcript, RecommendedTranscript};
 use group::GroupEncoding;
-use frost::{curve::Ciphersuite, ThresholdKeys};
+use frost::curve::Ciphersuite;
 
 use log::{info, warn, error};
 use tokio::time::sleep;
@@ -108,13 +108,12 @@ async fn get_fee<C: Coin>(coin: &C, block_number: usize) -> C::Fee {
 
 async fn prepare_send<C: Coin>(
   coin: &C,
-  keys: ThresholdKeys<C::Curve>,
   block_number: usize,
   fee: C::Fee,
   plan: Plan<C>,
 ) -> (Option<(C::SignableTransaction, C::Eventuality)>, Vec<PostFeeBranch>) {
   loop {
-    match coin.prepare_send(keys.clone(), block_number, plan.clone(), fee).await {
+    match coin.prepare_send(block_number, plan.clone(), fee).await {
       Ok(prepared) => {
         return prepared;
       }
@@ -214,9 +213,7 @@ async fn sign_plans<C: Coin, D: Db>(
 
     let key = plan.key.to_bytes();
     MainDb::<C, D>::save_signing(txn, key.as_ref(), block_number.try_into().unwrap(), &plan);
-    let (tx, branches) =
-      prepare_send(coin, signers.get_mut(key.as_ref()).unwrap().keys(), block_number, fee, plan)
-        .await;
+    let (tx, branches) = prepare_send(coin, block_number, fee, plan).await;
 
     for branch in branches {
       substrate_mutable
@@ -365,11 +362,11 @@ async fn handle_coordinator_msg<D: Db, C: Coin, Co: Coordinator>(
           let KeyConfirmed { substrate_keys, coin_keys } =
             tributary_mutable.key_gen.confirm(txn, set, key_pair).await;
           tributary_mutable.substrate_signers.insert(
-            substrate_keys.group_key().to_bytes().to_vec(),
+            substrate_keys[0].group_key().to_bytes().to_vec(),
             SubstrateSigner::new(substrate_keys),
           );
 
-          let key = coin_keys.group_key();
+          let key = coin_keys[0].group_key();
 
           substrate_mutable.scanner.rotate_key(txn, activation_number, key).await;
           substrate_mutable
@@ -458,16 +455,24 @@ async fn boot<C: Coin, D: Db>(
   raw_db: &mut D,
   coin: &C,
 ) -> (MainDb<C, D>, TributaryMutable<C, D>, SubstrateMutable<C, D>) {
-  let mut entropy_transcript = {
-    let entropy =
-      Zeroizing::new(env::var("ENTROPY").expect("entropy wasn't provided as an env var"));
-    if entropy.len() != 64 {
-      panic!("entropy isn't the right length");
+  // Read a 32-byte, hex-encoded secret from the specified env var
+  fn read_secret(var: &str) -> Zeroizing<[u8; 32]> {
+    let encoded = Zeroizing::new(
+      env::var(var).unwrap_or_else(|_| panic!("{var} wasn't provided as an env var")),
+    );
+    if encoded.len() != 64 {
+      panic!("{var} isn't the right length");
     }
-    let bytes = Zeroizing::new(hex::decode(entropy).expect("entropy wasn't hex-formatted"));
-    let mut entropy = Zeroizing::new([0; 32]);
-    let entropy_mut: &mut [u8] = entropy.as_mut();
-    entropy_mut.copy_from_slice(bytes.as_ref());
+    let bytes =
+      Zeroizing::new(hex::decode(encoded).unwrap_or_else(|_| panic!("{var} wasn't hex-formatted")));
+    let mut secret = Zeroizing::new([0; 32]);
+    let secret_mut: &mut [u8] = secret.as_mut();
+    secret_mut.copy_from_slice(bytes.as_ref());
+    secret
+  }
+
+  let mut entropy_transcript = {
+    let entropy = read_secret("ENTROPY");
 
     let mut transcript = RecommendedTranscript::new(b"Serai Processor Entropy");
     transcript.append_message(b"entropy", entropy);
@@ -487,7 +492,13 @@ async fn boot<C: Coin, D: Db>(
 
   // We don't need to re-issue GenerateKey orders because the coordinator is expected to
   // schedule/notify us of new attempts
-  let key_gen = KeyGen::<C, _>::new(raw_db.clone(), entropy(b"key-gen_entropy"));
+  // The key used to encrypt keys at rest is independent of the entropy, so the DB's contents
+  // can't be decrypted with the entropy alone
+  let key_gen = KeyGen::<C, _>::new(
+    raw_db.clone(),
+    entropy(b"key-gen_entropy"),
+    read_secret("KEYS_STORAGE_KEY"),
+  );
   // The scanner has no long-standing orders to re-issue
   let (mut scanner, active_keys) = Scanner::new(coin.clone(), raw_db.clone());
 
@@ -497,12 +508,21 @@ async fn boot<C: Coin, D: Db>(
 
   let main_db = MainDb::new(raw_db.clone());
 
+  // Encrypt any keys which were saved prior to keys being encrypted at rest
+  {
+    let mut txn = raw_db.txn();
+    for key in &active_keys {
+      key_gen.migrate_keys(&mut txn, key);
+    }
+    txn.commit();
+  }
+
   for key in &active_keys {
     // TODO: Load existing schedulers
 
     let (substrate_keys, coin_keys) = key_gen.keys(key);
 
-    let substrate_key = substrate_keys.group_key();
+    let substrate_key = substrate_keys[0].group_key();
     let substrate_signer = SubstrateSigner::new(substrate_keys);
     // We don't have to load any state for this since the Scanner will re-fire any events
     // necessary
@@ -520,8 +540,7 @@ async fn boot<C: Coin, D: Db>(
       let id = plan.id();
       info!("reloading plan {}: {:?}", hex::encode(id), plan);
 
-      let (Some((tx, eventuality)), _) =
-      prepare_send(coin, signer.keys(), block_number, fee, plan).await else {
+      let (Some((tx, eventuality)), _) = prepare_send(coin, block_number, fee, plan).await else {
         panic!("previously created transaction is no longer being created")
       };
 
@@ -93,18 +93,23 @@ pub struct Signer<C: Coin, D: Db> {
 
   coin: C,
 
-  keys: ThresholdKeys<C::Curve>,
+  // One set of keys per key share held
+  keys: Vec<ThresholdKeys<C::Curve>>,
 
   signable: HashMap<[u8; 32], C::SignableTransaction>,
   attempt: HashMap<[u8; 32], u32>,
-  preprocessing: HashMap<[u8; 32], <C::TransactionMachine as PreprocessMachine>::SignMachine>,
   #[allow(clippy::type_complexity)]
-  signing: HashMap<
-    [u8; 32],
-    <
-      <C::TransactionMachine as PreprocessMachine>::SignMachine as SignMachine<C::Transaction>
-    >::SignatureMachine,
-  >,
+  preprocessing: HashMap<[u8; 32], Vec<<C::TransactionMachine as PreprocessMachine>::SignMachine>>,
+  #[allow(clippy::type_complexity)]
+  signing:
+    HashMap<
+      [u8; 32],
+      Vec<
+        <<C::TransactionMachine as PreprocessMachine>::SignMachine as SignMachine<
+          C::Transaction,
+        >>::SignatureMachine,
+      >,
+    >,
 
   pub events: VecDeque<SignerEvent<C>>,
 }
@@ -121,7 +126,8 @@ impl<C: Coin, D: Db> fmt::Debug for Signer<C, D> {
 }
 
 impl<C: Coin, D: Db> Signer<C, D> {
-  pub fn new(coin: C, keys: ThresholdKeys<C::Curve>) -> Signer<C, D> {
+  pub fn new(coin: C, keys: Vec<ThresholdKeys<C::Curve>>) -> Signer<C, D> {
+    assert!(!keys.is_empty());
     Signer {
       db: PhantomData,
 
@@ -138,10 +144,6 @@ impl<C: Coin, D: Db> Signer<C, D> {
     }
   }
 
-  pub fn keys(&self) -> ThresholdKeys<C::Curve> {
-    self.keys.clone()
-  }
-
   fn verify_id(&self, id: &SignId) -> Result<(), ()> {
     // Check the attempt lines up
     match self.attempt.get(&id.id) {
@@ -215,7 +217,7 @@ impl<C: Coin, D: Db> Signer<C, D> {
     } else {
       debug!(
         "signer {} informed of the completion of {}. {}",
-        hex::encode(self.keys.group_key().to_bytes()),
+        hex::encode(self.keys[0].group_key().to_bytes()),
         hex::encode(id),
         "this signer did not have/has already completed that plan",
       );
@@ -289,7 +291,7 @@ impl<C: Coin, D: Db> Signer<C, D> {
     // Update the attempt number
     self.attempt.insert(id, attempt);
 
-    let id = SignId { key: self.keys.group_key().to_bytes().as_ref().to_vec(), id, attempt };
+    let id = SignId { key: self.keys[0].group_key().to_bytes().as_ref().to_vec(), id, attempt };
 
     info!("signing for {} #{}", hex::encode(id.id), id.attempt);
 
@@ -316,23 +318,28 @@ impl<C: Coin, D: Db> Signer<C, D> {
 
     SignerDb::<C, D>::attempt(txn, &id);
 
-    // Attempt to create the TX
-    let machine = match self.coin.attempt_send(tx).await {
-      Err(e) => {
-        error!("failed to attempt {}, #{}: {:?}", hex::encode(id.id), id.attempt, e);
-        return;
-      }
-      Ok(machine) => machine,
-    };
+    // Attempt to create the TX, with a machine per key share
+    let mut machines = vec![];
+    let mut preprocesses = vec![];
+    for keys in &self.keys {
+      let machine = match self.coin.attempt_send(keys.clone(), tx.clone()).await {
+        Err(e) => {
+          error!("failed to attempt {}, #{}: {:?}", hex::encode(id.id), id.attempt, e);
+          return;
+        }
+        Ok(machine) => machine,
+      };
 
-    let (machine, preprocess) = machine.preprocess(&mut OsRng);
-    self.preprocessing.insert(id.id, machine);
+      let (machine, preprocess) = machine.preprocess(&mut OsRng);
+      machines.push(machine);
+      preprocesses.push(preprocess.serialize());
+    }
+    self.preprocessing.insert(id.id, machines);
 
-    // Broadcast our preprocess
-    self.events.push_back(SignerEvent::ProcessorMessage(ProcessorMessage::Preprocess {
-      id,
-      preprocess: preprocess.serialize(),
-    }));
+    // Broadcast our preprocesses
+    self
+      .events
+      .push_back(SignerEvent::ProcessorMessage(ProcessorMessage::Preprocess { id, preprocesses }));
   }
 
   pub async fn sign_transaction(
@@ -354,12 +361,12 @@ impl<C: Coin, D: Db> Signer<C, D> {
 
   pub async fn handle(&mut self, txn: &mut D::Transaction<'_>, msg: CoordinatorMessage) {
     match msg {
-      CoordinatorMessage::Preprocesses { id, mut preprocesses } => {
+      CoordinatorMessage::Preprocesses { id, preprocesses } => {
         if self.verify_id(&id).is_err() {
           return;
         }
 
-        let machine = match self.preprocessing.remove(&id.id) {
+        let machines = match self.preprocessing.remove(&id.id) {
           // Either rebooted or RPC error, or some invariant
           None => {
             warn!(
@@ -368,47 +375,54 @@ impl<C: Coin, D: Db> Signer<C, D> {
             );
             return;
           }
-          Some(machine) => machine,
+          Some(machines) => machines,
         };
 
-        let preprocesses = match preprocesses
-          .drain()
-          .map(|(l, preprocess)| {
-            let mut preprocess_ref = preprocess.as_ref();
-            let res = machine
-              .read_preprocess::<&[u8]>(&mut preprocess_ref)
-              .map(|preprocess| (l, preprocess));
-            if !preprocess_ref.is_empty() {
-              todo!("malicious signer: extra bytes");
-            }
-            res
-          })
-          .collect::<Result<_, _>>()
-        {
-          Ok(preprocesses) => preprocesses,
-          Err(e) => todo!("malicious signer: {:?}", e),
-        };
-
-        // Use an empty message, as expected of TransactionMachines
-        let (machine, share) = match machine.sign(preprocesses, &[]) {
-          Ok(res) => res,
-          Err(e) => todo!("malicious signer: {:?}", e),
-        };
-        self.signing.insert(id.id, machine);
+        let mut signature_machines = vec![];
+        let mut shares = vec![];
+        for (keys, machine) in self.keys.iter().zip(machines) {
+          // The preprocesses include our own, which must be removed for each key share
+          let preprocesses = match preprocesses
+            .iter()
+            .filter(|(l, _)| **l != keys.params().i())
+            .map(|(l, preprocess)| {
+              let mut preprocess_ref = preprocess.as_ref();
+              let res = machine
+                .read_preprocess::<&[u8]>(&mut preprocess_ref)
+                .map(|preprocess| (*l, preprocess));
+              if !preprocess_ref.is_empty() {
+                todo!("malicious signer: extra bytes");
+              }
+              res
+            })
+            .collect::<Result<_, _>>()
+          {
+            Ok(preprocesses) => preprocesses,
+            Err(e) => todo!("malicious signer: {:?}", e),
+          };
+
+          // Use an empty message, as expected of TransactionMachines
+          let (machine, share) = match machine.sign(preprocesses, &[]) {
+            Ok(res) => res,
+            Err(e) => todo!("malicious signer: {:?}", e),
+          };
+          signature_machines.push(machine);
+          shares.push(share.serialize());
+        }
+        self.signing.insert(id.id, signature_machines);
 
-        // Broadcast our share
-        self.events.push_back(SignerEvent::ProcessorMessage(ProcessorMessage::Share {
-          id,
-          share: share.serialize(),
-        }));
+        // Broadcast our shares
+        self
+          .events
+          .push_back(SignerEvent::ProcessorMessage(ProcessorMessage::Share { id, shares }));
       }
 
-      CoordinatorMessage::Shares { id, mut shares } => {
+      CoordinatorMessage::Shares { id, shares } => {
         if self.verify_id(&id).is_err() {
           return;
         }
 
-        let machine = match self.signing.remove(&id.id) {
+        let mut machines = match self.signing.remove(&id.id) {
           // Rebooted, RPC error, or some invariant
           None => {
             // If preprocessing has this ID, it means we were never sent the preprocess by the
@@ -423,14 +437,17 @@ impl<C: Coin, D: Db> Signer<C, D> {
             );
             return;
           }
-          Some(machine) => machine,
+          Some(machines) => machines,
         };
 
+        // Every machine would produce the same transaction, so only the first is completed
+        let machine = machines.swap_remove(0);
         let shares = match shares
-          .drain()
+          .iter()
+          .filter(|(l, _)| **l != self.keys[0].params().i())
           .map(|(l, share)| {
             let mut share_ref = share.as_ref();
-            let res = machine.read_share::<&[u8]>(&mut share_ref).map(|share| (l, share));
+            let res = machine.read_share::<&[u8]>(&mut share_ref).map(|share| (*l, share));
             if !share_ref.is_empty() {
               todo!("malicious signer: extra bytes");
             }
@@ -67,12 +67,13 @@ impl<D: Db> SubstrateSignerDb<D> {
 pub struct SubstrateSigner<D: Db> {
   db: PhantomData<D>,
 
-  keys: ThresholdKeys<Ristretto>,
+  // One set of keys per key share held
+  keys: Vec<ThresholdKeys<Ristretto>>,
 
   signable: HashMap<[u8; 32], Batch>,
   attempt: HashMap<[u8; 32], u32>,
-  preprocessing: HashMap<[u8; 32], AlgorithmSignMachine<Ristretto, Schnorrkel>>,
-  signing: HashMap<[u8; 32], AlgorithmSignatureMachine<Ristretto, Schnorrkel>>,
+  preprocessing: HashMap<[u8; 32], Vec<AlgorithmSignMachine<Ristretto, Schnorrkel>>>,
+  signing: HashMap<[u8; 32], Vec<AlgorithmSignatureMachine<Ristretto, Schnorrkel>>>,
 
   pub events: VecDeque<SubstrateSignerEvent>,
 }
@@ -88,7 +89,8 @@ impl<D: Db> fmt::Debug for SubstrateSigner<D> {
 }
 
 impl<D: Db> SubstrateSigner<D> {
-  pub fn new(keys: ThresholdKeys<Ristretto>) -> SubstrateSigner<D> {
+  pub fn new(keys: Vec<ThresholdKeys<Ristretto>>) -> SubstrateSigner<D> {
+    assert!(!keys.is_empty());
     SubstrateSigner {
       db: PhantomData,
 
@@ -161,7 +163,7 @@ impl<D: Db> SubstrateSigner<D> {
     // Update the attempt number
     self.attempt.insert(id, attempt);
 
-    let id = SignId { key: self.keys.group_key().to_bytes().to_vec(), id, attempt };
+    let id = SignId { key: self.keys[0].group_key().to_bytes().to_vec(), id, attempt };
     info!("signing batch {} #{}", hex::encode(id.id), id.attempt);
 
     // If we reboot mid-sign, the current design has us abort all signs and wait for latter
@@ -187,15 +189,21 @@ impl<D: Db> SubstrateSigner<D> {
 
     SubstrateSignerDb::<D>::attempt(txn, &id);
 
-    // b"substrate" is a literal from sp-core
-    let machine = AlgorithmMachine::new(Schnorrkel::new(b"substrate"), self.keys.clone());
+    let mut machines = vec![];
+    let mut preprocesses = vec![];
+    for keys in &self.keys {
+      // b"substrate" is a literal from sp-core
+      let machine = AlgorithmMachine::new(Schnorrkel::new(b"substrate"), keys.clone());
 
-    let (machine, preprocess) = machine.preprocess(&mut OsRng);
-    self.preprocessing.insert(id.id, machine);
+      let (machine, preprocess) = machine.preprocess(&mut OsRng);
+      machines.push(machine);
+      preprocesses.push(preprocess.serialize());
+    }
+    self.preprocessing.insert(id.id, machines);
 
-    // Broadcast our preprocess
+    // Broadcast our preprocesses
     self.events.push_back(SubstrateSignerEvent::ProcessorMessage(
-      ProcessorMessage::BatchPreprocess { id, preprocess: preprocess.serialize() },
+      ProcessorMessage::BatchPreprocess { id, preprocesses },
     ));
   }
 
@@ -213,12 +221,12 @@ impl<D: Db> SubstrateSigner<D> {
 
   pub async fn handle(&mut self, txn: &mut D::Transaction<'_>, msg: CoordinatorMessage) {
     match msg {
-      CoordinatorMessage::BatchPreprocesses { id, mut preprocesses } => {
+      CoordinatorMessage::BatchPreprocesses { id, preprocesses } => {
         if self.verify_id(&id).is_err() {
           return;
         }
 
-        let machine = match self.preprocessing.remove(&id.id) {
+        let machines = match self.preprocessing.remove(&id.id) {
           // Either rebooted or RPC error, or some invariant
           None => {
             warn!(
@@ -227,47 +235,56 @@ impl<D: Db> SubstrateSigner<D> {
             );
             return;
           }
-          Some(machine) => machine,
-        };
-
-        let preprocesses = match preprocesses
-          .drain()
-          .map(|(l, preprocess)| {
-            let mut preprocess_ref = preprocess.as_ref();
-            let res = machine
-              .read_preprocess::<&[u8]>(&mut preprocess_ref)
-              .map(|preprocess| (l, preprocess));
-            if !preprocess_ref.is_empty() {
-              todo!("malicious signer: extra bytes");
-            }
-            res
-          })
-          .collect::<Result<_, _>>()
-        {
-          Ok(preprocesses) => preprocesses,
-          Err(e) => todo!("malicious signer: {:?}", e),
+          Some(machines) => machines,
         };
 
-        let (machine, share) = match machine.sign(preprocesses, &self.signable[&id.id].encode()) {
-          Ok(res) => res,
-          Err(e) => todo!("malicious signer: {:?}", e),
-        };
-        self.signing.insert(id.id, machine);
+        let mut signature_machines = vec![];
+        let mut shares = vec![];
+        for (keys, machine) in self.keys.iter().zip(machines) {
+          // The preprocesses include our own, which must be removed for each key share
+          let preprocesses = match preprocesses
+            .iter()
+            .filter(|(l, _)| **l != keys.params().i())
+            .map(|(l, preprocess)| {
+              let mut preprocess_ref = preprocess.as_ref();
+              let res = machine
+                .read_preprocess::<&[u8]>(&mut preprocess_ref)
+                .map(|preprocess| (*l, preprocess));
+              if !preprocess_ref.is_empty() {
+                todo!("malicious signer: extra bytes");
+              }
+              res
+            })
+            .collect::<Result<_, _>>()
+          {
+            Ok(preprocesses) => preprocesses,
+            Err(e) => todo!("malicious signer: {:?}", e),
+          };
+
+          let (machine, share) = match machine.sign(preprocesses, &self.signable[&id.id].encode()) {
+            Ok(res) => res,
+            Err(e) => todo!("malicious signer: {:?}", e),
+          };
+          signature_machines.push(machine);
+
+          let mut share_bytes = [0; 32];
+          share_bytes.copy_from_slice(&share.serialize());
+          shares.push(share_bytes);
+        }
+        self.signing.insert(id.id, signature_machines);
 
-        // Broadcast our share
-        let mut share_bytes = [0; 32];
-        share_bytes.copy_from_slice(&share.serialize());
+        // Broadcast our shares
         self.events.push_back(SubstrateSignerEvent::ProcessorMessage(
-          ProcessorMessage::BatchShare { id, share: share_bytes },
+          ProcessorMessage::BatchShare { id, shares },
         ));
       }
 
-      CoordinatorMessage::BatchShares { id, mut shares } => {
+      CoordinatorMessage::BatchShares { id, shares } => {
         if self.verify_id(&id).is_err() {
           return;
         }
 
-        let machine = match self.signing.remove(&id.id) {
+        let mut machines = match self.signing.remove(&id.id) {
           // Rebooted, RPC error, or some invariant
           None => {
             // If preprocessing has this ID, it means we were never sent the preprocess by the
@@ -282,14 +299,17 @@ impl<D: Db> SubstrateSigner<D> {
             );
             return;
           }
-          Some(machine) => machine,
+          Some(machines) => machines,
         };
 
+        // Every machine would produce the same signature, so only the first is completed
+        let machine = machines.swap_remove(0);
         let shares = match shares
-          .drain()
+          .iter()
+          .filter(|(l, _)| **l != self.keys[0].params().i())
           .map(|(l, share)| {
             let mut share_ref = share.as_ref();
-            let res = machine.read_share::<&[u8]>(&mut share_ref).map(|share| (l, share));
+            let res = machine.read_share::<&[u8]>(&mut share_ref).map(|share| (*l, share));
             if !share_ref.is_empty() {
               todo!("malicious signer: extra bytes");
             }
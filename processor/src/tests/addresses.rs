@@ -1,5 +1,5 @@
 use core::time::Duration;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use rand_core::OsRng;
 
@@ -18,14 +18,14 @@ use crate::{
 
 async fn spend<C: Coin, D: Db>(
   coin: &C,
-  keys: &HashMap<Participant, ThresholdKeys<C::Curve>>,
+  keys: &BTreeMap<Participant, ThresholdKeys<C::Curve>>,
   scanner: &mut ScannerHandle<C, D>,
   batch: u32,
   outputs: Vec<C::Output>,
 ) -> Vec<C::Output> {
   let key = keys[&Participant::new(1).unwrap()].group_key();
 
-  let mut keys_txs = HashMap::new();
+  let mut keys_txs = BTreeMap::new();
   for (i, keys) in keys {
     keys_txs.insert(
       *i,
@@ -33,7 +33,6 @@ async fn spend<C: Coin, D: Db>(
         keys.clone(),
         coin
           .prepare_send(
-            keys.clone(),
             coin.get_latest_block_number().await.unwrap() - C::CONFIRMATIONS,
             // Send to a change output
             Plan { key, inputs: outputs.clone(), payments: vec![], change: Some(key) },
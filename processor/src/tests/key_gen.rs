@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use zeroize::Zeroizing;
 
@@ -25,35 +25,43 @@ const ID: KeyGenId =
   KeyGenId { set: ValidatorSet { session: Session(1), network: NetworkId::Monero }, attempt: 3 };
 
 pub async fn test_key_gen<C: Coin>() {
-  let mut entropies = HashMap::new();
-  let mut dbs = HashMap::new();
-  let mut key_gens = HashMap::new();
+  let mut entropies = BTreeMap::new();
+  let mut storage_keys = BTreeMap::new();
+  let mut dbs = BTreeMap::new();
+  let mut key_gens = BTreeMap::new();
   for i in 1 ..= 5 {
     let mut entropy = Zeroizing::new([0; 32]);
     OsRng.fill_bytes(entropy.as_mut());
     entropies.insert(i, entropy);
+    let mut storage_key = Zeroizing::new([0; 32]);
+    OsRng.fill_bytes(storage_key.as_mut());
+    storage_keys.insert(i, storage_key);
     let db = MemDb::new();
     dbs.insert(i, db.clone());
-    key_gens.insert(i, KeyGen::<C, MemDb>::new(db, entropies[&i].clone()));
+    key_gens
+      .insert(i, KeyGen::<C, MemDb>::new(db, entropies[&i].clone(), storage_keys[&i].clone()));
   }
 
-  let mut all_commitments = HashMap::new();
+  let mut all_commitments = BTreeMap::new();
   for i in 1 ..= 5 {
     let key_gen = key_gens.get_mut(&i).unwrap();
     let mut txn = dbs.get_mut(&i).unwrap().txn();
-    if let ProcessorMessage::Commitments { id, commitments } = key_gen
+    if let ProcessorMessage::Commitments { id, mut commitments } = key_gen
       .handle(
         &mut txn,
         CoordinatorMessage::GenerateKey {
           id: ID,
           params: ThresholdParams::new(3, 5, Participant::new(u16::try_from(i).unwrap()).unwrap())
             .unwrap(),
+          shares: 1,
         },
       )
       .await
     {
       assert_eq!(id, ID);
-      all_commitments.insert(Participant::new(u16::try_from(i).unwrap()).unwrap(), commitments);
+      assert_eq!(commitments.len(), 1);
+      all_commitments
+        .insert(Participant::new(u16::try_from(i).unwrap()).unwrap(), commitments.swap_remove(0));
     } else {
       panic!("didn't get commitments back");
     }
@@ -63,19 +71,22 @@ pub async fn test_key_gen<C: Coin>() {
   // 1 is rebuilt on every step
   // 2 is rebuilt here
   // 3 ... are rebuilt once, one at each of the following steps
-  let rebuild = |key_gens: &mut HashMap<_, _>, dbs: &HashMap<_, MemDb>, i| {
+  let rebuild = |key_gens: &mut BTreeMap<_, _>, dbs: &BTreeMap<_, MemDb>, i| {
     key_gens.remove(&i);
-    key_gens.insert(i, KeyGen::<C, _>::new(dbs[&i].clone(), entropies[&i].clone()));
+    key_gens.insert(
+      i,
+      KeyGen::<C, _>::new(dbs[&i].clone(), entropies[&i].clone(), storage_keys[&i].clone()),
+    );
   };
   rebuild(&mut key_gens, &dbs, 1);
   rebuild(&mut key_gens, &dbs, 2);
 
-  let mut all_shares = HashMap::new();
+  let mut all_shares = BTreeMap::new();
   for i in 1 ..= 5 {
     let key_gen = key_gens.get_mut(&i).unwrap();
     let mut txn = dbs.get_mut(&i).unwrap().txn();
     let i = Participant::new(u16::try_from(i).unwrap()).unwrap();
-    if let ProcessorMessage::Shares { id, shares } = key_gen
+    if let ProcessorMessage::Shares { id, mut shares } = key_gen
       .handle(
         &mut txn,
         CoordinatorMessage::Commitments {
@@ -86,7 +97,8 @@ pub async fn test_key_gen<C: Coin>() {
       .await
     {
       assert_eq!(id, ID);
-      all_shares.insert(i, shares);
+      assert_eq!(shares.len(), 1);
+      all_shares.insert(i, shares.swap_remove(0));
     } else {
       panic!("didn't get shares back");
     }
@@ -107,10 +119,10 @@ pub async fn test_key_gen<C: Coin>() {
         &mut txn,
         CoordinatorMessage::Shares {
           id: ID,
-          shares: all_shares
+          shares: vec![all_shares
             .iter()
             .filter_map(|(l, shares)| if i == *l { None } else { Some((*l, shares[&i].clone())) })
-            .collect(),
+            .collect()],
         },
       )
       .await
@@ -141,11 +153,22 @@ pub async fn test_key_gen<C: Coin>() {
 
     let params =
       ThresholdParams::new(3, 5, Participant::new(u16::try_from(i).unwrap()).unwrap()).unwrap();
-    assert_eq!(substrate_keys.params(), params);
-    assert_eq!(coin_keys.params(), params);
+    assert_eq!(substrate_keys.len(), 1);
+    assert_eq!(coin_keys.len(), 1);
+    assert_eq!(substrate_keys[0].params(), params);
+    assert_eq!(coin_keys[0].params(), params);
     assert_eq!(
-      (substrate_keys.group_key().to_bytes(), coin_keys.group_key().to_bytes().as_ref().to_vec()),
+      (
+        substrate_keys[0].group_key().to_bytes(),
+        coin_keys[0].group_key().to_bytes().as_ref().to_vec()
+      ),
       res
     );
+
+    // The confirmed keys should be readable with the storage key
+    let (read_substrate_keys, read_coin_keys) = key_gen.keys(&coin_keys[0].group_key());
+    assert_eq!(read_substrate_keys[0].serialize(), substrate_keys[0].serialize());
+    assert_eq!(read_coin_keys[0].serialize(), coin_keys[0].serialize());
+    assert_eq!(read_coin_keys[0].group_key(), coin_keys[0].group_key());
   }
 }
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use rand_core::{RngCore, OsRng};
 
@@ -20,7 +20,7 @@ use crate::{
 #[allow(clippy::type_complexity)]
 pub async fn sign<C: Coin>(
   coin: C,
-  mut keys_txs: HashMap<
+  keys_txs: BTreeMap<
     Participant,
     (ThresholdKeys<C::Curve>, (C::SignableTransaction, C::Eventuality)),
   >,
@@ -31,21 +31,21 @@ pub async fn sign<C: Coin>(
     attempt: 0,
   };
 
-  let mut keys = HashMap::new();
-  let mut txs = HashMap::new();
-  for (i, (these_keys, this_tx)) in keys_txs.drain() {
+  let mut keys = BTreeMap::new();
+  let mut txs = BTreeMap::new();
+  for (i, (these_keys, this_tx)) in keys_txs.into_iter() {
     keys.insert(i, these_keys);
     txs.insert(i, this_tx);
   }
 
-  let mut signers = HashMap::new();
-  let mut dbs = HashMap::new();
+  let mut signers = BTreeMap::new();
+  let mut dbs = BTreeMap::new();
   let mut t = 0;
   for i in 1 ..= keys.len() {
     let i = Participant::new(u16::try_from(i).unwrap()).unwrap();
     let keys = keys.remove(&i).unwrap();
     t = keys.params().t();
-    signers.insert(i, Signer::<_, MemDb>::new(coin.clone(), keys));
+    signers.insert(i, Signer::<_, MemDb>::new(coin.clone(), vec![keys]));
     dbs.insert(i, MemDb::new());
   }
   drop(keys);
@@ -71,22 +71,25 @@ pub async fn sign<C: Coin>(
   }
 
   // All participants should emit a preprocess
-  let mut preprocesses = HashMap::new();
+  let mut preprocesses = BTreeMap::new();
   for i in 1 ..= signers.len() {
     let i = Participant::new(u16::try_from(i).unwrap()).unwrap();
-    if let SignerEvent::ProcessorMessage(ProcessorMessage::Preprocess { id, preprocess }) =
-      signers.get_mut(&i).unwrap().events.pop_front().unwrap()
+    if let SignerEvent::ProcessorMessage(ProcessorMessage::Preprocess {
+      id,
+      preprocesses: mut these_preprocesses,
+    }) = signers.get_mut(&i).unwrap().events.pop_front().unwrap()
     {
       assert_eq!(id, actual_id);
+      assert_eq!(these_preprocesses.len(), 1);
       if signing_set.contains(&i) {
-        preprocesses.insert(i, preprocess);
+        preprocesses.insert(i, these_preprocesses.swap_remove(0));
       }
     } else {
       panic!("didn't get preprocess back");
     }
   }
 
-  let mut shares = HashMap::new();
+  let mut shares = BTreeMap::new();
   for i in &signing_set {
     let mut txn = dbs.get_mut(i).unwrap().txn();
     signers
@@ -102,11 +105,12 @@ pub async fn sign<C: Coin>(
       .await;
     txn.commit();
 
-    if let SignerEvent::ProcessorMessage(ProcessorMessage::Share { id, share }) =
+    if let SignerEvent::ProcessorMessage(ProcessorMessage::Share { id, shares: mut these_shares }) =
       signers.get_mut(i).unwrap().events.pop_front().unwrap()
     {
       assert_eq!(id, actual_id);
-      shares.insert(*i, share);
+      assert_eq!(these_shares.len(), 1);
+      shares.insert(*i, these_shares.swap_remove(0));
     } else {
       panic!("didn't get share back");
     }
@@ -139,7 +143,7 @@ pub async fn sign<C: Coin>(
   }
 
   // Make sure there's no events left
-  for (_, mut signer) in signers.drain() {
+  for (_, mut signer) in signers.into_iter() {
     assert!(signer.events.pop_front().is_none());
   }
 
@@ -158,12 +162,11 @@ pub async fn test_signer<C: Coin>(coin: C) {
   let fee = coin.get_fee().await;
 
   let amount = 2 * C::DUST;
-  let mut keys_txs = HashMap::new();
+  let mut keys_txs = BTreeMap::new();
   let mut eventualities = vec![];
-  for (i, keys) in keys.drain() {
+  for (i, keys) in keys.into_iter() {
     let (signable, eventuality) = coin
       .prepare_send(
-        keys.clone(),
         sync_block,
         Plan {
           key,
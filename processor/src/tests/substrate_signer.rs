@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use rand_core::{RngCore, OsRng};
 
@@ -48,15 +48,15 @@ async fn test_substrate_signer() {
     ],
   };
 
-  let mut signers = HashMap::new();
-  let mut dbs = HashMap::new();
+  let mut signers = BTreeMap::new();
+  let mut dbs = BTreeMap::new();
   let mut t = 0;
   for i in 1 ..= keys.len() {
     let i = Participant::new(u16::try_from(i).unwrap()).unwrap();
     let keys = keys.remove(&i).unwrap();
     t = keys.params().t();
 
-    let mut signer = SubstrateSigner::<MemDb>::new(keys);
+    let mut signer = SubstrateSigner::<MemDb>::new(vec![keys]);
     let mut db = MemDb::new();
     let mut txn = db.txn();
     signer.sign(&mut txn, batch.clone()).await;
@@ -80,24 +80,25 @@ async fn test_substrate_signer() {
   }
 
   // All participants should emit a preprocess
-  let mut preprocesses = HashMap::new();
+  let mut preprocesses = BTreeMap::new();
   for i in 1 ..= signers.len() {
     let i = Participant::new(u16::try_from(i).unwrap()).unwrap();
     if let SubstrateSignerEvent::ProcessorMessage(ProcessorMessage::BatchPreprocess {
       id,
-      preprocess,
+      preprocesses: mut these_preprocesses,
     }) = signers.get_mut(&i).unwrap().events.pop_front().unwrap()
     {
       assert_eq!(id, actual_id);
+      assert_eq!(these_preprocesses.len(), 1);
       if signing_set.contains(&i) {
-        preprocesses.insert(i, preprocess);
+        preprocesses.insert(i, these_preprocesses.swap_remove(0));
       }
     } else {
       panic!("didn't get preprocess back");
     }
   }
 
-  let mut shares = HashMap::new();
+  let mut shares = BTreeMap::new();
   for i in &signing_set {
     let mut txn = dbs.get_mut(i).unwrap().txn();
     signers
@@ -113,11 +114,14 @@ async fn test_substrate_signer() {
       .await;
     txn.commit();
 
-    if let SubstrateSignerEvent::ProcessorMessage(ProcessorMessage::BatchShare { id, share }) =
-      signers.get_mut(i).unwrap().events.pop_front().unwrap()
+    if let SubstrateSignerEvent::ProcessorMessage(ProcessorMessage::BatchShare {
+      id,
+      shares: mut these_shares,
+    }) = signers.get_mut(i).unwrap().events.pop_front().unwrap()
     {
       assert_eq!(id, actual_id);
-      shares.insert(*i, share);
+      assert_eq!(these_shares.len(), 1);
+      shares.insert(*i, these_shares.swap_remove(0));
     } else {
       panic!("didn't get share back");
     }
@@ -150,7 +154,7 @@ async fn test_substrate_signer() {
   }
 
   // Make sure there's no events left
-  for (_, mut signer) in signers.drain() {
+  for (_, mut signer) in signers.into_iter() {
     assert!(signer.events.pop_front().is_none());
   }
 }
@@ -1,4 +1,4 @@
-use std::{time::Duration, collections::HashMap};
+use std::{time::Duration, collections::BTreeMap};
 
 use rand_core::OsRng;
 
@@ -71,11 +71,11 @@ pub async fn test_wallet<C: Coin>(coin: C) {
 
   // Execute the plan
   let fee = coin.get_fee().await;
-  let mut keys_txs = HashMap::new();
+  let mut keys_txs = BTreeMap::new();
   let mut eventualities = vec![];
-  for (i, keys) in keys.drain() {
+  for (i, keys) in keys.into_iter() {
     let (signable, eventuality) = coin
-      .prepare_send(keys.clone(), coin.get_block_number(&block_id).await, plans[0].clone(), fee)
+      .prepare_send(coin.get_block_number(&block_id).await, plans[0].clone(), fee)
       .await
       .unwrap()
       .0
@@ -13,14 +13,12 @@ pub use ciphersuite::*;
 
 pub use multiexp::*;
 
-// pub use dleq::*;
+pub use dleq::*;
 pub use schnorr_signatures::*;
 
-/*
 pub use dkg::*;
 pub use modular_frost::*;
-pub use frost_schnorrkel::*;
-*/
+// pub use frost_schnorrkel::*;
 
 pub use monero_generators::*;
-// pub use monero_serai::*;
+pub use monero_serai::*;